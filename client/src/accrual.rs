@@ -0,0 +1,180 @@
+//! Interest accrual of a decoded reserve between refreshes, as Kamino's `refreshReserve`
+//! applies it: the cumulative borrow rate and debt compound at the curve rate plus the fixed
+//! host rate, and the protocol's and referrers' shares of the new interest are set aside.
+//! Prices are not touched; they come from the oracles at refresh.
+
+use anchor_lang::prelude::*;
+use liquidity_lending::LendingError;
+
+use crate::kamino::{BigFractionBytes, ObligationLiquidity, Reserve, ReserveLiquidity};
+use crate::rates::{borrow_rate, utilization_rate, Fraction, SLOTS_PER_YEAR};
+
+mod big {
+    #![allow(clippy::all)]
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+pub use big::U256;
+
+/// Kamino's `BigFraction`: a 256-bit value with the same 60 fractional bits as `Fraction`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BigFraction(pub U256);
+
+impl BigFraction {
+    pub fn one() -> Self {
+        Self(U256::one() << 60)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        let limbs = self.0 .0;
+        limbs.iter().rev().fold(0.0, |value, &limb| value * 2f64.powi(64) + limb as f64) / 2f64.powi(60)
+    }
+
+    fn checked_mul(self, rhs: Fraction) -> Result<Self> {
+        let product = self
+            .0
+            .checked_mul(U256::from(rhs.to_bits()))
+            .ok_or_else(|| error!(LendingError::MathOverflow))?;
+        Ok(Self(product >> 60))
+    }
+
+    /// `self / rhs` as a `Fraction`, failing if it does not fit
+    fn checked_ratio(self, rhs: Self) -> Result<Fraction> {
+        let quotient = (self.0 << 60)
+            .checked_div(rhs.0)
+            .ok_or_else(|| error!(LendingError::MathOverflow))?;
+        if quotient.bits() > 128 {
+            return err!(LendingError::MathOverflow);
+        }
+        Ok(Fraction::from_bits(quotient.low_u128()))
+    }
+}
+
+impl From<BigFractionBytes> for BigFraction {
+    fn from(bytes: BigFractionBytes) -> Self {
+        Self(U256(bytes.value))
+    }
+}
+
+impl From<BigFraction> for BigFractionBytes {
+    fn from(value: BigFraction) -> Self {
+        Self { value: value.0 .0, padding: [0; 2] }
+    }
+}
+
+/// Growth factor of `rate` (an APR) over `slots`: exact up to four slots, then the first
+/// four terms of the binomial expansion of `(1 + rate / SLOTS_PER_YEAR)^slots`
+pub fn approximate_compounded_interest(rate: Fraction, slots: u64) -> Fraction {
+    let base = rate / u128::from(SLOTS_PER_YEAR);
+    let one_plus_base = Fraction::ONE + base;
+    match slots {
+        0 => return Fraction::ONE,
+        1 => return one_plus_base,
+        2 => return one_plus_base * one_plus_base,
+        3 => return one_plus_base * one_plus_base * one_plus_base,
+        4 => {
+            let squared = one_plus_base * one_plus_base;
+            return squared * squared;
+        }
+        _ => (),
+    }
+
+    let exp = u128::from(slots);
+    let base_squared = base * base;
+    let base_cubed = base_squared * base;
+    let first_term = base * exp;
+    let second_term = base_squared * exp * (exp - 1) / 2;
+    let third_term = base_cubed * exp * (exp - 1) * (exp - 2) / 6;
+    Fraction::ONE + first_term + second_term + third_term
+}
+
+/// Compound `liquidity`'s debt over `slots` at `borrow_rate` plus `host_fixed_rate`.
+/// `protocol_take_rate` of the variable interest goes to the protocol, and
+/// `referral_rate` of that to referrers; fixed host interest goes to the protocol whole.
+pub fn compound_interest(
+    liquidity: &mut ReserveLiquidity,
+    borrow_rate: Fraction,
+    host_fixed_rate: Fraction,
+    slots: u64,
+    protocol_take_rate: Fraction,
+    referral_rate: Fraction,
+) -> Result<()> {
+    let previous_cumulative_rate = BigFraction::from(liquidity.cumulative_borrow_rate_bsf);
+    let previous_debt = Fraction::from_bits(liquidity.borrowed_amount_sf);
+
+    let compounded_rate = approximate_compounded_interest(borrow_rate + host_fixed_rate, slots);
+    let compounded_fixed_rate = approximate_compounded_interest(host_fixed_rate, slots);
+    liquidity.cumulative_borrow_rate_bsf = previous_cumulative_rate.checked_mul(compounded_rate)?.into();
+
+    let new_debt = previous_debt * compounded_rate;
+    let fixed_host_fee = previous_debt * compounded_fixed_rate - previous_debt;
+    let net_new_variable_debt = new_debt - previous_debt - fixed_host_fee;
+
+    let variable_protocol_fee = net_new_variable_debt * protocol_take_rate;
+    let absolute_referral_rate = protocol_take_rate * referral_rate;
+    let max_referrer_fees = net_new_variable_debt * absolute_referral_rate;
+
+    liquidity.accumulated_protocol_fees_sf = (Fraction::from_bits(liquidity.accumulated_protocol_fees_sf)
+        + variable_protocol_fee
+        + fixed_host_fee
+        - max_referrer_fees)
+        .to_bits();
+    liquidity.pending_referrer_fees_sf = liquidity
+        .pending_referrer_fees_sf
+        .checked_add(max_referrer_fees.to_bits())
+        .ok_or_else(|| error!(LendingError::MathOverflow))?;
+    liquidity.borrowed_amount_sf = new_debt.to_bits();
+    liquidity.absolute_referral_rate_sf = absolute_referral_rate.to_bits();
+    Ok(())
+}
+
+/// Accrue `reserve`'s interest up to `slot` and mark it refreshed there, as `refreshReserve`
+/// does in a market charging `referral_fee_bps`. Fails with `InvalidAccountState` for a slot
+/// before the reserve's last update.
+pub fn refresh_reserve(reserve: &mut Reserve, slot: u64, referral_fee_bps: u16) -> Result<()> {
+    let slots = slot
+        .checked_sub(reserve.last_update.slot)
+        .ok_or_else(|| error!(LendingError::InvalidAccountState))?;
+    if slots > 0 {
+        let rate = borrow_rate(&reserve.config.borrow_rate_curve, utilization_rate(&reserve.liquidity))?;
+        compound_interest(
+            &mut reserve.liquidity,
+            rate,
+            Fraction::from_num(reserve.config.host_fixed_interest_rate_bps) / 10_000,
+            slots,
+            Fraction::from_num(reserve.config.protocol_take_rate_pct) / 100,
+            Fraction::from_num(referral_fee_bps) / 10_000,
+        )?;
+    }
+    reserve.last_update.slot = slot;
+    reserve.last_update.stale = 0;
+    Ok(())
+}
+
+/// Advance `reserve` by `slots` past its last update
+pub fn advance(reserve: &mut Reserve, slots: u64, referral_fee_bps: u16) -> Result<()> {
+    let slot = reserve
+        .last_update
+        .slot
+        .checked_add(slots)
+        .ok_or_else(|| error!(LendingError::MathOverflow))?;
+    refresh_reserve(reserve, slot, referral_fee_bps)
+}
+
+/// Bring an obligation's borrow up to its reserve's cumulative borrow rate, as
+/// `refreshObligation` does after the reserve is refreshed
+pub fn accrue_borrow(borrow: &mut ObligationLiquidity, reserve: &Reserve) -> Result<()> {
+    let previous = BigFraction::from(borrow.cumulative_borrow_rate_bsf);
+    let current = BigFraction::from(reserve.liquidity.cumulative_borrow_rate_bsf);
+    if current < previous {
+        return err!(LendingError::InvalidAccountState);
+    }
+    if current > previous {
+        let compounded_rate = current.checked_ratio(previous)?;
+        borrow.borrowed_amount_sf = (Fraction::from_bits(borrow.borrowed_amount_sf) * compounded_rate).to_bits();
+        borrow.cumulative_borrow_rate_bsf = current.into();
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Health of an obligation from its decoded accounts: LTV, health factor, liquidation
+//! prices and remaining borrowing power. Values are in the market's quote currency and use
+//! the per-asset market values from the obligation's last refresh.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use liquidity_lending::LendingError;
+
+use crate::kamino::{sf_to_f64, ElevationGroup, LendingMarket, Obligation, Reserve};
+
+/// Where a position stands, as of the obligation's last refresh
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionAnalytics {
+    pub deposited_value: f64,
+    pub borrowed_value: f64,
+    /// Debt with each borrow weighted by its reserve's borrow factor
+    pub borrow_factor_adjusted_debt: f64,
+    /// Debt the deposits support at their loan-to-value ratios
+    pub allowed_borrow_value: f64,
+    /// Debt at which the obligation becomes liquidatable
+    pub unhealthy_borrow_value: f64,
+    /// Borrow-factor-adjusted debt over deposited value
+    pub ltv: f64,
+    /// Unhealthy borrow value over borrow-factor-adjusted debt; infinite without debt, and
+    /// liquidatable below 1
+    pub health_factor: f64,
+    pub liquidation_prices: Vec<LiquidationPrice>,
+    pub borrowing_power: Vec<BorrowingPower>,
+}
+
+/// Price of a deposited asset at which the health factor reaches 1, other prices unchanged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LiquidationPrice {
+    pub reserve: Pubkey,
+    pub current_price: f64,
+    /// `None` when no price of this asset alone makes the position liquidatable
+    pub liquidation_price: Option<f64>,
+}
+
+/// What more the obligation can borrow from a reserve
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BorrowingPower {
+    pub reserve: Pubkey,
+    /// Market value that can still be borrowed
+    pub value: f64,
+    /// The same in base units, capped by the reserve's available liquidity and borrow limit
+    pub amount: u64,
+}
+
+/// Loan-to-value and liquidation thresholds of a deposit, as fractions
+fn collateral_ratios(reserve: &Reserve, group: Option<&ElevationGroup>) -> (f64, f64) {
+    match group {
+        Some(group) => (pct(group.ltv_pct.into()), pct(group.liquidation_threshold_pct.into())),
+        None => (
+            pct(reserve.config.loan_to_value_pct.into()),
+            pct(reserve.config.liquidation_threshold_pct.into()),
+        ),
+    }
+}
+
+/// Borrow factor of a reserve; elevation groups borrow at face value
+fn borrow_factor(reserve: &Reserve, group: Option<&ElevationGroup>) -> f64 {
+    match group {
+        Some(_) => 1.0,
+        None => pct(reserve.config.borrow_factor_pct.max(100)),
+    }
+}
+
+fn pct(value: u64) -> f64 {
+    value as f64 / 100.0
+}
+
+fn reserve<'a>(reserves: &'a BTreeMap<Pubkey, Reserve>, key: &Pubkey) -> Result<&'a Reserve> {
+    reserves
+        .get(key)
+        .ok_or_else(|| error!(LendingError::InvalidAccountState))
+}
+
+/// Analyse `obligation`, looking up its reserves in `reserves` and its elevation group in
+/// `market`. Borrowing power is reported for every reserve in `reserves`.
+pub fn analyze(
+    obligation: &Obligation,
+    reserves: &BTreeMap<Pubkey, Reserve>,
+    market: &LendingMarket,
+) -> Result<PositionAnalytics> {
+    let group = match obligation.elevation_group {
+        0 => None,
+        id => Some(
+            market
+                .elevation_group(id)
+                .ok_or_else(|| error!(LendingError::InvalidAccountState))?,
+        ),
+    };
+
+    // Per-asset (value, weighted for allowed borrow, weighted for liquidation)
+    let mut deposits = Vec::new();
+    for deposit in obligation.active_deposits() {
+        let (ltv, threshold) = collateral_ratios(reserve(reserves, &deposit.deposit_reserve)?, group);
+        let value = sf_to_f64(deposit.market_value_sf);
+        deposits.push((deposit.deposit_reserve, value, value * ltv, value * threshold));
+    }
+    let mut debts = Vec::new();
+    for borrow in obligation.active_borrows() {
+        let factor = borrow_factor(reserve(reserves, &borrow.borrow_reserve)?, group);
+        let value = sf_to_f64(borrow.market_value_sf);
+        debts.push((borrow.borrow_reserve, value, value * factor));
+    }
+
+    let deposited_value: f64 = deposits.iter().map(|deposit| deposit.1).sum();
+    let allowed_borrow_value: f64 = deposits.iter().map(|deposit| deposit.2).sum();
+    let unhealthy_borrow_value: f64 = deposits.iter().map(|deposit| deposit.3).sum();
+    let borrowed_value: f64 = debts.iter().map(|debt| debt.1).sum();
+    let borrow_factor_adjusted_debt: f64 = debts.iter().map(|debt| debt.2).sum();
+
+    let ltv = if deposited_value > 0.0 {
+        borrow_factor_adjusted_debt / deposited_value
+    } else {
+        0.0
+    };
+    let health_factor = if borrow_factor_adjusted_debt > 0.0 {
+        unhealthy_borrow_value / borrow_factor_adjusted_debt
+    } else {
+        f64::INFINITY
+    };
+
+    let mut liquidation_prices = Vec::new();
+    for &(key, _, _, weighted) in &deposits {
+        let current_price = reserve(reserves, &key)?.liquidity.market_price();
+        // The asset may be borrowed too, in which case its debt moves with the price as well
+        let debt: f64 = debts.iter().filter(|debt| debt.0 == key).map(|debt| debt.2).sum();
+        let other_collateral = unhealthy_borrow_value - weighted;
+        let other_debt = borrow_factor_adjusted_debt - debt;
+        // Solve other_collateral + weighted * x = other_debt + debt * x for the price ratio x
+        let ratio = (other_debt - other_collateral) / (weighted - debt);
+        let liquidation_price =
+            (borrow_factor_adjusted_debt > 0.0 && ratio.is_finite() && ratio > 0.0)
+                .then_some(current_price * ratio);
+        liquidation_prices.push(LiquidationPrice { reserve: key, current_price, liquidation_price });
+    }
+
+    let headroom = (allowed_borrow_value - borrow_factor_adjusted_debt).max(0.0);
+    let borrowing_power = reserves
+        .iter()
+        .map(|(key, reserve)| {
+            let allowed = match group {
+                Some(group) => group.allow_new_loans != 0 && group.debt_reserve == *key,
+                None => true,
+            };
+            let price = reserve.liquidity.market_price();
+            if !allowed || price <= 0.0 {
+                return BorrowingPower { reserve: *key, value: 0.0, amount: 0 };
+            }
+            let value = headroom / borrow_factor(reserve, group);
+            let borrowed = sf_to_f64(reserve.liquidity.borrowed_amount_sf);
+            let limit_room = (reserve.config.borrow_limit as f64 - borrowed).max(0.0);
+            let amount = (value / price * reserve.decimals_factor())
+                .min(reserve.liquidity.available_amount as f64)
+                .min(limit_room)
+                .floor() as u64;
+            BorrowingPower { reserve: *key, value, amount }
+        })
+        .collect();
+
+    Ok(PositionAnalytics {
+        deposited_value,
+        borrowed_value,
+        borrow_factor_adjusted_debt,
+        allowed_borrow_value,
+        unhealthy_borrow_value,
+        ltv,
+        health_factor,
+        liquidation_prices,
+        borrowing_power,
+    })
+}
@@ -0,0 +1,283 @@
+//! Kamino `Reserve`, `Obligation` and `LendingMarket` accounts decoded from raw account data,
+//! with the fields analytics and simulations need. Trailing padding is not decoded.
+
+use anchor_lang::prelude::*;
+use liquidity_lending::kamino::{
+    deserialize_data, discriminator, reserve_layout, LastUpdate, ReserveHeader,
+};
+
+/// Number of fractional bits of Kamino's scaled fractions (`value / 2^60`)
+pub const SF_FRACTIONAL_BITS: u32 = 60;
+
+/// A scaled fraction as a float, for display and analytics
+pub fn sf_to_f64(value_sf: u128) -> f64 {
+    value_sf as f64 / (1u128 << SF_FRACTIONAL_BITS) as f64
+}
+
+/// Kamino `BigFractionBytes`: a 256-bit fraction with 60 fractional bits, limbs little endian
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BigFractionBytes {
+    pub value: [u64; 4],
+    pub padding: [u64; 2],
+}
+
+/// Kamino `ReserveLiquidity`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveLiquidity {
+    pub mint_pubkey: Pubkey,
+    pub supply_vault: Pubkey,
+    pub fee_vault: Pubkey,
+    pub available_amount: u64,
+    pub borrowed_amount_sf: u128,
+    pub market_price_sf: u128,
+    pub market_price_last_updated_ts: u64,
+    pub mint_decimals: u64,
+    pub deposit_limit_crossed_timestamp: u64,
+    pub borrow_limit_crossed_timestamp: u64,
+    pub cumulative_borrow_rate_bsf: BigFractionBytes,
+    pub accumulated_protocol_fees_sf: u128,
+    pub accumulated_referrer_fees_sf: u128,
+    pub pending_referrer_fees_sf: u128,
+    pub absolute_referral_rate_sf: u128,
+    pub token_program: Pubkey,
+}
+
+impl ReserveLiquidity {
+    /// Liquidity owned by depositors, borrowed or not, as a scaled fraction
+    pub fn total_supply_sf(&self) -> u128 {
+        (u128::from(self.available_amount) << SF_FRACTIONAL_BITS)
+            .saturating_add(self.borrowed_amount_sf)
+            .saturating_sub(self.accumulated_protocol_fees_sf)
+            .saturating_sub(self.accumulated_referrer_fees_sf)
+            .saturating_sub(self.pending_referrer_fees_sf)
+    }
+
+    /// Share of the total supply that is borrowed, from 0 to 1
+    pub fn utilization(&self) -> f64 {
+        let total_supply_sf = self.total_supply_sf();
+        if total_supply_sf == 0 {
+            return 0.0;
+        }
+        (sf_to_f64(self.borrowed_amount_sf) / sf_to_f64(total_supply_sf)).min(1.0)
+    }
+
+    /// Market price of one whole token
+    pub fn market_price(&self) -> f64 {
+        sf_to_f64(self.market_price_sf)
+    }
+}
+
+/// Kamino `ReserveCollateral`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveCollateral {
+    pub mint_pubkey: Pubkey,
+    pub mint_total_supply: u64,
+    pub supply_vault: Pubkey,
+}
+
+/// Kamino `ReserveFees`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveFees {
+    pub borrow_fee_sf: u64,
+    pub flash_loan_fee_sf: u64,
+    pub padding: [u8; 8],
+}
+
+/// Kamino `CurvePoint`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub utilization_rate_bps: u32,
+    pub borrow_rate_bps: u32,
+}
+
+/// Kamino `BorrowRateCurve`: borrow APR by utilization, linear between points
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BorrowRateCurve {
+    pub points: [CurvePoint; 11],
+}
+
+/// Leading fields of a Kamino `ReserveConfig`, up to its borrow limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveConfig {
+    pub status: u8,
+    pub asset_tier: u8,
+    pub host_fixed_interest_rate_bps: u16,
+    pub reserved2: [u8; 2],
+    pub reserved3: [u8; 8],
+    pub protocol_take_rate_pct: u8,
+    pub protocol_liquidation_fee_pct: u8,
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub min_liquidation_bonus_bps: u16,
+    pub max_liquidation_bonus_bps: u16,
+    pub bad_debt_liquidation_bonus_bps: u16,
+    pub deleveraging_margin_call_period_secs: u64,
+    pub deleveraging_threshold_decrease_bps_per_day: u64,
+    pub fees: ReserveFees,
+    pub borrow_rate_curve: BorrowRateCurve,
+    pub borrow_factor_pct: u64,
+    pub deposit_limit: u64,
+    pub borrow_limit: u64,
+}
+
+/// Elevation group settings of a Kamino `ReserveConfig`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveElevationConfig {
+    pub elevation_groups: [u8; 20],
+    pub disable_usage_as_coll_outside_emode: u8,
+    pub utilization_limit_block_borrowing_above_pct: u8,
+    pub autodeleverage_enabled: u8,
+    pub reserved1: [u8; 1],
+    pub borrow_limit_outside_elevation_group: u64,
+}
+
+/// A decoded Kamino `Reserve`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reserve {
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub liquidity: ReserveLiquidity,
+    pub collateral: ReserveCollateral,
+    pub config: ReserveConfig,
+    pub elevation: ReserveElevationConfig,
+}
+
+impl Reserve {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let header: ReserveHeader = deserialize_data(data, &discriminator::RESERVE, 0)?;
+        Ok(Self {
+            last_update: header.last_update,
+            lending_market: header.lending_market,
+            liquidity: deserialize_data(data, &discriminator::RESERVE, reserve_layout::LIQUIDITY)?,
+            collateral: deserialize_data(data, &discriminator::RESERVE, reserve_layout::COLLATERAL)?,
+            config: deserialize_data(data, &discriminator::RESERVE, reserve_layout::CONFIG)?,
+            elevation: deserialize_data(
+                data,
+                &discriminator::RESERVE,
+                reserve_layout::CONFIG_ELEVATION_GROUPS,
+            )?,
+        })
+    }
+
+    /// Base units per whole token
+    pub fn decimals_factor(&self) -> f64 {
+        10f64.powi(self.liquidity.mint_decimals as i32)
+    }
+}
+
+/// Kamino `ObligationCollateral`; unused slots have a default reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObligationCollateral {
+    pub deposit_reserve: Pubkey,
+    pub deposited_amount: u64,
+    pub market_value_sf: u128,
+    pub borrowed_amount_against_this_collateral_in_elevation_group: u64,
+    pub padding: [u64; 9],
+}
+
+/// Kamino `ObligationLiquidity`; unused slots have a default reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObligationLiquidity {
+    pub borrow_reserve: Pubkey,
+    pub cumulative_borrow_rate_bsf: BigFractionBytes,
+    pub padding: u64,
+    pub borrowed_amount_sf: u128,
+    pub market_value_sf: u128,
+    pub borrow_factor_adjusted_market_value_sf: u128,
+    pub borrowed_amount_outside_elevation_groups: u64,
+    pub padding2: [u64; 7],
+}
+
+/// A decoded Kamino `Obligation`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Obligation {
+    pub tag: u64,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub deposits: [ObligationCollateral; 8],
+    pub lowest_reserve_deposit_liquidation_ltv: u64,
+    pub deposited_value_sf: u128,
+    pub borrows: [ObligationLiquidity; 5],
+    pub borrow_factor_adjusted_debt_value_sf: u128,
+    pub borrowed_assets_market_value_sf: u128,
+    pub allowed_borrow_value_sf: u128,
+    pub unhealthy_borrow_value_sf: u128,
+    pub deposits_asset_tiers: [u8; 8],
+    pub borrows_asset_tiers: [u8; 5],
+    pub elevation_group: u8,
+    pub num_of_obsolete_reserves: u8,
+    pub has_debt: u8,
+    pub referrer: Pubkey,
+    pub borrowing_disabled: u8,
+    pub autodeleverage_target_ltv_pct: u8,
+    pub lowest_reserve_deposit_max_ltv_pct: u8,
+    pub reserved: [u8; 5],
+    pub highest_borrow_factor_pct: u64,
+    pub autodeleverage_margin_call_started_timestamp: u64,
+}
+
+impl Obligation {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        deserialize_data(data, &discriminator::OBLIGATION, 0)
+    }
+
+    pub fn active_deposits(&self) -> impl Iterator<Item = &ObligationCollateral> {
+        self.deposits.iter().filter(|deposit| deposit.deposit_reserve != Pubkey::default())
+    }
+
+    pub fn active_borrows(&self) -> impl Iterator<Item = &ObligationLiquidity> {
+        self.borrows.iter().filter(|borrow| borrow.borrow_reserve != Pubkey::default())
+    }
+}
+
+/// Kamino `ElevationGroup`: tighter risk parameters for correlated assets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElevationGroup {
+    pub max_liquidation_bonus_bps: u16,
+    pub id: u8,
+    pub ltv_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub allow_new_loans: u8,
+    pub max_reserves_as_collateral: u8,
+    pub padding0: u8,
+    /// The only reserve obligations in the group may borrow from
+    pub debt_reserve: Pubkey,
+    pub padding1: [u64; 4],
+}
+
+/// Leading fields of a Kamino `LendingMarket`, up to its elevation groups
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LendingMarket {
+    pub version: u64,
+    pub bump_seed: u64,
+    pub lending_market_owner: Pubkey,
+    pub lending_market_owner_cached: Pubkey,
+    pub quote_currency: [u8; 32],
+    pub referral_fee_bps: u16,
+    pub emergency_mode: u8,
+    pub autodeleverage_enabled: u8,
+    pub borrow_disabled: u8,
+    pub price_refresh_trigger_to_max_age_pct: u8,
+    pub liquidation_max_debt_close_factor_pct: u8,
+    pub insolvency_risk_unhealthy_ltv_pct: u8,
+    pub min_full_liquidation_value_threshold: u64,
+    pub max_liquidatable_debt_market_value_at_once: u64,
+    pub reserved0: [u8; 8],
+    pub global_allowed_borrow_value: u64,
+    pub risk_council: Pubkey,
+    pub reserved1: [u8; 8],
+    pub elevation_groups: [ElevationGroup; 32],
+}
+
+impl LendingMarket {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        deserialize_data(data, &discriminator::LENDING_MARKET, 0)
+    }
+
+    /// The elevation group with the given id; id 0 means none
+    pub fn elevation_group(&self, id: u8) -> Option<&ElevationGroup> {
+        let index = usize::from(id).checked_sub(1)?;
+        self.elevation_groups.get(index).filter(|group| group.id == id)
+    }
+}
@@ -0,0 +1,835 @@
+//! Builds `liquidity_lending` instructions from owners, markets, reserves and amounts,
+//! deriving every account the program and Kamino expect.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, pubkey, sysvar};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::token;
+use liquidity_lending::batch::BatchAction;
+use liquidity_lending::kamino::ReserveFarmKind;
+use liquidity_lending::state::{ConfigChange, PauseTarget, Role};
+use liquidity_lending::{accounts, instruction, ID as PROGRAM_ID};
+
+pub mod accrual;
+pub mod analytics;
+pub mod kamino;
+pub mod pda;
+pub mod rates;
+
+/// Kamino lending on mainnet
+pub const KAMINO_LENDING_PROGRAM_ID: Pubkey = liquidity_lending::kamino::KAMINO_LENDING_ID;
+
+/// Kamino farms on mainnet
+pub const KAMINO_FARMS_PROGRAM_ID: Pubkey = liquidity_lending::farms::KAMINO_FARMS_ID;
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// A Kamino reserve and the accounts derived from its market and liquidity mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reserve {
+    pub address: Pubkey,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    /// SPL Token or Token-2022, whichever owns the liquidity mint
+    pub liquidity_token_program: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub fee_vault: Pubkey,
+    /// Collateral mints are always SPL Token mints
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+}
+
+impl Reserve {
+    pub fn new(
+        kamino_program: &Pubkey,
+        address: Pubkey,
+        lending_market: Pubkey,
+        liquidity_mint: Pubkey,
+        liquidity_token_program: Pubkey,
+    ) -> Self {
+        Self {
+            address,
+            lending_market,
+            liquidity_mint,
+            liquidity_token_program,
+            liquidity_supply: pda::kamino::reserve_liquidity_supply(kamino_program, &lending_market, &liquidity_mint),
+            fee_vault: pda::kamino::reserve_fee_vault(kamino_program, &lending_market, &liquidity_mint),
+            collateral_mint: pda::kamino::reserve_collateral_mint(kamino_program, &lending_market, &liquidity_mint),
+            collateral_supply: pda::kamino::reserve_collateral_supply(kamino_program, &lending_market, &liquidity_mint),
+        }
+    }
+
+    /// `owner`'s associated token account for the liquidity mint
+    pub fn liquidity_ata(&self, owner: &Pubkey) -> Pubkey {
+        pda::associated_token_address(owner, &self.liquidity_mint, &self.liquidity_token_program)
+    }
+
+    /// `owner`'s associated token account for the collateral mint
+    pub fn collateral_ata(&self, owner: &Pubkey) -> Pubkey {
+        pda::associated_token_address(owner, &self.collateral_mint, &token::ID)
+    }
+}
+
+/// Oracle accounts a borrow passes to the price guard, as configured on the reserve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BorrowOracles {
+    pub pyth_price: Option<Pubkey>,
+    pub switchboard_price: Option<Pubkey>,
+    pub switchboard_twap: Option<Pubkey>,
+    pub scope_prices: Option<Pubkey>,
+}
+
+/// One action of a batch, with the oracle accounts its reserve is refreshed with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchStep {
+    pub reserve: Reserve,
+    pub action: BatchAction,
+    pub oracles: BorrowOracles,
+}
+
+/// A farm reward to harvest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FarmReward {
+    /// The approved reserve whose collateral or debt farm pays the reward
+    pub reserve: Pubkey,
+    pub farm_state: Pubkey,
+    pub global_config: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_token_program: Pubkey,
+    pub reward_index: u64,
+}
+
+/// Builds instructions against one deployment of Kamino lending and farms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LendingClient {
+    pub kamino_program: Pubkey,
+    pub farms_program: Pubkey,
+}
+
+impl Default for LendingClient {
+    fn default() -> Self {
+        Self {
+            kamino_program: KAMINO_LENDING_PROGRAM_ID,
+            farms_program: KAMINO_FARMS_PROGRAM_ID,
+        }
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+impl LendingClient {
+    /// Describe `reserve` of `lending_market` with its derived accounts
+    pub fn reserve(
+        &self,
+        address: Pubkey,
+        lending_market: Pubkey,
+        liquidity_mint: Pubkey,
+        liquidity_token_program: Pubkey,
+    ) -> Reserve {
+        Reserve::new(&self.kamino_program, address, lending_market, liquidity_mint, liquidity_token_program)
+    }
+
+    /// The vanilla obligation of `owner` in `lending_market`
+    pub fn obligation(&self, owner: &Pubkey, lending_market: &Pubkey) -> Pubkey {
+        pda::kamino::obligation(&self.kamino_program, owner, lending_market)
+    }
+
+    pub fn initialize(&self, admin: Pubkey) -> Instruction {
+        build(
+            accounts::Initialize {
+                admin,
+                config: pda::config(),
+                registry: pda::registry(),
+                program: PROGRAM_ID,
+                program_data: pda::program_data(),
+                system_program: system_program::ID,
+            },
+            instruction::Initialize {},
+        )
+    }
+
+    /// Pause or unpause `target`; guardians pass their role assignment
+    pub fn set_pause(&self, authority: Pubkey, target: PauseTarget, paused: bool, as_guardian: bool) -> Instruction {
+        build(
+            accounts::SetPause {
+                authority,
+                config: pda::config(),
+                guardian_role: as_guardian.then(|| pda::role(Role::Guardian, &authority)),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::SetPause { target, paused },
+        )
+    }
+
+    pub fn grant_role(&self, admin: Pubkey, role: Role, holder: Pubkey) -> Instruction {
+        build(
+            accounts::GrantRole {
+                admin,
+                config: pda::config(),
+                role_assignment: pda::role(role, &holder),
+                system_program: system_program::ID,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::GrantRole { role, holder },
+        )
+    }
+
+    pub fn revoke_role(&self, admin: Pubkey, role: Role, holder: Pubkey) -> Instruction {
+        build(
+            accounts::RevokeRole {
+                admin,
+                config: pda::config(),
+                role_assignment: pda::role(role, &holder),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::RevokeRole {},
+        )
+    }
+
+    pub fn propose_admin(&self, admin: Pubkey, new_admin: Pubkey) -> Instruction {
+        build(
+            accounts::ProposeAdmin {
+                admin,
+                config: pda::config(),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::ProposeAdmin { new_admin },
+        )
+    }
+
+    pub fn accept_admin(&self, pending_admin: Pubkey) -> Instruction {
+        build(
+            accounts::AcceptAdmin {
+                pending_admin,
+                config: pda::config(),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::AcceptAdmin {},
+        )
+    }
+
+    /// Queue `change` as change `id`, which must be the config's current `config_change_count`
+    pub fn queue_config_change(
+        &self,
+        proposer: Pubkey,
+        id: u64,
+        change: ConfigChange,
+        as_fee_manager: bool,
+    ) -> Instruction {
+        build(
+            accounts::QueueConfigChange {
+                proposer,
+                fee_manager_role: as_fee_manager.then(|| pda::role(Role::FeeManager, &proposer)),
+                config: pda::config(),
+                pending_change: pda::config_change(id),
+                system_program: system_program::ID,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::QueueConfigChange { change },
+        )
+    }
+
+    pub fn execute_config_change(&self, executor: Pubkey, proposer: Pubkey, id: u64) -> Instruction {
+        build(
+            accounts::ExecuteConfigChange {
+                executor,
+                proposer,
+                config: pda::config(),
+                registry: pda::registry(),
+                pending_change: pda::config_change(id),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::ExecuteConfigChange {},
+        )
+    }
+
+    pub fn cancel_config_change(&self, authority: Pubkey, proposer: Pubkey, id: u64) -> Instruction {
+        build(
+            accounts::CancelConfigChange {
+                authority,
+                proposer,
+                config: pda::config(),
+                pending_change: pda::config_change(id),
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::CancelConfigChange {},
+        )
+    }
+
+    /// Create the position ledger of `owner`'s vanilla obligation in `lending_market`
+    pub fn init_user_position(&self, payer: Pubkey, owner: Pubkey, lending_market: Pubkey) -> Instruction {
+        build(
+            accounts::InitUserPosition {
+                payer,
+                owner,
+                lending_market,
+                obligation: self.obligation(&owner, &lending_market),
+                user_position: pda::user_position(&owner, &lending_market),
+                system_program: system_program::ID,
+                kamino_lending_program: self.kamino_program,
+            },
+            instruction::InitUserPosition {},
+        )
+    }
+
+    pub fn init_reserve_usage(&self, payer: Pubkey, reserve: &Reserve) -> Instruction {
+        build(
+            accounts::InitReserveUsage {
+                payer,
+                registry: pda::registry(),
+                reserve: reserve.address,
+                reserve_usage: pda::reserve_usage(&reserve.address),
+                system_program: system_program::ID,
+            },
+            instruction::InitReserveUsage {},
+        )
+    }
+
+    /// Create the account summing what `owner` borrows across their obligations
+    pub fn init_user_borrows(&self, payer: Pubkey, owner: Pubkey) -> Instruction {
+        build(
+            accounts::InitUserBorrows {
+                payer,
+                owner,
+                user_borrows: pda::user_borrows(&owner),
+                system_program: system_program::ID,
+            },
+            instruction::InitUserBorrows {},
+        )
+    }
+
+    /// Record the borrowed value of `owner`'s vanilla obligation in `lending_market`, which
+    /// must have been refreshed earlier in the transaction
+    pub fn sync_user_borrows(&self, owner: Pubkey, lending_market: Pubkey) -> Instruction {
+        build(
+            accounts::SyncUserBorrows {
+                user_borrows: pda::user_borrows(&owner),
+                obligation: self.obligation(&owner, &lending_market),
+                kamino_lending_program: self.kamino_program,
+            },
+            instruction::SyncUserBorrows {},
+        )
+    }
+
+    /// Deposit `amount` from `owner`'s liquidity ATA, minting collateral to their collateral ATA
+    pub fn deposit(&self, owner: Pubkey, reserve: &Reserve, amount: u64) -> Instruction {
+        build(
+            accounts::KaminoDepositReserveLiquidity {
+                owner,
+                user_position: pda::user_position(&owner, &reserve.lending_market),
+                config: pda::config(),
+                registry: pda::registry(),
+                reserve_usage: pda::reserve_usage(&reserve.address),
+                reserve: reserve.address,
+                lending_market: reserve.lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(
+                    &self.kamino_program,
+                    &reserve.lending_market,
+                ),
+                reserve_liquidity_mint: reserve.liquidity_mint,
+                reserve_liquidity_supply: reserve.liquidity_supply,
+                reserve_collateral_mint: reserve.collateral_mint,
+                user_source_liquidity: reserve.liquidity_ata(&owner),
+                user_destination_collateral: reserve.collateral_ata(&owner),
+                collateral_token_program: token::ID,
+                liquidity_token_program: reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoDepositReserveLiquidity { liquidity_amount: amount },
+        )
+    }
+
+    /// Borrow `amount` against `owner`'s vanilla obligation into their liquidity ATA
+    pub fn borrow(
+        &self,
+        owner: Pubkey,
+        reserve: &Reserve,
+        amount: u64,
+        oracles: BorrowOracles,
+        referrer: Option<Pubkey>,
+    ) -> Instruction {
+        build(
+            accounts::KaminoBorrowObligationLiquidity {
+                owner,
+                user_position: pda::user_position(&owner, &reserve.lending_market),
+                user_borrows: pda::user_borrows(&owner),
+                config: pda::config(),
+                registry: pda::registry(),
+                reserve_usage: pda::reserve_usage(&reserve.address),
+                obligation: self.obligation(&owner, &reserve.lending_market),
+                lending_market: reserve.lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(
+                    &self.kamino_program,
+                    &reserve.lending_market,
+                ),
+                borrow_reserve: reserve.address,
+                borrow_reserve_liquidity_mint: reserve.liquidity_mint,
+                reserve_source_liquidity: reserve.liquidity_supply,
+                borrow_reserve_liquidity_fee_receiver: reserve.fee_vault,
+                user_destination_liquidity: reserve.liquidity_ata(&owner),
+                referrer_token_state: referrer.map(|referrer| {
+                    pda::kamino::referrer_token_state(&self.kamino_program, &referrer, &reserve.liquidity_mint)
+                }),
+                pyth_price: oracles.pyth_price,
+                switchboard_price: oracles.switchboard_price,
+                switchboard_twap: oracles.switchboard_twap,
+                scope_prices: oracles.scope_prices,
+                token_program: reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoBorrowObligationLiquidity { liquidity_amount: amount },
+        )
+    }
+
+    /// Repay `amount` of `owner`'s debt from their liquidity ATA
+    pub fn repay(&self, owner: Pubkey, reserve: &Reserve, amount: u64) -> Instruction {
+        build(
+            accounts::KaminoRepayObligationLiquidity {
+                owner,
+                user_position: pda::user_position(&owner, &reserve.lending_market),
+                config: pda::config(),
+                registry: pda::registry(),
+                obligation: self.obligation(&owner, &reserve.lending_market),
+                lending_market: reserve.lending_market,
+                repay_reserve: reserve.address,
+                reserve_liquidity_mint: reserve.liquidity_mint,
+                reserve_destination_liquidity: reserve.liquidity_supply,
+                user_source_liquidity: reserve.liquidity_ata(&owner),
+                token_program: reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoRepayObligationLiquidity { liquidity_amount: amount },
+        )
+    }
+
+    /// Withdraw `collateral_amount` from `owner`'s obligation, redeemed into their liquidity ATA
+    pub fn withdraw(&self, owner: Pubkey, reserve: &Reserve, collateral_amount: u64) -> Instruction {
+        build(
+            accounts::KaminoWithdrawObligationCollateralAndRedeemReserveCollateral {
+                owner,
+                user_position: pda::user_position(&owner, &reserve.lending_market),
+                config: pda::config(),
+                registry: pda::registry(),
+                reserve_usage: pda::reserve_usage(&reserve.address),
+                obligation: self.obligation(&owner, &reserve.lending_market),
+                lending_market: reserve.lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(
+                    &self.kamino_program,
+                    &reserve.lending_market,
+                ),
+                withdraw_reserve: reserve.address,
+                reserve_liquidity_mint: reserve.liquidity_mint,
+                reserve_source_collateral: reserve.collateral_supply,
+                reserve_collateral_mint: reserve.collateral_mint,
+                reserve_liquidity_supply: reserve.liquidity_supply,
+                user_destination_liquidity: reserve.liquidity_ata(&owner),
+                collateral_token_program: token::ID,
+                liquidity_token_program: reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoWithdrawObligationCollateralAndRedeemReserveCollateral { collateral_amount },
+        )
+    }
+
+    /// Repay `amount` of `obligation_owner`'s debt in `repay_reserve` and seize collateral from
+    /// `withdraw_reserve`, using the liquidator's ATAs
+    #[allow(clippy::too_many_arguments)]
+    pub fn liquidate(
+        &self,
+        liquidator: Pubkey,
+        obligation_owner: Pubkey,
+        repay_reserve: &Reserve,
+        withdraw_reserve: &Reserve,
+        amount: u64,
+        min_acceptable_received_liquidity_amount: u64,
+        max_allowed_ltv_override_percent: u64,
+    ) -> Instruction {
+        let lending_market = repay_reserve.lending_market;
+        build(
+            accounts::KaminoLiquidateObligationAndRedeemReserveCollateral {
+                liquidator,
+                obligation: self.obligation(&obligation_owner, &lending_market),
+                user_position: pda::user_position(&obligation_owner, &lending_market),
+                config: pda::config(),
+                registry: pda::registry(),
+                lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(&self.kamino_program, &lending_market),
+                repay_reserve: repay_reserve.address,
+                repay_reserve_liquidity_mint: repay_reserve.liquidity_mint,
+                repay_reserve_liquidity_supply: repay_reserve.liquidity_supply,
+                withdraw_reserve: withdraw_reserve.address,
+                withdraw_reserve_liquidity_mint: withdraw_reserve.liquidity_mint,
+                withdraw_reserve_collateral_mint: withdraw_reserve.collateral_mint,
+                withdraw_reserve_collateral_supply: withdraw_reserve.collateral_supply,
+                withdraw_reserve_liquidity_supply: withdraw_reserve.liquidity_supply,
+                withdraw_reserve_liquidity_fee_receiver: withdraw_reserve.fee_vault,
+                user_source_liquidity: repay_reserve.liquidity_ata(&liquidator),
+                user_destination_collateral: withdraw_reserve.collateral_ata(&liquidator),
+                user_destination_liquidity: withdraw_reserve.liquidity_ata(&liquidator),
+                collateral_token_program: token::ID,
+                repay_liquidity_token_program: repay_reserve.liquidity_token_program,
+                withdraw_liquidity_token_program: withdraw_reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoLiquidateObligationAndRedeemReserveCollateral {
+                liquidity_amount: amount,
+                min_acceptable_received_liquidity_amount,
+                max_allowed_ltv_override_percent,
+            },
+        )
+    }
+
+    /// Repay `repay_amount` of `owner`'s debt then withdraw `withdraw_collateral_amount` from
+    /// their obligation, redeemed into their liquidity ATA. `collateral_farm` and `debt_farm` are
+    /// the withdraw reserve's collateral farm and the repay reserve's debt farm, if they have
+    /// them, and `obligation_reserves` the obligation's reserves after the repayment.
+    #[allow(clippy::too_many_arguments)]
+    pub fn repay_and_withdraw(
+        &self,
+        owner: Pubkey,
+        repay_reserve: &Reserve,
+        withdraw_reserve: &Reserve,
+        repay_amount: u64,
+        withdraw_collateral_amount: u64,
+        collateral_farm: Option<Pubkey>,
+        debt_farm: Option<Pubkey>,
+        obligation_reserves: &[Pubkey],
+    ) -> Instruction {
+        let lending_market = repay_reserve.lending_market;
+        let obligation = self.obligation(&owner, &lending_market);
+        let farm_user_state =
+            |farm: Option<Pubkey>| farm.map(|farm| pda::farms::obligation_farm(&self.farms_program, &farm, &obligation));
+        let mut ix = build(
+            accounts::KaminoRepayAndWithdrawAndRedeem {
+                owner,
+                user_position: pda::user_position(&owner, &lending_market),
+                user_borrows: pda::user_borrows(&owner),
+                config: pda::config(),
+                registry: pda::registry(),
+                withdraw_reserve_usage: pda::reserve_usage(&withdraw_reserve.address),
+                obligation,
+                lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(&self.kamino_program, &lending_market),
+                repay_reserve: repay_reserve.address,
+                repay_reserve_liquidity_mint: repay_reserve.liquidity_mint,
+                repay_reserve_liquidity_supply: repay_reserve.liquidity_supply,
+                user_source_liquidity: repay_reserve.liquidity_ata(&owner),
+                withdraw_reserve: withdraw_reserve.address,
+                withdraw_reserve_liquidity_mint: withdraw_reserve.liquidity_mint,
+                withdraw_reserve_collateral_supply: withdraw_reserve.collateral_supply,
+                withdraw_reserve_collateral_mint: withdraw_reserve.collateral_mint,
+                withdraw_reserve_liquidity_supply: withdraw_reserve.liquidity_supply,
+                user_destination_liquidity: withdraw_reserve.liquidity_ata(&owner),
+                collateral_obligation_farm_user_state: farm_user_state(collateral_farm),
+                collateral_reserve_farm_state: collateral_farm,
+                debt_obligation_farm_user_state: farm_user_state(debt_farm),
+                debt_reserve_farm_state: debt_farm,
+                farms_program: self.farms_program,
+                collateral_token_program: token::ID,
+                repay_liquidity_token_program: repay_reserve.liquidity_token_program,
+                withdraw_liquidity_token_program: withdraw_reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoRepayAndWithdrawAndRedeem { repay_amount, withdraw_collateral_amount },
+        );
+        ix.accounts
+            .extend(obligation_reserves.iter().map(|reserve| AccountMeta::new_readonly(*reserve, false)));
+        ix
+    }
+
+    /// Deposit `liquidity_amount` from `owner`'s liquidity ATA as collateral in `deposit_reserve`
+    /// then withdraw `withdraw_collateral_amount` from `withdraw_reserve`, redeemed into their
+    /// liquidity ATA. `deposit_farm` and `withdraw_farm` are the reserves' collateral farms, if
+    /// they have them, and `obligation_reserves` the obligation's reserves after the deposit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_and_withdraw(
+        &self,
+        owner: Pubkey,
+        deposit_reserve: &Reserve,
+        withdraw_reserve: &Reserve,
+        liquidity_amount: u64,
+        withdraw_collateral_amount: u64,
+        deposit_farm: Option<Pubkey>,
+        withdraw_farm: Option<Pubkey>,
+        obligation_reserves: &[Pubkey],
+    ) -> Instruction {
+        let lending_market = deposit_reserve.lending_market;
+        let obligation = self.obligation(&owner, &lending_market);
+        let farm_user_state =
+            |farm: Option<Pubkey>| farm.map(|farm| pda::farms::obligation_farm(&self.farms_program, &farm, &obligation));
+        let mut ix = build(
+            accounts::KaminoDepositAndWithdraw {
+                owner,
+                user_position: pda::user_position(&owner, &lending_market),
+                user_borrows: pda::user_borrows(&owner),
+                config: pda::config(),
+                registry: pda::registry(),
+                deposit_reserve_usage: pda::reserve_usage(&deposit_reserve.address),
+                withdraw_reserve_usage: pda::reserve_usage(&withdraw_reserve.address),
+                obligation,
+                lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(&self.kamino_program, &lending_market),
+                deposit_reserve: deposit_reserve.address,
+                deposit_reserve_liquidity_mint: deposit_reserve.liquidity_mint,
+                deposit_reserve_liquidity_supply: deposit_reserve.liquidity_supply,
+                deposit_reserve_collateral_mint: deposit_reserve.collateral_mint,
+                deposit_reserve_collateral_supply: deposit_reserve.collateral_supply,
+                user_source_liquidity: deposit_reserve.liquidity_ata(&owner),
+                withdraw_reserve: withdraw_reserve.address,
+                withdraw_reserve_liquidity_mint: withdraw_reserve.liquidity_mint,
+                withdraw_reserve_collateral_supply: withdraw_reserve.collateral_supply,
+                withdraw_reserve_collateral_mint: withdraw_reserve.collateral_mint,
+                withdraw_reserve_liquidity_supply: withdraw_reserve.liquidity_supply,
+                user_destination_liquidity: withdraw_reserve.liquidity_ata(&owner),
+                deposit_obligation_farm_user_state: farm_user_state(deposit_farm),
+                deposit_reserve_farm_state: deposit_farm,
+                withdraw_obligation_farm_user_state: farm_user_state(withdraw_farm),
+                withdraw_reserve_farm_state: withdraw_farm,
+                farms_program: self.farms_program,
+                collateral_token_program: token::ID,
+                deposit_liquidity_token_program: deposit_reserve.liquidity_token_program,
+                withdraw_liquidity_token_program: withdraw_reserve.liquidity_token_program,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoDepositAndWithdraw { liquidity_amount, withdraw_collateral_amount },
+        );
+        ix.accounts
+            .extend(obligation_reserves.iter().map(|reserve| AccountMeta::new_readonly(*reserve, false)));
+        ix
+    }
+
+    /// Run `steps` on `owner`'s vanilla obligation in one instruction, with liquidity moving
+    /// through their ATAs. `other_reserves` lists the obligation's reserves no step touches,
+    /// with their oracles, so the batch can refresh the obligation.
+    pub fn batch(
+        &self,
+        owner: Pubkey,
+        lending_market: Pubkey,
+        steps: &[BatchStep],
+        other_reserves: &[(Pubkey, BorrowOracles)],
+    ) -> Instruction {
+        let mut ix = build(
+            accounts::KaminoBatch {
+                owner,
+                user_position: pda::user_position(&owner, &lending_market),
+                user_borrows: pda::user_borrows(&owner),
+                config: pda::config(),
+                registry: pda::registry(),
+                obligation: self.obligation(&owner, &lending_market),
+                lending_market,
+                lending_market_authority: pda::kamino::lending_market_authority(&self.kamino_program, &lending_market),
+                collateral_token_program: token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::KaminoBatch { actions: steps.iter().map(|step| step.action).collect() },
+        );
+
+        // Absent oracles are passed as the Kamino program id, which is how Kamino reads them
+        let oracle_metas = |oracles: &BorrowOracles| {
+            [oracles.pyth_price, oracles.switchboard_price, oracles.switchboard_twap, oracles.scope_prices]
+                .map(|oracle| AccountMeta::new_readonly(oracle.unwrap_or(self.kamino_program), false))
+        };
+        for step in steps {
+            let reserve = &step.reserve;
+            ix.accounts.extend([
+                AccountMeta::new(reserve.address, false),
+                AccountMeta::new(pda::reserve_usage(&reserve.address), false),
+                AccountMeta::new_readonly(reserve.liquidity_mint, false),
+                AccountMeta::new(reserve.liquidity_supply, false),
+                AccountMeta::new(reserve.collateral_mint, false),
+                AccountMeta::new(reserve.collateral_supply, false),
+                AccountMeta::new(reserve.fee_vault, false),
+                AccountMeta::new(reserve.liquidity_ata(&owner), false),
+                AccountMeta::new_readonly(reserve.liquidity_token_program, false),
+            ]);
+            ix.accounts.extend(oracle_metas(&step.oracles));
+        }
+        for (reserve, oracles) in other_reserves {
+            ix.accounts.push(AccountMeta::new(*reserve, false));
+            ix.accounts.extend(oracle_metas(oracles));
+        }
+        ix
+    }
+
+    /// Record a Kamino flash loan taken in the same transaction
+    pub fn record_flash_loan(
+        &self,
+        user: Pubkey,
+        reserve: &Reserve,
+        borrow_instruction_index: u8,
+        repay_instruction_index: u8,
+    ) -> Instruction {
+        build(
+            accounts::RecordFlashLoan {
+                user,
+                reserve: reserve.address,
+                registry: pda::registry(),
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: self.kamino_program,
+                event_authority: pda::event_authority(),
+                program: PROGRAM_ID,
+            },
+            instruction::RecordFlashLoan { borrow_instruction_index, repay_instruction_index },
+        )
+    }
+
+    /// Enroll `owner`'s vanilla obligation in the reserve farm at `farm_state`
+    pub fn init_obligation_farms(
+        &self,
+        payer: Pubkey,
+        owner: Pubkey,
+        reserve: &Reserve,
+        farm_state: Pubkey,
+        mode: ReserveFarmKind,
+    ) -> Instruction {
+        let obligation = self.obligation(&owner, &reserve.lending_market);
+        build(
+            accounts::KaminoInitObligationFarmsForReserve {
+                payer,
+                owner,
+                registry: pda::registry(),
+                obligation,
+                lending_market_authority: pda::kamino::lending_market_authority(
+                    &self.kamino_program,
+                    &reserve.lending_market,
+                ),
+                reserve: reserve.address,
+                reserve_farm_state: farm_state,
+                obligation_farm: pda::farms::obligation_farm(&self.farms_program, &farm_state, &obligation),
+                lending_market: reserve.lending_market,
+                farms_program: self.farms_program,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+                kamino_lending_program: self.kamino_program,
+            },
+            instruction::KaminoInitObligationFarmsForReserve { mode },
+        )
+    }
+
+    /// Refresh `owner`'s stake in the reserve farm at `farm_state`, signed by a keeper
+    pub fn refresh_obligation_farms(
+        &self,
+        crank: Pubkey,
+        owner: Pubkey,
+        reserve: &Reserve,
+        farm_state: Pubkey,
+        mode: ReserveFarmKind,
+    ) -> Instruction {
+        let obligation = self.obligation(&owner, &reserve.lending_market);
+        build(
+            accounts::KaminoRefreshObligationFarmsForReserve {
+                crank,
+                keeper_role: pda::role(Role::Keeper, &crank),
+                registry: pda::registry(),
+                obligation,
+                lending_market_authority: pda::kamino::lending_market_authority(
+                    &self.kamino_program,
+                    &reserve.lending_market,
+                ),
+                reserve: reserve.address,
+                reserve_farm_state: farm_state,
+                obligation_farm_user_state: pda::farms::obligation_farm(
+                    &self.farms_program,
+                    &farm_state,
+                    &obligation,
+                ),
+                lending_market: reserve.lending_market,
+                farms_program: self.farms_program,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+                kamino_lending_program: self.kamino_program,
+            },
+            instruction::KaminoRefreshObligationFarmsForReserve { mode },
+        )
+    }
+
+    /// Harvest `owner`'s reward into their ATA, optionally forwarding it to `rewards_destination`.
+    /// While the config charges a fee, pass its `fee_recipient`, whose ATA receives the fee.
+    pub fn claim_farm_rewards(
+        &self,
+        owner: Pubkey,
+        lending_market: &Pubkey,
+        reward: FarmReward,
+        scope_prices: Option<Pubkey>,
+        rewards_destination: Option<Pubkey>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Instruction {
+        let obligation = self.obligation(&owner, lending_market);
+        build(
+            accounts::KaminoClaimFarmRewards {
+                owner,
+                config: pda::config(),
+                registry: pda::registry(),
+                reserve: reward.reserve,
+                lending_market: *lending_market,
+                obligation_farm_user_state: pda::farms::obligation_farm(
+                    &self.farms_program,
+                    &reward.farm_state,
+                    &obligation,
+                ),
+                farm_state: reward.farm_state,
+                global_config: reward.global_config,
+                reward_mint: reward.reward_mint,
+                user_reward_ata: pda::associated_token_address(
+                    &owner,
+                    &reward.reward_mint,
+                    &reward.reward_token_program,
+                ),
+                rewards_vault: pda::farms::rewards_vault(&self.farms_program, &reward.farm_state, &reward.reward_mint),
+                rewards_treasury_vault: pda::farms::rewards_treasury_vault(
+                    &self.farms_program,
+                    &reward.global_config,
+                    &reward.reward_mint,
+                ),
+                farm_vaults_authority: pda::farms::vaults_authority(&self.farms_program, &reward.farm_state),
+                scope_prices,
+                rewards_destination,
+                fee_account: fee_recipient.map(|recipient| {
+                    pda::associated_token_address(&recipient, &reward.reward_mint, &reward.reward_token_program)
+                }),
+                token_program: reward.reward_token_program,
+                farms_program: self.farms_program,
+                kamino_lending_program: self.kamino_program,
+            },
+            instruction::KaminoClaimFarmRewards { reward_index: reward.reward_index },
+        )
+    }
+}
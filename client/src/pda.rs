@@ -0,0 +1,97 @@
+//! Addresses of this program's accounts and of the Kamino accounts its instructions take
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use liquidity_lending::state::{
+    Role, CONFIG_CHANGE_SEED, CONFIG_SEED, REGISTRY_SEED, RESERVE_USAGE_SEED, ROLE_SEED,
+    USER_BORROWS_SEED, USER_POSITION_SEED,
+};
+use liquidity_lending::ID as PROGRAM_ID;
+
+use crate::ASSOCIATED_TOKEN_PROGRAM_ID;
+
+/// The program configuration
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], &PROGRAM_ID).0
+}
+
+/// The registry of approved markets and reserves
+pub fn registry() -> Pubkey {
+    Pubkey::find_program_address(&[REGISTRY_SEED], &PROGRAM_ID).0
+}
+
+/// The assignment of `role` to `holder`
+pub fn role(role: Role, holder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ROLE_SEED, &role.seed(), holder.as_ref()], &PROGRAM_ID).0
+}
+
+/// The queued configuration change with the given id
+pub fn config_change(id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_CHANGE_SEED, &id.to_le_bytes()], &PROGRAM_ID).0
+}
+
+/// Usage of an approved reserve's limits
+pub fn reserve_usage(reserve: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RESERVE_USAGE_SEED, reserve.as_ref()], &PROGRAM_ID).0
+}
+
+/// The position ledger of `owner` in `lending_market`
+pub fn user_position(owner: &Pubkey, lending_market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[USER_POSITION_SEED, owner.as_ref(), lending_market.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// What `owner` has borrowed across their obligations
+pub fn user_borrows(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_BORROWS_SEED, owner.as_ref()], &PROGRAM_ID).0
+}
+
+/// The authority Anchor signs event CPIs with
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &PROGRAM_ID).0
+}
+
+/// This program's data account, holding its upgrade authority
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// The associated token account of `owner` for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Kamino lending seed schemes, shared with the program
+pub use liquidity_lending::kamino_pda as kamino;
+
+/// Kamino farms seed schemes
+pub mod farms {
+    use super::*;
+
+    /// The farm user state of an obligation
+    pub fn obligation_farm(program: &Pubkey, farm_state: &Pubkey, obligation: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"user", farm_state.as_ref(), obligation.as_ref()], program).0
+    }
+
+    /// The authority over a farm's vaults
+    pub fn vaults_authority(program: &Pubkey, farm_state: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"authority", farm_state.as_ref()], program).0
+    }
+
+    /// The vault holding a farm's rewards in `reward_mint`
+    pub fn rewards_vault(program: &Pubkey, farm_state: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"rvault", farm_state.as_ref(), reward_mint.as_ref()], program).0
+    }
+
+    /// The treasury vault collecting the farms' cut of rewards in `reward_mint`
+    pub fn rewards_treasury_vault(program: &Pubkey, global_config: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"tvault", global_config.as_ref(), reward_mint.as_ref()], program).0
+    }
+}
@@ -0,0 +1,146 @@
+//! Borrow and supply rates of a reserve from its utilization and borrow rate curve, now and
+//! after a hypothetical deposit, withdrawal, borrow or repayment. Utilization and the curve
+//! are evaluated in Kamino's fixed point so rates match what `refreshReserve` accrues.
+
+use anchor_lang::prelude::*;
+use fixed::types::U68F60;
+use liquidity_lending::LendingError;
+
+use crate::kamino::{BorrowRateCurve, Reserve, ReserveLiquidity};
+
+/// Kamino's `Fraction`: 68 integer and 60 fractional bits, the scale of its `_sf` fields
+pub type Fraction = U68F60;
+
+/// Slots per year Kamino converts annual rates with, at 500ms slots
+pub const SLOTS_PER_YEAR: u64 = 63_072_000;
+
+/// Liquidity owned by depositors, borrowed or not, net of fees owed to the protocol and referrers
+pub fn total_supply(liquidity: &ReserveLiquidity) -> Fraction {
+    Fraction::from_bits(liquidity.total_supply_sf())
+}
+
+/// Share of the total supply that is borrowed; zero without supply
+pub fn utilization_rate(liquidity: &ReserveLiquidity) -> Fraction {
+    let total_supply = total_supply(liquidity);
+    if total_supply == Fraction::ZERO {
+        return Fraction::ZERO;
+    }
+    Fraction::from_bits(liquidity.borrowed_amount_sf) / total_supply
+}
+
+fn from_bps(bps: u32) -> Fraction {
+    Fraction::from_num(bps) / 10_000
+}
+
+/// Borrow APR the curve gives at `utilization`, capped at full utilization. Fails with
+/// `InvalidAccountState` if the curve does not cover the utilization.
+pub fn borrow_rate(curve: &BorrowRateCurve, utilization: Fraction) -> Result<Fraction> {
+    let utilization = utilization.min(Fraction::ONE);
+    let utilization_bps: u32 = (utilization * 10_000).round().to_num();
+
+    let (start, end) = curve
+        .points
+        .windows(2)
+        .map(|window| (window[0], window[1]))
+        .find(|(start, end)| {
+            utilization_bps >= start.utilization_rate_bps && utilization_bps <= end.utilization_rate_bps
+        })
+        .ok_or_else(|| error!(LendingError::InvalidAccountState))?;
+    if utilization_bps == start.utilization_rate_bps {
+        return Ok(from_bps(start.borrow_rate_bps));
+    } else if utilization_bps == end.utilization_rate_bps {
+        return Ok(from_bps(end.borrow_rate_bps));
+    }
+
+    let slope = Fraction::from_num(end.borrow_rate_bps - start.borrow_rate_bps)
+        / Fraction::from_num(end.utilization_rate_bps - start.utilization_rate_bps);
+    let progress = utilization - from_bps(start.utilization_rate_bps);
+    Ok(from_bps(start.borrow_rate_bps) + progress * slope)
+}
+
+/// APY of an APR compounded every slot
+pub fn apy(apr: f64) -> f64 {
+    (SLOTS_PER_YEAR as f64 * (apr / SLOTS_PER_YEAR as f64).ln_1p()).exp_m1()
+}
+
+/// Rates of a reserve, as fractions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReserveRates {
+    pub utilization: f64,
+    /// Curve rate plus the reserve's fixed host rate
+    pub borrow_apr: f64,
+    pub borrow_apy: f64,
+    /// Interest paid by borrowers spread over the supply, after the protocol's take
+    pub supply_apr: f64,
+    pub supply_apy: f64,
+}
+
+/// Current rates of `reserve`, as of its last refresh
+pub fn reserve_rates(reserve: &Reserve) -> Result<ReserveRates> {
+    rates_at(reserve, &reserve.liquidity)
+}
+
+/// A hypothetical change to a reserve's liquidity, in base units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityChange {
+    Deposit(u64),
+    Withdraw(u64),
+    Borrow(u64),
+    Repay(u64),
+}
+
+/// Rates of `reserve` after `change`. Withdrawals and borrows beyond the available
+/// liquidity fail with `InsufficientFunds`; repayments beyond the debt clear it.
+pub fn projected_rates(reserve: &Reserve, change: LiquidityChange) -> Result<ReserveRates> {
+    let mut liquidity = reserve.liquidity;
+    match change {
+        LiquidityChange::Deposit(amount) => {
+            liquidity.available_amount = liquidity
+                .available_amount
+                .checked_add(amount)
+                .ok_or_else(|| error!(LendingError::MathOverflow))?;
+        }
+        LiquidityChange::Withdraw(amount) => {
+            liquidity.available_amount = liquidity
+                .available_amount
+                .checked_sub(amount)
+                .ok_or_else(|| error!(LendingError::InsufficientFunds))?;
+        }
+        LiquidityChange::Borrow(amount) => {
+            liquidity.available_amount = liquidity
+                .available_amount
+                .checked_sub(amount)
+                .ok_or_else(|| error!(LendingError::InsufficientFunds))?;
+            liquidity.borrowed_amount_sf = liquidity
+                .borrowed_amount_sf
+                .checked_add(Fraction::from_num(amount).to_bits())
+                .ok_or_else(|| error!(LendingError::MathOverflow))?;
+        }
+        LiquidityChange::Repay(amount) => {
+            let repaid_sf = Fraction::from_num(amount).to_bits().min(liquidity.borrowed_amount_sf);
+            liquidity.available_amount = liquidity
+                .available_amount
+                .checked_add(Fraction::from_bits(repaid_sf).ceil().to_num())
+                .ok_or_else(|| error!(LendingError::MathOverflow))?;
+            liquidity.borrowed_amount_sf -= repaid_sf;
+        }
+    }
+    rates_at(reserve, &liquidity)
+}
+
+fn rates_at(reserve: &Reserve, liquidity: &ReserveLiquidity) -> Result<ReserveRates> {
+    let utilization = utilization_rate(liquidity).min(Fraction::ONE);
+    let curve_rate = borrow_rate(&reserve.config.borrow_rate_curve, utilization)?;
+    let host_rate = Fraction::from_num(reserve.config.host_fixed_interest_rate_bps) / 10_000;
+    let take_rate = Fraction::from_num(reserve.config.protocol_take_rate_pct.min(100)) / 100;
+
+    let borrow_apr = (curve_rate + host_rate).to_num::<f64>();
+    let supply_apr = (curve_rate * utilization * (Fraction::ONE - take_rate)).to_num::<f64>();
+    Ok(ReserveRates {
+        utilization: utilization.to_num(),
+        borrow_apr,
+        borrow_apy: apy(borrow_apr),
+        supply_apr,
+        supply_apy: apy(supply_apr),
+    })
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use liquidity_lending_client::analytics::analyze;
+use liquidity_lending_client::kamino::{
+    ElevationGroup, LendingMarket, Obligation, ObligationCollateral, ObligationLiquidity, Reserve,
+};
+
+fn sf(value: f64) -> u128 {
+    (value * (1u128 << 60) as f64) as u128
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+fn reserve(price: f64, decimals: u64, ltv: u8, threshold: u8, borrow_factor: u64) -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.liquidity.market_price_sf = sf(price);
+    reserve.liquidity.mint_decimals = decimals;
+    reserve.liquidity.available_amount = u64::MAX;
+    reserve.config.loan_to_value_pct = ltv;
+    reserve.config.liquidation_threshold_pct = threshold;
+    reserve.config.borrow_factor_pct = borrow_factor;
+    reserve.config.borrow_limit = u64::MAX;
+    reserve
+}
+
+/// 10 SOL at $100 deposited, 500 USDC borrowed
+fn fixture() -> (Obligation, BTreeMap<Pubkey, Reserve>, Pubkey, Pubkey) {
+    let sol = Pubkey::new_unique();
+    let usdc = Pubkey::new_unique();
+    let reserves = BTreeMap::from([
+        (sol, reserve(100.0, 9, 75, 85, 100)),
+        (usdc, reserve(1.0, 6, 80, 90, 120)),
+    ]);
+
+    let mut obligation = Obligation::default();
+    obligation.deposits[0] = ObligationCollateral {
+        deposit_reserve: sol,
+        market_value_sf: sf(1000.0),
+        ..ObligationCollateral::default()
+    };
+    obligation.borrows[0] = ObligationLiquidity {
+        borrow_reserve: usdc,
+        market_value_sf: sf(500.0),
+        ..ObligationLiquidity::default()
+    };
+    (obligation, reserves, sol, usdc)
+}
+
+#[test]
+fn test_position_health() {
+    let (obligation, reserves, sol, _) = fixture();
+    let analytics = analyze(&obligation, &reserves, &LendingMarket::default()).unwrap();
+
+    assert_close(analytics.deposited_value, 1000.0);
+    assert_close(analytics.borrowed_value, 500.0);
+    // USDC borrows count 120%
+    assert_close(analytics.borrow_factor_adjusted_debt, 600.0);
+    assert_close(analytics.allowed_borrow_value, 750.0);
+    assert_close(analytics.unhealthy_borrow_value, 850.0);
+    assert_close(analytics.ltv, 0.6);
+    assert_close(analytics.health_factor, 850.0 / 600.0);
+
+    // 10 SOL * 85% must cover $600
+    let liquidation = analytics.liquidation_prices[0];
+    assert_eq!(liquidation.reserve, sol);
+    assert_close(liquidation.current_price, 100.0);
+    assert_close(liquidation.liquidation_price.unwrap(), 600.0 / 8.5);
+}
+
+#[test]
+fn test_borrowing_power() {
+    let (obligation, mut reserves, sol, usdc) = fixture();
+    let analytics = analyze(&obligation, &reserves, &LendingMarket::default()).unwrap();
+
+    // $150 of headroom: 125 USDC at a 120% borrow factor, or 1.5 SOL
+    let power: BTreeMap<_, _> = analytics.borrowing_power.iter().map(|power| (power.reserve, *power)).collect();
+    assert_close(power[&usdc].value, 125.0);
+    assert_eq!(power[&usdc].amount, 125_000_000);
+    assert_close(power[&sol].value, 150.0);
+    assert_eq!(power[&sol].amount, 1_500_000_000);
+
+    // Capped by what the reserve has left
+    reserves.get_mut(&sol).unwrap().liquidity.available_amount = 1_000_000_000;
+    let analytics = analyze(&obligation, &reserves, &LendingMarket::default()).unwrap();
+    let sol_power = analytics.borrowing_power.iter().find(|power| power.reserve == sol).unwrap();
+    assert_eq!(sol_power.amount, 1_000_000_000);
+}
+
+#[test]
+fn test_elevation_group() {
+    let (mut obligation, reserves, sol, usdc) = fixture();
+    obligation.elevation_group = 1;
+    let mut market = LendingMarket::default();
+    market.elevation_groups[0] = ElevationGroup {
+        id: 1,
+        ltv_pct: 90,
+        liquidation_threshold_pct: 95,
+        allow_new_loans: 1,
+        debt_reserve: usdc,
+        ..ElevationGroup::default()
+    };
+
+    let analytics = analyze(&obligation, &reserves, &market).unwrap();
+    // The group's ratios replace the reserve's, and borrows count at face value
+    assert_close(analytics.borrow_factor_adjusted_debt, 500.0);
+    assert_close(analytics.allowed_borrow_value, 900.0);
+    assert_close(analytics.health_factor, 950.0 / 500.0);
+
+    let power: BTreeMap<_, _> = analytics.borrowing_power.iter().map(|power| (power.reserve, *power)).collect();
+    assert_eq!(power[&usdc].amount, 400_000_000);
+    // Only the group's debt reserve can be borrowed
+    assert_eq!(power[&sol].amount, 0);
+
+    obligation.elevation_group = 2;
+    assert!(analyze(&obligation, &reserves, &market).is_err());
+}
+
+#[test]
+fn test_no_debt() {
+    let (mut obligation, reserves, _, _) = fixture();
+    obligation.borrows[0] = ObligationLiquidity::default();
+
+    let analytics = analyze(&obligation, &reserves, &LendingMarket::default()).unwrap();
+    assert_eq!(analytics.health_factor, f64::INFINITY);
+    assert_close(analytics.ltv, 0.0);
+    assert_eq!(analytics.liquidation_prices[0].liquidation_price, None);
+}
+
+#[test]
+fn test_missing_reserve() {
+    let (obligation, mut reserves, sol, _) = fixture();
+    reserves.remove(&sol);
+    assert!(analyze(&obligation, &reserves, &LendingMarket::default()).is_err());
+}
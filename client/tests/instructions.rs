@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::Discriminator;
+use anchor_spl::{token, token_2022};
+use liquidity_lending::batch::{BatchAction, BatchActionKind, BATCH_ACTION_ACCOUNTS, BATCH_RESERVE_ACCOUNTS};
+use liquidity_lending::state::Role;
+use liquidity_lending::{instruction, ID as PROGRAM_ID};
+use liquidity_lending_client::{pda, BatchStep, BorrowOracles, LendingClient, KAMINO_LENDING_PROGRAM_ID};
+
+fn keys(ix: &anchor_lang::solana_program::instruction::Instruction) -> Vec<Pubkey> {
+    ix.accounts.iter().map(|meta| meta.pubkey).collect()
+}
+
+#[test]
+fn test_reserve_accounts_are_derived() {
+    let client = LendingClient::default();
+    let market = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let reserve = client.reserve(Pubkey::new_unique(), market, mint, token::ID);
+
+    let (supply, _) = Pubkey::find_program_address(
+        &[b"reserve_liq_supply", market.as_ref(), mint.as_ref()],
+        &KAMINO_LENDING_PROGRAM_ID,
+    );
+    assert_eq!(reserve.liquidity_supply, supply);
+
+    let derived = [reserve.liquidity_supply, reserve.fee_vault, reserve.collateral_mint, reserve.collateral_supply];
+    for (index, key) in derived.iter().enumerate() {
+        assert!(!key.is_on_curve());
+        assert!(!derived[index + 1..].contains(key));
+    }
+}
+
+#[test]
+fn test_borrow_accounts() {
+    let client = LendingClient::default();
+    let owner = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let scope_prices = Pubkey::new_unique();
+    let reserve = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token_2022::ID);
+
+    let oracles = BorrowOracles { scope_prices: Some(scope_prices), ..BorrowOracles::default() };
+    let ix = client.borrow(owner, &reserve, 1_000, oracles, None);
+
+    assert_eq!(ix.program_id, PROGRAM_ID);
+    assert_eq!(ix.data[..8], *instruction::KaminoBorrowObligationLiquidity::DISCRIMINATOR);
+    assert_eq!(ix.data[8..], 1_000u64.to_le_bytes());
+    assert_eq!(
+        keys(&ix),
+        vec![
+            owner,
+            pda::user_position(&owner, &market),
+            pda::user_borrows(&owner),
+            pda::config(),
+            pda::registry(),
+            pda::reserve_usage(&reserve.address),
+            client.obligation(&owner, &market),
+            market,
+            pda::kamino::lending_market_authority(&KAMINO_LENDING_PROGRAM_ID, &market),
+            reserve.address,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            reserve.fee_vault,
+            reserve.liquidity_ata(&owner),
+            // Absent optional accounts are passed as the program id
+            PROGRAM_ID,
+            PROGRAM_ID,
+            PROGRAM_ID,
+            PROGRAM_ID,
+            scope_prices,
+            token_2022::ID,
+            sysvar::instructions::ID,
+            KAMINO_LENDING_PROGRAM_ID,
+            pda::event_authority(),
+            PROGRAM_ID,
+        ]
+    );
+    assert!(ix.accounts[0].is_signer);
+    assert!(ix.accounts[1..].iter().all(|meta| !meta.is_signer));
+}
+
+#[test]
+fn test_referrer_token_state() {
+    let client = LendingClient::default();
+    let referrer = Pubkey::new_unique();
+    let reserve = client.reserve(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), token::ID);
+
+    let ix = client.borrow(Pubkey::new_unique(), &reserve, 1, BorrowOracles::default(), Some(referrer));
+    let referrer_token_state =
+        pda::kamino::referrer_token_state(&KAMINO_LENDING_PROGRAM_ID, &referrer, &reserve.liquidity_mint);
+    assert_eq!(ix.accounts[14].pubkey, referrer_token_state);
+    assert!(ix.accounts[14].is_writable);
+}
+
+#[test]
+fn test_guardian_role_is_optional() {
+    let client = LendingClient::default();
+    let authority = Pubkey::new_unique();
+    let target = liquidity_lending::state::PauseTarget::Borrows;
+
+    let as_admin = client.set_pause(authority, target, true, false);
+    let as_guardian = client.set_pause(authority, target, true, true);
+    assert_eq!(as_admin.accounts[2].pubkey, PROGRAM_ID);
+    assert_eq!(as_guardian.accounts[2].pubkey, pda::role(Role::Guardian, &authority));
+}
+
+#[test]
+fn test_liquidate_uses_obligation_owner_position() {
+    let client = LendingClient::default();
+    let liquidator = Pubkey::new_unique();
+    let borrower = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let repay = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+    let withdraw = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+
+    let ix = client.liquidate(liquidator, borrower, &repay, &withdraw, 10, 9, 0);
+    let keys = keys(&ix);
+    assert_eq!(keys[0], liquidator);
+    assert_eq!(keys[1], client.obligation(&borrower, &market));
+    assert_eq!(keys[2], pda::user_position(&borrower, &market));
+    assert!(keys.contains(&repay.liquidity_ata(&liquidator)));
+    assert!(keys.contains(&withdraw.collateral_ata(&liquidator)));
+}
+
+#[test]
+fn test_batch_accounts() {
+    let client = LendingClient::default();
+    let owner = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let pyth_price = Pubkey::new_unique();
+    let other_reserve = Pubkey::new_unique();
+    let deposit_reserve = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+    let borrow_reserve = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token_2022::ID);
+
+    let steps = [
+        BatchStep {
+            reserve: deposit_reserve,
+            action: BatchAction { kind: BatchActionKind::Deposit, amount: 1_000 },
+            oracles: BorrowOracles::default(),
+        },
+        BatchStep {
+            reserve: borrow_reserve,
+            action: BatchAction { kind: BatchActionKind::Borrow, amount: 500 },
+            oracles: BorrowOracles { pyth_price: Some(pyth_price), ..BorrowOracles::default() },
+        },
+    ];
+    let ix = client.batch(owner, market, &steps, &[(other_reserve, BorrowOracles::default())]);
+
+    assert_eq!(ix.data[..8], *instruction::KaminoBatch::DISCRIMINATOR);
+    let fixed = 13;
+    assert_eq!(ix.accounts.len(), fixed + 2 * BATCH_ACTION_ACCOUNTS + BATCH_RESERVE_ACCOUNTS);
+    assert_eq!(ix.accounts[fixed - 2].pubkey, pda::event_authority());
+
+    let borrow = &ix.accounts[fixed + BATCH_ACTION_ACCOUNTS..fixed + 2 * BATCH_ACTION_ACCOUNTS];
+    assert_eq!(borrow[0].pubkey, borrow_reserve.address);
+    assert_eq!(borrow[1].pubkey, pda::reserve_usage(&borrow_reserve.address));
+    assert_eq!(borrow[7].pubkey, borrow_reserve.liquidity_ata(&owner));
+    assert_eq!(borrow[8].pubkey, token_2022::ID);
+    // Absent oracles are the Kamino program id, as Kamino's refreshReserve takes them
+    let oracles: Vec<Pubkey> = borrow[9..].iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(oracles, [pyth_price, KAMINO_LENDING_PROGRAM_ID, KAMINO_LENDING_PROGRAM_ID, KAMINO_LENDING_PROGRAM_ID]);
+    assert!(borrow[..8].iter().enumerate().all(|(index, meta)| meta.is_writable != (index == 2)));
+
+    let other = &ix.accounts[fixed + 2 * BATCH_ACTION_ACCOUNTS..];
+    assert_eq!(other[0].pubkey, other_reserve);
+    assert!(other[0].is_writable);
+}
+
+#[test]
+fn test_repay_and_withdraw_accounts() {
+    let client = LendingClient::default();
+    let owner = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let debt_farm = Pubkey::new_unique();
+    let repay = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token_2022::ID);
+    let withdraw = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+
+    let ix = client.repay_and_withdraw(owner, &repay, &withdraw, 10, 20, None, Some(debt_farm), &[withdraw.address]);
+    assert_eq!(ix.data[..8], *instruction::KaminoRepayAndWithdrawAndRedeem::DISCRIMINATOR);
+    let keys = keys(&ix);
+    assert_eq!(keys[5], pda::reserve_usage(&withdraw.address));
+    assert_eq!(keys[12], repay.liquidity_ata(&owner));
+    assert_eq!(keys[18], withdraw.liquidity_ata(&owner));
+    // Without a collateral farm both its accounts are left out, which Anchor encodes as this
+    // program's id
+    let obligation = client.obligation(&owner, &market);
+    assert_eq!(
+        keys[19..23],
+        [
+            PROGRAM_ID,
+            PROGRAM_ID,
+            pda::farms::obligation_farm(&client.farms_program, &debt_farm, &obligation),
+            debt_farm,
+        ]
+    );
+    let last = ix.accounts.last().unwrap();
+    assert_eq!(last.pubkey, withdraw.address);
+    assert!(!last.is_writable);
+}
+
+#[test]
+fn test_deposit_and_withdraw_accounts() {
+    let client = LendingClient::default();
+    let owner = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let deposit = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+    let withdraw = client.reserve(Pubkey::new_unique(), market, Pubkey::new_unique(), token::ID);
+    let obligation_reserves = [withdraw.address, deposit.address];
+
+    let ix = client.deposit_and_withdraw(owner, &deposit, &withdraw, 10, 20, None, None, &obligation_reserves);
+    assert_eq!(ix.data[..8], *instruction::KaminoDepositAndWithdraw::DISCRIMINATOR);
+    let keys = keys(&ix);
+    assert_eq!(keys[5], pda::reserve_usage(&deposit.address));
+    assert_eq!(keys[6], pda::reserve_usage(&withdraw.address));
+    assert_eq!(keys[14], deposit.collateral_supply);
+    assert_eq!(keys[15], deposit.liquidity_ata(&owner));
+    assert_eq!(keys[21], withdraw.liquidity_ata(&owner));
+    assert_eq!(keys[keys.len() - 2..], obligation_reserves);
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use liquidity_lending::kamino::{discriminator, reserve_layout};
+use liquidity_lending_client::kamino::{
+    ElevationGroup, LendingMarket, Obligation, ObligationCollateral, Reserve, ReserveConfig,
+    ReserveElevationConfig, ReserveLiquidity,
+};
+
+/// Sizes of Kamino accounts, discriminator included
+const OBLIGATION_SIZE: usize = 3344;
+const RESERVE_SIZE: usize = 8624;
+const LENDING_MARKET_SIZE: usize = 4664;
+
+fn write<T: AnchorSerialize>(data: &mut [u8], offset: usize, value: &T) {
+    let bytes = value.try_to_vec().unwrap();
+    data[8 + offset..8 + offset + bytes.len()].copy_from_slice(&bytes);
+}
+
+#[test]
+fn test_obligation_round_trip() {
+    let mut obligation = Obligation {
+        owner: Pubkey::new_unique(),
+        deposited_value_sf: 7 << 60,
+        unhealthy_borrow_value_sf: 3 << 60,
+        elevation_group: 2,
+        highest_borrow_factor_pct: 150,
+        ..Obligation::default()
+    };
+    obligation.deposits[1] = ObligationCollateral {
+        deposit_reserve: Pubkey::new_unique(),
+        deposited_amount: 42,
+        ..ObligationCollateral::default()
+    };
+
+    let mut data = vec![0u8; OBLIGATION_SIZE];
+    data[..8].copy_from_slice(&discriminator::OBLIGATION);
+    write(&mut data, 0, &obligation);
+
+    let decoded = Obligation::decode(&data).unwrap();
+    assert_eq!(decoded, obligation);
+    assert_eq!(decoded.active_deposits().count(), 1);
+    assert_eq!(decoded.active_borrows().count(), 0);
+    assert!(Obligation::decode(&data[..1000]).is_err());
+}
+
+#[test]
+fn test_reserve_offsets() {
+    let liquidity = ReserveLiquidity {
+        mint_pubkey: Pubkey::new_unique(),
+        available_amount: 5,
+        market_price_sf: 9 << 60,
+        mint_decimals: 6,
+        token_program: Pubkey::new_unique(),
+        ..ReserveLiquidity::default()
+    };
+    let config = ReserveConfig {
+        loan_to_value_pct: 70,
+        borrow_factor_pct: 125,
+        borrow_limit: 1_000,
+        ..ReserveConfig::default()
+    };
+    let elevation = ReserveElevationConfig {
+        elevation_groups: [1; 20],
+        borrow_limit_outside_elevation_group: 77,
+        ..ReserveElevationConfig::default()
+    };
+
+    let mut data = vec![0u8; RESERVE_SIZE];
+    data[..8].copy_from_slice(&discriminator::RESERVE);
+    write(&mut data, reserve_layout::LIQUIDITY, &liquidity);
+    write(&mut data, reserve_layout::CONFIG, &config);
+    write(&mut data, reserve_layout::CONFIG_ELEVATION_GROUPS, &elevation);
+
+    let reserve = Reserve::decode(&data).unwrap();
+    assert_eq!(reserve.liquidity, liquidity);
+    assert_eq!(reserve.config, config);
+    assert_eq!(reserve.elevation, elevation);
+    assert_eq!(reserve.liquidity.market_price(), 9.0);
+    // The borrow factor sits where the program's preflight reads it
+    assert_eq!(reserve_layout::CONFIG + 152, reserve_layout::CONFIG_BORROW_FACTOR_PCT);
+}
+
+#[test]
+fn test_lending_market_elevation_groups() {
+    let mut market = LendingMarket { referral_fee_bps: 100, ..LendingMarket::default() };
+    market.elevation_groups[2] = ElevationGroup { id: 3, ltv_pct: 90, ..ElevationGroup::default() };
+
+    let mut data = vec![0u8; LENDING_MARKET_SIZE];
+    data[..8].copy_from_slice(&discriminator::LENDING_MARKET);
+    write(&mut data, 0, &market);
+
+    let decoded = LendingMarket::decode(&data).unwrap();
+    assert_eq!(decoded, market);
+    assert_eq!(decoded.elevation_group(3).unwrap().ltv_pct, 90);
+    assert!(decoded.elevation_group(0).is_none());
+    assert!(decoded.elevation_group(1).is_none());
+    assert!(decoded.elevation_group(33).is_none());
+}
@@ -0,0 +1,98 @@
+use liquidity_lending_client::kamino::{BorrowRateCurve, CurvePoint, Reserve};
+use liquidity_lending_client::rates::{
+    apy, borrow_rate, projected_rates, reserve_rates, Fraction, LiquidityChange,
+};
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+}
+
+/// 0% at 0, 10% at 80% utilization, 100% at full utilization
+fn curve() -> BorrowRateCurve {
+    let mut points = [CurvePoint { utilization_rate_bps: 10_000, borrow_rate_bps: 10_000 }; 11];
+    points[0] = CurvePoint { utilization_rate_bps: 0, borrow_rate_bps: 0 };
+    points[1] = CurvePoint { utilization_rate_bps: 8_000, borrow_rate_bps: 1_000 };
+    BorrowRateCurve { points }
+}
+
+/// 1,000 tokens supplied, `borrowed` of them borrowed, 20% protocol take
+fn reserve(borrowed: u64) -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.liquidity.available_amount = 1_000 - borrowed;
+    reserve.liquidity.borrowed_amount_sf = Fraction::from_num(borrowed).to_bits();
+    reserve.config.borrow_rate_curve = curve();
+    reserve.config.protocol_take_rate_pct = 20;
+    reserve
+}
+
+#[test]
+fn test_borrow_rate_curve() {
+    let curve = curve();
+    let rate = |utilization: f64| borrow_rate(&curve, Fraction::from_num(utilization)).unwrap().to_num::<f64>();
+
+    // Points are returned exactly, and rates are linear between them
+    assert_eq!(rate(0.0), 0.0);
+    assert_close(rate(0.8), 0.1);
+    assert_eq!(rate(1.0), 1.0);
+    assert_close(rate(0.4), 0.05);
+    assert_close(rate(0.9), 0.55);
+    // Utilization beyond 100% is capped
+    assert_eq!(rate(1.5), 1.0);
+
+    // A curve that stops short of full utilization is invalid
+    let mut short = curve;
+    short.points = [CurvePoint { utilization_rate_bps: 5_000, borrow_rate_bps: 500 }; 11];
+    short.points[0] = CurvePoint::default();
+    assert!(borrow_rate(&short, Fraction::from_num(0.6)).is_err());
+}
+
+#[test]
+fn test_reserve_rates() {
+    let rates = reserve_rates(&reserve(400)).unwrap();
+    assert_close(rates.utilization, 0.4);
+    assert_close(rates.borrow_apr, 0.05);
+    // Borrowers' 5% is spread over 2.5 times as much supply, less the 20% take
+    assert_close(rates.supply_apr, 0.016);
+    assert!((rates.borrow_apy - 0.05f64.exp_m1()).abs() < 1e-9);
+    assert_eq!(apy(0.0), 0.0);
+
+    // Fees owed to the protocol are not supply, and the fixed host rate adds to the borrow APR
+    let mut reserve = reserve(400);
+    reserve.liquidity.accumulated_protocol_fees_sf = Fraction::from_num(100).to_bits();
+    reserve.config.host_fixed_interest_rate_bps = 100;
+    let rates = reserve_rates(&reserve).unwrap();
+    assert_close(rates.utilization, 400.0 / 900.0);
+    assert_close(rates.borrow_apr, 0.01 + 0.1 * (400.0 / 900.0) / 0.8);
+
+    // An empty reserve has no utilization
+    let mut empty = Reserve::default();
+    empty.config.borrow_rate_curve = curve();
+    let rates = reserve_rates(&empty).unwrap();
+    assert_eq!((rates.utilization, rates.borrow_apr, rates.supply_apr), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_projected_rates() {
+    let reserve = reserve(400);
+
+    // Borrowing 400 more takes utilization to 80%
+    let rates = projected_rates(&reserve, LiquidityChange::Borrow(400)).unwrap();
+    assert_close(rates.utilization, 0.8);
+    assert_close(rates.borrow_apr, 0.1);
+    // Depositing 1,000 halves it
+    let rates = projected_rates(&reserve, LiquidityChange::Deposit(1_000)).unwrap();
+    assert_close(rates.utilization, 0.2);
+    assert_close(rates.borrow_apr, 0.025);
+    // Withdrawing 500 raises it to 80%
+    let rates = projected_rates(&reserve, LiquidityChange::Withdraw(500)).unwrap();
+    assert_close(rates.utilization, 0.8);
+    // Repaying more than the debt clears it
+    let rates = projected_rates(&reserve, LiquidityChange::Repay(1_000)).unwrap();
+    assert_eq!(rates.utilization, 0.0);
+
+    // Only available liquidity can be withdrawn or borrowed
+    assert!(projected_rates(&reserve, LiquidityChange::Withdraw(601)).is_err());
+    assert!(projected_rates(&reserve, LiquidityChange::Borrow(601)).is_err());
+    // The reserve itself is untouched
+    assert_eq!(reserve_rates(&reserve).unwrap().utilization, 0.4);
+}
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# Dump the Kamino program, the clock and the given accounts from a cluster, for the
+# fixture tests in programs/liquidity_lending/tests.
+#
+# Usage: fixtures/dump.sh NAME=ADDRESS...
+# Without accounts only the program is fetched, leaving a committed dump and its clock as
+# they are. The cluster defaults to mainnet-beta; set SOLANA_URL to use another RPC.
+set -euo pipefail
+
+cd "$(dirname "$0")"
+url="${SOLANA_URL:-https://api.mainnet-beta.solana.com}"
+kamino=KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD
+
+solana program dump --url "$url" "$kamino" kamino_lending.so
+[ $# -gt 0 ] || exit 0
+
+mkdir -p accounts
+solana account --url "$url" SysvarC1ock11111111111111111111111111111111 \
+    --output json --output-file accounts/clock.json > /dev/null
+for pair in "$@"; do
+    name="${pair%%=*}"
+    address="${pair#*=}"
+    solana account --url "$url" "$address" --output json --output-file "accounts/$name.json" > /dev/null
+done
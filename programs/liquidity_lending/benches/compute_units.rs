@@ -0,0 +1,293 @@
+//! Compute units of every instruction we can run against the mock Kamino program, and of the
+//! obligation-bound ones on the heaviest obligations Kamino allows. Build first, then run:
+//!
+//! ```text
+//! anchor build && cargo bench -p liquidity_lending --bench compute_units
+//! ```
+//!
+//! Results are compared with `benches/compute_units.json`, and the run fails when a case
+//! uses more than `CU_REGRESSION_PCT` percent (5 by default) over its baseline. Set
+//! `CU_UPDATE_BASELINE=1` to write the results as the new baseline, and commit it with the
+//! change that moved them.
+//!
+//! Only our instruction is measured, not the refreshes sent before it, but the CPIs into the
+//! mock count, including the refreshes the batch makes itself, so numbers compare runs of
+//! this harness rather than mainnet costs. Liquidation
+//! and flash loans are not covered: the mock does not liquidate, and a flash loan needs both
+//! of its instructions in one transaction.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_account::Account;
+
+use common::scenario::Scenario;
+use common::{kamino_account, ONE, TOKEN};
+use liquidity_lending::batch::{BatchAction, BatchActionKind};
+use liquidity_lending::kamino::obligation_layout::{MAX_BORROWS, MAX_DEPOSITS};
+use liquidity_lending::kamino::{deserialize_data, discriminator, ReserveFarmKind, ReserveHeader};
+use liquidity_lending::state::{ConfigChange, PauseTarget, Role, ROLE_SEED};
+use mock_kamino::state as kamino;
+
+const DEFAULT_REGRESSION_PCT: u64 = 5;
+
+/// Compute units per case, by name
+type Results = BTreeMap<String, u64>;
+
+fn baseline_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("benches/compute_units.json")
+}
+
+/// Run the instruction `build` makes for the scenario's current state and keep its units
+fn record(results: &mut Results, name: &str, scenario: &mut Scenario, build: impl FnOnce(&Scenario) -> Instruction) {
+    let instruction = build(scenario);
+    let units = scenario.process(&instruction).compute_units_consumed;
+    results.insert(name.to_owned(), units);
+}
+
+/// Attach a farm of `kind` to the market's reserve, returning the farm state
+fn attach_farm(scenario: &mut Scenario, kind: ReserveFarmKind) -> Pubkey {
+    let farm = Pubkey::new_unique();
+    let reserve = scenario.market.reserve;
+    let mut data = scenario.account(&reserve).data.clone();
+    let mut header: ReserveHeader = deserialize_data(&data, &discriminator::RESERVE, 0).unwrap();
+    match kind {
+        ReserveFarmKind::Collateral => header.farm_collateral = farm,
+        ReserveFarmKind::Debt => header.farm_debt = farm,
+    }
+    kamino::write(&mut data, 0, &header).unwrap();
+    scenario.set_account(reserve, kamino_account(data));
+    scenario.set_account(farm, Account::default());
+    farm
+}
+
+/// Every instruction of a user's lifetime, on an obligation with one deposit and one borrow
+fn single_reserve(results: &mut Results) {
+    let mut scenario = Scenario::new();
+    let program = liquidity_lending::ID;
+
+    record(results, "initialize", &mut scenario, Scenario::initialize_instruction);
+    let set_market = ConfigChange::SetMarket {
+        lending_market: scenario.market.lending_market,
+        allowed: true,
+    };
+    record(results, "queue_config_change", &mut scenario, |scenario| {
+        scenario.queue_config_change_instruction(set_market)
+    });
+    scenario.wait(common::scenario::slots(scenario.config().timelock_delay));
+    record(results, "execute_config_change", &mut scenario, |scenario| {
+        scenario.execute_config_change_instruction(0)
+    });
+    scenario.change_config(ConfigChange::SetReserve { entry: scenario.market.registry_entry() });
+    record(results, "init_reserve_usage", &mut scenario, Scenario::init_reserve_usage_instruction);
+    record(results, "init_user_position", &mut scenario, Scenario::init_user_position_instruction);
+    record(results, "init_user_borrows", &mut scenario, Scenario::init_user_borrows_instruction);
+
+    let keeper = scenario.admin;
+    let keeper_role = Pubkey::find_program_address(&[ROLE_SEED, &Role::Keeper.seed(), keeper.as_ref()], &program).0;
+    let grant_role = Instruction {
+        program_id: program,
+        accounts: liquidity_lending::accounts::GrantRole {
+            admin: scenario.admin,
+            config: scenario.protocol.config,
+            role_assignment: keeper_role,
+            system_program: anchor_lang::system_program::ID,
+            event_authority: scenario.protocol.event_authority,
+            program,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::GrantRole { role: Role::Keeper, holder: keeper }.data(),
+    };
+    record(results, "grant_role", &mut scenario, |_| grant_role);
+
+    let set_pause = |paused| Instruction {
+        program_id: program,
+        accounts: liquidity_lending::accounts::SetPause {
+            authority: scenario.admin,
+            config: scenario.protocol.config,
+            guardian_role: None,
+            event_authority: scenario.protocol.event_authority,
+            program,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::SetPause { target: PauseTarget::Deposits, paused }.data(),
+    };
+    let (pause, unpause) = (set_pause(true), set_pause(false));
+    record(results, "set_pause", &mut scenario, |_| pause);
+    scenario.process(&unpause);
+
+    scenario.refresh();
+    record(results, "kamino_deposit_reserve_liquidity", &mut scenario, |scenario| {
+        scenario.deposit_instruction(1_000 * TOKEN)
+    });
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.refresh();
+    record(results, "kamino_borrow_obligation_liquidity", &mut scenario, |scenario| {
+        scenario.borrow_instruction(500 * TOKEN)
+    });
+    scenario.refresh();
+    record(results, "kamino_repay_obligation_liquidity", &mut scenario, |scenario| {
+        scenario.repay_instruction(100 * TOKEN)
+    });
+    scenario.refresh();
+    record(results, "kamino_withdraw_obligation_collateral_and_redeem_reserve_collateral", &mut scenario, |scenario| {
+        scenario.withdraw_instruction(100 * TOKEN)
+    });
+    record(results, "kamino_batch", &mut scenario, |scenario| {
+        scenario.batch_instruction(&[
+            BatchAction { kind: BatchActionKind::Deposit, amount: 100 * TOKEN },
+            BatchAction { kind: BatchActionKind::Borrow, amount: 50 * TOKEN },
+        ])
+    });
+
+    // Collateral and debt farms on the reserve, with the obligation's farm user states
+    let rent = scenario.mollusk.sysvars.keyed_account_for_rent_sysvar();
+    scenario.set_account(rent.0, rent.1);
+    for (kind, name) in [(ReserveFarmKind::Collateral, "collateral_farm"), (ReserveFarmKind::Debt, "debt_farm")] {
+        let farm = attach_farm(&mut scenario, kind);
+        let obligation_farm = Pubkey::new_unique();
+        let farms_program = Pubkey::new_unique();
+        scenario.set_account(obligation_farm, Account::default());
+        scenario.set_account(farms_program, Account::default());
+
+        let init = Instruction {
+            program_id: program,
+            accounts: liquidity_lending::accounts::KaminoInitObligationFarmsForReserve {
+                payer: scenario.owner,
+                owner: scenario.owner,
+                registry: scenario.protocol.registry,
+                obligation: scenario.obligation,
+                lending_market_authority: scenario.market.lending_market_authority,
+                reserve: scenario.market.reserve,
+                reserve_farm_state: farm,
+                obligation_farm,
+                lending_market: scenario.market.lending_market,
+                farms_program,
+                rent: sysvar::rent::ID,
+                system_program: anchor_lang::system_program::ID,
+                kamino_lending_program: mock_kamino::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoInitObligationFarmsForReserve { mode: kind }.data(),
+        };
+        record(results, &format!("kamino_init_obligation_farms_for_reserve/{name}"), &mut scenario, |_| init);
+
+        let refresh = Instruction {
+            program_id: program,
+            accounts: liquidity_lending::accounts::KaminoRefreshObligationFarmsForReserve {
+                crank: keeper,
+                keeper_role,
+                registry: scenario.protocol.registry,
+                obligation: scenario.obligation,
+                lending_market_authority: scenario.market.lending_market_authority,
+                reserve: scenario.market.reserve,
+                reserve_farm_state: farm,
+                obligation_farm_user_state: obligation_farm,
+                lending_market: scenario.market.lending_market,
+                farms_program,
+                rent: sysvar::rent::ID,
+                system_program: anchor_lang::system_program::ID,
+                kamino_lending_program: mock_kamino::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoRefreshObligationFarmsForReserve { mode: kind }.data(),
+        };
+        record(results, &format!("kamino_refresh_obligation_farms_for_reserve/{name}"), &mut scenario, |_| refresh);
+    }
+}
+
+/// Borrow, repay and withdraw on an obligation using every deposit and borrow slot, which is
+/// as much as refreshes and health checks ever have to read
+fn full_obligation(results: &mut Results) {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+
+    let mut obligation = scenario.obligation_state();
+    let reserve = scenario.market.reserve_state();
+    for _ in 1..MAX_DEPOSITS {
+        obligation.deposit(scenario.add_reserve(), 100 * TOKEN).unwrap();
+    }
+    for _ in 1..MAX_BORROWS {
+        obligation.borrow(&reserve, scenario.add_reserve(), u128::from(TOKEN) * ONE).unwrap();
+    }
+    scenario.store_obligation(&obligation);
+
+    scenario.refresh();
+    record(results, "kamino_borrow_obligation_liquidity/full_obligation", &mut scenario, |scenario| {
+        scenario.borrow_instruction(500 * TOKEN)
+    });
+    scenario.refresh();
+    record(results, "kamino_repay_obligation_liquidity/full_obligation", &mut scenario, |scenario| {
+        scenario.repay_instruction(100 * TOKEN)
+    });
+    scenario.refresh();
+    record(
+        results,
+        "kamino_withdraw_obligation_collateral_and_redeem_reserve_collateral/full_obligation",
+        &mut scenario,
+        |scenario| scenario.withdraw_instruction(100 * TOKEN),
+    );
+}
+
+fn main() {
+    let mut results = Results::new();
+    single_reserve(&mut results);
+    full_obligation(&mut results);
+
+    let path = baseline_path();
+    if std::env::var_os("CU_UPDATE_BASELINE").is_some() {
+        let json = serde_json::to_string_pretty(&results).unwrap();
+        std::fs::write(&path, json + "\n").unwrap();
+        println!("Wrote {} cases to {}", results.len(), path.display());
+        return;
+    }
+
+    let json = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("cannot read baseline {}: {err}", path.display()));
+    let baseline: Results = serde_json::from_str(&json).expect("invalid baseline");
+    let threshold = std::env::var("CU_REGRESSION_PCT")
+        .map(|pct| pct.parse().expect("CU_REGRESSION_PCT must be a whole percentage"))
+        .unwrap_or(DEFAULT_REGRESSION_PCT);
+
+    let width = results.keys().map(String::len).max().unwrap_or(0);
+    println!("{:width$}  {:>9}  {:>9}  {:>8}", "case", "baseline", "units", "change");
+    let mut regressions = Vec::new();
+    let mut missing = Vec::new();
+    for (name, &units) in &results {
+        let Some(&before) = baseline.get(name) else {
+            println!("{name:width$}  {:>9}  {units:>9}  {:>8}", "-", "MISSING");
+            missing.push(name);
+            continue;
+        };
+        let change = (units as f64 / before.max(1) as f64 - 1.0) * 100.0;
+        let flag = if units * 100 > before * (100 + threshold) { " REGRESSION" } else { "" };
+        println!("{name:width$}  {before:>9}  {units:>9}  {change:>+7.1}%{flag}");
+        if !flag.is_empty() {
+            regressions.push(name);
+        }
+    }
+    for name in baseline.keys().filter(|name| !results.contains_key(*name)) {
+        println!("{name:width$}  no longer measured");
+    }
+
+    if !missing.is_empty() {
+        eprintln!(
+            "{} case(s) have no baseline; rerun with CU_UPDATE_BASELINE=1 and commit the result",
+            missing.len()
+        );
+    }
+    if !regressions.is_empty() {
+        eprintln!("{} case(s) used more than {threshold}% over the baseline", regressions.len());
+    }
+    if !missing.is_empty() || !regressions.is_empty() {
+        std::process::exit(1);
+    }
+}
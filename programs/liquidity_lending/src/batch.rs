@@ -0,0 +1,583 @@
+//! `kamino_batch`: deposits, withdrawals, borrows and repayments on several reserves of one
+//! obligation in a single instruction.
+//!
+//! Each action's accounts are laid out in `remaining_accounts`, [`BATCH_ACTION_ACCOUNTS`] per
+//! action in the order of [`ActionAccounts`], followed by [`BATCH_RESERVE_ACCOUNTS`] per
+//! reserve the obligation holds that no action touches, so it can be refreshed. An oracle
+//! slot holding the Kamino program id is empty, as for Anchor's optional accounts.
+//!
+//! Every reserve and the obligation are refreshed up front, then the actions run in order
+//! with each one's own checks, and the obligation's health is checked once at the end rather
+//! than after every action. Kamino marks the action's reserve and the obligation stale once
+//! it is done, and refuses the next action on stale accounts, so both are refreshed again
+//! after every action; a batch refreshing only once is not possible against Kamino. Kamino
+//! also runs its own checks on each action, so an action it would refuse on its own, such as
+//! a borrow ahead of the deposit backing it, has to come after the actions that make room
+//! for it.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::events::{Borrowed, Deposited, Repaid, Withdrawn};
+use crate::kamino::{
+    instruction_discriminator, invoke_kamino, obligation_reserves, serialize_kamino_instruction,
+    ObligationMetrics, ReserveHeader, ReserveStatus, KAMINO_LENDING_ID,
+};
+use crate::kamino_pda::{self, ReserveAccounts};
+use crate::oracle::OracleAccounts;
+use crate::state::{
+    Config, PauseTarget, PositionAction, Registry, ReserveUsage, UserBorrows, UserPosition,
+    CONFIG_SEED, REGISTRY_SEED, RESERVE_USAGE_SEED, USER_BORROWS_SEED, USER_POSITION_SEED,
+};
+use crate::{limits, preflight, price_guard, sentinel, token, LendingError};
+
+/// Most actions a batch may hold. Four actions on different reserves with every oracle set
+/// take 52 accounts, which with the fixed accounts, our program and the compute budget program
+/// reach the 64 accounts a transaction may lock, address lookup tables or not.
+pub const MAX_BATCH_ACTIONS: usize = 4;
+
+/// Remaining accounts each action takes
+pub const BATCH_ACTION_ACCOUNTS: usize = 13;
+
+/// Remaining accounts each other reserve of the obligation takes: the reserve and its oracles
+pub const BATCH_RESERVE_ACCOUNTS: usize = 5;
+
+/// What a batch action does with its reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchActionKind {
+    /// Deposit liquidity and pledge the collateral to the obligation
+    Deposit,
+    /// Withdraw collateral from the obligation and redeem it for liquidity
+    Withdraw,
+    Borrow,
+    Repay,
+}
+
+/// One step of a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchAction {
+    pub kind: BatchActionKind,
+    /// Liquidity to deposit, borrow or repay, or collateral to withdraw
+    pub amount: u64,
+}
+
+/// The remaining accounts of one action, in order
+struct ActionAccounts<'info> {
+    reserve: &'info AccountInfo<'info>,
+    reserve_usage: &'info AccountInfo<'info>,
+    liquidity_mint: &'info AccountInfo<'info>,
+    liquidity_supply: &'info AccountInfo<'info>,
+    collateral_mint: &'info AccountInfo<'info>,
+    collateral_supply: &'info AccountInfo<'info>,
+    fee_vault: &'info AccountInfo<'info>,
+    /// The owner's source or destination liquidity account
+    user_liquidity: &'info AccountInfo<'info>,
+    liquidity_token_program: &'info AccountInfo<'info>,
+    /// Pyth price, Switchboard price, Switchboard TWAP and Scope prices
+    oracles: &'info [AccountInfo<'info>],
+}
+
+impl<'info> ActionAccounts<'info> {
+    fn new(accounts: &'info [AccountInfo<'info>]) -> Self {
+        Self {
+            reserve: &accounts[0],
+            reserve_usage: &accounts[1],
+            liquidity_mint: &accounts[2],
+            liquidity_supply: &accounts[3],
+            collateral_mint: &accounts[4],
+            collateral_supply: &accounts[5],
+            fee_vault: &accounts[6],
+            user_liquidity: &accounts[7],
+            liquidity_token_program: &accounts[8],
+            oracles: &accounts[9..BATCH_ACTION_ACCOUNTS],
+        }
+    }
+
+    /// The oracle accounts as the price guard takes them
+    fn oracle_accounts(&self, kamino_program: &Pubkey) -> OracleAccounts<'info, 'info> {
+        let slot = |index: usize| Some(&self.oracles[index]).filter(|account| account.key != kamino_program);
+        OracleAccounts {
+            pyth_price: slot(0),
+            switchboard_price: slot(1),
+            switchboard_twap: slot(2),
+            scope_prices: slot(3),
+        }
+    }
+}
+
+/// A reserve the batch can refresh, with its oracle accounts
+type Refreshable<'info> = (&'info AccountInfo<'info>, &'info [AccountInfo<'info>]);
+
+/// What an action moved, for the position ledger and its event
+struct ActionOutcome {
+    /// Amount asked of Kamino, with a `u64::MAX` repayment or withdrawal resolved
+    amount: u64,
+    /// Liquidity that reached the reserve or the owner after fees
+    liquidity_received: u64,
+    /// Collateral minted into the obligation, for deposits
+    collateral_minted: u64,
+}
+
+fn token_balance<'info>(account: &'info AccountInfo<'info>) -> Result<u64> {
+    Ok(InterfaceAccount::<TokenAccount>::try_from(account)?.amount)
+}
+
+pub fn batch<'info>(
+    mut ctx: Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+    actions: Vec<BatchAction>,
+) -> Result<()> {
+    require!(
+        !actions.is_empty() && actions.len() <= MAX_BATCH_ACTIONS,
+        LendingError::InvalidBatch
+    );
+    let action_len = actions.len() * BATCH_ACTION_ACCOUNTS;
+    let remaining = ctx.remaining_accounts;
+    require!(remaining.len() >= action_len, LendingError::InvalidBatch);
+    let (action_accounts, reserve_accounts) = remaining.split_at(action_len);
+    require!(
+        reserve_accounts.chunks_exact(BATCH_RESERVE_ACCOUNTS).remainder().is_empty(),
+        LendingError::InvalidBatch
+    );
+    let groups: Vec<ActionAccounts> = action_accounts
+        .chunks(BATCH_ACTION_ACCOUNTS)
+        .map(ActionAccounts::new)
+        .collect();
+
+    let kamino_program = &ctx.accounts.kamino_lending_program.key();
+    let lending_market = &ctx.accounts.lending_market.key();
+    kamino_pda::require_derived(
+        ctx.accounts.lending_market_authority.key,
+        kamino_pda::lending_market_authority(kamino_program, lending_market),
+    )?;
+
+    let refreshable: Vec<Refreshable> = groups
+        .iter()
+        .map(|group| (group.reserve, group.oracles))
+        .chain(
+            reserve_accounts
+                .chunks(BATCH_RESERVE_ACCOUNTS)
+                .map(|accounts| (&accounts[0], &accounts[1..])),
+        )
+        .collect();
+    for (reserve, _) in &refreshable {
+        require!(reserve.is_writable, ErrorCode::ConstraintMut);
+        let header = ReserveHeader::load(reserve, kamino_program)?;
+        require_keys_eq!(header.lending_market, *lending_market, LendingError::InvalidKaminoAccount);
+    }
+
+    let mut refreshed: Vec<Pubkey> = Vec::new();
+    for (reserve, oracles) in &refreshable {
+        if !refreshed.contains(reserve.key) {
+            refresh_reserve(&ctx, reserve, oracles)?;
+            refreshed.push(*reserve.key);
+        }
+    }
+    refresh_obligation(&ctx, &refreshable)?;
+
+    let clock = Clock::get()?;
+    for (action, accounts) in actions.iter().zip(&groups) {
+        let outcome = run_action(&ctx, action, accounts, &clock)?;
+
+        // Kamino marks the reserve and obligation stale after every action
+        refresh_reserve(&ctx, accounts.reserve, accounts.oracles)?;
+        refresh_obligation(&ctx, &refreshable)?;
+        record_action(&mut ctx, action, accounts.reserve.key(), outcome)?;
+    }
+
+    // Intermediate steps only had to satisfy Kamino; the end state has to satisfy us too
+    let metrics = ObligationMetrics::load(&ctx.accounts.obligation, kamino_program)?;
+    limits::require_healthy(&metrics)?;
+    limits::record_user_borrow_value(
+        &ctx.accounts.config,
+        &mut ctx.accounts.user_borrows,
+        ctx.accounts.obligation.key(),
+        &metrics,
+        clock.unix_timestamp,
+    )?;
+
+    msg!("Successfully ran a batch of {} actions", actions.len());
+    Ok(())
+}
+
+fn refresh_reserve<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+    reserve: &AccountInfo<'info>,
+    oracles: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut cpi_accounts = vec![reserve.clone(), ctx.accounts.lending_market.clone()];
+    cpi_accounts.extend(oracles.iter().cloned());
+    let instruction_data = serialize_kamino_instruction(instruction_discriminator::REFRESH_RESERVE, &())?;
+    invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)
+}
+
+/// Refresh the obligation with the reserves it holds right now, which a deposit into a new
+/// reserve or a full repayment changes
+fn refresh_obligation<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+    refreshable: &[Refreshable<'info>],
+) -> Result<()> {
+    let mut cpi_accounts = vec![
+        ctx.accounts.lending_market.clone(),
+        ctx.accounts.obligation.clone(),
+    ];
+    for key in obligation_reserves(&ctx.accounts.obligation, ctx.accounts.kamino_lending_program.key)? {
+        let (reserve, _) = refreshable
+            .iter()
+            .find(|(reserve, _)| *reserve.key == key)
+            .ok_or(LendingError::InvalidBatch)?;
+        cpi_accounts.push((*reserve).clone());
+    }
+    let instruction_data = serialize_kamino_instruction(instruction_discriminator::REFRESH_OBLIGATION, &())?;
+    invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)
+}
+
+/// Check one action as its standalone wrapper would, then run it through Kamino
+fn run_action<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+    action: &BatchAction,
+    accounts: &ActionAccounts<'info>,
+    clock: &Clock,
+) -> Result<ActionOutcome> {
+    require!(action.amount > 0, LendingError::InvalidAmount);
+    let config = &ctx.accounts.config;
+    config.require_not_paused(match action.kind {
+        BatchActionKind::Deposit => PauseTarget::Deposits,
+        BatchActionKind::Withdraw => PauseTarget::Withdrawals,
+        BatchActionKind::Borrow => PauseTarget::Borrows,
+        BatchActionKind::Repay => PauseTarget::Global,
+    })?;
+
+    let kamino_program = ctx.accounts.kamino_lending_program.key;
+    let lending_market = ctx.accounts.lending_market.key;
+    let entry = ctx
+        .accounts
+        .registry
+        .require_reserve(lending_market, accounts.reserve.key)?;
+
+    // The accounts an `Accounts` struct would check for this reserve
+    let token_program = Interface::<TokenInterface>::try_from(accounts.liquidity_token_program)?;
+    let liquidity_mint = InterfaceAccount::<Mint>::try_from(accounts.liquidity_mint)?;
+    require_keys_eq!(
+        *accounts.liquidity_mint.owner,
+        token_program.key(),
+        ErrorCode::ConstraintMintTokenProgram
+    );
+    InterfaceAccount::<Mint>::try_from(accounts.collateral_mint)?;
+    require_keys_eq!(
+        *accounts.collateral_mint.owner,
+        ctx.accounts.collateral_token_program.key(),
+        ErrorCode::ConstraintMintTokenProgram
+    );
+    let user_liquidity = InterfaceAccount::<TokenAccount>::try_from(accounts.user_liquidity)?;
+    require_keys_eq!(user_liquidity.mint, liquidity_mint.key(), ErrorCode::ConstraintTokenMint);
+    require_keys_eq!(
+        *accounts.user_liquidity.owner,
+        token_program.key(),
+        ErrorCode::ConstraintTokenTokenProgram
+    );
+    let spends_liquidity = matches!(action.kind, BatchActionKind::Deposit | BatchActionKind::Repay);
+    if spends_liquidity {
+        require_keys_eq!(user_liquidity.owner, ctx.accounts.owner.key(), ErrorCode::ConstraintTokenOwner);
+    }
+    for account in [
+        accounts.reserve,
+        accounts.reserve_usage,
+        accounts.liquidity_supply,
+        accounts.collateral_mint,
+        accounts.collateral_supply,
+        accounts.fee_vault,
+        accounts.user_liquidity,
+    ] {
+        require!(account.is_writable, ErrorCode::ConstraintMut);
+    }
+    let mut usage = Account::<ReserveUsage>::try_from(accounts.reserve_usage)?;
+    let usage_address = Pubkey::create_program_address(
+        &[RESERVE_USAGE_SEED, accounts.reserve.key.as_ref(), &[usage.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(usage_address, accounts.reserve_usage.key(), ErrorCode::ConstraintSeeds);
+
+    ReserveAccounts {
+        liquidity_supply: Some(accounts.liquidity_supply.key),
+        fee_vault: Some(accounts.fee_vault.key),
+        collateral_mint: Some(accounts.collateral_mint.key),
+        collateral_supply: Some(accounts.collateral_supply.key),
+        ..ReserveAccounts::default()
+    }
+    .require_derived(kamino_program, lending_market, &liquidity_mint.key())?;
+
+    match action.kind {
+        BatchActionKind::Deposit => {
+            require!(entry.decimals == liquidity_mint.decimals, LendingError::ReserveMetadataMismatch);
+            ReserveStatus::require_active(accounts.reserve, kamino_program)?;
+            usage.record_deposit(entry, action.amount, clock.epoch)?;
+        }
+        BatchActionKind::Borrow => {
+            require!(entry.borrow_allowed, LendingError::BorrowNotAllowed);
+            require!(entry.decimals == liquidity_mint.decimals, LendingError::ReserveMetadataMismatch);
+            ReserveStatus::require_active(accounts.reserve, kamino_program)?;
+            usage.record_borrow(entry, action.amount, clock.epoch)?;
+            price_guard::check(
+                &config.price_guard,
+                accounts.reserve,
+                kamino_program,
+                &accounts.oracle_accounts(kamino_program),
+                clock.unix_timestamp,
+            )?;
+            preflight::check_borrow(
+                &ctx.accounts.obligation,
+                accounts.reserve,
+                kamino_program,
+                action.amount,
+                liquidity_mint.decimals,
+                clock,
+            )?;
+        }
+        // Repayments and withdrawals stay open on deprecated reserves so users can exit
+        BatchActionKind::Withdraw | BatchActionKind::Repay => {}
+    }
+    // The obligation was refreshed after the previous action, so its debt and deposits are current
+    let amount = match action.kind {
+        BatchActionKind::Repay => {
+            let amount =
+                sentinel::repay_amount(&ctx.accounts.obligation, kamino_program, accounts.reserve.key, action.amount)?;
+            require!(user_liquidity.amount >= amount, LendingError::InsufficientFunds);
+            amount
+        }
+        BatchActionKind::Withdraw => {
+            sentinel::withdraw_amount(&ctx.accounts.obligation, kamino_program, accounts.reserve.key, action.amount)?
+        }
+        BatchActionKind::Deposit | BatchActionKind::Borrow => action.amount,
+    };
+    token::validate_mint(accounts.liquidity_mint)?;
+
+    let owner = ctx.accounts.owner.to_account_info();
+    let obligation = ctx.accounts.obligation.clone();
+    let market = ctx.accounts.lending_market.clone();
+    let market_authority = ctx.accounts.lending_market_authority.clone();
+    let collateral_token_program = ctx.accounts.collateral_token_program.to_account_info();
+    let instruction_sysvar = ctx.accounts.instruction_sysvar_account.clone();
+    let kamino = ctx.accounts.kamino_lending_program.clone();
+    let (index, cpi_accounts) = match action.kind {
+        BatchActionKind::Deposit => (
+            instruction_discriminator::DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL,
+            vec![
+                owner,
+                obligation,
+                market,
+                market_authority,
+                accounts.reserve.clone(),
+                accounts.liquidity_mint.clone(),
+                accounts.liquidity_supply.clone(),
+                accounts.collateral_mint.clone(),
+                accounts.collateral_supply.clone(),
+                accounts.user_liquidity.clone(),
+                // No user collateral account: the collateral goes straight to the obligation
+                kamino,
+                collateral_token_program,
+                accounts.liquidity_token_program.clone(),
+                instruction_sysvar,
+            ],
+        ),
+        BatchActionKind::Withdraw => (
+            instruction_discriminator::WITHDRAW_OBLIGATION_COLLATERAL_AND_REDEEM_RESERVE_COLLATERAL,
+            vec![
+                owner,
+                obligation,
+                market,
+                market_authority,
+                accounts.reserve.clone(),
+                accounts.liquidity_mint.clone(),
+                accounts.collateral_supply.clone(),
+                accounts.collateral_mint.clone(),
+                accounts.liquidity_supply.clone(),
+                accounts.user_liquidity.clone(),
+                kamino,
+                collateral_token_program,
+                accounts.liquidity_token_program.clone(),
+                instruction_sysvar,
+            ],
+        ),
+        BatchActionKind::Borrow => (
+            instruction_discriminator::BORROW_OBLIGATION_LIQUIDITY,
+            vec![
+                owner,
+                obligation,
+                market,
+                market_authority,
+                accounts.reserve.clone(),
+                accounts.liquidity_mint.clone(),
+                accounts.liquidity_supply.clone(),
+                accounts.fee_vault.clone(),
+                accounts.user_liquidity.clone(),
+                // No referrer
+                kamino,
+                accounts.liquidity_token_program.clone(),
+                instruction_sysvar,
+            ],
+        ),
+        BatchActionKind::Repay => (
+            instruction_discriminator::REPAY_OBLIGATION_LIQUIDITY,
+            vec![
+                owner,
+                obligation,
+                market,
+                accounts.reserve.clone(),
+                accounts.liquidity_mint.clone(),
+                accounts.liquidity_supply.clone(),
+                accounts.user_liquidity.clone(),
+                accounts.liquidity_token_program.clone(),
+                instruction_sysvar,
+            ],
+        ),
+    };
+    let liquidity_before = user_liquidity.amount;
+    let collateral_before = token_balance(accounts.collateral_supply)?;
+    let instruction_data = serialize_kamino_instruction(index, &amount)?;
+    invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+    let outcome = match action.kind {
+        BatchActionKind::Deposit => ActionOutcome {
+            amount,
+            liquidity_received: token::amount_after_transfer_fee(accounts.liquidity_mint, amount)?,
+            collateral_minted: token_balance(accounts.collateral_supply)?.saturating_sub(collateral_before),
+        },
+        BatchActionKind::Repay => ActionOutcome {
+            amount,
+            liquidity_received: token::amount_after_transfer_fee(accounts.liquidity_mint, amount)?,
+            collateral_minted: 0,
+        },
+        // Borrow and transfer fees mean the owner can receive less than the amount
+        BatchActionKind::Withdraw | BatchActionKind::Borrow => ActionOutcome {
+            amount,
+            liquidity_received: token_balance(accounts.user_liquidity)?.saturating_sub(liquidity_before),
+            collateral_minted: 0,
+        },
+    };
+    if action.kind == BatchActionKind::Withdraw {
+        usage.record_withdrawal(outcome.liquidity_received);
+    }
+    usage.exit(&crate::ID)?;
+    Ok(outcome)
+}
+
+/// Record an action in the owner's position and emit its event, with the obligation's
+/// metrics once it was refreshed after the action
+fn record_action<'info>(
+    ctx: &mut Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+    action: &BatchAction,
+    reserve: Pubkey,
+    outcome: ActionOutcome,
+) -> Result<()> {
+    let (position_action, position_amount) = match action.kind {
+        BatchActionKind::Deposit => (PositionAction::Deposit, outcome.amount),
+        BatchActionKind::Withdraw => (PositionAction::Withdraw, outcome.liquidity_received),
+        BatchActionKind::Borrow => (PositionAction::Borrow, outcome.liquidity_received),
+        BatchActionKind::Repay => (PositionAction::Repay, outcome.amount),
+    };
+    ctx.accounts
+        .user_position
+        .record(reserve, position_action, position_amount, Clock::get()?.unix_timestamp)?;
+
+    let owner = ctx.accounts.owner.key();
+    let obligation = ctx.accounts.obligation.key();
+    let metrics = ObligationMetrics::load(&ctx.accounts.obligation, ctx.accounts.kamino_lending_program.key)?;
+    match action.kind {
+        BatchActionKind::Deposit => emit_cpi!(Deposited {
+            owner,
+            reserve,
+            obligation: Some(obligation),
+            liquidity_amount: outcome.amount,
+            liquidity_received: outcome.liquidity_received,
+            collateral_minted: outcome.collateral_minted,
+            metrics: Some(metrics),
+        }),
+        BatchActionKind::Withdraw => emit_cpi!(Withdrawn {
+            owner,
+            reserve,
+            obligation,
+            collateral_amount: outcome.amount,
+            liquidity_received: outcome.liquidity_received,
+            metrics,
+        }),
+        BatchActionKind::Borrow => emit_cpi!(Borrowed {
+            owner,
+            reserve,
+            obligation,
+            liquidity_amount: outcome.amount,
+            liquidity_received: outcome.liquidity_received,
+            metrics,
+        }),
+        BatchActionKind::Repay => emit_cpi!(Repaid {
+            owner,
+            reserve,
+            obligation,
+            liquidity_amount: outcome.amount,
+            liquidity_received: outcome.liquidity_received,
+            metrics,
+        }),
+    }
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct KaminoBatch<'info> {
+    /// The obligation owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// What the owner has borrowed across their obligations
+    #[account(
+        mut,
+        seeds = [USER_BORROWS_SEED, owner.key().as_ref()],
+        bump = user_borrows.bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The obligation account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Validated by Kamino program
+    pub lending_market: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Checked against its derivation from the lending market
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The collateral token program
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
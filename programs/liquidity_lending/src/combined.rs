@@ -0,0 +1,730 @@
+//! Kamino's two-step instructions: `repayAndWithdrawAndRedeem`, which unwinds part of a
+//! position, and `depositAndWithdraw`, which swaps one collateral for another.
+//!
+//! Both steps get the checks their standalone wrappers make before the single CPI. Kamino
+//! refreshes the obligation between the steps, so the obligation's reserves as they stand
+//! after the first step, deposits then borrows, follow in `remaining_accounts`. Once Kamino
+//! is done, the obligation it left must be within its allowed borrow value and our
+//! per-user borrow limit.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::events::{Deposited, Repaid, Withdrawn};
+use crate::kamino::{
+    instruction_discriminator, invoke_kamino, serialize_kamino_instruction, ObligationMetrics,
+    ReserveFarmKind, ReserveHeader, ReserveStatus, KAMINO_LENDING_ID,
+};
+use crate::farms::KAMINO_FARMS_ID;
+use crate::kamino_pda::ReserveAccounts;
+use crate::state::{
+    Config, PauseTarget, PositionAction, Registry, ReserveUsage, UserBorrows, UserPosition,
+    CONFIG_SEED, REGISTRY_SEED, RESERVE_USAGE_SEED, USER_BORROWS_SEED, USER_POSITION_SEED,
+};
+use crate::{limits, preflight, sentinel, token, LendingError};
+
+/// Check the farm accounts given for `reserve`'s `kind` farm. Kamino moves the obligation's
+/// stake in a farm the reserve has, so both accounts are needed then, and neither otherwise.
+fn require_farm_accounts(
+    reserve: &AccountInfo,
+    kamino_program: &Pubkey,
+    kind: ReserveFarmKind,
+    reserve_farm_state: Option<&AccountInfo>,
+    obligation_farm_user_state: Option<&AccountInfo>,
+) -> Result<()> {
+    let farm = ReserveHeader::load(reserve, kamino_program)?.farm(kind);
+    match (farm, reserve_farm_state, obligation_farm_user_state) {
+        (Some(farm), Some(farm_state), Some(_)) => {
+            require_keys_eq!(farm, farm_state.key(), LendingError::InvalidFarmAccount);
+        }
+        (None, None, None) => {}
+        (Some(_), _, _) => return err!(LendingError::InvalidFarmAccount),
+        (None, _, _) => return err!(LendingError::FarmNotConfigured),
+    }
+    Ok(())
+}
+
+/// An optional account as Kamino reads it: absent when it is the Kamino program itself
+fn or_kamino<'info>(account: &Option<AccountInfo<'info>>, kamino_program: &AccountInfo<'info>) -> AccountInfo<'info> {
+    account.clone().unwrap_or_else(|| kamino_program.clone())
+}
+
+pub fn repay_and_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, KaminoRepayAndWithdrawAndRedeem<'info>>,
+    repay_amount: u64,
+    withdraw_collateral_amount: u64,
+) -> Result<()> {
+    require!(
+        repay_amount > 0 && withdraw_collateral_amount > 0,
+        LendingError::InvalidAmount
+    );
+    // Repayments are only stopped by a global pause, which this checks too
+    ctx.accounts.config.require_not_paused(PauseTarget::Withdrawals)?;
+    let registry = &ctx.accounts.registry;
+    let lending_market = ctx.accounts.lending_market.key;
+    registry.require_reserve(lending_market, ctx.accounts.repay_reserve.key)?;
+    registry.require_reserve(lending_market, ctx.accounts.withdraw_reserve.key)?;
+
+    let kamino_program = ctx.accounts.kamino_lending_program.key;
+    ReserveAccounts {
+        lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+        liquidity_supply: Some(ctx.accounts.repay_reserve_liquidity_supply.key),
+        ..ReserveAccounts::default()
+    }
+    .require_derived(kamino_program, lending_market, &ctx.accounts.repay_reserve_liquidity_mint.key())?;
+    ReserveAccounts {
+        liquidity_supply: Some(ctx.accounts.withdraw_reserve_liquidity_supply.key),
+        collateral_mint: Some(&ctx.accounts.withdraw_reserve_collateral_mint.key()),
+        collateral_supply: Some(ctx.accounts.withdraw_reserve_collateral_supply.key),
+        ..ReserveAccounts::default()
+    }
+    .require_derived(kamino_program, lending_market, &ctx.accounts.withdraw_reserve_liquidity_mint.key())?;
+    let slot = Clock::get()?.slot;
+    preflight::require_reserve_fresh(&ctx.accounts.repay_reserve, kamino_program, slot)?;
+    preflight::require_reserve_fresh(&ctx.accounts.withdraw_reserve, kamino_program, slot)?;
+    preflight::require_obligation_fresh(&ctx.accounts.obligation, kamino_program, slot)?;
+    require_farm_accounts(
+        &ctx.accounts.repay_reserve,
+        kamino_program,
+        ReserveFarmKind::Debt,
+        ctx.accounts.debt_reserve_farm_state.as_ref(),
+        ctx.accounts.debt_obligation_farm_user_state.as_ref(),
+    )?;
+    require_farm_accounts(
+        &ctx.accounts.withdraw_reserve,
+        kamino_program,
+        ReserveFarmKind::Collateral,
+        ctx.accounts.collateral_reserve_farm_state.as_ref(),
+        ctx.accounts.collateral_obligation_farm_user_state.as_ref(),
+    )?;
+
+    let repay_amount = sentinel::repay_amount(
+        &ctx.accounts.obligation,
+        kamino_program,
+        ctx.accounts.repay_reserve.key,
+        repay_amount,
+    )?;
+    require!(
+        ctx.accounts.user_source_liquidity.amount >= repay_amount,
+        LendingError::InsufficientFunds
+    );
+    // Repaying does not touch the deposits, so they can be read before it
+    let withdraw_collateral_amount = sentinel::withdraw_amount(
+        &ctx.accounts.obligation,
+        kamino_program,
+        ctx.accounts.withdraw_reserve.key,
+        withdraw_collateral_amount,
+    )?;
+
+    let repay_mint = ctx.accounts.repay_reserve_liquidity_mint.to_account_info();
+    token::validate_mint(&repay_mint)?;
+    token::validate_mint(&ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info())?;
+    let repay_received = token::amount_after_transfer_fee(&repay_mint, repay_amount)?;
+    let balance_before = ctx.accounts.user_destination_liquidity.amount;
+
+    let kamino = ctx.accounts.kamino_lending_program.to_account_info();
+    let mut cpi_accounts = vec![
+        // Repay
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.repay_reserve.to_account_info(),
+        repay_mint,
+        ctx.accounts.repay_reserve_liquidity_supply.to_account_info(),
+        ctx.accounts.user_source_liquidity.to_account_info(),
+        ctx.accounts.repay_liquidity_token_program.to_account_info(),
+        ctx.accounts.instruction_sysvar_account.to_account_info(),
+        // Withdraw
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts.withdraw_reserve.to_account_info(),
+        ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info(),
+        ctx.accounts.withdraw_reserve_collateral_supply.to_account_info(),
+        ctx.accounts.withdraw_reserve_collateral_mint.to_account_info(),
+        ctx.accounts.withdraw_reserve_liquidity_supply.to_account_info(),
+        ctx.accounts.user_destination_liquidity.to_account_info(),
+        // No user collateral account: the collateral is redeemed
+        kamino.clone(),
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.withdraw_liquidity_token_program.to_account_info(),
+        ctx.accounts.instruction_sysvar_account.to_account_info(),
+        // Farms
+        or_kamino(&ctx.accounts.collateral_obligation_farm_user_state, &kamino),
+        or_kamino(&ctx.accounts.collateral_reserve_farm_state, &kamino),
+        or_kamino(&ctx.accounts.debt_obligation_farm_user_state, &kamino),
+        or_kamino(&ctx.accounts.debt_reserve_farm_state, &kamino),
+        ctx.accounts.farms_program.to_account_info(),
+    ];
+    cpi_accounts.extend(ctx.remaining_accounts.iter().cloned());
+
+    let instruction_data = serialize_kamino_instruction(
+        instruction_discriminator::REPAY_AND_WITHDRAW_AND_REDEEM,
+        &(repay_amount, withdraw_collateral_amount),
+    )?;
+
+    invoke_kamino(&kamino, &cpi_accounts, instruction_data)?;
+
+    ctx.accounts.user_destination_liquidity.reload()?;
+    let received_amount = ctx
+        .accounts
+        .user_destination_liquidity
+        .amount
+        .saturating_sub(balance_before);
+
+    let metrics = ObligationMetrics::load(&ctx.accounts.obligation, kamino_program)?;
+    limits::require_healthy(&metrics)?;
+    let now = Clock::get()?.unix_timestamp;
+    limits::record_user_borrow_value(
+        &ctx.accounts.config,
+        &mut ctx.accounts.user_borrows,
+        ctx.accounts.obligation.key(),
+        &metrics,
+        now,
+    )?;
+
+    let position = &mut ctx.accounts.user_position;
+    position.record(ctx.accounts.repay_reserve.key(), PositionAction::Repay, repay_amount, now)?;
+    position.record(
+        ctx.accounts.withdraw_reserve.key(),
+        PositionAction::Withdraw,
+        received_amount,
+        now,
+    )?;
+    ctx.accounts.withdraw_reserve_usage.record_withdrawal(received_amount);
+
+    emit_cpi!(Repaid {
+        owner: ctx.accounts.owner.key(),
+        reserve: ctx.accounts.repay_reserve.key(),
+        obligation: ctx.accounts.obligation.key(),
+        liquidity_amount: repay_amount,
+        liquidity_received: repay_received,
+        metrics,
+    });
+    emit_cpi!(Withdrawn {
+        owner: ctx.accounts.owner.key(),
+        reserve: ctx.accounts.withdraw_reserve.key(),
+        obligation: ctx.accounts.obligation.key(),
+        collateral_amount: withdraw_collateral_amount,
+        liquidity_received: received_amount,
+        metrics,
+    });
+
+    msg!(
+        "Successfully repaid {} liquidity and withdrew {} collateral ({} liquidity received)",
+        repay_amount,
+        withdraw_collateral_amount,
+        received_amount
+    );
+    Ok(())
+}
+
+pub fn deposit_and_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, KaminoDepositAndWithdraw<'info>>,
+    liquidity_amount: u64,
+    withdraw_collateral_amount: u64,
+) -> Result<()> {
+    require!(
+        liquidity_amount > 0 && withdraw_collateral_amount > 0,
+        LendingError::InvalidAmount
+    );
+    let config = &ctx.accounts.config;
+    config.require_not_paused(PauseTarget::Deposits)?;
+    config.require_not_paused(PauseTarget::Withdrawals)?;
+    let registry = &ctx.accounts.registry;
+    let lending_market = ctx.accounts.lending_market.key;
+    let entry = registry.require_reserve(lending_market, ctx.accounts.deposit_reserve.key)?;
+    registry.require_reserve(lending_market, ctx.accounts.withdraw_reserve.key)?;
+    require!(
+        entry.decimals == ctx.accounts.deposit_reserve_liquidity_mint.decimals,
+        LendingError::ReserveMetadataMismatch
+    );
+
+    let kamino_program = ctx.accounts.kamino_lending_program.key;
+    ReserveStatus::require_active(&ctx.accounts.deposit_reserve, kamino_program)?;
+    ReserveAccounts {
+        lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+        liquidity_supply: Some(ctx.accounts.deposit_reserve_liquidity_supply.key),
+        collateral_mint: Some(&ctx.accounts.deposit_reserve_collateral_mint.key()),
+        collateral_supply: Some(&ctx.accounts.deposit_reserve_collateral_supply.key()),
+        ..ReserveAccounts::default()
+    }
+    .require_derived(kamino_program, lending_market, &ctx.accounts.deposit_reserve_liquidity_mint.key())?;
+    ReserveAccounts {
+        liquidity_supply: Some(ctx.accounts.withdraw_reserve_liquidity_supply.key),
+        collateral_mint: Some(&ctx.accounts.withdraw_reserve_collateral_mint.key()),
+        collateral_supply: Some(ctx.accounts.withdraw_reserve_collateral_supply.key),
+        ..ReserveAccounts::default()
+    }
+    .require_derived(kamino_program, lending_market, &ctx.accounts.withdraw_reserve_liquidity_mint.key())?;
+    let clock = Clock::get()?;
+    preflight::require_reserve_fresh(&ctx.accounts.deposit_reserve, kamino_program, clock.slot)?;
+    preflight::require_reserve_fresh(&ctx.accounts.withdraw_reserve, kamino_program, clock.slot)?;
+    preflight::require_obligation_fresh(&ctx.accounts.obligation, kamino_program, clock.slot)?;
+    require_farm_accounts(
+        &ctx.accounts.deposit_reserve,
+        kamino_program,
+        ReserveFarmKind::Collateral,
+        ctx.accounts.deposit_reserve_farm_state.as_ref(),
+        ctx.accounts.deposit_obligation_farm_user_state.as_ref(),
+    )?;
+    require_farm_accounts(
+        &ctx.accounts.withdraw_reserve,
+        kamino_program,
+        ReserveFarmKind::Collateral,
+        ctx.accounts.withdraw_reserve_farm_state.as_ref(),
+        ctx.accounts.withdraw_obligation_farm_user_state.as_ref(),
+    )?;
+    ctx.accounts
+        .deposit_reserve_usage
+        .record_deposit(entry, liquidity_amount, clock.epoch)?;
+
+    // The deposit goes to another reserve, so the withdrawal can be resolved before it
+    let withdraw_collateral_amount = sentinel::withdraw_amount(
+        &ctx.accounts.obligation,
+        kamino_program,
+        ctx.accounts.withdraw_reserve.key,
+        withdraw_collateral_amount,
+    )?;
+
+    let deposit_mint = ctx.accounts.deposit_reserve_liquidity_mint.to_account_info();
+    token::validate_mint(&deposit_mint)?;
+    token::validate_mint(&ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info())?;
+    let deposit_received = token::amount_after_transfer_fee(&deposit_mint, liquidity_amount)?;
+    let collateral_before = ctx.accounts.deposit_reserve_collateral_supply.amount;
+    let balance_before = ctx.accounts.user_destination_liquidity.amount;
+
+    let kamino = ctx.accounts.kamino_lending_program.to_account_info();
+    let mut cpi_accounts = vec![
+        // Deposit
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts.deposit_reserve.to_account_info(),
+        deposit_mint,
+        ctx.accounts.deposit_reserve_liquidity_supply.to_account_info(),
+        ctx.accounts.deposit_reserve_collateral_mint.to_account_info(),
+        ctx.accounts.deposit_reserve_collateral_supply.to_account_info(),
+        ctx.accounts.user_source_liquidity.to_account_info(),
+        // No user collateral account: the collateral goes straight to the obligation
+        kamino.clone(),
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.deposit_liquidity_token_program.to_account_info(),
+        ctx.accounts.instruction_sysvar_account.to_account_info(),
+        // Withdraw
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts.withdraw_reserve.to_account_info(),
+        ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info(),
+        ctx.accounts.withdraw_reserve_collateral_supply.to_account_info(),
+        ctx.accounts.withdraw_reserve_collateral_mint.to_account_info(),
+        ctx.accounts.withdraw_reserve_liquidity_supply.to_account_info(),
+        ctx.accounts.user_destination_liquidity.to_account_info(),
+        kamino.clone(),
+        ctx.accounts.collateral_token_program.to_account_info(),
+        ctx.accounts.withdraw_liquidity_token_program.to_account_info(),
+        ctx.accounts.instruction_sysvar_account.to_account_info(),
+        // Farms
+        or_kamino(&ctx.accounts.deposit_obligation_farm_user_state, &kamino),
+        or_kamino(&ctx.accounts.deposit_reserve_farm_state, &kamino),
+        or_kamino(&ctx.accounts.withdraw_obligation_farm_user_state, &kamino),
+        or_kamino(&ctx.accounts.withdraw_reserve_farm_state, &kamino),
+        ctx.accounts.farms_program.to_account_info(),
+    ];
+    cpi_accounts.extend(ctx.remaining_accounts.iter().cloned());
+
+    let instruction_data = serialize_kamino_instruction(
+        instruction_discriminator::DEPOSIT_AND_WITHDRAW,
+        &(liquidity_amount, withdraw_collateral_amount),
+    )?;
+
+    invoke_kamino(&kamino, &cpi_accounts, instruction_data)?;
+
+    ctx.accounts.deposit_reserve_collateral_supply.reload()?;
+    let collateral_minted = ctx
+        .accounts
+        .deposit_reserve_collateral_supply
+        .amount
+        .saturating_sub(collateral_before);
+    ctx.accounts.user_destination_liquidity.reload()?;
+    let received_amount = ctx
+        .accounts
+        .user_destination_liquidity
+        .amount
+        .saturating_sub(balance_before);
+
+    let metrics = ObligationMetrics::load(&ctx.accounts.obligation, kamino_program)?;
+    limits::require_healthy(&metrics)?;
+    let now = clock.unix_timestamp;
+    limits::record_user_borrow_value(
+        &ctx.accounts.config,
+        &mut ctx.accounts.user_borrows,
+        ctx.accounts.obligation.key(),
+        &metrics,
+        now,
+    )?;
+
+    let position = &mut ctx.accounts.user_position;
+    position.record(ctx.accounts.deposit_reserve.key(), PositionAction::Deposit, liquidity_amount, now)?;
+    position.record(
+        ctx.accounts.withdraw_reserve.key(),
+        PositionAction::Withdraw,
+        received_amount,
+        now,
+    )?;
+    ctx.accounts.withdraw_reserve_usage.record_withdrawal(received_amount);
+
+    emit_cpi!(Deposited {
+        owner: ctx.accounts.owner.key(),
+        reserve: ctx.accounts.deposit_reserve.key(),
+        obligation: Some(ctx.accounts.obligation.key()),
+        liquidity_amount,
+        liquidity_received: deposit_received,
+        collateral_minted,
+        metrics: Some(metrics),
+    });
+    emit_cpi!(Withdrawn {
+        owner: ctx.accounts.owner.key(),
+        reserve: ctx.accounts.withdraw_reserve.key(),
+        obligation: ctx.accounts.obligation.key(),
+        collateral_amount: withdraw_collateral_amount,
+        liquidity_received: received_amount,
+        metrics,
+    });
+
+    msg!(
+        "Successfully deposited {} liquidity and withdrew {} collateral ({} liquidity received)",
+        liquidity_amount,
+        withdraw_collateral_amount,
+        received_amount
+    );
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct KaminoRepayAndWithdrawAndRedeem<'info> {
+    /// The obligation owner repaying and withdrawing
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// What the owner has borrowed across their obligations
+    #[account(
+        mut,
+        seeds = [USER_BORROWS_SEED, owner.key().as_ref()],
+        bump = user_borrows.bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// Usage of the withdraw reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, withdraw_reserve.key().as_ref()],
+        bump = withdraw_reserve_usage.bump,
+    )]
+    pub withdraw_reserve_usage: Box<Account<'info, ReserveUsage>>,
+
+    /// The obligation account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Validated by Kamino program
+    pub lending_market: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Checked against its derivation from the lending market
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve to repay to
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub repay_reserve: AccountInfo<'info>,
+
+    /// The repay reserve's liquidity mint
+    #[account(mint::token_program = repay_liquidity_token_program)]
+    pub repay_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The repay reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub repay_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The user's source liquidity account for the repayment
+    #[account(
+        mut,
+        token::mint = repay_reserve_liquidity_mint,
+        token::authority = owner,
+        token::token_program = repay_liquidity_token_program,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The reserve to withdraw from
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve: AccountInfo<'info>,
+
+    /// The withdraw reserve's liquidity mint
+    #[account(mint::token_program = withdraw_liquidity_token_program)]
+    pub withdraw_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's collateral supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub withdraw_reserve_collateral_supply: AccountInfo<'info>,
+
+    /// The withdraw reserve's collateral mint
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub withdraw_reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub withdraw_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The user's destination liquidity account for the redeemed collateral
+    #[account(
+        mut,
+        token::mint = withdraw_reserve_liquidity_mint,
+        token::token_program = withdraw_liquidity_token_program,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The obligation's stake in the withdraw reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub collateral_obligation_farm_user_state: Option<AccountInfo<'info>>,
+
+    /// The withdraw reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's collateral farm
+    pub collateral_reserve_farm_state: Option<AccountInfo<'info>>,
+
+    /// The obligation's stake in the repay reserve's debt farm, if it has one
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub debt_obligation_farm_user_state: Option<AccountInfo<'info>>,
+
+    /// The repay reserve's debt farm, if it has one
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's debt farm
+    pub debt_reserve_farm_state: Option<AccountInfo<'info>>,
+
+    /// The Kamino farms program
+    /// CHECK: Kamino's farms program ID
+    #[account(address = KAMINO_FARMS_ID)]
+    pub farms_program: AccountInfo<'info>,
+
+    /// The collateral token program
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// The repay reserve's liquidity token program
+    pub repay_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The withdraw reserve's liquidity token program
+    pub withdraw_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct KaminoDepositAndWithdraw<'info> {
+    /// The obligation owner swapping collateral
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// What the owner has borrowed across their obligations
+    #[account(
+        mut,
+        seeds = [USER_BORROWS_SEED, owner.key().as_ref()],
+        bump = user_borrows.bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// Usage of the deposit reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, deposit_reserve.key().as_ref()],
+        bump = deposit_reserve_usage.bump,
+    )]
+    pub deposit_reserve_usage: Box<Account<'info, ReserveUsage>>,
+
+    /// Usage of the withdraw reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, withdraw_reserve.key().as_ref()],
+        bump = withdraw_reserve_usage.bump,
+    )]
+    pub withdraw_reserve_usage: Box<Account<'info, ReserveUsage>>,
+
+    /// The obligation account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Validated by Kamino program
+    pub lending_market: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Checked against its derivation from the lending market
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve to deposit into
+    #[account(
+        mut,
+        constraint = deposit_reserve.key() != withdraw_reserve.key() @ LendingError::SameReserve,
+    )]
+    /// CHECK: Validated by Kamino program
+    pub deposit_reserve: AccountInfo<'info>,
+
+    /// The deposit reserve's liquidity mint
+    #[account(mint::token_program = deposit_liquidity_token_program)]
+    pub deposit_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The deposit reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub deposit_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The deposit reserve's collateral mint
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub deposit_reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The deposit reserve's collateral supply account, which receives the new collateral
+    #[account(mut, token::token_program = collateral_token_program)]
+    pub deposit_reserve_collateral_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user's source liquidity account for the deposit
+    #[account(
+        mut,
+        token::mint = deposit_reserve_liquidity_mint,
+        token::authority = owner,
+        token::token_program = deposit_liquidity_token_program,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The reserve to withdraw from
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve: AccountInfo<'info>,
+
+    /// The withdraw reserve's liquidity mint
+    #[account(mint::token_program = withdraw_liquidity_token_program)]
+    pub withdraw_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's collateral supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub withdraw_reserve_collateral_supply: AccountInfo<'info>,
+
+    /// The withdraw reserve's collateral mint
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub withdraw_reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Checked against its derivation from the reserve's mint
+    pub withdraw_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The user's destination liquidity account for the redeemed collateral
+    #[account(
+        mut,
+        token::mint = withdraw_reserve_liquidity_mint,
+        token::token_program = withdraw_liquidity_token_program,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The obligation's stake in the deposit reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub deposit_obligation_farm_user_state: Option<AccountInfo<'info>>,
+
+    /// The deposit reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's collateral farm
+    pub deposit_reserve_farm_state: Option<AccountInfo<'info>>,
+
+    /// The obligation's stake in the withdraw reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_obligation_farm_user_state: Option<AccountInfo<'info>>,
+
+    /// The withdraw reserve's collateral farm, if it has one
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's collateral farm
+    pub withdraw_reserve_farm_state: Option<AccountInfo<'info>>,
+
+    /// The Kamino farms program
+    /// CHECK: Kamino's farms program ID
+    #[account(address = KAMINO_FARMS_ID)]
+    pub farms_program: AccountInfo<'info>,
+
+    /// The collateral token program
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// The deposit reserve's liquidity token program
+    pub deposit_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The withdraw reserve's liquidity token program
+    pub withdraw_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
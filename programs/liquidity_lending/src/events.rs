@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+
+use crate::kamino::ObligationMetrics;
+use crate::state::{ConfigChange, PauseTarget, Role};
+
+/// Liquidity deposited into a reserve in exchange for collateral tokens
+#[event]
+pub struct Deposited {
+    pub owner: Pubkey,
+    pub reserve: Pubkey,
+    /// Obligation the collateral was deposited into, if any
+    pub obligation: Option<Pubkey>,
+    pub liquidity_amount: u64,
+    /// Liquidity that reached the reserve after transfer fees
+    pub liquidity_received: u64,
+    pub collateral_minted: u64,
+    pub metrics: Option<ObligationMetrics>,
+}
+
+/// Liquidity borrowed against an obligation
+#[event]
+pub struct Borrowed {
+    pub owner: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub liquidity_amount: u64,
+    /// Liquidity that reached the user after borrow and transfer fees
+    pub liquidity_received: u64,
+    pub metrics: ObligationMetrics,
+}
+
+/// Borrowed liquidity repaid to a reserve
+#[event]
+pub struct Repaid {
+    pub owner: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub liquidity_amount: u64,
+    /// Liquidity that reached the reserve after transfer fees
+    pub liquidity_received: u64,
+    pub metrics: ObligationMetrics,
+}
+
+/// Collateral withdrawn from an obligation and redeemed for liquidity
+#[event]
+pub struct Withdrawn {
+    pub owner: Pubkey,
+    pub reserve: Pubkey,
+    pub obligation: Pubkey,
+    pub collateral_amount: u64,
+    pub liquidity_received: u64,
+    pub metrics: ObligationMetrics,
+}
+
+/// An unhealthy obligation liquidated by `liquidator`
+#[event]
+pub struct Liquidated {
+    pub liquidator: Pubkey,
+    pub obligation: Pubkey,
+    pub repay_reserve: Pubkey,
+    pub withdraw_reserve: Pubkey,
+    pub liquidity_amount: u64,
+    /// Withdraw reserve liquidity the liquidator received
+    pub liquidity_received: u64,
+    pub metrics: ObligationMetrics,
+}
+
+/// A Kamino flash borrow and its repayment within one transaction
+#[event]
+pub struct FlashLoan {
+    pub user: Pubkey,
+    pub reserve: Pubkey,
+    pub liquidity_amount: u64,
+    pub repay_amount: u64,
+    pub borrow_instruction_index: u8,
+    pub repay_instruction_index: u8,
+}
+
+/// A pause flag set or cleared by the admin or guardian
+#[event]
+pub struct PauseUpdated {
+    pub authority: Pubkey,
+    pub target: PauseTarget,
+    pub paused: bool,
+    /// All pause flags after the update
+    pub flags: u8,
+    pub timestamp: i64,
+}
+
+/// A role granted by the admin
+#[event]
+pub struct RoleGranted {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub granted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// A role revoked by the admin
+#[event]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// First step of an admin transfer
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Admin transfer accepted by the proposed admin
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// A configuration change queued behind the timelock
+#[event]
+pub struct ConfigChangeQueued {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub proposer: Pubkey,
+    pub eta: i64,
+}
+
+/// A queued configuration change applied after its timelock elapsed
+#[event]
+pub struct ConfigChangeExecuted {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// A queued configuration change dropped before it was applied
+#[event]
+pub struct ConfigChangeCancelled {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,510 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::kamino::{
+    deserialize_account, instruction_discriminator, invoke_kamino, serialize_kamino_instruction,
+    ReserveFarmKind, ReserveHeader, KAMINO_LENDING_ID,
+};
+use crate::state::{
+    Config, PauseTarget, Registry, Role, RoleAssignment, CONFIG_SEED, REGISTRY_SEED, ROLE_SEED,
+};
+use crate::{kamino_pda, token, LendingError};
+
+/// The Kamino farms program, on mainnet and in local tests
+pub const KAMINO_FARMS_ID: Pubkey = pubkey!("FarmsPZpWu9i7Kky8tPN37rs2TpmMrAZrC7S7vJa91Hr");
+
+/// Number of reward slots tracked per farm
+pub const FARMS_MAX_REWARDS: usize = 10;
+
+/// Anchor discriminator of the `harvestReward` instruction in `external_idls/kamino_farms.json`,
+/// the first 8 bytes of `sha256("global:harvest_reward")`
+pub const FARMS_HARVEST_REWARD_DISCRIMINATOR: [u8; 8] = [68, 200, 228, 233, 184, 32, 226, 188];
+
+/// Anchor discriminator of the Kamino farms `UserState` account
+pub const USER_STATE_DISCRIMINATOR: [u8; 8] = [72, 177, 85, 249, 76, 167, 186, 126];
+
+/// Kamino farms `UserState`, the per-obligation stake record of a reserve farm, as laid out in
+/// `external_idls/kamino_farms.json`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserState {
+    pub user_id: u64,
+    pub farm_state: Pubkey,
+    pub owner: Pubkey,
+    pub is_farm_delegated: u8,
+    pub padding0: [u8; 7],
+    pub rewards_tally_scaled: [u128; FARMS_MAX_REWARDS],
+    pub rewards_issued_unclaimed: [u64; FARMS_MAX_REWARDS],
+    pub last_claim_ts: [u64; FARMS_MAX_REWARDS],
+    pub active_stake_scaled: u128,
+    pub pending_deposit_stake_scaled: u128,
+    pub pending_deposit_stake_ts: u64,
+    pub pending_withdrawal_unstake_scaled: u128,
+    pub pending_withdrawal_unstake_ts: u64,
+    pub bump: u64,
+    pub delegatee: Pubkey,
+    pub last_stake_ts: u64,
+    pub padding1: [u64; 50],
+}
+
+impl UserState {
+    /// Decode a user state owned by `farms_program`
+    pub fn load(user_state: &AccountInfo, farms_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*user_state.owner, *farms_program, LendingError::InvalidFarmAccount);
+        deserialize_account(user_state, &USER_STATE_DISCRIMINATOR, 0)
+    }
+
+    /// Rewards issued to this user but not yet harvested, for the given reward slot
+    pub fn pending_rewards(&self, reward_index: usize) -> Option<u64> {
+        self.rewards_issued_unclaimed.get(reward_index).copied()
+    }
+}
+
+/// Load `reserve`, which the registry must approve in `lending_market`
+fn load_approved_reserve(
+    registry: &Registry,
+    reserve: &AccountInfo,
+    lending_market: &AccountInfo,
+    kamino_program: &AccountInfo,
+) -> Result<ReserveHeader> {
+    registry.require_reserve(lending_market.key, reserve.key)?;
+    let header = ReserveHeader::load(reserve, kamino_program.key)?;
+    require_keys_eq!(
+        header.lending_market,
+        lending_market.key(),
+        LendingError::InvalidAccountState
+    );
+    Ok(header)
+}
+
+/// Check that `reserve_farm_state` is the farm Kamino attached to `reserve` for `mode`
+fn validate_reserve_farm(
+    registry: &Registry,
+    reserve: &AccountInfo,
+    lending_market: &AccountInfo,
+    lending_market_authority: &AccountInfo,
+    reserve_farm_state: &AccountInfo,
+    kamino_program: &AccountInfo,
+    mode: ReserveFarmKind,
+) -> Result<()> {
+    kamino_pda::require_derived(
+        lending_market_authority.key,
+        kamino_pda::lending_market_authority(kamino_program.key, lending_market.key),
+    )?;
+    let header = load_approved_reserve(registry, reserve, lending_market, kamino_program)?;
+
+    let farm = header.farm(mode).ok_or(LendingError::FarmNotConfigured)?;
+    require_keys_eq!(farm, reserve_farm_state.key(), LendingError::InvalidFarmAccount);
+    Ok(())
+}
+
+pub fn init_obligation_farms_for_reserve(
+    ctx: Context<KaminoInitObligationFarmsForReserve>,
+    mode: ReserveFarmKind,
+) -> Result<()> {
+    validate_reserve_farm(
+        &ctx.accounts.registry,
+        &ctx.accounts.reserve,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        &ctx.accounts.reserve_farm_state,
+        &ctx.accounts.kamino_lending_program,
+        mode,
+    )?;
+
+    let cpi_accounts = vec![
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts.reserve.to_account_info(),
+        ctx.accounts.reserve_farm_state.to_account_info(),
+        ctx.accounts.obligation_farm.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.farms_program.to_account_info(),
+        ctx.accounts.rent.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    ];
+
+    let instruction_data = serialize_kamino_instruction(
+        instruction_discriminator::INIT_OBLIGATION_FARMS_FOR_RESERVE,
+        &mode,
+    )?;
+
+    invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+    msg!(
+        "Initialized {:?} farm for obligation {}",
+        mode,
+        ctx.accounts.obligation.key()
+    );
+    Ok(())
+}
+
+pub fn refresh_obligation_farms_for_reserve(
+    ctx: Context<KaminoRefreshObligationFarmsForReserve>,
+    mode: ReserveFarmKind,
+) -> Result<()> {
+    validate_reserve_farm(
+        &ctx.accounts.registry,
+        &ctx.accounts.reserve,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        &ctx.accounts.reserve_farm_state,
+        &ctx.accounts.kamino_lending_program,
+        mode,
+    )?;
+
+    let cpi_accounts = vec![
+        ctx.accounts.crank.to_account_info(),
+        ctx.accounts.obligation.to_account_info(),
+        ctx.accounts.lending_market_authority.to_account_info(),
+        ctx.accounts.reserve.to_account_info(),
+        ctx.accounts.reserve_farm_state.to_account_info(),
+        ctx.accounts.obligation_farm_user_state.to_account_info(),
+        ctx.accounts.lending_market.to_account_info(),
+        ctx.accounts.farms_program.to_account_info(),
+        ctx.accounts.rent.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    ];
+
+    let instruction_data = serialize_kamino_instruction(
+        instruction_discriminator::REFRESH_OBLIGATION_FARMS_FOR_RESERVE,
+        &mode,
+    )?;
+
+    invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+    msg!(
+        "Refreshed {:?} farm for obligation {}",
+        mode,
+        ctx.accounts.obligation.key()
+    );
+    Ok(())
+}
+
+pub fn claim_farm_rewards(ctx: Context<KaminoClaimFarmRewards>, reward_index: u64) -> Result<()> {
+    ctx.accounts.config.require_not_paused(PauseTarget::Global)?;
+
+    let user_state = UserState::load(
+        &ctx.accounts.obligation_farm_user_state,
+        ctx.accounts.farms_program.key,
+    )?;
+    require_keys_eq!(
+        user_state.farm_state,
+        ctx.accounts.farm_state.key(),
+        LendingError::InvalidFarmAccount
+    );
+    require_keys_eq!(
+        user_state.owner,
+        ctx.accounts.owner.key(),
+        LendingError::InvalidFarmAccount
+    );
+    // Only farms of approved reserves pay out through the program
+    let header = load_approved_reserve(
+        &ctx.accounts.registry,
+        &ctx.accounts.reserve,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.kamino_lending_program,
+    )?;
+    require!(
+        [ReserveFarmKind::Collateral, ReserveFarmKind::Debt]
+            .into_iter()
+            .any(|kind| header.farm(kind) == Some(user_state.farm_state)),
+        LendingError::InvalidFarmAccount
+    );
+
+    token::validate_mint(&ctx.accounts.reward_mint.to_account_info())?;
+
+    let pending = usize::try_from(reward_index)
+        .ok()
+        .and_then(|index| user_state.pending_rewards(index))
+        .ok_or(LendingError::InvalidInstructionData)?;
+
+    let balance_before = ctx.accounts.user_reward_ata.amount;
+
+    let farms_program = ctx.accounts.farms_program.to_account_info();
+    let cpi_accounts = vec![
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.obligation_farm_user_state.to_account_info(),
+        ctx.accounts.farm_state.to_account_info(),
+        ctx.accounts.global_config.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.user_reward_ata.to_account_info(),
+        ctx.accounts.rewards_vault.to_account_info(),
+        ctx.accounts.rewards_treasury_vault.to_account_info(),
+        ctx.accounts.farm_vaults_authority.to_account_info(),
+        ctx.accounts
+            .scope_prices
+            .clone()
+            .unwrap_or(farms_program.clone())
+            .to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    ];
+
+    let instruction_data = serialize_kamino_instruction(FARMS_HARVEST_REWARD_DISCRIMINATOR, &reward_index)?;
+
+    invoke_kamino(&farms_program, &cpi_accounts, instruction_data)?;
+
+    ctx.accounts.user_reward_ata.reload()?;
+    let harvested = ctx
+        .accounts
+        .user_reward_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(LendingError::InvalidAccountState)?;
+
+    // The program's fee comes out of the harvest before anything is forwarded
+    let fee = ctx.accounts.config.fee(harvested);
+    if fee > 0 {
+        let fee_account = ctx
+            .accounts
+            .fee_account
+            .as_ref()
+            .ok_or(LendingError::MissingFeeAccount)?;
+        ctx.accounts.transfer_rewards(fee_account.to_account_info(), fee)?;
+    }
+    let claimed = harvested - fee;
+
+    // Forward the harvested rewards when a different destination (e.g. a vault) is given
+    if let Some(destination) = &ctx.accounts.rewards_destination {
+        if destination.key() != ctx.accounts.user_reward_ata.key() && claimed > 0 {
+            ctx.accounts.transfer_rewards(destination.to_account_info(), claimed)?;
+        }
+    }
+
+    msg!(
+        "Successfully claimed {} rewards from farm {} ({} were pending, {} paid as fee)",
+        claimed,
+        ctx.accounts.farm_state.key(),
+        pending,
+        fee
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct KaminoInitObligationFarmsForReserve<'info> {
+    /// The account paying for the obligation farm account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The obligation owner
+    /// CHECK: Validated by Kamino program
+    pub owner: AccountInfo<'info>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The obligation account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Validated by Kamino program
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve whose farm the obligation joins
+    #[account(mut)]
+    /// CHECK: Decoded and checked against the reserve farm state
+    pub reserve: AccountInfo<'info>,
+
+    /// The reserve's farm state for the requested kind
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's configured farm
+    pub reserve_farm_state: AccountInfo<'info>,
+
+    /// The obligation's farm user state to create
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation_farm: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Checked against the reserve's lending market
+    pub lending_market: AccountInfo<'info>,
+
+    /// The Kamino farms program
+    /// CHECK: Kamino's farms program ID
+    #[account(address = KAMINO_FARMS_ID)]
+    pub farms_program: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct KaminoRefreshObligationFarmsForReserve<'info> {
+    /// The keeper cranking the refresh
+    pub crank: Signer<'info>,
+
+    /// The crank's keeper role assignment
+    #[account(
+        seeds = [ROLE_SEED, &Role::Keeper.seed(), crank.key().as_ref()],
+        bump = keeper_role.bump,
+        constraint = keeper_role.role == Role::Keeper @ LendingError::NotKeeper,
+    )]
+    pub keeper_role: Account<'info, RoleAssignment>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The obligation account
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Validated by Kamino program
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve whose farm is refreshed
+    /// CHECK: Decoded and checked against the reserve farm state
+    pub reserve: AccountInfo<'info>,
+
+    /// The reserve's farm state for the requested kind
+    #[account(mut)]
+    /// CHECK: Checked against the reserve's configured farm
+    pub reserve_farm_state: AccountInfo<'info>,
+
+    /// The obligation's farm user state
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation_farm_user_state: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Checked against the reserve's lending market
+    pub lending_market: AccountInfo<'info>,
+
+    /// The Kamino farms program
+    /// CHECK: Kamino's farms program ID
+    #[account(address = KAMINO_FARMS_ID)]
+    pub farms_program: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct KaminoClaimFarmRewards<'info> {
+    /// The obligation owner harvesting the rewards
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The reserve whose collateral or debt farm pays the reward
+    /// CHECK: Decoded and checked against the registry and the farm state
+    pub reserve: AccountInfo<'info>,
+
+    /// The lending market account
+    /// CHECK: Checked against the reserve's lending market
+    pub lending_market: AccountInfo<'info>,
+
+    /// The obligation's farm user state
+    #[account(mut)]
+    /// CHECK: Decoded and checked against the farm state and owner
+    pub obligation_farm_user_state: AccountInfo<'info>,
+
+    /// The reserve's farm state
+    #[account(mut)]
+    /// CHECK: Checked against the user state
+    pub farm_state: AccountInfo<'info>,
+
+    /// The farms global config
+    /// CHECK: Validated by farms program
+    pub global_config: AccountInfo<'info>,
+
+    /// The mint of the reward being harvested
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The owner's token account receiving the harvested rewards
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub user_reward_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The farm's vault for this reward
+    #[account(mut)]
+    /// CHECK: Validated by farms program
+    pub rewards_vault: AccountInfo<'info>,
+
+    /// The farm's treasury vault for this reward
+    #[account(mut)]
+    /// CHECK: Validated by farms program
+    pub rewards_treasury_vault: AccountInfo<'info>,
+
+    /// The farm vaults authority
+    /// CHECK: Validated by farms program
+    pub farm_vaults_authority: AccountInfo<'info>,
+
+    /// Optional scope prices account used by the farm
+    /// CHECK: Validated by farms program
+    pub scope_prices: Option<AccountInfo<'info>>,
+
+    /// Optional account the harvested rewards are forwarded to, e.g. a vault
+    #[account(mut, token::mint = reward_mint, token::token_program = token_program)]
+    pub rewards_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The fee recipient's token account for the reward, required while a fee is charged
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = config.fee_recipient,
+        token::token_program = token_program,
+    )]
+    pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The Kamino farms program
+    /// CHECK: Kamino's farms program ID
+    #[account(address = KAMINO_FARMS_ID)]
+    pub farms_program: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
+
+impl<'info> KaminoClaimFarmRewards<'info> {
+    /// Move `amount` of harvested rewards out of the owner's reward account
+    fn transfer_rewards(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.user_reward_ata.to_account_info(),
+                    mint: self.reward_mint.to_account_info(),
+                    to,
+                    authority: self.owner.to_account_info(),
+                },
+            ),
+            amount,
+            self.reward_mint.decimals,
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    sysvar::{self, instructions::load_instruction_at_checked},
+};
+
+use crate::events::FlashLoan;
+use crate::kamino::{instruction_discriminator, KAMINO_LENDING_ID};
+use crate::state::{Registry, REGISTRY_SEED};
+use crate::LendingError;
+
+/// Position of the lending market in Kamino's flash borrow and flash repay accounts
+const FLASH_LOAN_LENDING_MARKET_ACCOUNT: usize = 2;
+
+/// Position of the reserve in Kamino's flash borrow and flash repay accounts
+const FLASH_LOAN_RESERVE_ACCOUNT: usize = 3;
+
+/// Arguments decoded from a Kamino flash borrow or flash repay instruction
+struct FlashLoanInstruction {
+    user: Pubkey,
+    lending_market: Pubkey,
+    reserve: Pubkey,
+    liquidity_amount: u64,
+    borrow_instruction_index: Option<u8>,
+}
+
+/// Decode `ix` as a Kamino flash loan instruction of the given kind
+fn decode_flash_loan_instruction(
+    ix: &Instruction,
+    kamino_program: &Pubkey,
+    expected_discriminator: [u8; 8],
+) -> Result<FlashLoanInstruction> {
+    require_keys_eq!(ix.program_id, *kamino_program, LendingError::FlashLoanNotFound);
+    require!(
+        ix.data.get(..8) == Some(&expected_discriminator[..]),
+        LendingError::FlashLoanNotFound
+    );
+
+    let amount = ix
+        .data
+        .get(8..16)
+        .ok_or(LendingError::InvalidInstructionData)?;
+    let user = ix.accounts.first().ok_or(LendingError::FlashLoanNotFound)?;
+    let lending_market = ix
+        .accounts
+        .get(FLASH_LOAN_LENDING_MARKET_ACCOUNT)
+        .ok_or(LendingError::FlashLoanNotFound)?;
+    let reserve = ix
+        .accounts
+        .get(FLASH_LOAN_RESERVE_ACCOUNT)
+        .ok_or(LendingError::FlashLoanNotFound)?;
+
+    Ok(FlashLoanInstruction {
+        user: user.pubkey,
+        lending_market: lending_market.pubkey,
+        reserve: reserve.pubkey,
+        liquidity_amount: u64::from_le_bytes(amount.try_into().unwrap()),
+        borrow_instruction_index: ix.data.get(16).copied(),
+    })
+}
+
+pub fn record(
+    ctx: Context<RecordFlashLoan>,
+    borrow_instruction_index: u8,
+    repay_instruction_index: u8,
+) -> Result<()> {
+    require!(
+        borrow_instruction_index < repay_instruction_index,
+        LendingError::FlashLoanNotFound
+    );
+
+    let instructions = ctx.accounts.instruction_sysvar_account.to_account_info();
+    let kamino_program = ctx.accounts.kamino_lending_program.key;
+
+    let borrow = decode_flash_loan_instruction(
+        &load_instruction_at_checked(borrow_instruction_index.into(), &instructions)?,
+        kamino_program,
+        instruction_discriminator::FLASH_BORROW_RESERVE_LIQUIDITY,
+    )?;
+    let repay = decode_flash_loan_instruction(
+        &load_instruction_at_checked(repay_instruction_index.into(), &instructions)?,
+        kamino_program,
+        instruction_discriminator::FLASH_REPAY_RESERVE_LIQUIDITY,
+    )?;
+
+    // The repay must close the borrow we were pointed at, for the same user and reserve
+    require!(
+        repay.borrow_instruction_index == Some(borrow_instruction_index),
+        LendingError::FlashLoanNotFound
+    );
+    require_keys_eq!(borrow.user, ctx.accounts.user.key(), LendingError::FlashLoanNotFound);
+    require_keys_eq!(repay.user, borrow.user, LendingError::FlashLoanNotFound);
+    require_keys_eq!(borrow.reserve, ctx.accounts.reserve.key(), LendingError::FlashLoanNotFound);
+    require_keys_eq!(repay.reserve, borrow.reserve, LendingError::FlashLoanNotFound);
+    require_keys_eq!(repay.lending_market, borrow.lending_market, LendingError::FlashLoanNotFound);
+    ctx.accounts
+        .registry
+        .require_reserve(&borrow.lending_market, &borrow.reserve)?;
+
+    emit_cpi!(FlashLoan {
+        user: borrow.user,
+        reserve: borrow.reserve,
+        liquidity_amount: borrow.liquidity_amount,
+        repay_amount: repay.liquidity_amount,
+        borrow_instruction_index,
+        repay_instruction_index,
+    });
+
+    msg!(
+        "Recorded flash loan of {} liquidity from reserve {}",
+        borrow.liquidity_amount,
+        borrow.reserve
+    );
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordFlashLoan<'info> {
+    /// The user who took the flash loan
+    pub user: Signer<'info>,
+
+    /// The reserve the flash loan was taken from
+    /// CHECK: Matched against the flash loan instructions
+    pub reserve: AccountInfo<'info>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
@@ -0,0 +1,453 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+
+use crate::LendingError;
+
+/// The Kamino lending program, on mainnet and in local tests
+pub const KAMINO_LENDING_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// Anchor discriminators of the Kamino lending instructions we call, the first 8 bytes of
+/// `sha256("global:<instruction_name>")` for the snake case names in
+/// `external_idls/kamino_lending.json`
+pub mod instruction_discriminator {
+    pub const REFRESH_RESERVE: [u8; 8] = [2, 218, 138, 235, 79, 201, 25, 102];
+    pub const DEPOSIT_RESERVE_LIQUIDITY: [u8; 8] = [169, 201, 30, 126, 6, 205, 102, 68];
+    pub const INIT_OBLIGATION_FARMS_FOR_RESERVE: [u8; 8] = [136, 63, 15, 186, 211, 152, 168, 164];
+    pub const REFRESH_OBLIGATION_FARMS_FOR_RESERVE: [u8; 8] = [140, 144, 253, 21, 10, 74, 248, 3];
+    pub const REFRESH_OBLIGATION: [u8; 8] = [33, 132, 147, 228, 151, 192, 72, 89];
+    pub const DEPOSIT_OBLIGATION_COLLATERAL: [u8; 8] = [108, 209, 4, 72, 21, 22, 118, 133];
+    pub const BORROW_OBLIGATION_LIQUIDITY: [u8; 8] = [121, 127, 18, 204, 73, 245, 225, 65];
+    pub const REPAY_OBLIGATION_LIQUIDITY: [u8; 8] = [145, 178, 13, 225, 76, 240, 147, 72];
+    pub const REPAY_AND_WITHDRAW_AND_REDEEM: [u8; 8] = [2, 54, 152, 3, 148, 96, 109, 218];
+    pub const DEPOSIT_AND_WITHDRAW: [u8; 8] = [141, 153, 39, 15, 64, 61, 88, 84];
+    pub const DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL: [u8; 8] = [129, 199, 4, 2, 222, 39, 26, 46];
+    pub const WITHDRAW_OBLIGATION_COLLATERAL_AND_REDEEM_RESERVE_COLLATERAL: [u8; 8] =
+        [75, 93, 93, 220, 34, 150, 218, 196];
+    pub const LIQUIDATE_OBLIGATION_AND_REDEEM_RESERVE_COLLATERAL: [u8; 8] = [177, 71, 154, 188, 226, 133, 74, 55];
+    pub const FLASH_REPAY_RESERVE_LIQUIDITY: [u8; 8] = [185, 117, 0, 203, 96, 245, 180, 186];
+    pub const FLASH_BORROW_RESERVE_LIQUIDITY: [u8; 8] = [135, 231, 52, 167, 7, 52, 212, 193];
+}
+
+/// Anchor discriminators of the Kamino accounts we decode
+pub mod discriminator {
+    pub const RESERVE: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+    pub const OBLIGATION: [u8; 8] = [168, 206, 141, 106, 88, 76, 172, 167];
+    pub const LENDING_MARKET: [u8; 8] = [246, 114, 50, 98, 72, 157, 28, 120];
+}
+
+/// Byte offsets into a Kamino `Obligation`, past the discriminator
+pub mod obligation_layout {
+    pub const MAX_DEPOSITS: usize = 8;
+    pub const MAX_BORROWS: usize = 5;
+    pub const COLLATERAL_SIZE: usize = 136;
+    pub const LIQUIDITY_SIZE: usize = 200;
+
+    /// Offsets within a deposit slot and a borrow slot
+    pub const COLLATERAL_DEPOSITED_AMOUNT: usize = 32;
+    pub const LIQUIDITY_BORROWED_AMOUNT_SF: usize = 88;
+
+    pub const DEPOSITS: usize = 88;
+    pub const DEPOSITED_VALUE_SF: usize = DEPOSITS + MAX_DEPOSITS * COLLATERAL_SIZE + 8;
+    pub const BORROWS: usize = DEPOSITED_VALUE_SF + 16;
+    pub const BORROW_FACTOR_ADJUSTED_DEBT_VALUE_SF: usize = BORROWS + MAX_BORROWS * LIQUIDITY_SIZE;
+    /// Past the four debt figures and the deposit and borrow asset tiers
+    pub const ELEVATION_GROUP: usize = BORROW_FACTOR_ADJUSTED_DEBT_VALUE_SF + 4 * 16 + MAX_DEPOSITS + MAX_BORROWS;
+}
+
+/// Byte offsets into a Kamino `Reserve`, past the discriminator
+pub mod reserve_layout {
+    pub const LIQUIDITY_SIZE: usize = 1232;
+    pub const COLLATERAL_SIZE: usize = 1096;
+    /// `[u64; 150]` padding Kamino keeps after both the liquidity and the collateral
+    pub const PADDING_SIZE: usize = 150 * 8;
+
+    pub const LIQUIDITY: usize = 120;
+    pub const LIQUIDITY_MARKET_PRICE_SF: usize = LIQUIDITY + 120;
+    pub const COLLATERAL: usize = LIQUIDITY + LIQUIDITY_SIZE + PADDING_SIZE;
+    pub const CONFIG: usize = COLLATERAL + COLLATERAL_SIZE + PADDING_SIZE;
+    pub const CONFIG_STATUS: usize = CONFIG;
+    pub const CONFIG_BORROW_FACTOR_PCT: usize = CONFIG + 152;
+    pub const CONFIG_TOKEN_INFO: usize = CONFIG + 176;
+    /// Deposit then debt withdrawal caps, right after the token info
+    pub const CONFIG_WITHDRAWAL_CAPS: usize = CONFIG_TOKEN_INFO + 384;
+    /// Elevation group settings, right after both withdrawal caps
+    pub const CONFIG_ELEVATION_GROUPS: usize = CONFIG_WITHDRAWAL_CAPS + 64;
+}
+
+/// Kamino `ReserveStatus`, the first field of a reserve's config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveStatus {
+    Active,
+    /// Deprecated, only open for repayments and withdrawals
+    Obsolete,
+    Hidden,
+}
+
+impl ReserveStatus {
+    /// Read the status of a reserve owned by `kamino_program`
+    pub fn load(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*reserve.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(reserve, &discriminator::RESERVE, reserve_layout::CONFIG_STATUS)
+    }
+
+    /// Fail unless the reserve accepts new deposits and borrows
+    pub fn require_active(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<()> {
+        require!(
+            Self::load(reserve, kamino_program)? == ReserveStatus::Active,
+            LendingError::ReserveNotActive
+        );
+        Ok(())
+    }
+}
+
+/// Kamino's cached price of a reserve's liquidity, refreshed by `refreshReserve`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveMarketPrice {
+    pub market_price_sf: u128,
+    pub market_price_last_updated_ts: u64,
+}
+
+impl ReserveMarketPrice {
+    /// Read the cached price of a reserve owned by `kamino_program`
+    pub fn load(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*reserve.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(
+            reserve,
+            &discriminator::RESERVE,
+            reserve_layout::LIQUIDITY_MARKET_PRICE_SF,
+        )
+    }
+}
+
+/// Kamino `PriceHeuristic`: bounds a price must fall within, as `value / 10^exp`.
+/// A zero bound is not checked.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriceHeuristic {
+    pub lower: u64,
+    pub upper: u64,
+    pub exp: u64,
+}
+
+/// Kamino `ScopeConfiguration`; chains end at the first `u16::MAX`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScopeConfiguration {
+    pub price_feed: Pubkey,
+    pub price_chain: [u16; 4],
+    pub twap_chain: [u16; 4],
+}
+
+/// Kamino `SwitchboardConfiguration`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwitchboardConfiguration {
+    pub price_aggregator: Pubkey,
+    pub twap_aggregator: Pubkey,
+}
+
+/// Kamino `PythConfiguration`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PythConfiguration {
+    pub price: Pubkey,
+}
+
+/// Kamino `TokenInfo`, the oracle configuration of a reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub name: [u8; 32],
+    pub heuristic: PriceHeuristic,
+    pub max_twap_divergence_bps: u64,
+    pub max_age_price_seconds: u64,
+    pub max_age_twap_seconds: u64,
+    pub scope_configuration: ScopeConfiguration,
+    pub switchboard_configuration: SwitchboardConfiguration,
+    pub pyth_configuration: PythConfiguration,
+    pub block_price_usage: u8,
+}
+
+impl TokenInfo {
+    /// Read the oracle configuration of a reserve owned by `kamino_program`
+    pub fn load(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*reserve.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(reserve, &discriminator::RESERVE, reserve_layout::CONFIG_TOKEN_INFO)
+    }
+}
+
+/// Which side of a reserve a farm is attached to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveFarmKind {
+    Collateral,
+    Debt,
+}
+
+/// Kamino `LastUpdate`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LastUpdate {
+    pub slot: u64,
+    pub stale: u8,
+    pub price_status: u8,
+    pub placeholder: [u8; 6],
+}
+
+impl LastUpdate {
+    /// Kamino only accepts accounts refreshed in the current slot
+    pub fn is_stale(&self, slot: u64) -> bool {
+        self.stale != 0 || self.slot != slot
+    }
+}
+
+/// Kamino `WithdrawalCaps`: at most `config_capacity` may leave a reserve per interval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WithdrawalCaps {
+    pub config_capacity: i64,
+    pub current_total: i64,
+    pub last_interval_start_timestamp: u64,
+    pub config_interval_length_seconds: u64,
+}
+
+impl WithdrawalCaps {
+    /// Whether `amount` more can leave the reserve at `now`; a cap without capacity or
+    /// interval is disabled
+    pub fn allows(&self, amount: u64, now: i64) -> bool {
+        if self.config_capacity <= 0 || self.config_interval_length_seconds == 0 {
+            return true;
+        }
+        let elapsed = (now.max(0) as u64).saturating_sub(self.last_interval_start_timestamp);
+        let current = if elapsed >= self.config_interval_length_seconds {
+            0
+        } else {
+            self.current_total.max(0) as u64
+        };
+        current.saturating_add(amount) <= self.config_capacity as u64
+    }
+}
+
+/// Both withdrawal caps of a Kamino reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveWithdrawalCaps {
+    /// Liquidity withdrawn by depositors
+    pub deposit: WithdrawalCaps,
+    /// Liquidity borrowed
+    pub debt: WithdrawalCaps,
+}
+
+impl ReserveWithdrawalCaps {
+    /// Read the withdrawal caps of a reserve owned by `kamino_program`
+    pub fn load(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*reserve.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(
+            reserve,
+            &discriminator::RESERVE,
+            reserve_layout::CONFIG_WITHDRAWAL_CAPS,
+        )
+    }
+}
+
+/// Leading fields of a Kamino `Reserve` account, up to and including its farms
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveHeader {
+    pub version: u64,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub farm_collateral: Pubkey,
+    pub farm_debt: Pubkey,
+}
+
+impl ReserveHeader {
+    /// Decode the header of a reserve owned by `kamino_program`
+    pub fn load(reserve: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*reserve.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(reserve, &discriminator::RESERVE, 0)
+    }
+
+    /// Farm state attached to the given side of the reserve, if any
+    pub fn farm(&self, kind: ReserveFarmKind) -> Option<Pubkey> {
+        let farm = match kind {
+            ReserveFarmKind::Collateral => self.farm_collateral,
+            ReserveFarmKind::Debt => self.farm_debt,
+        };
+        (farm != Pubkey::default()).then_some(farm)
+    }
+}
+
+/// Leading fields of a Kamino `Obligation` account, up to and including its owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObligationHeader {
+    pub tag: u64,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+}
+
+impl ObligationHeader {
+    /// Decode the header of an obligation owned by `kamino_program`
+    pub fn load(obligation: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*obligation.owner, *kamino_program, LendingError::InvalidAccountState);
+        deserialize_account(obligation, &discriminator::OBLIGATION, 0)
+    }
+}
+
+/// Health figures Kamino keeps on an obligation, as scaled fractions (`value / 2^60`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObligationMetrics {
+    pub deposited_value_sf: u128,
+    pub borrow_factor_adjusted_debt_value_sf: u128,
+    pub borrowed_assets_market_value_sf: u128,
+    pub allowed_borrow_value_sf: u128,
+    pub unhealthy_borrow_value_sf: u128,
+    /// Elevation group the obligation borrows in, zero for none
+    pub elevation_group: u8,
+}
+
+impl ObligationMetrics {
+    /// Read the metrics of an obligation owned by `kamino_program`
+    pub fn load(obligation: &AccountInfo, kamino_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*obligation.owner, *kamino_program, LendingError::InvalidAccountState);
+        let deposited_value_sf = deserialize_account(
+            obligation,
+            &discriminator::OBLIGATION,
+            obligation_layout::DEPOSITED_VALUE_SF,
+        )?;
+        // The four debt figures are laid out back to back after the borrows
+        let debt_values: [u128; 4] = deserialize_account(
+            obligation,
+            &discriminator::OBLIGATION,
+            obligation_layout::BORROW_FACTOR_ADJUSTED_DEBT_VALUE_SF,
+        )?;
+        let elevation_group = deserialize_account(
+            obligation,
+            &discriminator::OBLIGATION,
+            obligation_layout::ELEVATION_GROUP,
+        )?;
+
+        Ok(Self {
+            deposited_value_sf,
+            borrow_factor_adjusted_debt_value_sf: debt_values[0],
+            borrowed_assets_market_value_sf: debt_values[1],
+            allowed_borrow_value_sf: debt_values[2],
+            unhealthy_borrow_value_sf: debt_values[3],
+            elevation_group,
+        })
+    }
+}
+
+/// Offsets of an obligation's deposit slots, then its borrow slots, each starting with the
+/// slot's reserve
+fn obligation_slots() -> impl Iterator<Item = usize> {
+    let deposits = (0..obligation_layout::MAX_DEPOSITS)
+        .map(|slot| obligation_layout::DEPOSITS + slot * obligation_layout::COLLATERAL_SIZE);
+    let borrows = (0..obligation_layout::MAX_BORROWS)
+        .map(|slot| obligation_layout::BORROWS + slot * obligation_layout::LIQUIDITY_SIZE);
+    deposits.chain(borrows)
+}
+
+/// Reserves an obligation deposits into, then those it borrows from, which is the order
+/// `refreshObligation` takes them in as remaining accounts
+pub fn obligation_reserves(obligation: &AccountInfo, kamino_program: &Pubkey) -> Result<Vec<Pubkey>> {
+    require_keys_eq!(*obligation.owner, *kamino_program, LendingError::InvalidAccountState);
+    let data = obligation.try_borrow_data()?;
+    let mut reserves = Vec::new();
+    for offset in obligation_slots() {
+        let reserve: Pubkey = deserialize_data(&data, &discriminator::OBLIGATION, offset)?;
+        if reserve != Pubkey::default() {
+            reserves.push(reserve);
+        }
+    }
+    Ok(reserves)
+}
+
+/// Read the field `field` bytes into the first of `slots` holding `reserve`, if any
+fn read_obligation_slot<T: AnchorDeserialize>(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    slots: impl Iterator<Item = usize>,
+    reserve: &Pubkey,
+    field: usize,
+) -> Result<Option<T>> {
+    require_keys_eq!(*obligation.owner, *kamino_program, LendingError::InvalidAccountState);
+    let data = obligation.try_borrow_data()?;
+    for offset in slots {
+        let key: Pubkey = deserialize_data(&data, &discriminator::OBLIGATION, offset)?;
+        if key == *reserve {
+            return deserialize_data(&data, &discriminator::OBLIGATION, offset + field).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Collateral an obligation owned by `kamino_program` holds in `reserve`, 0 if none
+pub fn obligation_deposited_amount(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    reserve: &Pubkey,
+) -> Result<u64> {
+    let slots = obligation_slots().take(obligation_layout::MAX_DEPOSITS);
+    let field = obligation_layout::COLLATERAL_DEPOSITED_AMOUNT;
+    Ok(read_obligation_slot(obligation, kamino_program, slots, reserve, field)?.unwrap_or(0))
+}
+
+/// Debt an obligation owned by `kamino_program` owes `reserve` as of its last refresh, as a
+/// scaled fraction, 0 if none
+pub fn obligation_borrowed_amount_sf(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    reserve: &Pubkey,
+) -> Result<u128> {
+    let slots = obligation_slots().skip(obligation_layout::MAX_DEPOSITS);
+    let field = obligation_layout::LIQUIDITY_BORROWED_AMOUNT_SF;
+    Ok(read_obligation_slot(obligation, kamino_program, slots, reserve, field)?.unwrap_or(0))
+}
+
+/// Deserialize `T` from an Anchor account's data, `offset` bytes past the discriminator
+pub fn deserialize_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: &[u8; 8],
+    offset: usize,
+) -> Result<T> {
+    deserialize_data(&account.try_borrow_data()?, discriminator, offset)
+}
+
+/// Deserialize `T` from raw Anchor account data, `offset` bytes past the discriminator
+pub fn deserialize_data<T: AnchorDeserialize>(
+    data: &[u8],
+    discriminator: &[u8; 8],
+    offset: usize,
+) -> Result<T> {
+    require!(
+        data.len() >= 8 && data[..8] == discriminator[..],
+        LendingError::InvalidAccountState
+    );
+    let mut body = data
+        .get(8 + offset..)
+        .ok_or(LendingError::InvalidAccountState)?;
+    T::deserialize(&mut body).map_err(|_| error!(LendingError::InvalidAccountState))
+}
+
+/// Serialize a Kamino instruction with the given discriminator and arguments
+pub fn serialize_kamino_instruction<T: AnchorSerialize>(
+    discriminator: [u8; 8],
+    args: &T,
+) -> Result<Vec<u8>> {
+    let mut data = discriminator.to_vec();
+    args.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// Invoke `program` with `accounts` passed through in order
+pub fn invoke_kamino(program: &AccountInfo, accounts: &[AccountInfo], data: Vec<u8>) -> Result<()> {
+    let account_metas: Vec<AccountMeta> = accounts
+        .iter()
+        .map(|acc| AccountMeta {
+            pubkey: *acc.key,
+            is_signer: acc.is_signer,
+            is_writable: acc.is_writable,
+        })
+        .collect();
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: account_metas,
+        data,
+    };
+
+    invoke(&ix, accounts)?;
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::LendingError;
+
+/// First custom error code of the Kamino lending program
+pub const KAMINO_ERROR_OFFSET: u32 = 6000;
+
+/// A Kamino lending error, as listed in `external_idls/kamino_lending.json`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KaminoErrorInfo {
+    pub code: u32,
+    pub name: &'static str,
+    pub msg: &'static str,
+}
+
+impl KaminoErrorInfo {
+    /// Our own error for the Kamino errors we also check for before the CPI
+    pub fn lending_error(&self) -> Option<LendingError> {
+        match self.name {
+            "ReserveStale" => Some(LendingError::ReserveStale),
+            "ObligationStale" => Some(LendingError::ObligationStale),
+            "BorrowTooLarge" => Some(LendingError::BorrowTooLarge),
+            "WithdrawalCapReached" => Some(LendingError::WithdrawalCapReached),
+            _ => None,
+        }
+    }
+}
+
+/// Every Kamino lending error, indexed by `code - KAMINO_ERROR_OFFSET`
+pub const KAMINO_ERRORS: [KaminoErrorInfo; 123] = [
+    KaminoErrorInfo { code: 6000, name: "InvalidMarketAuthority", msg: "Market authority is invalid" },
+    KaminoErrorInfo { code: 6001, name: "InvalidMarketOwner", msg: "Market owner is invalid" },
+    KaminoErrorInfo { code: 6002, name: "InvalidAccountOwner", msg: "Input account owner is not the program address" },
+    KaminoErrorInfo { code: 6003, name: "InvalidAmount", msg: "Input amount is invalid" },
+    KaminoErrorInfo { code: 6004, name: "InvalidConfig", msg: "Input config value is invalid" },
+    KaminoErrorInfo { code: 6005, name: "InvalidSigner", msg: "Input account must be a signer" },
+    KaminoErrorInfo { code: 6006, name: "InvalidAccountInput", msg: "Invalid account input" },
+    KaminoErrorInfo { code: 6007, name: "MathOverflow", msg: "Math operation overflow" },
+    KaminoErrorInfo { code: 6008, name: "InsufficientLiquidity", msg: "Insufficient liquidity available" },
+    KaminoErrorInfo { code: 6009, name: "ReserveStale", msg: "Reserve state needs to be refreshed" },
+    KaminoErrorInfo { code: 6010, name: "WithdrawTooSmall", msg: "Withdraw amount too small" },
+    KaminoErrorInfo { code: 6011, name: "WithdrawTooLarge", msg: "Withdraw amount too large" },
+    KaminoErrorInfo { code: 6012, name: "BorrowTooSmall", msg: "Borrow amount too small to receive liquidity after fees" },
+    KaminoErrorInfo { code: 6013, name: "BorrowTooLarge", msg: "Borrow amount too large for deposited collateral" },
+    KaminoErrorInfo { code: 6014, name: "RepayTooSmall", msg: "Repay amount too small to transfer liquidity" },
+    KaminoErrorInfo { code: 6015, name: "LiquidationTooSmall", msg: "Liquidation amount too small to receive collateral" },
+    KaminoErrorInfo { code: 6016, name: "ObligationHealthy", msg: "Cannot liquidate healthy obligations" },
+    KaminoErrorInfo { code: 6017, name: "ObligationStale", msg: "Obligation state needs to be refreshed" },
+    KaminoErrorInfo { code: 6018, name: "ObligationReserveLimit", msg: "Obligation reserve limit exceeded" },
+    KaminoErrorInfo { code: 6019, name: "InvalidObligationOwner", msg: "Obligation owner is invalid" },
+    KaminoErrorInfo { code: 6020, name: "ObligationDepositsEmpty", msg: "Obligation deposits are empty" },
+    KaminoErrorInfo { code: 6021, name: "ObligationBorrowsEmpty", msg: "Obligation borrows are empty" },
+    KaminoErrorInfo { code: 6022, name: "ObligationDepositsZero", msg: "Obligation deposits have zero value" },
+    KaminoErrorInfo { code: 6023, name: "ObligationBorrowsZero", msg: "Obligation borrows have zero value" },
+    KaminoErrorInfo { code: 6024, name: "InvalidObligationCollateral", msg: "Invalid obligation collateral" },
+    KaminoErrorInfo { code: 6025, name: "InvalidObligationLiquidity", msg: "Invalid obligation liquidity" },
+    KaminoErrorInfo { code: 6026, name: "ObligationCollateralEmpty", msg: "Obligation collateral is empty" },
+    KaminoErrorInfo { code: 6027, name: "ObligationLiquidityEmpty", msg: "Obligation liquidity is empty" },
+    KaminoErrorInfo { code: 6028, name: "NegativeInterestRate", msg: "Interest rate is negative" },
+    KaminoErrorInfo { code: 6029, name: "InvalidOracleConfig", msg: "Input oracle config is invalid" },
+    KaminoErrorInfo { code: 6030, name: "InsufficientProtocolFeesToRedeem", msg: "Insufficient protocol fees to claim or no liquidity available" },
+    KaminoErrorInfo { code: 6031, name: "FlashBorrowCpi", msg: "No cpi flash borrows allowed" },
+    KaminoErrorInfo { code: 6032, name: "NoFlashRepayFound", msg: "No corresponding repay found for flash borrow" },
+    KaminoErrorInfo { code: 6033, name: "InvalidFlashRepay", msg: "Invalid repay found" },
+    KaminoErrorInfo { code: 6034, name: "FlashRepayCpi", msg: "No cpi flash repays allowed" },
+    KaminoErrorInfo { code: 6035, name: "MultipleFlashBorrows", msg: "Multiple flash borrows not allowed in the same transaction" },
+    KaminoErrorInfo { code: 6036, name: "FlashLoansDisabled", msg: "Flash loans are disabled for this reserve" },
+    KaminoErrorInfo { code: 6037, name: "SwitchboardV2Error", msg: "Switchboard error" },
+    KaminoErrorInfo { code: 6038, name: "CouldNotDeserializeScope", msg: "Cannot deserialize the scope price account" },
+    KaminoErrorInfo { code: 6039, name: "PriceTooOld", msg: "Price too old" },
+    KaminoErrorInfo { code: 6040, name: "PriceTooDivergentFromTwap", msg: "Price too divergent from twap" },
+    KaminoErrorInfo { code: 6041, name: "InvalidTwapPrice", msg: "Invalid twap price" },
+    KaminoErrorInfo { code: 6042, name: "GlobalEmergencyMode", msg: "Emergency mode is enabled" },
+    KaminoErrorInfo { code: 6043, name: "InvalidFlag", msg: "Invalid lending market config" },
+    KaminoErrorInfo { code: 6044, name: "PriceNotValid", msg: "Price is not valid" },
+    KaminoErrorInfo { code: 6045, name: "PriceIsBiggerThanHeuristic", msg: "Price is bigger than allowed by heuristic" },
+    KaminoErrorInfo { code: 6046, name: "PriceIsLowerThanHeuristic", msg: "Price lower than allowed by heuristic" },
+    KaminoErrorInfo { code: 6047, name: "PriceIsZero", msg: "Price is zero" },
+    KaminoErrorInfo { code: 6048, name: "PriceConfidenceTooWide", msg: "Price confidence too wide" },
+    KaminoErrorInfo { code: 6049, name: "IntegerOverflow", msg: "Conversion between integers failed" },
+    KaminoErrorInfo { code: 6050, name: "NoFarmForReserve", msg: "This reserve does not have a farm" },
+    KaminoErrorInfo { code: 6051, name: "IncorrectInstructionInPosition", msg: "Wrong instruction at expected position" },
+    KaminoErrorInfo { code: 6052, name: "NoPriceFound", msg: "No price found" },
+    KaminoErrorInfo { code: 6053, name: "InvalidTwapConfig", msg: "Invalid Twap configuration: Twap is enabled but one of the enabled price doesn't have a twap" },
+    KaminoErrorInfo { code: 6054, name: "InvalidPythPriceAccount", msg: "Pyth price account does not match configuration" },
+    KaminoErrorInfo { code: 6055, name: "InvalidSwitchboardAccount", msg: "Switchboard account(s) do not match configuration" },
+    KaminoErrorInfo { code: 6056, name: "InvalidScopePriceAccount", msg: "Scope price account does not match configuration" },
+    KaminoErrorInfo { code: 6057, name: "ObligationCollateralLtvZero", msg: "The obligation has one collateral with an LTV set to 0. Withdraw it before withdrawing other collaterals" },
+    KaminoErrorInfo { code: 6058, name: "InvalidObligationSeedsValue", msg: "Seeds must be default pubkeys for tag 0, and mint addresses for tag 1 or 2" },
+    KaminoErrorInfo { code: 6059, name: "DeprecatedInvalidObligationId", msg: "[DEPRECATED] Obligation id must be 0" },
+    KaminoErrorInfo { code: 6060, name: "InvalidBorrowRateCurvePoint", msg: "Invalid borrow rate curve point" },
+    KaminoErrorInfo { code: 6061, name: "InvalidUtilizationRate", msg: "Invalid utilization rate" },
+    KaminoErrorInfo { code: 6062, name: "CannotSocializeObligationWithCollateral", msg: "Obligation hasn't been fully liquidated and debt cannot be socialized." },
+    KaminoErrorInfo { code: 6063, name: "ObligationEmpty", msg: "Obligation has no borrows or deposits." },
+    KaminoErrorInfo { code: 6064, name: "WithdrawalCapReached", msg: "Withdrawal cap is reached" },
+    KaminoErrorInfo { code: 6065, name: "LastTimestampGreaterThanCurrent", msg: "The last interval start timestamp is greater than the current timestamp" },
+    KaminoErrorInfo { code: 6066, name: "LiquidationRewardTooSmall", msg: "The reward amount is less than the minimum acceptable received liquidity" },
+    KaminoErrorInfo { code: 6067, name: "IsolatedAssetTierViolation", msg: "Isolated Asset Tier Violation" },
+    KaminoErrorInfo { code: 6068, name: "InconsistentElevationGroup", msg: "The obligation's elevation group and the reserve's are not the same" },
+    KaminoErrorInfo { code: 6069, name: "InvalidElevationGroup", msg: "The elevation group chosen for the reserve does not exist in the lending market" },
+    KaminoErrorInfo { code: 6070, name: "InvalidElevationGroupConfig", msg: "The elevation group updated has wrong parameters set" },
+    KaminoErrorInfo { code: 6071, name: "UnhealthyElevationGroupLtv", msg: "The current obligation must have most or all its debt repaid before changing the elevation group" },
+    KaminoErrorInfo { code: 6072, name: "ElevationGroupNewLoansDisabled", msg: "Elevation group does not accept any new loans or any new borrows/withdrawals" },
+    KaminoErrorInfo { code: 6073, name: "ReserveDeprecated", msg: "Reserve was deprecated, no longer usable" },
+    KaminoErrorInfo { code: 6074, name: "ReferrerAccountNotInitialized", msg: "Referrer account not initialized" },
+    KaminoErrorInfo { code: 6075, name: "ReferrerAccountMintMissmatch", msg: "Referrer account mint does not match the operation reserve mint" },
+    KaminoErrorInfo { code: 6076, name: "ReferrerAccountWrongAddress", msg: "Referrer account address is not a valid program address" },
+    KaminoErrorInfo { code: 6077, name: "ReferrerAccountReferrerMissmatch", msg: "Referrer account referrer does not match the owner referrer" },
+    KaminoErrorInfo { code: 6078, name: "ReferrerAccountMissing", msg: "Referrer account missing for obligation with referrer" },
+    KaminoErrorInfo { code: 6079, name: "InsufficientReferralFeesToRedeem", msg: "Insufficient referral fees to claim or no liquidity available" },
+    KaminoErrorInfo { code: 6080, name: "CpiDisabled", msg: "CPI disabled for this instruction" },
+    KaminoErrorInfo { code: 6081, name: "ShortUrlNotAsciiAlphanumeric", msg: "Referrer short_url is not ascii alphanumeric" },
+    KaminoErrorInfo { code: 6082, name: "ReserveObsolete", msg: "Reserve is marked as obsolete" },
+    KaminoErrorInfo { code: 6083, name: "ElevationGroupAlreadyActivated", msg: "Obligation already part of the same elevation group" },
+    KaminoErrorInfo { code: 6084, name: "ObligationInDeprecatedReserve", msg: "Obligation has a deposit in a deprecated reserve" },
+    KaminoErrorInfo { code: 6085, name: "ReferrerStateOwnerMismatch", msg: "Referrer state owner does not match the given signer" },
+    KaminoErrorInfo { code: 6086, name: "UserMetadataOwnerAlreadySet", msg: "User metadata owner is already set" },
+    KaminoErrorInfo { code: 6087, name: "CollateralNonLiquidatable", msg: "This collateral cannot be liquidated (LTV set to 0)" },
+    KaminoErrorInfo { code: 6088, name: "BorrowingDisabled", msg: "Borrowing is disabled" },
+    KaminoErrorInfo { code: 6089, name: "BorrowLimitExceeded", msg: "Cannot borrow above borrow limit" },
+    KaminoErrorInfo { code: 6090, name: "DepositLimitExceeded", msg: "Cannot deposit above deposit limit" },
+    KaminoErrorInfo { code: 6091, name: "BorrowingDisabledOutsideElevationGroup", msg: "Reserve does not accept any new borrows outside elevation group" },
+    KaminoErrorInfo { code: 6092, name: "NetValueRemainingTooSmall", msg: "Net value remaining too small" },
+    KaminoErrorInfo { code: 6093, name: "WorseLTVBlocked", msg: "Cannot get the obligation in a worse position" },
+    KaminoErrorInfo { code: 6094, name: "LiabilitiesBiggerThanAssets", msg: "Cannot have more liabilities than assets in a position" },
+    KaminoErrorInfo { code: 6095, name: "ReserveTokenBalanceMismatch", msg: "Reserve state and token account cannot drift" },
+    KaminoErrorInfo { code: 6096, name: "ReserveVaultBalanceMismatch", msg: "Reserve token account has been unexpectedly modified" },
+    KaminoErrorInfo { code: 6097, name: "ReserveAccountingMismatch", msg: "Reserve internal state accounting has been unexpectedly modified" },
+    KaminoErrorInfo { code: 6098, name: "BorrowingAboveUtilizationRateDisabled", msg: "Borrowing above set utilization rate is disabled" },
+    KaminoErrorInfo { code: 6099, name: "LiquidationBorrowFactorPriority", msg: "Liquidation must prioritize the debt with the highest borrow factor" },
+    KaminoErrorInfo { code: 6100, name: "LiquidationLowestLTVPriority", msg: "Liquidation must prioritize the collateral with the lowest LTV" },
+    KaminoErrorInfo { code: 6101, name: "ElevationGroupBorrowLimitExceeded", msg: "Elevation group borrow limit exceeded" },
+    KaminoErrorInfo { code: 6102, name: "ElevationGroupWithoutDebtReserve", msg: "The elevation group does not have a debt reserve defined" },
+    KaminoErrorInfo { code: 6103, name: "ElevationGroupMaxCollateralReserveZero", msg: "The elevation group does not allow any collateral reserves" },
+    KaminoErrorInfo { code: 6104, name: "ElevationGroupHasAnotherDebtReserve", msg: "In elevation group attempt to borrow from a reserve that is not the debt reserve" },
+    KaminoErrorInfo { code: 6105, name: "ElevationGroupDebtReserveAsCollateral", msg: "The elevation group's debt reserve cannot be used as a collateral reserve" },
+    KaminoErrorInfo { code: 6106, name: "ObligationCollateralExceedsElevationGroupLimit", msg: "Obligation have more collateral than the maximum allowed by the elevation group" },
+    KaminoErrorInfo { code: 6107, name: "ObligationElevationGroupMultipleDebtReserve", msg: "Obligation is an elevation group but have more than one debt reserve" },
+    KaminoErrorInfo { code: 6108, name: "UnsupportedTokenExtension", msg: "Mint has a token (2022) extension that is not supported" },
+    KaminoErrorInfo { code: 6109, name: "InvalidTokenAccount", msg: "Can't have an spl token mint with a t22 account" },
+    KaminoErrorInfo { code: 6110, name: "DepositDisabledOutsideElevationGroup", msg: "Can't deposit into this reserve outside elevation group" },
+    KaminoErrorInfo { code: 6111, name: "CannotCalculateReferralAmountDueToSlotsMismatch", msg: "Cannot calculate referral amount due to slots mismatch" },
+    KaminoErrorInfo { code: 6112, name: "ObligationOwnersMustMatch", msg: "Obligation owners must match" },
+    KaminoErrorInfo { code: 6113, name: "ObligationsMustMatch", msg: "Obligations must match" },
+    KaminoErrorInfo { code: 6114, name: "LendingMarketsMustMatch", msg: "Lending markets must match" },
+    KaminoErrorInfo { code: 6115, name: "ObligationCurrentlyMarkedForDeleveraging", msg: "Obligation is already marked for deleveraging" },
+    KaminoErrorInfo { code: 6116, name: "MaximumWithdrawValueZero", msg: "Maximum withdrawable value of this collateral is zero, LTV needs improved" },
+    KaminoErrorInfo { code: 6117, name: "ZeroMaxLtvAssetsInDeposits", msg: "No max LTV 0 assets allowed in deposits for repay and withdraw" },
+    KaminoErrorInfo { code: 6118, name: "MinLtvAssetsPriority", msg: "The operation must prioritize the collateral with the lowest LTV" },
+    KaminoErrorInfo { code: 6119, name: "WorseLTVThanUnhealthyLTV", msg: "Cannot get the obligation liquidatable" },
+    KaminoErrorInfo { code: 6120, name: "FarmAccountsMissing", msg: "Farm accounts to refresh are missing" },
+    KaminoErrorInfo { code: 6121, name: "RepayTooSmallForFullLiquidation", msg: "Repay amount is too small to satisfy the mandatory full liquidation" },
+    KaminoErrorInfo { code: 6122, name: "InsufficientRepayAmount", msg: "Liquidator provided repay amount lower than required by liquidation rules" },
+];
+
+/// Look up a Kamino lending error by its custom error code
+pub fn lookup(code: u32) -> Option<&'static KaminoErrorInfo> {
+    let index = code.checked_sub(KAMINO_ERROR_OFFSET)?;
+    KAMINO_ERRORS.get(index as usize)
+}
+
+/// Describe a custom error code from a failed Kamino CPI, e.g. for logs.
+///
+/// A failed CPI aborts the whole transaction, so the code reaches clients as the
+/// transaction's `Custom` instruction error rather than our handlers.
+pub fn describe(code: u32) -> String {
+    match lookup(code) {
+        Some(error) => format!("Kamino error {} {}: {}", error.code, error.name, error.msg),
+        None => format!("Unknown Kamino error {}", code),
+    }
+}
@@ -0,0 +1,120 @@
+//! Kamino lending seed schemes, shared by the wrappers and off-chain clients
+
+use anchor_lang::prelude::*;
+
+use crate::LendingError;
+
+pub const LENDING_MARKET_AUTHORITY_SEED: &[u8] = b"lma";
+pub const RESERVE_LIQUIDITY_SUPPLY_SEED: &[u8] = b"reserve_liq_supply";
+pub const RESERVE_FEE_VAULT_SEED: &[u8] = b"fee_receiver";
+pub const RESERVE_COLLATERAL_MINT_SEED: &[u8] = b"reserve_coll_mint";
+pub const RESERVE_COLLATERAL_SUPPLY_SEED: &[u8] = b"reserve_coll_supply";
+pub const USER_METADATA_SEED: &[u8] = b"user_meta";
+pub const REFERRER_TOKEN_STATE_SEED: &[u8] = b"referrer_acc";
+
+/// The authority over a lending market's reserve vaults
+pub fn lending_market_authority(program: &Pubkey, lending_market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LENDING_MARKET_AUTHORITY_SEED, lending_market.as_ref()], program).0
+}
+
+/// Derives a reserve account from the program, lending market and liquidity mint
+type ReserveDerivation = fn(&Pubkey, &Pubkey, &Pubkey) -> Pubkey;
+
+fn reserve_account(seed: &[u8], program: &Pubkey, lending_market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[seed, lending_market.as_ref(), mint.as_ref()], program).0
+}
+
+/// The vault holding a reserve's liquidity
+pub fn reserve_liquidity_supply(program: &Pubkey, lending_market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    reserve_account(RESERVE_LIQUIDITY_SUPPLY_SEED, program, lending_market, mint)
+}
+
+/// The vault receiving a reserve's borrow and flash loan fees
+pub fn reserve_fee_vault(program: &Pubkey, lending_market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    reserve_account(RESERVE_FEE_VAULT_SEED, program, lending_market, mint)
+}
+
+/// The mint of a reserve's collateral tokens
+pub fn reserve_collateral_mint(program: &Pubkey, lending_market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    reserve_account(RESERVE_COLLATERAL_MINT_SEED, program, lending_market, mint)
+}
+
+/// The vault holding collateral tokens deposited into obligations
+pub fn reserve_collateral_supply(program: &Pubkey, lending_market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    reserve_account(RESERVE_COLLATERAL_SUPPLY_SEED, program, lending_market, mint)
+}
+
+/// An obligation of `owner` in `lending_market`, as created by Kamino's `initObligation`
+pub fn obligation_with_seeds(
+    program: &Pubkey,
+    tag: u8,
+    id: u8,
+    owner: &Pubkey,
+    lending_market: &Pubkey,
+    seed1: &Pubkey,
+    seed2: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[&[tag], &[id], owner.as_ref(), lending_market.as_ref(), seed1.as_ref(), seed2.as_ref()],
+        program,
+    )
+    .0
+}
+
+/// The vanilla obligation of `owner` in `lending_market`: tag and id 0, no seed accounts
+pub fn obligation(program: &Pubkey, owner: &Pubkey, lending_market: &Pubkey) -> Pubkey {
+    obligation_with_seeds(program, 0, 0, owner, lending_market, &Pubkey::default(), &Pubkey::default())
+}
+
+/// The metadata account Kamino keeps per user
+pub fn user_metadata(program: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_METADATA_SEED, owner.as_ref()], program).0
+}
+
+/// The account accruing a referrer's fees in `mint`
+pub fn referrer_token_state(program: &Pubkey, referrer: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRER_TOKEN_STATE_SEED, referrer.as_ref(), mint.as_ref()], program).0
+}
+
+/// Market and reserve accounts an instruction passed, each checked if present
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReserveAccounts<'a> {
+    pub lending_market_authority: Option<&'a Pubkey>,
+    pub liquidity_supply: Option<&'a Pubkey>,
+    pub fee_vault: Option<&'a Pubkey>,
+    pub collateral_mint: Option<&'a Pubkey>,
+    pub collateral_supply: Option<&'a Pubkey>,
+}
+
+impl ReserveAccounts<'_> {
+    /// Fail with `InvalidKaminoAccount` unless every account present is the one Kamino
+    /// derives for `lending_market` and the reserve's `liquidity_mint`
+    pub fn require_derived(
+        &self,
+        program: &Pubkey,
+        lending_market: &Pubkey,
+        liquidity_mint: &Pubkey,
+    ) -> Result<()> {
+        if let Some(key) = self.lending_market_authority {
+            require_derived(key, lending_market_authority(program, lending_market))?;
+        }
+        let derivations: [(Option<&Pubkey>, ReserveDerivation); 4] = [
+            (self.liquidity_supply, reserve_liquidity_supply),
+            (self.fee_vault, reserve_fee_vault),
+            (self.collateral_mint, reserve_collateral_mint),
+            (self.collateral_supply, reserve_collateral_supply),
+        ];
+        for (key, derive) in derivations {
+            if let Some(key) = key {
+                require_derived(key, derive(program, lending_market, liquidity_mint))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fail with `InvalidKaminoAccount` unless `key` is the `expected` derived address
+pub fn require_derived(key: &Pubkey, expected: Pubkey) -> Result<()> {
+    require_keys_eq!(*key, expected, LendingError::InvalidKaminoAccount);
+    Ok(())
+}
@@ -1,7 +1,50 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+pub mod batch;
+pub mod combined;
+pub mod events;
+pub mod farms;
+pub mod flash_loan;
+pub mod kamino;
+pub mod kamino_error;
+pub mod kamino_pda;
+pub mod limits;
+pub mod oracle;
+pub mod pause;
+pub mod position;
+pub mod preflight;
+pub mod price_guard;
+pub mod roles;
+pub mod sentinel;
+pub mod state;
+pub mod timelock;
+pub mod token;
+
+use events::*;
+pub use batch::*;
+pub use combined::*;
+pub use farms::*;
+pub use flash_loan::*;
+pub use limits::*;
+pub use pause::*;
+pub use position::*;
+pub use roles::*;
+pub use timelock::*;
+use kamino::{
+    instruction_discriminator, invoke_kamino, serialize_kamino_instruction, ObligationMetrics,
+    ReserveFarmKind, ReserveStatus, KAMINO_LENDING_ID,
+};
+use kamino_pda::ReserveAccounts;
+use oracle::OracleAccounts;
+use state::{
+    Config, ConfigChange, PauseTarget, PositionAction, Registry, ReserveUsage, Role, UserBorrows,
+    UserPosition, CONFIG_SEED, DEFAULT_PRICE_GUARD, DEFAULT_TIMELOCK_DELAY, REGISTRY_SEED,
+    RESERVE_USAGE_SEED, USER_BORROWS_SEED, USER_POSITION_SEED,
+};
 
 declare_id!("56PWFoBr3NtHRAgaAvJaERidrh87e7W4SxjqLzg7ePxZ");
 
@@ -18,6 +61,120 @@ pub enum LendingError {
     InvalidProgramId,
     #[msg("Invalid instruction data")]
     InvalidInstructionData,
+    #[msg("Reserve has no farm for the requested kind")]
+    FarmNotConfigured,
+    #[msg("Farm account does not match the reserve or obligation")]
+    InvalidFarmAccount,
+    #[msg("Mint has a Token-2022 extension Kamino does not support")]
+    UnsupportedMintExtension,
+    #[msg("Flash loan instructions not found in the transaction")]
+    FlashLoanNotFound,
+    #[msg("User position does not match the owner, market or obligation")]
+    InvalidUserPosition,
+    #[msg("User position already tracks the maximum number of reserves")]
+    PositionReservesFull,
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("Signer is not the admin")]
+    NotAdmin,
+    #[msg("Signer is neither the admin nor a guardian")]
+    NotAdminOrGuardian,
+    #[msg("Signer is not a keeper")]
+    NotKeeper,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("Fee exceeds the maximum rate")]
+    InvalidFeeConfig,
+    #[msg("Timelock delay is out of range")]
+    InvalidTimelockDelay,
+    #[msg("Timelock has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("Signer is neither the admin nor the proposer")]
+    NotAdminOrProposer,
+    #[msg("Registry entry is missing its market or reserve")]
+    InvalidRegistryEntry,
+    #[msg("Registry has no free slot")]
+    RegistryFull,
+    #[msg("Lending market is not approved")]
+    MarketNotAllowed,
+    #[msg("Reserve is not approved for this lending market")]
+    ReserveNotAllowed,
+    #[msg("Reserve does not match its registry metadata")]
+    ReserveMetadataMismatch,
+    #[msg("Reserve is not active")]
+    ReserveNotActive,
+    #[msg("Borrowing is not allowed from this reserve")]
+    BorrowNotAllowed,
+    #[msg("Deposit would exceed the reserve's exposure cap")]
+    DepositCapExceeded,
+    #[msg("Amount would exceed the reserve's volume limit for this epoch")]
+    EpochVolumeExceeded,
+    #[msg("Borrow would exceed the per-user borrow value limit")]
+    UserBorrowLimitExceeded,
+    #[msg("Reserve configures no oracle")]
+    OracleNotConfigured,
+    #[msg("Oracle account does not match the reserve's configuration")]
+    InvalidOracleAccount,
+    #[msg("Oracle price is zero or negative")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is too old")]
+    PriceTooOld,
+    #[msg("Oracle price is outside the reserve's heuristic bounds")]
+    PriceOutOfRange,
+    #[msg("Reserve blocks usage of its price")]
+    PriceUsageBlocked,
+    #[msg("Price guard thresholds are out of range")]
+    InvalidPriceGuard,
+    #[msg("Cached or oracle price is older than the price guard allows")]
+    PriceStale,
+    #[msg("Cached price deviates too far from the oracle price")]
+    PriceDeviationTooHigh,
+    #[msg("Oracle price deviates too far from its TWAP")]
+    TwapDeviationTooHigh,
+    #[msg("Reserve configures a TWAP but none was provided")]
+    TwapNotAvailable,
+    #[msg("Reserve needs to be refreshed in this slot")]
+    ReserveStale,
+    #[msg("Obligation needs to be refreshed in this slot")]
+    ObligationStale,
+    #[msg("Borrow amount too large for deposited collateral")]
+    BorrowTooLarge,
+    #[msg("Reserve withdrawal cap is reached")]
+    WithdrawalCapReached,
+    #[msg("Account is not the one Kamino derives for this market and reserve")]
+    InvalidKaminoAccount,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Deposits are paused")]
+    DepositsPaused,
+    #[msg("Borrows are paused")]
+    BorrowsPaused,
+    #[msg("Withdrawals are paused")]
+    WithdrawalsPaused,
+    #[msg("Liquidations are paused")]
+    LiquidationsPaused,
+    #[msg("Batch has no actions, too many, or accounts that do not match them")]
+    InvalidBatch,
+    #[msg("Obligation's debt exceeds its allowed borrow value")]
+    ObligationUnhealthy,
+    #[msg("Obligation owes nothing to this reserve")]
+    NoDebt,
+    #[msg("Obligation holds no collateral in this reserve")]
+    NoCollateral,
+    #[msg("Deposit and withdrawal reserves must differ")]
+    SameReserve,
+    #[msg("An oracle the reserve configures was not supplied")]
+    OracleAccountMissing,
+    #[msg("Pyth price update is not fully verified")]
+    UnverifiedOraclePrice,
+    #[msg("User borrows already track the maximum number of obligations")]
+    UserBorrowsFull,
+    #[msg("User borrows do not belong to the obligation's owner")]
+    InvalidUserBorrows,
+    #[msg("A fee is charged but the fee recipient's token account is missing")]
+    MissingFeeAccount,
+    #[msg("Signer is not a fee manager")]
+    NotFeeManager,
 }
 
 /// Program for interacting with Kamino lending protocol
@@ -40,12 +197,127 @@ pub mod liquidity_lending {
         pub liquidity_amount: u64,
     }
 
-    /// Initialize the program
+    /// Initialize the program configuration
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.fee_recipient = ctx.accounts.admin.key();
+        config.timelock_delay = DEFAULT_TIMELOCK_DELAY;
+        config.max_user_borrow_value = u64::MAX;
+        config.price_guard = DEFAULT_PRICE_GUARD;
+        config.bump = ctx.bumps.config;
+        ctx.accounts.registry.bump = ctx.bumps.registry;
+
         msg!("Initializing liquidity lending program");
         Ok(())
     }
 
+    /// Pause or unpause the program or one kind of operation
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `target` - What to pause
+    /// * `paused` - Whether to pause or unpause it
+    pub fn set_pause(ctx: Context<SetPause>, target: PauseTarget, paused: bool) -> Result<()> {
+        pause::set(ctx, target, paused)
+    }
+
+    /// Grant a guardian, keeper or fee manager role
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `role` - The role to grant
+    /// * `holder` - The key receiving the role
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        roles::grant(ctx, role, holder)
+    }
+
+    /// Revoke a role by closing its assignment
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn revoke_role(ctx: Context<RevokeRole>) -> Result<()> {
+        roles::revoke(ctx)
+    }
+
+    /// Propose a new admin, who takes over once they accept
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `new_admin` - The proposed admin
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        roles::propose(ctx, new_admin)
+    }
+
+    /// Accept a pending admin transfer
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        roles::accept(ctx)
+    }
+
+    /// Queue a configuration change behind the timelock
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `change` - The change to apply once the timelock elapses
+    pub fn queue_config_change(ctx: Context<QueueConfigChange>, change: ConfigChange) -> Result<()> {
+        timelock::queue(ctx, change)
+    }
+
+    /// Apply a queued configuration change whose timelock elapsed
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        timelock::execute(ctx)
+    }
+
+    /// Drop a queued configuration change
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        timelock::cancel(ctx)
+    }
+
+    /// Create the position ledger for a user's obligation in a lending market
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn init_user_position(ctx: Context<InitUserPosition>) -> Result<()> {
+        position::init(ctx)
+    }
+
+    /// Create the account tracking what the program routed into an approved reserve
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn init_reserve_usage(ctx: Context<InitReserveUsage>) -> Result<()> {
+        limits::init_usage(ctx)
+    }
+
+    /// Create the account summing what a user has borrowed across their obligations
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn init_user_borrows(ctx: Context<InitUserBorrows>) -> Result<()> {
+        limits::init_borrows(ctx)
+    }
+
+    /// Update a user's borrows with the value of an obligation refreshed in this slot, such
+    /// as one repaid since the program last saw it; anyone can call it
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    pub fn sync_user_borrows(ctx: Context<SyncUserBorrows>) -> Result<()> {
+        limits::sync_borrows(ctx)
+    }
+
     /// Deposit liquidity into a Kamino reserve
     /// 
     /// # Arguments
@@ -57,14 +329,39 @@ pub mod liquidity_lending {
     ) -> Result<()> {
         // Validate amount
         require!(liquidity_amount > 0, LendingError::InvalidAmount);
+        ctx.accounts.config.require_not_paused(PauseTarget::Deposits)?;
 
         let cpi_program = ctx.accounts.kamino_lending_program.to_account_info();
 
-        // Validate program ID
+        let entry = ctx
+            .accounts
+            .registry
+            .require_reserve(ctx.accounts.lending_market.key, ctx.accounts.reserve.key)?;
         require!(
-            cpi_program.key() == ctx.accounts.kamino_lending_program.key(),
-            LendingError::InvalidProgramId
+            entry.decimals == ctx.accounts.reserve_liquidity_mint.decimals,
+            LendingError::ReserveMetadataMismatch
         );
+        ReserveStatus::require_active(&ctx.accounts.reserve, cpi_program.key)?;
+        ReserveAccounts {
+            lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+            liquidity_supply: Some(ctx.accounts.reserve_liquidity_supply.key),
+            collateral_mint: Some(&ctx.accounts.reserve_collateral_mint.key()),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(
+            cpi_program.key,
+            ctx.accounts.lending_market.key,
+            &ctx.accounts.reserve_liquidity_mint.key(),
+        )?;
+        preflight::require_reserve_fresh(&ctx.accounts.reserve, cpi_program.key, Clock::get()?.slot)?;
+        ctx.accounts
+            .reserve_usage
+            .record_deposit(entry, liquidity_amount, Clock::get()?.epoch)?;
+
+        let liquidity_mint = ctx.accounts.reserve_liquidity_mint.to_account_info();
+        token::validate_mint(&liquidity_mint)?;
+        let received_amount = token::amount_after_transfer_fee(&liquidity_mint, liquidity_amount)?;
+        let collateral_before = ctx.accounts.user_destination_collateral.amount;
 
         let cpi_accounts = vec![
             ctx.accounts.owner.to_account_info(),
@@ -81,26 +378,42 @@ pub mod liquidity_lending {
             ctx.accounts.instruction_sysvar_account.to_account_info(),
         ];
 
-        let instruction_data = serialize_kamino_instruction(13, &liquidity_amount)?;
-
-        let account_metas: Vec<AccountMeta> = cpi_accounts
-            .iter()
-            .map(|acc| AccountMeta {
-                pubkey: *acc.key,
-                is_signer: acc.is_signer,
-                is_writable: acc.is_writable,
-            })
-            .collect();
-
-        let ix = Instruction {
-            program_id: cpi_program.key(),
-            accounts: account_metas,
-            data: instruction_data,
-        };
-
-        invoke(&ix, &cpi_accounts)?;
-
-        msg!("Successfully deposited {} liquidity", liquidity_amount);
+        let instruction_data = serialize_kamino_instruction(
+            instruction_discriminator::DEPOSIT_RESERVE_LIQUIDITY,
+            &liquidity_amount,
+        )?;
+
+        invoke_kamino(&cpi_program, &cpi_accounts, instruction_data)?;
+
+        ctx.accounts.user_destination_collateral.reload()?;
+        let collateral_minted = ctx
+            .accounts
+            .user_destination_collateral
+            .amount
+            .saturating_sub(collateral_before);
+
+        ctx.accounts.user_position.record(
+            ctx.accounts.reserve.key(),
+            PositionAction::Deposit,
+            liquidity_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit_cpi!(Deposited {
+            owner: ctx.accounts.owner.key(),
+            reserve: ctx.accounts.reserve.key(),
+            obligation: None,
+            liquidity_amount,
+            liquidity_received: received_amount,
+            collateral_minted,
+            metrics: None,
+        });
+
+        msg!(
+            "Successfully deposited {} liquidity ({} after transfer fees)",
+            liquidity_amount,
+            received_amount
+        );
         Ok(())
     }
 
@@ -115,6 +428,60 @@ pub mod liquidity_lending {
     ) -> Result<()> {
         // Validate amount
         require!(liquidity_amount > 0, LendingError::InvalidAmount);
+        ctx.accounts.config.require_not_paused(PauseTarget::Borrows)?;
+
+        let clock = Clock::get()?;
+        let entry = ctx
+            .accounts
+            .registry
+            .require_reserve(ctx.accounts.lending_market.key, ctx.accounts.borrow_reserve.key)?;
+        require!(entry.borrow_allowed, LendingError::BorrowNotAllowed);
+        require!(
+            entry.decimals == ctx.accounts.borrow_reserve_liquidity_mint.decimals,
+            LendingError::ReserveMetadataMismatch
+        );
+        ReserveStatus::require_active(
+            &ctx.accounts.borrow_reserve,
+            ctx.accounts.kamino_lending_program.key,
+        )?;
+        ReserveAccounts {
+            lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+            liquidity_supply: Some(ctx.accounts.reserve_source_liquidity.key),
+            fee_vault: Some(ctx.accounts.borrow_reserve_liquidity_fee_receiver.key),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(
+            ctx.accounts.kamino_lending_program.key,
+            ctx.accounts.lending_market.key,
+            &ctx.accounts.borrow_reserve_liquidity_mint.key(),
+        )?;
+        ctx.accounts
+            .reserve_usage
+            .record_borrow(entry, liquidity_amount, clock.epoch)?;
+
+        price_guard::check(
+            &ctx.accounts.config.price_guard,
+            &ctx.accounts.borrow_reserve,
+            ctx.accounts.kamino_lending_program.key,
+            &OracleAccounts {
+                pyth_price: ctx.accounts.pyth_price.as_ref(),
+                switchboard_price: ctx.accounts.switchboard_price.as_ref(),
+                switchboard_twap: ctx.accounts.switchboard_twap.as_ref(),
+                scope_prices: ctx.accounts.scope_prices.as_ref(),
+            },
+            clock.unix_timestamp,
+        )?;
+        preflight::check_borrow(
+            &ctx.accounts.obligation,
+            &ctx.accounts.borrow_reserve,
+            ctx.accounts.kamino_lending_program.key,
+            liquidity_amount,
+            ctx.accounts.borrow_reserve_liquidity_mint.decimals,
+            &clock,
+        )?;
+
+        token::validate_mint(&ctx.accounts.borrow_reserve_liquidity_mint.to_account_info())?;
+        let balance_before = ctx.accounts.user_destination_liquidity.amount;
 
         let cpi_accounts = vec![
             ctx.accounts.owner.to_account_info(),
@@ -126,31 +493,63 @@ pub mod liquidity_lending {
             ctx.accounts.reserve_source_liquidity.to_account_info(),
             ctx.accounts.borrow_reserve_liquidity_fee_receiver.to_account_info(),
             ctx.accounts.user_destination_liquidity.to_account_info(),
-            ctx.accounts.referrer_token_state.clone().unwrap_or(ctx.accounts.token_program.clone()).to_account_info(),
+            ctx.accounts
+                .referrer_token_state
+                .clone()
+                .unwrap_or(ctx.accounts.kamino_lending_program.to_account_info()),
             ctx.accounts.token_program.to_account_info(),
             ctx.accounts.instruction_sysvar_account.to_account_info(),
         ];
 
-        let instruction_data = serialize_kamino_instruction(23, &liquidity_amount)?;
-
-        let account_metas: Vec<AccountMeta> = cpi_accounts
-            .iter()
-            .map(|acc| AccountMeta {
-                pubkey: *acc.key,
-                is_signer: acc.is_signer,
-                is_writable: acc.is_writable,
-            })
-            .collect();
-
-        let ix = Instruction {
-            program_id: ctx.accounts.kamino_lending_program.key(),
-            accounts: account_metas,
-            data: instruction_data,
-        };
-
-        invoke(&ix, &cpi_accounts)?;
-
-        msg!("Successfully borrowed {} liquidity", liquidity_amount);
+        let instruction_data = serialize_kamino_instruction(
+            instruction_discriminator::BORROW_OBLIGATION_LIQUIDITY,
+            &liquidity_amount,
+        )?;
+
+        invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+        // Transfer fees mean the user can receive less than was borrowed
+        ctx.accounts.user_destination_liquidity.reload()?;
+        let received_amount = ctx
+            .accounts
+            .user_destination_liquidity
+            .amount
+            .saturating_sub(balance_before);
+
+        ctx.accounts.user_position.record(
+            ctx.accounts.borrow_reserve.key(),
+            PositionAction::Borrow,
+            received_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Kamino refreshed the obligation's borrowed value during the borrow
+        let metrics = ObligationMetrics::load(
+            &ctx.accounts.obligation,
+            ctx.accounts.kamino_lending_program.key,
+        )?;
+        limits::record_user_borrow_value(
+            &ctx.accounts.config,
+            &mut ctx.accounts.user_borrows,
+            ctx.accounts.obligation.key(),
+            &metrics,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit_cpi!(Borrowed {
+            owner: ctx.accounts.owner.key(),
+            reserve: ctx.accounts.borrow_reserve.key(),
+            obligation: ctx.accounts.obligation.key(),
+            liquidity_amount,
+            liquidity_received: received_amount,
+            metrics,
+        });
+
+        msg!(
+            "Successfully borrowed {} liquidity ({} received)",
+            liquidity_amount,
+            received_amount
+        );
         Ok(())
     }
 
@@ -158,22 +557,48 @@ pub mod liquidity_lending {
     /// 
     /// # Arguments
     /// * `ctx` - The context of accounts
-    /// * `liquidity_amount` - Amount of liquidity to repay
+    /// * `liquidity_amount` - Amount of liquidity to repay, or `u64::MAX` for the whole debt
     pub fn kamino_repay_obligation_liquidity(
         ctx: Context<KaminoRepayObligationLiquidity>,
         liquidity_amount: u64,
     ) -> Result<()> {
         // Validate amount
         require!(liquidity_amount > 0, LendingError::InvalidAmount);
+        ctx.accounts.config.require_not_paused(PauseTarget::Global)?;
 
         let cpi_program = ctx.accounts.kamino_lending_program.to_account_info();
 
-        // Validate program ID
+        // Repayments and withdrawals stay open on deprecated reserves so users can exit
+        ctx.accounts
+            .registry
+            .require_reserve(ctx.accounts.lending_market.key, ctx.accounts.repay_reserve.key)?;
+        ReserveAccounts {
+            liquidity_supply: Some(ctx.accounts.reserve_destination_liquidity.key),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(
+            cpi_program.key,
+            ctx.accounts.lending_market.key,
+            &ctx.accounts.reserve_liquidity_mint.key(),
+        )?;
+        let slot = Clock::get()?.slot;
+        preflight::require_reserve_fresh(&ctx.accounts.repay_reserve, cpi_program.key, slot)?;
+        preflight::require_obligation_fresh(&ctx.accounts.obligation, cpi_program.key, slot)?;
+        let liquidity_amount = sentinel::repay_amount(
+            &ctx.accounts.obligation,
+            cpi_program.key,
+            ctx.accounts.repay_reserve.key,
+            liquidity_amount,
+        )?;
         require!(
-            cpi_program.key() == ctx.accounts.kamino_lending_program.key(),
-            LendingError::InvalidProgramId
+            ctx.accounts.user_source_liquidity.amount >= liquidity_amount,
+            LendingError::InsufficientFunds
         );
 
+        let liquidity_mint = ctx.accounts.reserve_liquidity_mint.to_account_info();
+        token::validate_mint(&liquidity_mint)?;
+        let received_amount = token::amount_after_transfer_fee(&liquidity_mint, liquidity_amount)?;
+
         let cpi_accounts = vec![
             ctx.accounts.owner.to_account_info(),
             ctx.accounts.obligation.to_account_info(),
@@ -186,38 +611,428 @@ pub mod liquidity_lending {
             ctx.accounts.instruction_sysvar_account.to_account_info(),
         ];
 
-        let instruction_data = serialize_kamino_instruction(25, &liquidity_amount)?;
+        let instruction_data = serialize_kamino_instruction(
+            instruction_discriminator::REPAY_OBLIGATION_LIQUIDITY,
+            &liquidity_amount,
+        )?;
+
+        invoke_kamino(&cpi_program, &cpi_accounts, instruction_data)?;
+
+        ctx.accounts.user_position.record(
+            ctx.accounts.repay_reserve.key(),
+            PositionAction::Repay,
+            liquidity_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit_cpi!(Repaid {
+            owner: ctx.accounts.owner.key(),
+            reserve: ctx.accounts.repay_reserve.key(),
+            obligation: ctx.accounts.obligation.key(),
+            liquidity_amount,
+            liquidity_received: received_amount,
+            metrics: ObligationMetrics::load(&ctx.accounts.obligation, cpi_program.key)?,
+        });
+
+        msg!(
+            "Successfully repaid {} liquidity ({} after transfer fees)",
+            liquidity_amount,
+            received_amount
+        );
+        Ok(())
+    }
 
-        let account_metas: Vec<AccountMeta> = cpi_accounts
-            .iter()
-            .map(|acc| AccountMeta {
-                pubkey: *acc.key,
-                is_signer: acc.is_signer,
-                is_writable: acc.is_writable,
-            })
-            .collect();
+    /// Withdraw collateral from an obligation and redeem it for liquidity
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `collateral_amount` - Amount of collateral to withdraw, or `u64::MAX` for all of it
+    pub fn kamino_withdraw_obligation_collateral_and_redeem_reserve_collateral(
+        ctx: Context<KaminoWithdrawObligationCollateralAndRedeemReserveCollateral>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        // Validate amount
+        require!(collateral_amount > 0, LendingError::InvalidAmount);
+        ctx.accounts.config.require_not_paused(PauseTarget::Withdrawals)?;
+        ctx.accounts
+            .registry
+            .require_reserve(ctx.accounts.lending_market.key, ctx.accounts.withdraw_reserve.key)?;
+        let kamino_program = ctx.accounts.kamino_lending_program.key;
+        ReserveAccounts {
+            lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+            liquidity_supply: Some(ctx.accounts.reserve_liquidity_supply.key),
+            collateral_mint: Some(&ctx.accounts.reserve_collateral_mint.key()),
+            collateral_supply: Some(ctx.accounts.reserve_source_collateral.key),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(
+            kamino_program,
+            ctx.accounts.lending_market.key,
+            &ctx.accounts.reserve_liquidity_mint.key(),
+        )?;
+        let slot = Clock::get()?.slot;
+        preflight::require_reserve_fresh(&ctx.accounts.withdraw_reserve, kamino_program, slot)?;
+        preflight::require_obligation_fresh(&ctx.accounts.obligation, kamino_program, slot)?;
+        let collateral_amount = sentinel::withdraw_amount(
+            &ctx.accounts.obligation,
+            kamino_program,
+            ctx.accounts.withdraw_reserve.key,
+            collateral_amount,
+        )?;
+
+        token::validate_mint(&ctx.accounts.reserve_liquidity_mint.to_account_info())?;
+        let balance_before = ctx.accounts.user_destination_liquidity.amount;
 
-        let ix = Instruction {
-            program_id: cpi_program.key(),
-            accounts: account_metas,
-            data: instruction_data,
-        };
+        let cpi_accounts = vec![
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
+            ctx.accounts.lending_market.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.withdraw_reserve.to_account_info(),
+            ctx.accounts.reserve_liquidity_mint.to_account_info(),
+            ctx.accounts.reserve_source_collateral.to_account_info(),
+            ctx.accounts.reserve_collateral_mint.to_account_info(),
+            ctx.accounts.reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.user_destination_liquidity.to_account_info(),
+            ctx.accounts.kamino_lending_program.to_account_info(),
+            ctx.accounts.collateral_token_program.to_account_info(),
+            ctx.accounts.liquidity_token_program.to_account_info(),
+            ctx.accounts.instruction_sysvar_account.to_account_info(),
+        ];
 
-        invoke(&ix, &cpi_accounts)?;
+        let instruction_data = serialize_kamino_instruction(
+            instruction_discriminator::WITHDRAW_OBLIGATION_COLLATERAL_AND_REDEEM_RESERVE_COLLATERAL,
+            &collateral_amount,
+        )?;
+
+        invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+        ctx.accounts.user_destination_liquidity.reload()?;
+        let received_amount = ctx
+            .accounts
+            .user_destination_liquidity
+            .amount
+            .saturating_sub(balance_before);
+
+        ctx.accounts.user_position.record(
+            ctx.accounts.withdraw_reserve.key(),
+            PositionAction::Withdraw,
+            received_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+        ctx.accounts.reserve_usage.record_withdrawal(received_amount);
+
+        emit_cpi!(Withdrawn {
+            owner: ctx.accounts.owner.key(),
+            reserve: ctx.accounts.withdraw_reserve.key(),
+            obligation: ctx.accounts.obligation.key(),
+            collateral_amount,
+            liquidity_received: received_amount,
+            metrics: ObligationMetrics::load(
+                &ctx.accounts.obligation,
+                ctx.accounts.kamino_lending_program.key,
+            )?,
+        });
+
+        msg!(
+            "Successfully withdrew {} collateral ({} liquidity received)",
+            collateral_amount,
+            received_amount
+        );
+        Ok(())
+    }
+
+    /// Liquidate an unhealthy obligation and redeem the seized collateral
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `liquidity_amount` - Amount of debt to repay
+    /// * `min_acceptable_received_liquidity_amount` - Minimum liquidity the liquidator accepts
+    /// * `max_allowed_ltv_override_percent` - LTV override passed through to Kamino
+    pub fn kamino_liquidate_obligation_and_redeem_reserve_collateral(
+        ctx: Context<KaminoLiquidateObligationAndRedeemReserveCollateral>,
+        liquidity_amount: u64,
+        min_acceptable_received_liquidity_amount: u64,
+        max_allowed_ltv_override_percent: u64,
+    ) -> Result<()> {
+        // Validate amount
+        require!(liquidity_amount > 0, LendingError::InvalidAmount);
+        ctx.accounts.config.require_not_paused(PauseTarget::Liquidations)?;
+        let registry = &ctx.accounts.registry;
+        registry.require_reserve(ctx.accounts.lending_market.key, ctx.accounts.repay_reserve.key)?;
+        registry.require_reserve(ctx.accounts.lending_market.key, ctx.accounts.withdraw_reserve.key)?;
+        let kamino_program = ctx.accounts.kamino_lending_program.key;
+        let lending_market = ctx.accounts.lending_market.key;
+        ReserveAccounts {
+            lending_market_authority: Some(ctx.accounts.lending_market_authority.key),
+            liquidity_supply: Some(ctx.accounts.repay_reserve_liquidity_supply.key),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(kamino_program, lending_market, &ctx.accounts.repay_reserve_liquidity_mint.key())?;
+        ReserveAccounts {
+            liquidity_supply: Some(ctx.accounts.withdraw_reserve_liquidity_supply.key),
+            fee_vault: Some(ctx.accounts.withdraw_reserve_liquidity_fee_receiver.key),
+            collateral_mint: Some(&ctx.accounts.withdraw_reserve_collateral_mint.key()),
+            collateral_supply: Some(ctx.accounts.withdraw_reserve_collateral_supply.key),
+            ..ReserveAccounts::default()
+        }
+        .require_derived(kamino_program, lending_market, &ctx.accounts.withdraw_reserve_liquidity_mint.key())?;
+        let slot = Clock::get()?.slot;
+        preflight::require_reserve_fresh(&ctx.accounts.repay_reserve, kamino_program, slot)?;
+        preflight::require_reserve_fresh(&ctx.accounts.withdraw_reserve, kamino_program, slot)?;
+        preflight::require_obligation_fresh(&ctx.accounts.obligation, kamino_program, slot)?;
+
+        token::validate_mint(&ctx.accounts.repay_reserve_liquidity_mint.to_account_info())?;
+        token::validate_mint(&ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info())?;
+        let balance_before = ctx.accounts.user_destination_liquidity.amount;
+
+        let cpi_accounts = vec![
+            ctx.accounts.liquidator.to_account_info(),
+            ctx.accounts.obligation.to_account_info(),
+            ctx.accounts.lending_market.to_account_info(),
+            ctx.accounts.lending_market_authority.to_account_info(),
+            ctx.accounts.repay_reserve.to_account_info(),
+            ctx.accounts.repay_reserve_liquidity_mint.to_account_info(),
+            ctx.accounts.repay_reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.withdraw_reserve.to_account_info(),
+            ctx.accounts.withdraw_reserve_liquidity_mint.to_account_info(),
+            ctx.accounts.withdraw_reserve_collateral_mint.to_account_info(),
+            ctx.accounts.withdraw_reserve_collateral_supply.to_account_info(),
+            ctx.accounts.withdraw_reserve_liquidity_supply.to_account_info(),
+            ctx.accounts.withdraw_reserve_liquidity_fee_receiver.to_account_info(),
+            ctx.accounts.user_source_liquidity.to_account_info(),
+            ctx.accounts.user_destination_collateral.to_account_info(),
+            ctx.accounts.user_destination_liquidity.to_account_info(),
+            ctx.accounts.collateral_token_program.to_account_info(),
+            ctx.accounts.repay_liquidity_token_program.to_account_info(),
+            ctx.accounts.withdraw_liquidity_token_program.to_account_info(),
+            ctx.accounts.instruction_sysvar_account.to_account_info(),
+        ];
 
-        msg!("Successfully repaid {} liquidity", liquidity_amount);
+        let instruction_data = serialize_kamino_instruction(
+            instruction_discriminator::LIQUIDATE_OBLIGATION_AND_REDEEM_RESERVE_COLLATERAL,
+            &(
+                liquidity_amount,
+                min_acceptable_received_liquidity_amount,
+                max_allowed_ltv_override_percent,
+            ),
+        )?;
+
+        invoke_kamino(&ctx.accounts.kamino_lending_program, &cpi_accounts, instruction_data)?;
+
+        ctx.accounts.user_destination_liquidity.reload()?;
+        let received_amount = ctx
+            .accounts
+            .user_destination_liquidity
+            .amount
+            .saturating_sub(balance_before);
+
+        let now = Clock::get()?.unix_timestamp;
+        let position = &mut ctx.accounts.user_position;
+        position.record(
+            ctx.accounts.repay_reserve.key(),
+            PositionAction::LiquidatedDebt,
+            liquidity_amount,
+            now,
+        )?;
+        position.record(
+            ctx.accounts.withdraw_reserve.key(),
+            PositionAction::SeizedCollateral,
+            received_amount,
+            now,
+        )?;
+
+        emit_cpi!(Liquidated {
+            liquidator: ctx.accounts.liquidator.key(),
+            obligation: ctx.accounts.obligation.key(),
+            repay_reserve: ctx.accounts.repay_reserve.key(),
+            withdraw_reserve: ctx.accounts.withdraw_reserve.key(),
+            liquidity_amount,
+            liquidity_received: received_amount,
+            metrics: ObligationMetrics::load(
+                &ctx.accounts.obligation,
+                ctx.accounts.kamino_lending_program.key,
+            )?,
+        });
+
+        msg!(
+            "Successfully liquidated {} liquidity ({} received)",
+            liquidity_amount,
+            received_amount
+        );
         Ok(())
     }
+
+    /// Record a Kamino flash borrow and repay pair executed in this transaction
+    ///
+    /// Kamino refuses flash loans through CPI, so the borrow and repay run as top-level
+    /// instructions and this instruction emits the matching event. The reserve must be in the
+    /// registry.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `borrow_instruction_index` - Index of the flash borrow in the transaction
+    /// * `repay_instruction_index` - Index of the flash repay in the transaction
+    pub fn record_flash_loan(
+        ctx: Context<RecordFlashLoan>,
+        borrow_instruction_index: u8,
+        repay_instruction_index: u8,
+    ) -> Result<()> {
+        flash_loan::record(ctx, borrow_instruction_index, repay_instruction_index)
+    }
+
+    /// Enroll an obligation in a reserve's collateral or debt farm
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `mode` - Which of the reserve's farms to join
+    pub fn kamino_init_obligation_farms_for_reserve(
+        ctx: Context<KaminoInitObligationFarmsForReserve>,
+        mode: ReserveFarmKind,
+    ) -> Result<()> {
+        farms::init_obligation_farms_for_reserve(ctx, mode)
+    }
+
+    /// Refresh an obligation's stake in a reserve farm
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `mode` - Which of the reserve's farms to refresh
+    pub fn kamino_refresh_obligation_farms_for_reserve(
+        ctx: Context<KaminoRefreshObligationFarmsForReserve>,
+        mode: ReserveFarmKind,
+    ) -> Result<()> {
+        farms::refresh_obligation_farms_for_reserve(ctx, mode)
+    }
+
+    /// Run up to four deposits, withdrawals, borrows and repayments on one obligation,
+    /// checking the obligation's health only after the last action
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts, with each action's accounts in `remaining_accounts`
+    /// * `actions` - The actions to run, in order
+    pub fn kamino_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, KaminoBatch<'info>>,
+        actions: Vec<BatchAction>,
+    ) -> Result<()> {
+        batch::batch(ctx, actions)
+    }
+
+    /// Repay borrowed liquidity then withdraw and redeem collateral in one Kamino instruction,
+    /// checking the obligation's health once both are done
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts, with the obligation's reserves after the repayment,
+    ///   deposits then borrows, in `remaining_accounts`
+    /// * `repay_amount` - Amount of liquidity to repay, or `u64::MAX` for the whole debt
+    /// * `withdraw_collateral_amount` - Amount of collateral to withdraw, or `u64::MAX` for all of it
+    pub fn kamino_repay_and_withdraw_and_redeem<'info>(
+        ctx: Context<'_, '_, 'info, 'info, KaminoRepayAndWithdrawAndRedeem<'info>>,
+        repay_amount: u64,
+        withdraw_collateral_amount: u64,
+    ) -> Result<()> {
+        combined::repay_and_withdraw(ctx, repay_amount, withdraw_collateral_amount)
+    }
+
+    /// Deposit liquidity as collateral in one reserve then withdraw and redeem collateral from
+    /// another in one Kamino instruction, checking the obligation's health once both are done
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts, with the obligation's reserves after the deposit,
+    ///   deposits then borrows, in `remaining_accounts`
+    /// * `liquidity_amount` - Amount of liquidity to deposit
+    /// * `withdraw_collateral_amount` - Amount of collateral to withdraw, or `u64::MAX` for all of it
+    pub fn kamino_deposit_and_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, KaminoDepositAndWithdraw<'info>>,
+        liquidity_amount: u64,
+        withdraw_collateral_amount: u64,
+    ) -> Result<()> {
+        combined::deposit_and_withdraw(ctx, liquidity_amount, withdraw_collateral_amount)
+    }
+
+    /// Harvest farm rewards and optionally forward them to another account
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts
+    /// * `reward_index` - Reward slot of the farm to harvest
+    pub fn kamino_claim_farm_rewards(
+        ctx: Context<KaminoClaimFarmRewards>,
+        reward_index: u64,
+    ) -> Result<()> {
+        farms::claim_farm_rewards(ctx, reward_index)
+    }
 }
 
 #[derive(Accounts)]
-pub struct Initialize {}
+pub struct Initialize<'info> {
+    /// The program's upgrade authority, which becomes the admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The program configuration to create
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The registry of approved markets and reserves to create, empty until changes land
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [REGISTRY_SEED],
+        bump,
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// This program, whose upgrade authority may initialize it
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::LiquidityLending>,
+
+    /// This program's data account
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ LendingError::NotAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct KaminoDepositReserveLiquidity<'info> {
     /// The account paying for the deposit
     pub owner: Signer<'info>,
 
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// Usage of the reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, reserve.key().as_ref()],
+        bump = reserve_usage.bump,
+    )]
+    pub reserve_usage: Box<Account<'info, ReserveUsage>>,
+
     /// The reserve account to deposit into
     #[account(mut)]
     /// CHECK: Validated by Kamino program
@@ -232,8 +1047,8 @@ pub struct KaminoDepositReserveLiquidity<'info> {
     pub lending_market_authority: AccountInfo<'info>,
 
     /// The reserve's liquidity mint
-    /// CHECK: Validated by Kamino program
-    pub reserve_liquidity_mint: AccountInfo<'info>,
+    #[account(mint::token_program = liquidity_token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The reserve's liquidity supply account
     #[account(mut)]
@@ -241,42 +1056,82 @@ pub struct KaminoDepositReserveLiquidity<'info> {
     pub reserve_liquidity_supply: AccountInfo<'info>,
 
     /// The reserve's collateral mint
-    #[account(mut)]
-    /// CHECK: Validated by Kamino program
-    pub reserve_collateral_mint: AccountInfo<'info>,
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The user's source liquidity account
-    #[account(mut)]
-    /// CHECK: Validated by Kamino program
-    pub user_source_liquidity: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = reserve_liquidity_mint,
+        token::authority = owner,
+        token::token_program = liquidity_token_program,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The user's destination collateral account
-    #[account(mut)]
-    /// CHECK: Validated by Kamino program
-    pub user_destination_collateral: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = reserve_collateral_mint,
+        token::token_program = collateral_token_program,
+    )]
+    pub user_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The collateral token program
-    /// CHECK: Validated by Kamino program
-    pub collateral_token_program: AccountInfo<'info>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
 
     /// The liquidity token program
-    /// CHECK: Validated by Kamino program
-    pub liquidity_token_program: AccountInfo<'info>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
 
     /// The instruction sysvar account
-    /// CHECK: Validated by Kamino program
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
     pub instruction_sysvar_account: AccountInfo<'info>,
 
     /// The Kamino lending program
-    /// CHECK: Validated by program ID check
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
     pub kamino_lending_program: AccountInfo<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct KaminoBorrowObligationLiquidity<'info> {
     /// The account paying for the borrow
     pub owner: Signer<'info>,
 
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// What the owner has borrowed across their obligations
+    #[account(
+        mut,
+        seeds = [USER_BORROWS_SEED, owner.key().as_ref()],
+        bump = user_borrows.bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// Usage of the reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, borrow_reserve.key().as_ref()],
+        bump = reserve_usage.bump,
+    )]
+    pub reserve_usage: Box<Account<'info, ReserveUsage>>,
+
     /// The obligation account
     #[account(mut)]
     /// CHECK: Validated by Kamino program
@@ -296,8 +1151,8 @@ pub struct KaminoBorrowObligationLiquidity<'info> {
     pub borrow_reserve: AccountInfo<'info>,
 
     /// The reserve's liquidity mint
-    /// CHECK: Validated by Kamino program
-    pub borrow_reserve_liquidity_mint: AccountInfo<'info>,
+    #[account(mint::token_program = token_program)]
+    pub borrow_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The reserve's source liquidity account
     #[account(mut)]
@@ -310,33 +1165,71 @@ pub struct KaminoBorrowObligationLiquidity<'info> {
     pub borrow_reserve_liquidity_fee_receiver: AccountInfo<'info>,
 
     /// The user's destination liquidity account
-    #[account(mut)]
-    /// CHECK: Validated by Kamino program
-    pub user_destination_liquidity: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = borrow_reserve_liquidity_mint,
+        token::token_program = token_program,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Optional referrer token state account
-    #[account(mut)]
-    /// CHECK: Validated by Kamino program
+    #[account(mut, owner = kamino_lending_program.key() @ LendingError::InvalidKaminoAccount)]
+    /// CHECK: Owned by Kamino, which matches it against the obligation's referrer
     pub referrer_token_state: Option<AccountInfo<'info>>,
 
+    /// The borrow reserve's Pyth price update
+    /// CHECK: Matched against the reserve's oracle configuration
+    pub pyth_price: Option<AccountInfo<'info>>,
+
+    /// The borrow reserve's Switchboard price feed
+    /// CHECK: Matched against the reserve's oracle configuration
+    pub switchboard_price: Option<AccountInfo<'info>>,
+
+    /// The borrow reserve's Switchboard TWAP feed
+    /// CHECK: Matched against the reserve's oracle configuration
+    pub switchboard_twap: Option<AccountInfo<'info>>,
+
+    /// The borrow reserve's Scope prices
+    /// CHECK: Matched against the reserve's oracle configuration
+    pub scope_prices: Option<AccountInfo<'info>>,
+
     /// The token program
-    /// CHECK: Validated by Kamino program
-    pub token_program: AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     /// The instruction sysvar account
-    /// CHECK: Validated by Kamino program
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
     pub instruction_sysvar_account: AccountInfo<'info>,
 
     /// The Kamino lending program
-    /// CHECK: Validated by program ID check
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
     pub kamino_lending_program: AccountInfo<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct KaminoRepayObligationLiquidity<'info> {
     /// The account paying for the repay
     pub owner: Signer<'info>,
 
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
     /// The obligation account
     #[account(mut)]
     /// CHECK: Validated by Kamino program
@@ -352,8 +1245,8 @@ pub struct KaminoRepayObligationLiquidity<'info> {
     pub repay_reserve: AccountInfo<'info>,
 
     /// The reserve's liquidity mint
-    /// CHECK: Validated by Kamino program
-    pub reserve_liquidity_mint: AccountInfo<'info>,
+    #[account(mint::token_program = token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The reserve's destination liquidity account
     #[account(mut)]
@@ -361,27 +1254,240 @@ pub struct KaminoRepayObligationLiquidity<'info> {
     pub reserve_destination_liquidity: AccountInfo<'info>,
 
     /// The user's source liquidity account
+    #[account(
+        mut,
+        token::mint = reserve_liquidity_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct KaminoWithdrawObligationCollateralAndRedeemReserveCollateral<'info> {
+    /// The obligation owner withdrawing collateral
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The owner's position ledger
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// Usage of the reserve's limits
+    #[account(
+        mut,
+        seeds = [RESERVE_USAGE_SEED, withdraw_reserve.key().as_ref()],
+        bump = reserve_usage.bump,
+    )]
+    pub reserve_usage: Box<Account<'info, ReserveUsage>>,
+
+    /// The obligation account
     #[account(mut)]
     /// CHECK: Validated by Kamino program
-    pub user_source_liquidity: AccountInfo<'info>,
+    pub obligation: AccountInfo<'info>,
 
-    /// The token program
+    /// The lending market account
     /// CHECK: Validated by Kamino program
-    pub token_program: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
 
-    /// The instruction sysvar account
+    /// The lending market authority account
+    /// CHECK: Validated by Kamino program
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve to withdraw from
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve: AccountInfo<'info>,
+
+    /// The reserve's liquidity mint
+    #[account(mint::token_program = liquidity_token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The reserve's collateral supply account
+    #[account(mut)]
     /// CHECK: Validated by Kamino program
+    pub reserve_source_collateral: AccountInfo<'info>,
+
+    /// The reserve's collateral mint
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The user's destination liquidity account
+    #[account(
+        mut,
+        token::mint = reserve_liquidity_mint,
+        token::token_program = liquidity_token_program,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The collateral token program
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// The liquidity token program
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
     pub instruction_sysvar_account: AccountInfo<'info>,
 
     /// The Kamino lending program
-    /// CHECK: Validated by program ID check
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
     pub kamino_lending_program: AccountInfo<'info>,
 }
 
-/// Serialize a Kamino instruction with the given index and amount
-fn serialize_kamino_instruction(instruction_index: u8, amount: &u64) -> Result<Vec<u8>> {
-    let mut data = Vec::new();
-    data.push(instruction_index);
-    data.extend_from_slice(&amount.to_le_bytes());
-    Ok(data)
-}
\ No newline at end of file
+#[event_cpi]
+#[derive(Accounts)]
+pub struct KaminoLiquidateObligationAndRedeemReserveCollateral<'info> {
+    /// The liquidator repaying the debt
+    pub liquidator: Signer<'info>,
+
+    /// The obligation being liquidated
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub obligation: AccountInfo<'info>,
+
+    /// The position ledger of the obligation's owner
+    #[account(
+        mut,
+        seeds = [USER_POSITION_SEED, user_position.owner.as_ref(), lending_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.obligation == obligation.key() @ LendingError::InvalidUserPosition,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The lending market account
+    /// CHECK: Validated by Kamino program
+    pub lending_market: AccountInfo<'info>,
+
+    /// The lending market authority account
+    /// CHECK: Validated by Kamino program
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// The reserve the debt is repaid to
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub repay_reserve: AccountInfo<'info>,
+
+    /// The repay reserve's liquidity mint
+    #[account(mint::token_program = repay_liquidity_token_program)]
+    pub repay_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The repay reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub repay_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The reserve the collateral is seized from
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve: AccountInfo<'info>,
+
+    /// The withdraw reserve's liquidity mint
+    #[account(mint::token_program = withdraw_liquidity_token_program)]
+    pub withdraw_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's collateral mint
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub withdraw_reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The withdraw reserve's collateral supply account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve_collateral_supply: AccountInfo<'info>,
+
+    /// The withdraw reserve's liquidity supply account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve_liquidity_supply: AccountInfo<'info>,
+
+    /// The withdraw reserve's liquidity fee receiver account
+    #[account(mut)]
+    /// CHECK: Validated by Kamino program
+    pub withdraw_reserve_liquidity_fee_receiver: AccountInfo<'info>,
+
+    /// The liquidator's source liquidity account
+    #[account(
+        mut,
+        token::mint = repay_reserve_liquidity_mint,
+        token::authority = liquidator,
+        token::token_program = repay_liquidity_token_program,
+    )]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The liquidator's destination collateral account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve_collateral_mint,
+        token::token_program = collateral_token_program,
+    )]
+    pub user_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The liquidator's destination liquidity account
+    #[account(
+        mut,
+        token::mint = withdraw_reserve_liquidity_mint,
+        token::token_program = withdraw_liquidity_token_program,
+    )]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The collateral token program
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// The repay reserve's liquidity token program
+    pub repay_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The withdraw reserve's liquidity token program
+    pub withdraw_liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// The instruction sysvar account
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+
+use crate::kamino::{ObligationHeader, ObligationMetrics, KAMINO_LENDING_ID};
+use crate::state::{
+    Config, Registry, ReserveUsage, UserBorrows, REGISTRY_SEED, RESERVE_USAGE_SEED,
+    USER_BORROWS_SEED,
+};
+use crate::LendingError;
+
+/// Fail if what the user has borrowed across their obligations exceeds the per-user limit
+pub fn require_user_borrow_value(config: &Config, user_borrows: &UserBorrows) -> Result<()> {
+    // Scaled fractions carry 60 fractional bits, and a u64 limit shifted by 60 fits a u128
+    let limit_sf = u128::from(config.max_user_borrow_value) << 60;
+    require!(
+        user_borrows.total_sf()? <= limit_sf,
+        LendingError::UserBorrowLimitExceeded
+    );
+    Ok(())
+}
+
+/// Record the obligation's fresh borrowed value in the user's borrows, then check the
+/// per-user limit against the new total
+pub fn record_user_borrow_value(
+    config: &Config,
+    user_borrows: &mut UserBorrows,
+    obligation: Pubkey,
+    metrics: &ObligationMetrics,
+    now: i64,
+) -> Result<()> {
+    user_borrows.record(obligation, metrics.borrowed_assets_market_value_sf, now)?;
+    require_user_borrow_value(config, user_borrows)
+}
+
+/// Fail if the obligation's debt exceeds its allowed borrow value
+pub fn require_healthy(metrics: &ObligationMetrics) -> Result<()> {
+    require!(
+        metrics.borrow_factor_adjusted_debt_value_sf <= metrics.allowed_borrow_value_sf,
+        LendingError::ObligationUnhealthy
+    );
+    Ok(())
+}
+
+pub fn init_usage(ctx: Context<InitReserveUsage>) -> Result<()> {
+    let reserve = ctx.accounts.reserve.key();
+    require!(
+        ctx.accounts.registry.reserve(&reserve).is_some(),
+        LendingError::ReserveNotAllowed
+    );
+
+    let usage = &mut ctx.accounts.reserve_usage;
+    usage.reserve = reserve;
+    usage.epoch = Clock::get()?.epoch;
+    usage.bump = ctx.bumps.reserve_usage;
+
+    msg!("Initialized usage tracking for reserve {}", reserve);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitReserveUsage<'info> {
+    /// The account paying for the usage account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The registry of approved markets and reserves
+    #[account(seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The approved reserve to track
+    /// CHECK: Looked up in the registry
+    pub reserve: AccountInfo<'info>,
+
+    /// The usage account to create
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ReserveUsage::INIT_SPACE,
+        seeds = [RESERVE_USAGE_SEED, reserve.key().as_ref()],
+        bump,
+    )]
+    pub reserve_usage: Box<Account<'info, ReserveUsage>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_borrows(ctx: Context<InitUserBorrows>) -> Result<()> {
+    let user_borrows = &mut ctx.accounts.user_borrows;
+    user_borrows.owner = ctx.accounts.owner.key();
+    user_borrows.bump = ctx.bumps.user_borrows;
+
+    msg!("Initialized borrow tracking for {}", user_borrows.owner);
+    Ok(())
+}
+
+pub fn sync_borrows(ctx: Context<SyncUserBorrows>) -> Result<()> {
+    let kamino_program = ctx.accounts.kamino_lending_program.key;
+    let header = ObligationHeader::load(&ctx.accounts.obligation, kamino_program)?;
+    require_keys_eq!(
+        header.owner,
+        ctx.accounts.user_borrows.owner,
+        LendingError::InvalidUserBorrows
+    );
+    // A stale obligation's value leaves out interest and price moves since its last refresh
+    let clock = Clock::get()?;
+    require!(!header.last_update.is_stale(clock.slot), LendingError::ObligationStale);
+
+    let metrics = ObligationMetrics::load(&ctx.accounts.obligation, kamino_program)?;
+    ctx.accounts.user_borrows.record(
+        ctx.accounts.obligation.key(),
+        metrics.borrowed_assets_market_value_sf,
+        clock.unix_timestamp,
+    )?;
+
+    msg!(
+        "Synced borrowed value of obligation {} for {}",
+        ctx.accounts.obligation.key(),
+        header.owner
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitUserBorrows<'info> {
+    /// The account paying for the borrows account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The user whose borrows are tracked
+    pub owner: Signer<'info>,
+
+    /// The borrows account to create
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserBorrows::INIT_SPACE,
+        seeds = [USER_BORROWS_SEED, owner.key().as_ref()],
+        bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncUserBorrows<'info> {
+    /// The borrows account of the obligation's owner
+    #[account(
+        mut,
+        seeds = [USER_BORROWS_SEED, user_borrows.owner.as_ref()],
+        bump = user_borrows.bump,
+    )]
+    pub user_borrows: Box<Account<'info, UserBorrows>>,
+
+    /// The obligation to sync, refreshed in this slot
+    /// CHECK: Decoded and checked against the borrows account's owner
+    pub obligation: AccountInfo<'info>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
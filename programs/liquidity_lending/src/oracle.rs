@@ -0,0 +1,383 @@
+use anchor_lang::prelude::*;
+
+use crate::kamino::{deserialize_account, PriceHeuristic, ScopeConfiguration, TokenInfo};
+use crate::LendingError;
+
+/// Fractional bits of a scaled fraction, Kamino's `Fraction` type
+pub const SF_FRACTIONAL_BITS: u32 = 60;
+
+/// Anchor discriminators of the oracle accounts we decode
+pub mod discriminator {
+    /// Pyth receiver `PriceUpdateV2`
+    pub const PYTH_PRICE_UPDATE: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+    /// Switchboard on-demand `PullFeedAccountData`
+    pub const SWITCHBOARD_PULL_FEED: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+    /// Scope `OraclePrices`
+    pub const SCOPE_ORACLE_PRICES: [u8; 8] = [89, 128, 118, 221, 6, 72, 180, 146];
+}
+
+/// Byte offsets into a Switchboard on-demand `PullFeedAccountData`, past the discriminator
+pub mod switchboard_layout {
+    pub const LAST_UPDATE_TIMESTAMP: usize = 2208;
+    pub const RESULT: usize = 2256;
+    /// Decimals of the feed's fixed point values
+    pub const PRECISION: u32 = 18;
+}
+
+/// Byte offsets into a Scope `OraclePrices`, past the discriminator
+pub mod scope_layout {
+    pub const PRICES: usize = 32;
+    pub const DATED_PRICE_SIZE: usize = 56;
+    pub const MAX_ENTRIES: usize = 512;
+    /// Marks the end of a price or TWAP chain
+    pub const CHAIN_END: u16 = u16::MAX;
+}
+
+/// Oracle a price was read from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleSource {
+    Pyth,
+    Switchboard,
+    Scope,
+}
+
+/// A price normalized to a scaled fraction (`value / 2^60`) of the quote currency
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price_sf: u128,
+    /// Confidence interval around the price, zero when the oracle has none
+    pub confidence_sf: u128,
+    /// Unix time the oracle published the price
+    pub timestamp: i64,
+    pub source: OracleSource,
+}
+
+/// Oracle accounts supplied for a reserve; each oracle the reserve configures must be present
+#[derive(Clone, Copy, Default)]
+pub struct OracleAccounts<'a, 'info> {
+    pub pyth_price: Option<&'a AccountInfo<'info>>,
+    pub switchboard_price: Option<&'a AccountInfo<'info>>,
+    pub switchboard_twap: Option<&'a AccountInfo<'info>>,
+    pub scope_prices: Option<&'a AccountInfo<'info>>,
+}
+
+/// Pyth receiver `VerificationLevel`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+enum PythVerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+/// Pyth `PriceFeedMessage`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct PythPriceMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    prev_publish_time: i64,
+    ema_price: i64,
+    ema_conf: u64,
+}
+
+/// Leading fields of a Pyth receiver `PriceUpdateV2`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct PythPriceUpdate {
+    write_authority: Pubkey,
+    verification_level: PythVerificationLevel,
+    price_message: PythPriceMessage,
+}
+
+/// Leading fields of a Switchboard on-demand `CurrentResult`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct SwitchboardResult {
+    value: i128,
+    std_dev: i128,
+}
+
+/// Leading fields of a Scope `DatedPrice`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct ScopeDatedPrice {
+    value: u64,
+    exp: u64,
+    last_updated_slot: u64,
+    unix_timestamp: u64,
+}
+
+/// Convert `value / 10^decimals` to a scaled fraction
+pub fn to_sf(value: u128, decimals: u32) -> Result<u128> {
+    let scale = 10u128
+        .checked_pow(decimals)
+        .ok_or(LendingError::MathOverflow)?;
+    // Split off the integer part first so large values do not overflow when shifted
+    let integer = (value / scale)
+        .checked_mul(1 << SF_FRACTIONAL_BITS)
+        .ok_or(LendingError::MathOverflow)?;
+    let fraction = (value % scale)
+        .checked_mul(1 << SF_FRACTIONAL_BITS)
+        .ok_or(LendingError::MathOverflow)?
+        / scale;
+    Ok(integer + fraction)
+}
+
+/// Convert `mantissa * 10^exponent`, as Pyth publishes prices, to a scaled fraction
+fn exponent_to_sf(mantissa: u128, exponent: i32) -> Result<u128> {
+    if exponent <= 0 {
+        return to_sf(mantissa, exponent.unsigned_abs());
+    }
+    let value = 10u128
+        .checked_pow(exponent as u32)
+        .and_then(|scale| mantissa.checked_mul(scale))
+        .ok_or(LendingError::MathOverflow)?;
+    to_sf(value, 0)
+}
+
+/// Multiply two scaled fractions
+pub fn mul_sf(a: u128, b: u128) -> Result<u128> {
+    const MASK: u128 = (1 << SF_FRACTIONAL_BITS) - 1;
+    let (a_int, a_frac) = (a >> SF_FRACTIONAL_BITS, a & MASK);
+    let (b_int, b_frac) = (b >> SF_FRACTIONAL_BITS, b & MASK);
+
+    let int_int = a_int
+        .checked_mul(b_int)
+        .and_then(|product| product.checked_mul(1 << SF_FRACTIONAL_BITS))
+        .ok_or(LendingError::MathOverflow)?;
+    let cross = a_int
+        .checked_mul(b_frac)
+        .and_then(|product| product.checked_add(a_frac.checked_mul(b_int)?))
+        .ok_or(LendingError::MathOverflow)?;
+    let frac_frac = (a_frac * b_frac) >> SF_FRACTIONAL_BITS;
+
+    int_int
+        .checked_add(cross)
+        .and_then(|sum| sum.checked_add(frac_frac))
+        .ok_or_else(|| error!(LendingError::MathOverflow))
+}
+
+/// Fail if `account` is not the oracle account configured at `expected`
+fn require_configured(account: &AccountInfo, expected: &Pubkey) -> Result<()> {
+    require!(
+        *expected != Pubkey::default() && account.key == expected,
+        LendingError::InvalidOracleAccount
+    );
+    Ok(())
+}
+
+/// The oracle account configured at `expected`, failing if it is `required` but missing
+fn configured_account<'a, 'info>(
+    account: Option<&'a AccountInfo<'info>>,
+    expected: &Pubkey,
+    required: bool,
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    match account {
+        Some(account) => {
+            require_configured(account, expected)?;
+            Ok(Some(account))
+        }
+        None => {
+            require!(!required, LendingError::OracleAccountMissing);
+            Ok(None)
+        }
+    }
+}
+
+fn read_pyth(account: &AccountInfo, twap: bool) -> Result<OraclePrice> {
+    let update: PythPriceUpdate =
+        deserialize_account(account, &discriminator::PYTH_PRICE_UPDATE, 0)?;
+    // Partially verified updates carry fewer guardian signatures than Kamino accepts
+    require!(
+        update.verification_level == PythVerificationLevel::Full,
+        LendingError::UnverifiedOraclePrice
+    );
+    let message = update.price_message;
+    let (price, conf) = if twap {
+        (message.ema_price, message.ema_conf)
+    } else {
+        (message.price, message.conf)
+    };
+    require!(price > 0, LendingError::InvalidOraclePrice);
+
+    Ok(OraclePrice {
+        price_sf: exponent_to_sf(price as u128, message.exponent)?,
+        confidence_sf: exponent_to_sf(conf.into(), message.exponent)?,
+        timestamp: message.publish_time,
+        source: OracleSource::Pyth,
+    })
+}
+
+fn read_switchboard(account: &AccountInfo) -> Result<OraclePrice> {
+    let result: SwitchboardResult = deserialize_account(
+        account,
+        &discriminator::SWITCHBOARD_PULL_FEED,
+        switchboard_layout::RESULT,
+    )?;
+    let timestamp: i64 = deserialize_account(
+        account,
+        &discriminator::SWITCHBOARD_PULL_FEED,
+        switchboard_layout::LAST_UPDATE_TIMESTAMP,
+    )?;
+    require!(result.value > 0, LendingError::InvalidOraclePrice);
+
+    Ok(OraclePrice {
+        price_sf: to_sf(result.value as u128, switchboard_layout::PRECISION)?,
+        confidence_sf: to_sf(result.std_dev.unsigned_abs(), switchboard_layout::PRECISION)?,
+        timestamp,
+        source: OracleSource::Switchboard,
+    })
+}
+
+/// Whether a Scope chain has at least one entry
+fn scope_chain_configured(chain: &[u16; 4]) -> bool {
+    chain[0] != scope_layout::CHAIN_END
+}
+
+/// Multiply the Scope prices along `chain`; the result is as old as its oldest entry
+fn read_scope(account: &AccountInfo, chain: &[u16; 4]) -> Result<OraclePrice> {
+    let mut price_sf = 1 << SF_FRACTIONAL_BITS;
+    let mut timestamp = i64::MAX;
+    for &index in chain.iter().take_while(|&&index| index != scope_layout::CHAIN_END) {
+        let index = usize::from(index);
+        require!(index < scope_layout::MAX_ENTRIES, LendingError::InvalidOracleAccount);
+        let entry: ScopeDatedPrice = deserialize_account(
+            account,
+            &discriminator::SCOPE_ORACLE_PRICES,
+            scope_layout::PRICES + index * scope_layout::DATED_PRICE_SIZE,
+        )?;
+        require!(entry.value > 0, LendingError::InvalidOraclePrice);
+
+        let exp = u32::try_from(entry.exp).map_err(|_| LendingError::InvalidOraclePrice)?;
+        price_sf = mul_sf(price_sf, to_sf(entry.value.into(), exp)?)?;
+        timestamp = timestamp.min(i64::try_from(entry.unix_timestamp).unwrap_or(i64::MAX));
+    }
+
+    Ok(OraclePrice {
+        price_sf,
+        confidence_sf: 0,
+        timestamp,
+        source: OracleSource::Scope,
+    })
+}
+
+/// Fail if `price_sf` falls outside the reserve's heuristic bounds
+pub fn check_heuristic(heuristic: &PriceHeuristic, price_sf: u128) -> Result<()> {
+    let exp = u32::try_from(heuristic.exp).map_err(|_| LendingError::InvalidOraclePrice)?;
+    if heuristic.lower != 0 {
+        require!(
+            price_sf >= to_sf(heuristic.lower.into(), exp)?,
+            LendingError::PriceOutOfRange
+        );
+    }
+    if heuristic.upper != 0 {
+        require!(
+            price_sf <= to_sf(heuristic.upper.into(), exp)?,
+            LendingError::PriceOutOfRange
+        );
+    }
+    Ok(())
+}
+
+/// Fail if `price` is older than `max_age` seconds
+fn check_age(price: &OraclePrice, max_age: u64, now: i64) -> Result<()> {
+    let age = now.saturating_sub(price.timestamp).max(0) as u64;
+    require!(age <= max_age, LendingError::PriceTooOld);
+    Ok(())
+}
+
+/// Most recent of the prices read, if any
+fn most_recent(prices: [Option<OraclePrice>; 3]) -> Option<OraclePrice> {
+    prices.into_iter().flatten().max_by_key(|price| price.timestamp)
+}
+
+/// Read the spot price of a reserve from every oracle it configures, keeping the most
+/// recent one. Each configured oracle must be supplied, so a caller cannot pick the price
+/// by leaving the others out.
+///
+/// The price must be within the reserve's `max_age_price_seconds` and heuristic bounds.
+pub fn read_price(token_info: &TokenInfo, accounts: &OracleAccounts, now: i64) -> Result<OraclePrice> {
+    require!(token_info.block_price_usage == 0, LendingError::PriceUsageBlocked);
+
+    let pyth = pyth_account(token_info, accounts)?
+        .map(|account| read_pyth(account, false))
+        .transpose()?;
+    let switchboard = switchboard_account(
+        accounts.switchboard_price,
+        &token_info.switchboard_configuration.price_aggregator,
+    )?
+    .map(read_switchboard)
+    .transpose()?;
+    let scope = read_scope_chain(
+        &token_info.scope_configuration,
+        &token_info.scope_configuration.price_chain,
+        accounts.scope_prices,
+    )?;
+
+    let price = most_recent([pyth, switchboard, scope]).ok_or(LendingError::OracleNotConfigured)?;
+    check_age(&price, token_info.max_age_price_seconds, now)?;
+    check_heuristic(&token_info.heuristic, price.price_sf)?;
+    Ok(price)
+}
+
+/// Read the TWAP of a reserve from every oracle it configures one for, keeping the most
+/// recent one. Pyth's EMA price serves as its TWAP.
+///
+/// Returns `None` when the reserve configures no TWAP source.
+pub fn read_twap(
+    token_info: &TokenInfo,
+    accounts: &OracleAccounts,
+    now: i64,
+) -> Result<Option<OraclePrice>> {
+    let pyth = pyth_account(token_info, accounts)?
+        .map(|account| read_pyth(account, true))
+        .transpose()?;
+    let switchboard = switchboard_account(
+        accounts.switchboard_twap,
+        &token_info.switchboard_configuration.twap_aggregator,
+    )?
+    .map(read_switchboard)
+    .transpose()?;
+    let scope = read_scope_chain(
+        &token_info.scope_configuration,
+        &token_info.scope_configuration.twap_chain,
+        accounts.scope_prices,
+    )?;
+
+    let Some(twap) = most_recent([pyth, switchboard, scope]) else {
+        return Ok(None);
+    };
+    check_age(&twap, token_info.max_age_twap_seconds, now)?;
+    Ok(Some(twap))
+}
+
+/// The reserve's Pyth price account, required when it configures one
+fn pyth_account<'a, 'info>(
+    token_info: &TokenInfo,
+    accounts: &OracleAccounts<'a, 'info>,
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    let expected = &token_info.pyth_configuration.price;
+    configured_account(accounts.pyth_price, expected, *expected != Pubkey::default())
+}
+
+/// A Switchboard feed account, required when the reserve configures the aggregator
+fn switchboard_account<'a, 'info>(
+    account: Option<&'a AccountInfo<'info>>,
+    aggregator: &Pubkey,
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    configured_account(account, aggregator, *aggregator != Pubkey::default())
+}
+
+/// Read a Scope chain if it is configured; the feed must then be supplied
+fn read_scope_chain(
+    config: &ScopeConfiguration,
+    chain: &[u16; 4],
+    account: Option<&AccountInfo>,
+) -> Result<Option<OraclePrice>> {
+    let configured = config.price_feed != Pubkey::default() && scope_chain_configured(chain);
+    let Some(account) = configured_account(account, &config.price_feed, configured)? else {
+        return Ok(None);
+    };
+    if !configured {
+        return Ok(None);
+    }
+    read_scope(account, chain).map(Some)
+}
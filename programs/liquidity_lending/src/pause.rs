@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::events::PauseUpdated;
+use crate::state::{Config, PauseTarget, Role, RoleAssignment, CONFIG_SEED, ROLE_SEED};
+use crate::LendingError;
+
+pub fn set(ctx: Context<SetPause>, target: PauseTarget, paused: bool) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let config = &mut ctx.accounts.config;
+
+    // The guardian can only pull the brake, releasing it is up to the admin
+    if paused {
+        let is_guardian = ctx
+            .accounts
+            .guardian_role
+            .as_ref()
+            .is_some_and(|assignment| assignment.role == Role::Guardian);
+        require!(
+            authority == config.admin || is_guardian,
+            LendingError::NotAdminOrGuardian
+        );
+    } else {
+        require_keys_eq!(authority, config.admin, LendingError::NotAdmin);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    config.set_paused(target, paused, authority, now);
+
+    emit_cpi!(PauseUpdated {
+        authority,
+        target,
+        paused,
+        flags: config.paused,
+        timestamp: now,
+    });
+
+    msg!("{:?} pause set to {} by {}", target, paused, authority);
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    /// The admin or guardian changing the pause
+    pub authority: Signer<'info>,
+
+    /// The program configuration
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// The authority's guardian role assignment, when it is not the admin
+    #[account(
+        seeds = [ROLE_SEED, &Role::Guardian.seed(), authority.key().as_ref()],
+        bump = guardian_role.bump,
+    )]
+    pub guardian_role: Option<Account<'info, RoleAssignment>>,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::kamino::{ObligationHeader, KAMINO_LENDING_ID};
+use crate::state::{UserPosition, USER_POSITION_SEED};
+use crate::LendingError;
+
+pub fn init(ctx: Context<InitUserPosition>) -> Result<()> {
+    let header = ObligationHeader::load(
+        &ctx.accounts.obligation,
+        ctx.accounts.kamino_lending_program.key,
+    )?;
+    require_keys_eq!(header.owner, ctx.accounts.owner.key(), LendingError::InvalidUserPosition);
+    require_keys_eq!(
+        header.lending_market,
+        ctx.accounts.lending_market.key(),
+        LendingError::InvalidUserPosition
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let position = &mut ctx.accounts.user_position;
+    position.owner = ctx.accounts.owner.key();
+    position.lending_market = ctx.accounts.lending_market.key();
+    position.obligation = ctx.accounts.obligation.key();
+    position.created_at = now;
+    position.updated_at = now;
+    position.bump = ctx.bumps.user_position;
+
+    msg!(
+        "Initialized position for {} in market {}",
+        position.owner,
+        position.lending_market
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitUserPosition<'info> {
+    /// The account paying for the position account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The user the position tracks
+    pub owner: Signer<'info>,
+
+    /// The lending market account
+    /// CHECK: Checked against the obligation's lending market
+    pub lending_market: AccountInfo<'info>,
+
+    /// The user's obligation in the lending market
+    /// CHECK: Decoded and checked against the owner and lending market
+    pub obligation: AccountInfo<'info>,
+
+    /// The position account to create
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [USER_POSITION_SEED, owner.key().as_ref(), lending_market.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The Kamino lending program
+    /// CHECK: Kamino's program ID
+    #[account(address = KAMINO_LENDING_ID)]
+    pub kamino_lending_program: AccountInfo<'info>,
+}
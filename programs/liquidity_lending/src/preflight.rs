@@ -0,0 +1,99 @@
+//! Checks mirroring Kamino's own, run before a CPI so the common failures surface as
+//! [`LendingError`]s instead of raw Kamino error codes.
+
+use anchor_lang::prelude::*;
+
+use crate::kamino::{
+    deserialize_account, discriminator, reserve_layout, ObligationHeader, ObligationMetrics,
+    ReserveHeader, ReserveMarketPrice, ReserveWithdrawalCaps,
+};
+use crate::LendingError;
+
+/// Fail with `ReserveStale` unless the reserve was refreshed in `slot`
+pub fn require_reserve_fresh(reserve: &AccountInfo, kamino_program: &Pubkey, slot: u64) -> Result<()> {
+    let header = ReserveHeader::load(reserve, kamino_program)?;
+    require!(!header.last_update.is_stale(slot), LendingError::ReserveStale);
+    Ok(())
+}
+
+/// Fail with `ObligationStale` unless the obligation was refreshed in `slot`
+pub fn require_obligation_fresh(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    slot: u64,
+) -> Result<()> {
+    let header = ObligationHeader::load(obligation, kamino_program)?;
+    require!(!header.last_update.is_stale(slot), LendingError::ObligationStale);
+    Ok(())
+}
+
+/// Borrow-factor-adjusted value of borrowing `amount`, as a scaled fraction
+pub fn borrow_value_sf(
+    amount: u64,
+    decimals: u8,
+    market_price_sf: u128,
+    borrow_factor_pct: u64,
+) -> Option<u128> {
+    let value_sf = market_price_sf.checked_mul(amount.into())? / 10u128.checked_pow(decimals.into())?;
+    // Kamino treats a zero borrow factor as 100%
+    let borrow_factor_pct = borrow_factor_pct.max(100);
+    value_sf.checked_mul(borrow_factor_pct.into()).map(|value| value / 100)
+}
+
+/// Fail with `BorrowTooLarge` if borrowing `amount` would push the obligation past its
+/// allowed borrow value. Borrow fees are left out, so Kamino may still refuse a borrow
+/// this accepts, but never the other way round.
+///
+/// Obligations in an elevation group borrow at face value, whatever the reserve's
+/// borrow factor.
+pub fn require_borrow_within_limit(
+    metrics: &ObligationMetrics,
+    amount: u64,
+    decimals: u8,
+    market_price_sf: u128,
+    borrow_factor_pct: u64,
+) -> Result<()> {
+    let remaining_sf = metrics
+        .allowed_borrow_value_sf
+        .saturating_sub(metrics.borrow_factor_adjusted_debt_value_sf);
+    let borrow_factor_pct = if metrics.elevation_group != 0 { 100 } else { borrow_factor_pct };
+    let value_sf = borrow_value_sf(amount, decimals, market_price_sf, borrow_factor_pct)
+        .ok_or(LendingError::BorrowTooLarge)?;
+    require!(value_sf <= remaining_sf, LendingError::BorrowTooLarge);
+    Ok(())
+}
+
+/// Run Kamino's borrow checks that can be made before the CPI: both accounts refreshed
+/// this slot, room under the obligation's borrow limit and under the reserve's debt cap
+pub fn check_borrow(
+    obligation: &AccountInfo,
+    reserve: &AccountInfo,
+    kamino_program: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    clock: &Clock,
+) -> Result<()> {
+    require_reserve_fresh(reserve, kamino_program, clock.slot)?;
+    require_obligation_fresh(obligation, kamino_program, clock.slot)?;
+
+    let price = ReserveMarketPrice::load(reserve, kamino_program)?;
+    let borrow_factor_pct: u64 = deserialize_account(
+        reserve,
+        &discriminator::RESERVE,
+        reserve_layout::CONFIG_BORROW_FACTOR_PCT,
+    )?;
+    require_borrow_within_limit(
+        &ObligationMetrics::load(obligation, kamino_program)?,
+        amount,
+        decimals,
+        price.market_price_sf,
+        borrow_factor_pct,
+    )?;
+
+    let caps = ReserveWithdrawalCaps::load(reserve, kamino_program)?;
+    require!(
+        caps.debt.allows(amount, clock.unix_timestamp),
+        LendingError::WithdrawalCapReached
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::kamino::{ReserveMarketPrice, TokenInfo};
+use crate::oracle::{self, OracleAccounts};
+use crate::state::{PriceGuard, BPS_SCALE};
+use crate::LendingError;
+
+/// Gap between `price` and `reference`, in basis points of `reference`
+pub fn deviation_bps(price: u128, reference: u128) -> Result<u128> {
+    require!(reference > 0, LendingError::InvalidOraclePrice);
+    let gap = price
+        .abs_diff(reference)
+        .checked_mul(BPS_SCALE.into())
+        .ok_or(LendingError::MathOverflow)?;
+    Ok(gap / reference)
+}
+
+/// Whether the reserve configures any source of a TWAP; Pyth's EMA price counts as one
+fn has_twap(token_info: &TokenInfo) -> bool {
+    token_info.pyth_configuration.price != Pubkey::default()
+        || token_info.switchboard_configuration.twap_aggregator != Pubkey::default()
+        || token_info.scope_configuration.twap_chain[0] != oracle::scope_layout::CHAIN_END
+}
+
+/// Check a reserve's cached price against a fresh oracle read and its TWAP.
+///
+/// Fails if the cached or oracle price is older than the guard allows, if they drift
+/// apart by more than `max_price_deviation_bps`, or if the oracle price strays from its
+/// TWAP by more than `max_twap_deviation_bps`. A TWAP is required whenever the reserve
+/// configures one.
+pub fn check(
+    guard: &PriceGuard,
+    reserve: &AccountInfo,
+    kamino_program: &Pubkey,
+    oracles: &OracleAccounts,
+    now: i64,
+) -> Result<()> {
+    let cached = ReserveMarketPrice::load(reserve, kamino_program)?;
+    let token_info = TokenInfo::load(reserve, kamino_program)?;
+    check_prices(guard, &cached, &token_info, oracles, now)
+}
+
+/// [`check`] with the reserve already decoded
+pub fn check_prices(
+    guard: &PriceGuard,
+    cached: &ReserveMarketPrice,
+    token_info: &TokenInfo,
+    oracles: &OracleAccounts,
+    now: i64,
+) -> Result<()> {
+    let max_age = i64::try_from(guard.max_price_age_seconds).unwrap_or(i64::MAX);
+    let cached_ts = i64::try_from(cached.market_price_last_updated_ts).unwrap_or(i64::MAX);
+    require!(now.saturating_sub(cached_ts) <= max_age, LendingError::PriceStale);
+
+    let price = oracle::read_price(token_info, oracles, now)?;
+    require!(now.saturating_sub(price.timestamp) <= max_age, LendingError::PriceStale);
+    require!(
+        deviation_bps(cached.market_price_sf, price.price_sf)?
+            <= u128::from(guard.max_price_deviation_bps),
+        LendingError::PriceDeviationTooHigh
+    );
+
+    match oracle::read_twap(token_info, oracles, now)? {
+        Some(twap) => require!(
+            deviation_bps(price.price_sf, twap.price_sf)?
+                <= u128::from(guard.max_twap_deviation_bps),
+            LendingError::TwapDeviationTooHigh
+        ),
+        None => require!(!has_twap(token_info), LendingError::TwapNotAvailable),
+    }
+    Ok(())
+}
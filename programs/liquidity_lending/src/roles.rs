@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{AdminProposed, AdminTransferred, RoleGranted, RoleRevoked};
+use crate::state::{Config, Role, RoleAssignment, CONFIG_SEED, ROLE_SEED};
+use crate::LendingError;
+
+pub fn grant(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let assignment = &mut ctx.accounts.role_assignment;
+    assignment.role = role;
+    assignment.holder = holder;
+    assignment.granted_by = ctx.accounts.admin.key();
+    assignment.granted_at = now;
+    assignment.bump = ctx.bumps.role_assignment;
+
+    emit_cpi!(RoleGranted {
+        role,
+        holder,
+        granted_by: ctx.accounts.admin.key(),
+        timestamp: now,
+    });
+
+    msg!("Granted {:?} to {}", role, holder);
+    Ok(())
+}
+
+pub fn revoke(ctx: Context<RevokeRole>) -> Result<()> {
+    let assignment = &ctx.accounts.role_assignment;
+
+    emit_cpi!(RoleRevoked {
+        role: assignment.role,
+        holder: assignment.holder,
+        revoked_by: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Revoked {:?} from {}", assignment.role, assignment.holder);
+    Ok(())
+}
+
+pub fn propose(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.pending_admin = new_admin;
+
+    emit_cpi!(AdminProposed {
+        admin: ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Proposed {} as admin", new_admin);
+    Ok(())
+}
+
+pub fn accept(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = ctx.accounts.pending_admin.key();
+    config.pending_admin = Pubkey::default();
+
+    emit_cpi!(AdminTransferred {
+        previous_admin,
+        admin: config.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Admin transferred from {} to {}", previous_admin, config.admin);
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(role: Role, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    /// The admin granting the role
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin @ LendingError::NotAdmin)]
+    pub config: Account<'info, Config>,
+
+    /// The role assignment to create
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RoleAssignment::INIT_SPACE,
+        seeds = [ROLE_SEED, &role.seed(), holder.as_ref()],
+        bump,
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    /// The admin revoking the role, refunded the account rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin @ LendingError::NotAdmin)]
+    pub config: Account<'info, Config>,
+
+    /// The role assignment to close
+    #[account(
+        mut,
+        close = admin,
+        seeds = [ROLE_SEED, &role_assignment.role.seed(), role_assignment.holder.as_ref()],
+        bump = role_assignment.bump,
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    /// The current admin
+    pub admin: Signer<'info>,
+
+    /// The program configuration
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump, has_one = admin @ LendingError::NotAdmin)]
+    pub config: Account<'info, Config>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The proposed admin accepting the transfer
+    pub pending_admin: Signer<'info>,
+
+    /// The program configuration
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = pending_admin @ LendingError::NotPendingAdmin,
+    )]
+    pub config: Account<'info, Config>,
+}
@@ -0,0 +1,44 @@
+//! `u64::MAX` as a repayment or withdrawal amount, meaning the whole debt or all the
+//! collateral in a reserve. It is resolved from the obligation before the CPI, so Kamino is
+//! asked for an exact amount and our ledgers and events record what actually moved. The
+//! obligation must have been refreshed in this slot for the debt to include its interest.
+
+use anchor_lang::prelude::*;
+
+use crate::kamino::{obligation_borrowed_amount_sf, obligation_deposited_amount};
+use crate::LendingError;
+
+/// Amount repaying all the debt, or withdrawing all the collateral, of a reserve
+pub const ALL: u64 = u64::MAX;
+
+/// Liquidity to repay for `amount`: `amount` itself, or for [`ALL`] the obligation's debt to
+/// `reserve` rounded up to whole base units, so no dust is left on the borrow
+pub fn repay_amount(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    reserve: &Pubkey,
+    amount: u64,
+) -> Result<u64> {
+    if amount != ALL {
+        return Ok(amount);
+    }
+    let borrowed_sf = obligation_borrowed_amount_sf(obligation, kamino_program, reserve)?;
+    require!(borrowed_sf > 0, LendingError::NoDebt);
+    u64::try_from(borrowed_sf.div_ceil(1 << 60)).map_err(|_| error!(LendingError::MathOverflow))
+}
+
+/// Collateral to withdraw for `amount`: `amount` itself, or for [`ALL`] everything the
+/// obligation holds in `reserve`
+pub fn withdraw_amount(
+    obligation: &AccountInfo,
+    kamino_program: &Pubkey,
+    reserve: &Pubkey,
+    amount: u64,
+) -> Result<u64> {
+    if amount != ALL {
+        return Ok(amount);
+    }
+    let deposited = obligation_deposited_amount(obligation, kamino_program, reserve)?;
+    require!(deposited > 0, LendingError::NoCollateral);
+    Ok(deposited)
+}
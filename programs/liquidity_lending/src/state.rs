@@ -0,0 +1,551 @@
+use anchor_lang::prelude::*;
+
+use crate::LendingError;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const ROLE_SEED: &[u8] = b"role";
+pub const CONFIG_CHANGE_SEED: &[u8] = b"config_change";
+pub const REGISTRY_SEED: &[u8] = b"registry";
+pub const RESERVE_USAGE_SEED: &[u8] = b"reserve_usage";
+pub const USER_POSITION_SEED: &[u8] = b"user_position";
+pub const USER_BORROWS_SEED: &[u8] = b"user_borrows";
+
+/// Lending markets the registry can approve
+pub const MAX_REGISTRY_MARKETS: usize = 8;
+
+/// Reserves the registry can approve, across all markets
+pub const MAX_REGISTRY_RESERVES: usize = 32;
+
+/// Distinct reserves a position can track: Kamino's 8 deposits plus 5 borrows
+pub const MAX_POSITION_RESERVES: usize = 13;
+
+/// Obligations a user's borrows are tracked across: one per approved lending market
+pub const MAX_USER_OBLIGATIONS: usize = MAX_REGISTRY_MARKETS;
+
+/// Operations that can be paused independently
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseTarget {
+    /// Every operation that routes funds
+    Global,
+    Deposits,
+    Borrows,
+    Withdrawals,
+    Liquidations,
+}
+
+impl PauseTarget {
+    /// Bit of this target in [`Config::paused`]
+    pub fn flag(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Basis points in a whole
+pub const BPS_SCALE: u64 = 10_000;
+
+/// Maximum fee rate, in basis points
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+/// Timelock delay of a freshly initialized program, in seconds
+pub const DEFAULT_TIMELOCK_DELAY: i64 = 2 * 24 * 60 * 60;
+
+/// Longest timelock delay, so a bad change cannot lock the configuration for good
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// Price guard of a freshly initialized program
+pub const DEFAULT_PRICE_GUARD: PriceGuard = PriceGuard {
+    max_price_age_seconds: 120,
+    max_price_deviation_bps: 200,
+    max_twap_deviation_bps: 500,
+};
+
+/// Our own limits on the prices a borrow may rely on, on top of Kamino's
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct PriceGuard {
+    /// Oldest the reserve's cached price and the oracle price may be
+    pub max_price_age_seconds: u64,
+    /// Largest gap between the reserve's cached price and the oracle price
+    pub max_price_deviation_bps: u64,
+    /// Largest gap between the oracle price and its TWAP
+    pub max_twap_deviation_bps: u64,
+}
+
+/// Program-wide configuration and circuit breakers
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Config {
+    /// Can change configuration, grant roles and unpause
+    pub admin: Pubkey,
+    /// Proposed admin, who must accept before the transfer takes effect
+    pub pending_admin: Pubkey,
+    /// Share of harvested farm rewards taken as a fee, in basis points
+    pub fee_bps: u16,
+    /// Owner of the token accounts the fee is paid into
+    pub fee_recipient: Pubkey,
+    /// Seconds a queued [`ConfigChange`] waits before it can be executed
+    pub timelock_delay: i64,
+    /// Changes queued so far, used as the id of the next one
+    pub config_change_count: u64,
+    /// Largest borrowed market value a user may reach through the program, summed over
+    /// their obligations in a [`UserBorrows`], in whole units of the quote currency
+    pub max_user_borrow_value: u64,
+    pub price_guard: PriceGuard,
+    /// Bitmask of paused [`PauseTarget`]s
+    pub paused: u8,
+    pub pause_updated_at: i64,
+    pub pause_updated_by: Pubkey,
+    pub pause_update_count: u64,
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn is_paused(&self, target: PauseTarget) -> bool {
+        self.paused & target.flag() != 0
+    }
+
+    /// Fail if `target` or the whole program is paused.
+    ///
+    /// Passing [`PauseTarget::Global`] only checks the global pause, which is how
+    /// repayments stay open while borrowing is paused.
+    pub fn require_not_paused(&self, target: PauseTarget) -> Result<()> {
+        require!(!self.is_paused(PauseTarget::Global), LendingError::ProgramPaused);
+        let error = match target {
+            PauseTarget::Global => return Ok(()),
+            PauseTarget::Deposits => LendingError::DepositsPaused,
+            PauseTarget::Borrows => LendingError::BorrowsPaused,
+            PauseTarget::Withdrawals => LendingError::WithdrawalsPaused,
+            PauseTarget::Liquidations => LendingError::LiquidationsPaused,
+        };
+        if self.is_paused(target) {
+            return Err(error.into());
+        }
+        Ok(())
+    }
+
+    /// Set or clear the pause flag of `target`, recording who changed it and when
+    pub fn set_paused(&mut self, target: PauseTarget, paused: bool, authority: Pubkey, now: i64) {
+        if paused {
+            self.paused |= target.flag();
+        } else {
+            self.paused &= !target.flag();
+        }
+        self.pause_updated_at = now;
+        self.pause_updated_by = authority;
+        self.pause_update_count = self.pause_update_count.saturating_add(1);
+    }
+
+    /// The fee taken from `amount` of harvested rewards, rounded down
+    pub fn fee(&self, amount: u64) -> u64 {
+        // At most `amount`, as the rate never exceeds `MAX_FEE_BPS`
+        (u128::from(amount) * u128::from(self.fee_bps) / u128::from(BPS_SCALE)) as u64
+    }
+}
+
+/// A configuration update that only takes effect after the timelock delay
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum ConfigChange {
+    FeeConfig { fee_bps: u16, fee_recipient: Pubkey },
+    TimelockDelay { delay: i64 },
+    MaxUserBorrowValue { value: u64 },
+    PriceGuard { guard: PriceGuard },
+    /// Approve or drop a lending market
+    SetMarket { lending_market: Pubkey, allowed: bool },
+    /// Approve a reserve, or replace its metadata
+    SetReserve { entry: ReserveEntry },
+    RemoveReserve { reserve: Pubkey },
+}
+
+impl ConfigChange {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ConfigChange::FeeConfig { fee_bps, .. } => {
+                require!(*fee_bps <= MAX_FEE_BPS, LendingError::InvalidFeeConfig);
+            }
+            ConfigChange::TimelockDelay { delay } => {
+                require!(
+                    (0..=MAX_TIMELOCK_DELAY).contains(delay),
+                    LendingError::InvalidTimelockDelay
+                );
+            }
+            ConfigChange::SetMarket { lending_market, .. } => {
+                require!(
+                    *lending_market != Pubkey::default(),
+                    LendingError::InvalidRegistryEntry
+                );
+            }
+            ConfigChange::SetReserve { entry } => {
+                require!(
+                    entry.reserve != Pubkey::default() && entry.lending_market != Pubkey::default(),
+                    LendingError::InvalidRegistryEntry
+                );
+            }
+            ConfigChange::PriceGuard { guard } => {
+                require!(
+                    guard.max_price_deviation_bps <= BPS_SCALE
+                        && guard.max_twap_deviation_bps <= BPS_SCALE,
+                    LendingError::InvalidPriceGuard
+                );
+            }
+            ConfigChange::MaxUserBorrowValue { .. } | ConfigChange::RemoveReserve { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Write the change into `config` or `registry`
+    pub fn apply(&self, config: &mut Config, registry: &mut Registry) -> Result<()> {
+        match *self {
+            ConfigChange::FeeConfig { fee_bps, fee_recipient } => {
+                config.fee_bps = fee_bps;
+                config.fee_recipient = fee_recipient;
+            }
+            ConfigChange::TimelockDelay { delay } => config.timelock_delay = delay,
+            ConfigChange::MaxUserBorrowValue { value } => config.max_user_borrow_value = value,
+            ConfigChange::PriceGuard { guard } => config.price_guard = guard,
+            ConfigChange::SetMarket { lending_market, allowed } => {
+                registry.set_market(lending_market, allowed)?
+            }
+            ConfigChange::SetReserve { entry } => registry.set_reserve(entry)?,
+            ConfigChange::RemoveReserve { reserve } => registry.remove_reserve(&reserve),
+        }
+        Ok(())
+    }
+}
+
+/// A queued [`ConfigChange`], public so users can exit before it lands
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct PendingConfigChange {
+    pub id: u64,
+    pub change: ConfigChange,
+    /// Refunded the account rent once the change is executed or cancelled
+    pub proposer: Pubkey,
+    pub queued_at: i64,
+    /// Earliest time the change can be executed
+    pub eta: i64,
+    pub bump: u8,
+}
+
+impl PendingConfigChange {
+    pub fn is_ready(&self, now: i64) -> bool {
+        now >= self.eta
+    }
+}
+
+/// An approved Kamino reserve and the limits we apply to it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ReserveEntry {
+    pub reserve: Pubkey,
+    pub lending_market: Pubkey,
+    /// Ticker of the liquidity token, UTF-8 and zero padded
+    pub symbol: [u8; 16],
+    /// Decimals of the liquidity mint
+    pub decimals: u8,
+    /// Most liquidity the program may route into the reserve, in token units
+    pub max_exposure: u64,
+    pub borrow_allowed: bool,
+    /// Most liquidity that may be deposited through the program per epoch
+    pub epoch_deposit_limit: u64,
+    /// Most liquidity that may be borrowed through the program per epoch
+    pub epoch_borrow_limit: u64,
+}
+
+/// Kamino lending markets and reserves the wrappers may route funds to
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Registry {
+    /// Approved markets; unused slots are the default key
+    pub markets: [Pubkey; MAX_REGISTRY_MARKETS],
+    /// Approved reserves; unused slots have a default `reserve`
+    pub reserves: [ReserveEntry; MAX_REGISTRY_RESERVES],
+    pub bump: u8,
+}
+
+impl Registry {
+    pub fn is_market_allowed(&self, lending_market: &Pubkey) -> bool {
+        *lending_market != Pubkey::default() && self.markets.contains(lending_market)
+    }
+
+    pub fn reserve(&self, reserve: &Pubkey) -> Option<&ReserveEntry> {
+        if *reserve == Pubkey::default() {
+            return None;
+        }
+        self.reserves.iter().find(|entry| entry.reserve == *reserve)
+    }
+
+    /// Entry of `reserve`, failing unless both it and `lending_market` are approved
+    pub fn require_reserve(&self, lending_market: &Pubkey, reserve: &Pubkey) -> Result<&ReserveEntry> {
+        require!(self.is_market_allowed(lending_market), LendingError::MarketNotAllowed);
+        let entry = self.reserve(reserve).ok_or(LendingError::ReserveNotAllowed)?;
+        require_keys_eq!(entry.lending_market, *lending_market, LendingError::ReserveNotAllowed);
+        Ok(entry)
+    }
+
+    pub fn set_market(&mut self, lending_market: Pubkey, allowed: bool) -> Result<()> {
+        let slot = self.markets.iter().position(|market| *market == lending_market);
+        match (slot, allowed) {
+            (Some(_), true) => {}
+            (Some(index), false) => self.markets[index] = Pubkey::default(),
+            (None, true) => {
+                let index = self
+                    .markets
+                    .iter()
+                    .position(|market| *market == Pubkey::default())
+                    .ok_or(LendingError::RegistryFull)?;
+                self.markets[index] = lending_market;
+            }
+            (None, false) => {}
+        }
+        Ok(())
+    }
+
+    /// Approve `entry.reserve`, replacing its metadata if it is already approved
+    pub fn set_reserve(&mut self, entry: ReserveEntry) -> Result<()> {
+        let index = self
+            .reserves
+            .iter()
+            .position(|existing| existing.reserve == entry.reserve)
+            .or_else(|| {
+                self.reserves
+                    .iter()
+                    .position(|existing| existing.reserve == Pubkey::default())
+            })
+            .ok_or(LendingError::RegistryFull)?;
+        self.reserves[index] = entry;
+        Ok(())
+    }
+
+    pub fn remove_reserve(&mut self, reserve: &Pubkey) {
+        if let Some(entry) = self.reserves.iter_mut().find(|entry| entry.reserve == *reserve) {
+            *entry = ReserveEntry::default();
+        }
+    }
+}
+
+/// What the program routed into one reserve, checked against its [`ReserveEntry`] limits
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct ReserveUsage {
+    pub reserve: Pubkey,
+    /// Liquidity deposited through the program, net of withdrawals.
+    ///
+    /// Collateral seized in liquidations is not subtracted, so this can overstate the
+    /// exposure but never understate it.
+    pub exposure: u64,
+    /// Epoch the volumes below were accumulated in
+    pub epoch: u64,
+    pub epoch_deposited: u64,
+    pub epoch_borrowed: u64,
+    pub bump: u8,
+}
+
+impl ReserveUsage {
+    /// Start counting volumes afresh when `epoch` moves on
+    fn roll_epoch(&mut self, epoch: u64) {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.epoch_deposited = 0;
+            self.epoch_borrowed = 0;
+        }
+    }
+
+    /// Add a deposit, failing if it breaks the reserve's exposure cap or epoch volume
+    pub fn record_deposit(&mut self, entry: &ReserveEntry, amount: u64, epoch: u64) -> Result<()> {
+        self.roll_epoch(epoch);
+        let exposure = self.exposure.checked_add(amount).ok_or(LendingError::MathOverflow)?;
+        require!(exposure <= entry.max_exposure, LendingError::DepositCapExceeded);
+        let volume = self
+            .epoch_deposited
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        require!(volume <= entry.epoch_deposit_limit, LendingError::EpochVolumeExceeded);
+
+        self.exposure = exposure;
+        self.epoch_deposited = volume;
+        Ok(())
+    }
+
+    /// Add a borrow, failing if it breaks the reserve's epoch volume
+    pub fn record_borrow(&mut self, entry: &ReserveEntry, amount: u64, epoch: u64) -> Result<()> {
+        self.roll_epoch(epoch);
+        let volume = self
+            .epoch_borrowed
+            .checked_add(amount)
+            .ok_or(LendingError::MathOverflow)?;
+        require!(volume <= entry.epoch_borrow_limit, LendingError::EpochVolumeExceeded);
+
+        self.epoch_borrowed = volume;
+        Ok(())
+    }
+
+    /// Release exposure freed by a withdrawal, which may include earned interest
+    pub fn record_withdrawal(&mut self, amount: u64) {
+        self.exposure = self.exposure.saturating_sub(amount);
+    }
+}
+
+/// Roles that can be granted to any number of keys, besides the single admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    /// Can pause, but not unpause or change anything else
+    Guardian,
+    /// Runs rebalancing and harvesting cranks
+    Keeper,
+    /// Can queue fee configuration changes
+    FeeManager,
+}
+
+impl Role {
+    /// Seeds of the [`RoleAssignment`] granting this role to `holder`
+    pub fn seed(self) -> [u8; 1] {
+        [self as u8]
+    }
+}
+
+/// Proof that `holder` was granted `role`; revoking closes the account
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub granted_by: Pubkey,
+    pub granted_at: i64,
+    pub bump: u8,
+}
+
+/// Lending action recorded against a reserve in a [`UserPosition`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionAction {
+    Deposit,
+    Withdraw,
+    Borrow,
+    Repay,
+    /// Debt repaid on the user's behalf by a liquidator
+    LiquidatedDebt,
+    /// Collateral seized from the user by a liquidator
+    SeizedCollateral,
+}
+
+/// Cumulative amounts for one reserve, in the reserve's liquidity token units.
+///
+/// Amounts are what left or reached the user's wallet, so transfer fees are part of
+/// the cost basis.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ReservePosition {
+    pub reserve: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub borrowed: u64,
+    pub repaid: u64,
+    pub liquidated_debt: u64,
+    pub seized_collateral: u64,
+    pub last_action_ts: i64,
+}
+
+/// Ledger of what this program did for a user in one lending market
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct UserPosition {
+    pub owner: Pubkey,
+    pub lending_market: Pubkey,
+    pub obligation: Pubkey,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub reserves: [ReservePosition; MAX_POSITION_RESERVES],
+}
+
+impl UserPosition {
+    /// Tracked entry for `reserve`, if any
+    pub fn reserve(&self, reserve: &Pubkey) -> Option<&ReservePosition> {
+        self.reserves.iter().find(|entry| entry.reserve == *reserve)
+    }
+
+    /// Add `amount` of `action` on `reserve` to the ledger
+    pub fn record(
+        &mut self,
+        reserve: Pubkey,
+        action: PositionAction,
+        amount: u64,
+        now: i64,
+    ) -> Result<()> {
+        let index = match self.reserves.iter().position(|entry| entry.reserve == reserve) {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .reserves
+                    .iter()
+                    .position(|entry| entry.reserve == Pubkey::default())
+                    .ok_or(LendingError::PositionReservesFull)?;
+                self.reserves[index].reserve = reserve;
+                index
+            }
+        };
+
+        let entry = &mut self.reserves[index];
+        let total = match action {
+            PositionAction::Deposit => &mut entry.deposited,
+            PositionAction::Withdraw => &mut entry.withdrawn,
+            PositionAction::Borrow => &mut entry.borrowed,
+            PositionAction::Repay => &mut entry.repaid,
+            PositionAction::LiquidatedDebt => &mut entry.liquidated_debt,
+            PositionAction::SeizedCollateral => &mut entry.seized_collateral,
+        };
+        *total = total.checked_add(amount).ok_or(LendingError::MathOverflow)?;
+        entry.last_action_ts = now;
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// Borrowed market value of one obligation, as of the last time the program saw it fresh
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ObligationBorrow {
+    pub obligation: Pubkey,
+    /// Kamino's `borrowed_assets_market_value_sf`, a scaled fraction of the quote currency
+    pub borrowed_value_sf: u128,
+    pub updated_at: i64,
+}
+
+/// What a user has borrowed across all their obligations, checked against
+/// [`Config::max_user_borrow_value`].
+///
+/// Each entry is updated whenever a wrapper checks the obligation's health. Interest accrued
+/// since then is not counted, and neither are repayments until the obligation is synced.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct UserBorrows {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub obligations: [ObligationBorrow; MAX_USER_OBLIGATIONS],
+}
+
+impl UserBorrows {
+    /// Set the borrowed value of `obligation`, freeing its entry once nothing is borrowed
+    pub fn record(&mut self, obligation: Pubkey, borrowed_value_sf: u128, now: i64) -> Result<()> {
+        let index = match self.obligations.iter().position(|entry| entry.obligation == obligation) {
+            Some(index) => index,
+            None if borrowed_value_sf == 0 => return Ok(()),
+            None => self
+                .obligations
+                .iter()
+                .position(|entry| entry.obligation == Pubkey::default())
+                .ok_or(LendingError::UserBorrowsFull)?,
+        };
+
+        self.obligations[index] = if borrowed_value_sf == 0 {
+            ObligationBorrow::default()
+        } else {
+            ObligationBorrow { obligation, borrowed_value_sf, updated_at: now }
+        };
+        Ok(())
+    }
+
+    /// Borrowed value summed over every tracked obligation, as a scaled fraction
+    pub fn total_sf(&self) -> Result<u128> {
+        self.obligations.iter().try_fold(0u128, |total, entry| {
+            total
+                .checked_add(entry.borrowed_value_sf)
+                .ok_or_else(|| error!(LendingError::MathOverflow))
+        })
+    }
+}
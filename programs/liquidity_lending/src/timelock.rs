@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+
+use crate::events::{ConfigChangeCancelled, ConfigChangeExecuted, ConfigChangeQueued};
+use crate::state::{
+    Config, ConfigChange, PendingConfigChange, Registry, Role, RoleAssignment,
+    CONFIG_CHANGE_SEED, CONFIG_SEED, REGISTRY_SEED, ROLE_SEED,
+};
+use crate::LendingError;
+
+pub fn queue(ctx: Context<QueueConfigChange>, change: ConfigChange) -> Result<()> {
+    let proposer = ctx.accounts.proposer.key();
+    let config = &mut ctx.accounts.config;
+
+    // Fee managers may only propose fee changes, everything else is up to the admin
+    if proposer != config.admin {
+        let is_fee_manager = ctx
+            .accounts
+            .fee_manager_role
+            .as_ref()
+            .is_some_and(|assignment| assignment.role == Role::FeeManager);
+        match change {
+            ConfigChange::FeeConfig { .. } => {
+                require!(is_fee_manager, LendingError::NotFeeManager)
+            }
+            _ => return Err(LendingError::NotAdmin.into()),
+        }
+    }
+    change.validate()?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let eta = now
+        .checked_add(config.timelock_delay)
+        .ok_or(LendingError::MathOverflow)?;
+    let id = config.config_change_count;
+    config.config_change_count = id.checked_add(1).ok_or(LendingError::MathOverflow)?;
+
+    let pending = &mut ctx.accounts.pending_change;
+    pending.id = id;
+    pending.change = change.clone();
+    pending.proposer = proposer;
+    pending.queued_at = now;
+    pending.eta = eta;
+    pending.bump = ctx.bumps.pending_change;
+
+    emit_cpi!(ConfigChangeQueued {
+        id,
+        change,
+        proposer,
+        eta,
+    });
+
+    msg!("Queued config change {} executable at {}", id, eta);
+    Ok(())
+}
+
+pub fn execute(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pending = &ctx.accounts.pending_change;
+    require!(pending.is_ready(now), LendingError::TimelockNotElapsed);
+
+    pending
+        .change
+        .apply(&mut ctx.accounts.config, &mut ctx.accounts.registry)?;
+
+    emit_cpi!(ConfigChangeExecuted {
+        id: pending.id,
+        change: pending.change.clone(),
+        executed_by: ctx.accounts.executor.key(),
+        timestamp: now,
+    });
+
+    msg!("Executed config change {}", pending.id);
+    Ok(())
+}
+
+pub fn cancel(ctx: Context<CancelConfigChange>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let pending = &ctx.accounts.pending_change;
+    require!(
+        authority == ctx.accounts.config.admin || authority == pending.proposer,
+        LendingError::NotAdminOrProposer
+    );
+
+    emit_cpi!(ConfigChangeCancelled {
+        id: pending.id,
+        change: pending.change.clone(),
+        cancelled_by: authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Cancelled config change {}", pending.id);
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    /// The admin, or a fee manager for fee changes
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// The proposer's fee manager role assignment, when it is not the admin
+    #[account(
+        seeds = [ROLE_SEED, &Role::FeeManager.seed(), proposer.key().as_ref()],
+        bump = fee_manager_role.bump,
+    )]
+    pub fee_manager_role: Option<Account<'info, RoleAssignment>>,
+
+    /// The program configuration
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// The queued change to create, keyed by the config's change count
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [CONFIG_CHANGE_SEED, &config.config_change_count.to_le_bytes()],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    /// Anyone can execute a change once its timelock elapsed
+    pub executor: Signer<'info>,
+
+    /// The proposer, refunded the pending change's rent
+    /// CHECK: Matched against the pending change
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    /// The program configuration
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// The registry of approved markets and reserves
+    #[account(mut, seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+
+    /// The change to apply
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer,
+        seeds = [CONFIG_CHANGE_SEED, &pending_change.id.to_le_bytes()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    /// The admin or the change's proposer
+    pub authority: Signer<'info>,
+
+    /// The proposer, refunded the pending change's rent
+    /// CHECK: Matched against the pending change
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    /// The program configuration
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// The change to drop
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer,
+        seeds = [CONFIG_CHANGE_SEED, &pending_change.id.to_le_bytes()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+}
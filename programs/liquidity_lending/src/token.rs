@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+};
+
+use crate::LendingError;
+
+/// Token-2022 mint extensions Kamino accepts for reserve liquidity.
+///
+/// Anything else, notably transfer hooks, confidential transfers and non-transferable
+/// or interest-bearing mints, is refused before we route funds.
+pub const SUPPORTED_MINT_EXTENSIONS: &[ExtensionType] = &[
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+];
+
+/// Refuse Token-2022 mints carrying extensions Kamino does not support
+///
+/// SPL Token mints have no extensions and always pass.
+pub fn validate_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+            msg!("Unsupported mint extension {:?} on {}", extension, mint.key());
+            return err!(LendingError::UnsupportedMintExtension);
+        }
+    }
+    Ok(())
+}
+
+/// Transfer fee withheld when moving `amount` of `mint` during `epoch`
+pub fn transfer_fee(mint: &AccountInfo, amount: u64, epoch: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or_else(|| error!(LendingError::InvalidAmount)),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Amount that actually arrives when `amount` of `mint` is transferred in the current epoch
+pub fn amount_after_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let fee = transfer_fee(mint, amount, Clock::get()?.epoch)?;
+    Ok(amount.saturating_sub(fee))
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+
+use common::scenario::{Scenario, STARTING_BALANCE};
+use common::TOKEN;
+use liquidity_lending::batch::{BatchAction, BatchActionKind, MAX_BATCH_ACTIONS};
+use liquidity_lending::state::ConfigChange;
+use liquidity_lending::LendingError;
+
+fn action(kind: BatchActionKind, amount: u64) -> BatchAction {
+    BatchAction { kind, amount }
+}
+
+fn lending_error(error: LendingError) -> ProgramError {
+    ProgramError::Custom(u32::from(error))
+}
+
+#[test]
+fn test_batch_runs_actions_in_order_on_stale_accounts() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    // Publish a price, then let the reserve and obligation go stale: the batch refreshes them
+    scenario.refresh();
+    scenario.wait(1);
+
+    scenario.process(&scenario.batch_instruction(&[
+        action(BatchActionKind::Deposit, 1_000 * TOKEN),
+        action(BatchActionKind::Borrow, 500 * TOKEN),
+        action(BatchActionKind::Repay, 200 * TOKEN),
+        action(BatchActionKind::Withdraw, 100 * TOKEN),
+    ]));
+
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 600 * TOKEN);
+    // The deposit's collateral went straight into the obligation
+    assert_eq!(scenario.balance(&scenario.user_collateral), 0);
+    let obligation = scenario.obligation_state();
+    assert_eq!(obligation.deposits[0].deposit_reserve, scenario.market.reserve);
+    assert_eq!(obligation.deposits[0].deposited_amount, 900 * TOKEN);
+    assert_eq!(scenario.debt(), 300 * TOKEN);
+
+    let position = scenario.user_position();
+    let entry = position.reserve(&scenario.market.reserve).unwrap();
+    assert_eq!(
+        (entry.deposited, entry.borrowed, entry.repaid, entry.withdrawn),
+        (1_000 * TOKEN, 500 * TOKEN, 200 * TOKEN, 100 * TOKEN)
+    );
+    assert_eq!(scenario.reserve_usage().exposure, 900 * TOKEN);
+}
+
+#[test]
+fn test_batch_checks_borrow_limit_only_at_the_end() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.change_config(ConfigChange::MaxUserBorrowValue { value: 400 });
+    scenario.refresh();
+
+    // Borrowing 500 alone is over the limit
+    let over_limit = scenario.batch_instruction(&[
+        action(BatchActionKind::Deposit, 1_000 * TOKEN),
+        action(BatchActionKind::Borrow, 500 * TOKEN),
+    ]);
+    scenario.process_err(&over_limit, lending_error(LendingError::UserBorrowLimitExceeded));
+
+    // Repaying within the same batch brings it back under
+    scenario.process(&scenario.batch_instruction(&[
+        action(BatchActionKind::Deposit, 1_000 * TOKEN),
+        action(BatchActionKind::Borrow, 500 * TOKEN),
+        action(BatchActionKind::Repay, 200 * TOKEN),
+    ]));
+    assert_eq!(scenario.debt(), 300 * TOKEN);
+}
+
+#[test]
+fn test_batch_refuses_malformed_input() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.refresh();
+
+    let empty = scenario.batch_instruction(&[]);
+    scenario.process_err(&empty, lending_error(LendingError::InvalidBatch));
+
+    let too_many = scenario.batch_instruction(&[action(BatchActionKind::Deposit, TOKEN); MAX_BATCH_ACTIONS + 1]);
+    scenario.process_err(&too_many, lending_error(LendingError::InvalidBatch));
+
+    let mut missing_account = scenario.batch_instruction(&[action(BatchActionKind::Deposit, TOKEN)]);
+    missing_account.accounts.pop();
+    scenario.process_err(&missing_account, lending_error(LendingError::InvalidBatch));
+
+    let zero = scenario.batch_instruction(&[
+        action(BatchActionKind::Deposit, TOKEN),
+        action(BatchActionKind::Borrow, 0),
+    ]);
+    scenario.process_err(&zero, lending_error(LendingError::InvalidAmount));
+
+    // Borrows get the same preflight as on their own
+    let too_large = scenario.batch_instruction(&[
+        action(BatchActionKind::Deposit, 100 * TOKEN),
+        action(BatchActionKind::Borrow, 1_000 * TOKEN),
+    ]);
+    scenario.process_err(&too_large, lending_error(LendingError::BorrowTooLarge));
+}
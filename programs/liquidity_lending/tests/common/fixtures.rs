@@ -0,0 +1,249 @@
+//! Mainnet state dumped into `fixtures/` at the repository root by `fixtures/dump.sh`: the
+//! Kamino program as `kamino_lending.so`, and accounts as `accounts/{name}.json` in the format
+//! of `solana account --output json`, including the `clock` sysvar at the time of the dump.
+//! Tests replay it offline, moving the clock forward and setting oracle prices as they go.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, sysvar::clock};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use mollusk_svm::{program::loader_keys, Mollusk};
+use serde_json::{json, Value};
+use solana_account::Account;
+
+use liquidity_lending::kamino::{deserialize_data, discriminator, reserve_layout, ReserveHeader, TokenInfo};
+use liquidity_lending::oracle::{scope_layout, switchboard_layout};
+
+pub fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../fixtures")
+}
+
+/// Mollusk with the dumped Kamino program at Kamino's address and SPL Token, at the clock of
+/// the dump
+pub fn mollusk(fixtures: &Fixtures) -> Mollusk {
+    let path = fixtures_dir().join("kamino_lending.so");
+    let elf = std::fs::read(&path)
+        .unwrap_or_else(|_| panic!("{} not found, run `fixtures/dump.sh` first", path.display()));
+    let mut mollusk = Mollusk::default();
+    mollusk.add_program_with_elf_and_loader(&mock_kamino::ID, &elf, &loader_keys::LOADER_V3);
+    mollusk_svm_programs_token::token::add_program(&mut mollusk);
+
+    let clock = fixtures.clock();
+    mollusk.warp_to_slot(clock.slot);
+    mollusk.sysvars.clock = clock;
+    mollusk
+}
+
+/// Parse an account printed by `solana account --output json`
+pub fn parse_account(json: &str) -> (Pubkey, Account) {
+    let value: Value = serde_json::from_str(json).expect("invalid account JSON");
+    let field = |name: &str| &value["account"][name];
+    let pubkey = |value: &Value| value.as_str().and_then(|key| key.parse().ok()).expect("invalid pubkey");
+
+    let data = field("data");
+    assert_eq!(data[1], "base64", "account data must be base64 encoded");
+    let account = Account {
+        lamports: field("lamports").as_u64().expect("invalid lamports"),
+        data: BASE64.decode(data[0].as_str().expect("invalid data")).expect("invalid base64"),
+        owner: pubkey(field("owner")),
+        executable: field("executable").as_bool().expect("invalid executable flag"),
+        rent_epoch: field("rentEpoch").as_u64().expect("invalid rent epoch"),
+    };
+    (pubkey(&value["pubkey"]), account)
+}
+
+/// Print an account as `solana account --output json` does, to snapshot replayed state
+pub fn account_json(key: &Pubkey, account: &Account) -> String {
+    json!({
+        "pubkey": key.to_string(),
+        "account": {
+            "lamports": account.lamports,
+            "data": [BASE64.encode(&account.data), "base64"],
+            "owner": account.owner.to_string(),
+            "executable": account.executable,
+            "rentEpoch": account.rent_epoch,
+            "space": account.data.len(),
+        },
+    })
+    .to_string()
+}
+
+/// Accounts loaded from `fixtures/accounts`, updated as instructions run against them
+#[derive(Default)]
+pub struct Fixtures {
+    names: BTreeMap<String, Pubkey>,
+    accounts: BTreeMap<Pubkey, Account>,
+}
+
+impl Fixtures {
+    /// The dumped state, or `None` when `fixtures/dump.sh` has not been run here
+    pub fn dumped() -> Option<Self> {
+        let dir = fixtures_dir();
+        (dir.join("kamino_lending.so").is_file() && dir.join("accounts").is_dir()).then(Self::load)
+    }
+
+    pub fn load() -> Self {
+        let dir = fixtures_dir().join("accounts");
+        let entries = std::fs::read_dir(&dir)
+            .unwrap_or_else(|_| panic!("{} not found, run `fixtures/dump.sh` first", dir.display()));
+        let mut fixtures = Self::default();
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let (key, account) = parse_account(&std::fs::read_to_string(&path).unwrap());
+                fixtures.insert(&name, key, account);
+            }
+        }
+        fixtures
+    }
+
+    pub fn insert(&mut self, name: &str, key: Pubkey, account: Account) {
+        self.names.insert(name.to_owned(), key);
+        self.accounts.insert(key, account);
+    }
+
+    /// Address of the account dumped as `name`
+    pub fn key(&self, name: &str) -> Pubkey {
+        *self
+            .names
+            .get(name)
+            .unwrap_or_else(|| panic!("no fixture named {name}"))
+    }
+
+    pub fn get(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn account(&self, name: &str) -> &Account {
+        &self.accounts[&self.key(name)]
+    }
+
+    /// Every account, to pass to Mollusk
+    pub fn accounts(&self) -> Vec<(Pubkey, Account)> {
+        self.accounts
+            .iter()
+            .map(|(key, account)| (*key, account.clone()))
+            .collect()
+    }
+
+    /// Take the state instructions left the accounts in, for the next step of a replay
+    pub fn update(&mut self, resulting_accounts: &[(Pubkey, Account)]) {
+        for (key, account) in resulting_accounts {
+            if let Some(fixture) = self.accounts.get_mut(key) {
+                *fixture = account.clone();
+            }
+        }
+    }
+
+    /// The clock sysvar at the time of the dump
+    pub fn clock(&self) -> Clock {
+        let data = &self.accounts[&clock::ID].data;
+        let field = |index: usize| u64::from_le_bytes(data[index * 8..index * 8 + 8].try_into().unwrap());
+        Clock {
+            slot: field(0),
+            epoch_start_timestamp: field(1) as i64,
+            epoch: field(2),
+            leader_schedule_epoch: field(3),
+            unix_timestamp: field(4) as i64,
+        }
+    }
+
+    pub fn token_info(&self, reserve: &Pubkey) -> TokenInfo {
+        deserialize_data(&self.accounts[reserve].data, &discriminator::RESERVE, reserve_layout::CONFIG_TOKEN_INFO)
+            .unwrap()
+    }
+
+    /// Publish `price`, in the quote currency, on every configured oracle of `reserve` that
+    /// was dumped, as of `clock`. Scope chains get the price on their first entry and 1 on
+    /// the rest.
+    pub fn set_reserve_price(&mut self, reserve: &Pubkey, price: f64, clock: &Clock) {
+        let token_info = self.token_info(reserve);
+        let pyth = token_info.pyth_configuration.price;
+        if let Some(account) = self.accounts.get_mut(&pyth) {
+            set_pyth_price(&mut account.data, price, clock.unix_timestamp);
+        }
+        let switchboard = &token_info.switchboard_configuration;
+        for feed in [switchboard.price_aggregator, switchboard.twap_aggregator] {
+            if let Some(account) = self.accounts.get_mut(&feed) {
+                set_switchboard_price(&mut account.data, price, clock.unix_timestamp);
+            }
+        }
+        let scope = &token_info.scope_configuration;
+        if let Some(account) = self.accounts.get_mut(&scope.price_feed) {
+            for chain in [scope.price_chain, scope.twap_chain] {
+                let mut entries = chain.into_iter().take_while(|&index| index != scope_layout::CHAIN_END);
+                if let Some(first) = entries.next() {
+                    set_scope_price(&mut account.data, first, price, clock);
+                }
+                for index in entries {
+                    set_scope_price(&mut account.data, index, 1.0, clock);
+                }
+            }
+        }
+    }
+
+    /// Kamino `refreshReserve` for a dumped reserve, with its configured oracles
+    pub fn refresh_reserve(&self, reserve: &Pubkey) -> Instruction {
+        let header: ReserveHeader =
+            deserialize_data(&self.accounts[reserve].data, &discriminator::RESERVE, 0).unwrap();
+        let token_info = self.token_info(reserve);
+        // Kamino reads its own id as a missing optional account
+        let optional = |key: Pubkey| {
+            let key = if key == Pubkey::default() { mock_kamino::ID } else { key };
+            AccountMeta::new_readonly(key, false)
+        };
+        Instruction {
+            program_id: mock_kamino::ID,
+            accounts: vec![
+                AccountMeta::new(*reserve, false),
+                AccountMeta::new_readonly(header.lending_market, false),
+                optional(token_info.pyth_configuration.price),
+                optional(token_info.switchboard_configuration.price_aggregator),
+                optional(token_info.switchboard_configuration.twap_aggregator),
+                optional(token_info.scope_configuration.price_feed),
+            ],
+            data: liquidity_lending::kamino::instruction_discriminator::REFRESH_RESERVE.to_vec(),
+        }
+    }
+}
+
+fn write(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Set the price and EMA of a Pyth `PriceUpdateV2`, keeping its exponent
+pub fn set_pyth_price(data: &mut [u8], price: f64, publish_time: i64) {
+    // Past the write authority and the verification level, which is one byte when `Full`
+    // and two when `Partial`
+    let level = 8 + 32;
+    let message = level + if data[level] == 0 { 2 } else { 1 } + 32;
+    let exponent = i32::from_le_bytes(data[message + 16..message + 20].try_into().unwrap());
+    let mantissa = (price / 10f64.powi(exponent)).round() as i64;
+    write(data, message, &mantissa.to_le_bytes());
+    write(data, message + 8, &0u64.to_le_bytes());
+    write(data, message + 20, &publish_time.to_le_bytes());
+    write(data, message + 28, &publish_time.to_le_bytes());
+    write(data, message + 36, &mantissa.to_le_bytes());
+    write(data, message + 44, &0u64.to_le_bytes());
+}
+
+/// Set the current result of a Switchboard on-demand `PullFeedAccountData`
+pub fn set_switchboard_price(data: &mut [u8], price: f64, timestamp: i64) {
+    let value = (price * 10f64.powi(switchboard_layout::PRECISION as i32)).round() as i128;
+    write(data, 8 + switchboard_layout::RESULT, &value.to_le_bytes());
+    write(data, 8 + switchboard_layout::RESULT + 16, &0i128.to_le_bytes());
+    write(data, 8 + switchboard_layout::LAST_UPDATE_TIMESTAMP, &timestamp.to_le_bytes());
+}
+
+/// Set entry `index` of a Scope `OraclePrices`, keeping its exponent
+pub fn set_scope_price(data: &mut [u8], index: u16, price: f64, clock: &Clock) {
+    let offset = 8 + scope_layout::PRICES + usize::from(index) * scope_layout::DATED_PRICE_SIZE;
+    let exp = u64::from_le_bytes(data[offset + 8..offset + 16].try_into().unwrap());
+    let value = (price * 10f64.powi(exp as i32)).round() as u64;
+    write(data, offset, &value.to_le_bytes());
+    write(data, offset + 16, &clock.slot.to_le_bytes());
+    write(data, offset + 24, &(clock.unix_timestamp as u64).to_le_bytes());
+}
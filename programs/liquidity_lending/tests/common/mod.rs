@@ -0,0 +1,366 @@
+//! Mollusk harness running the program against the mock Kamino programs in
+//! `programs/mock_kamino` and `programs/mock_farms` and SPL Token, with builders for the
+//! accounts both sides read. The programs must be built with `anchor build` first.
+
+#![allow(dead_code)]
+
+pub mod fixtures;
+pub mod scenario;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program_option::COption, program_pack::Pack, sysvar};
+use anchor_lang::{AccountSerialize, Discriminator};
+use anchor_spl::token::spl_token;
+use mollusk_svm::{program::loader_keys, Mollusk};
+use solana_account::Account;
+
+use liquidity_lending::kamino::{reserve_layout, TokenInfo};
+use liquidity_lending::kamino_pda;
+use liquidity_lending::oracle::{discriminator, scope_layout};
+use liquidity_lending::state::{
+    Config, Registry, ReserveEntry, ReservePosition, ReserveUsage, UserBorrows, UserPosition,
+    CONFIG_SEED, DEFAULT_PRICE_GUARD, MAX_POSITION_RESERVES, REGISTRY_SEED, RESERVE_USAGE_SEED,
+    USER_BORROWS_SEED, USER_POSITION_SEED,
+};
+use mock_kamino::state::{self as kamino, BigFractionBytes, Obligation, Reserve};
+
+pub const SLOT: u64 = 1_000;
+pub const NOW: i64 = 1_700_000_000;
+pub const ONE: u128 = 1 << 60;
+pub const DECIMALS: u8 = 6;
+/// One whole token in base units
+pub const TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+
+/// Mainnet slot time, which the clock helpers assume
+pub const SLOT_DURATION_MS: u64 = 400;
+
+fn program_elf(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("../../target/deploy/{name}.so"));
+    std::fs::read(&path).unwrap_or_else(|_| panic!("{} not found, run `anchor build` first", path.display()))
+}
+
+/// Load `target/deploy/{name}.so` at `program_id`
+pub fn add_program(mollusk: &mut Mollusk, program_id: &Pubkey, name: &str) {
+    mollusk.add_program_with_elf_and_loader(program_id, &program_elf(name), &loader_keys::LOADER_V3);
+}
+
+/// Mollusk with our program, the mock Kamino lending and farms programs and SPL Token loaded,
+/// at `SLOT` and `NOW`
+pub fn mollusk() -> Mollusk {
+    let mut mollusk = Mollusk::default();
+    add_program(&mut mollusk, &liquidity_lending::ID, "liquidity_lending");
+    add_program(&mut mollusk, &mock_kamino::ID, "mock_kamino");
+    add_program(&mut mollusk, &mock_farms::ID, "mock_farms");
+    mollusk_svm_programs_token::token::add_program(&mut mollusk);
+    mollusk.warp_to_slot(SLOT);
+    mollusk.sysvars.clock.unix_timestamp = NOW;
+    mollusk
+}
+
+/// Move the clock `slots` ahead, with unix time following at `SLOT_DURATION_MS` a slot
+pub fn fast_forward(mollusk: &mut Mollusk, slots: u64) {
+    let clock = mollusk.sysvars.clock.clone();
+    mollusk.warp_to_slot(clock.slot + slots);
+    mollusk.sysvars.clock.epoch_start_timestamp = clock.epoch_start_timestamp;
+    mollusk.sysvars.clock.unix_timestamp = clock.unix_timestamp + (slots * SLOT_DURATION_MS / 1_000) as i64;
+}
+
+fn rent_exempt(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An account of ours, as Anchor would have initialized it
+pub fn program_account<T: AccountSerialize + Space>(value: &T) -> Account {
+    let mut data = Vec::with_capacity(8 + T::INIT_SPACE);
+    value.try_serialize(&mut data).unwrap();
+    data.resize(8 + T::INIT_SPACE, 0);
+    rent_exempt(data, liquidity_lending::ID)
+}
+
+pub fn kamino_account(data: Vec<u8>) -> Account {
+    rent_exempt(data, mock_kamino::ID)
+}
+
+pub fn system_account() -> Account {
+    Account::new(1_000_000_000, 0, &anchor_lang::system_program::ID)
+}
+
+pub fn mint_account(authority: Pubkey, supply: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(authority),
+        supply,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    rent_exempt(data, spl_token::ID)
+}
+
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    }
+    .pack_into_slice(&mut data);
+    rent_exempt(data, spl_token::ID)
+}
+
+pub fn token_balance(account: &Account) -> u64 {
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub fn decode<T: AnchorDeserialize + Discriminator>(account: &Account) -> T {
+    assert_eq!(&account.data[..8], T::DISCRIMINATOR);
+    T::deserialize(&mut &account.data[8..]).unwrap()
+}
+
+/// A Pyth `PriceUpdateV2` at `price` with a matching EMA, -8 exponent and full verification
+pub fn pyth_price_update(price: i64, publish_time: i64) -> Vec<u8> {
+    let mut data = discriminator::PYTH_PRICE_UPDATE.to_vec();
+    data.extend_from_slice(&[0; 32]);
+    data.push(1);
+    data.extend_from_slice(&[0; 32]);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data
+}
+
+/// A Kamino market with one 6-decimal reserve priced at $1 by Pyth, holding 1,000,000
+/// tokens, and everything the program needs to route funds to it
+pub struct Market {
+    pub lending_market: Pubkey,
+    pub lending_market_authority: Pubkey,
+    pub reserve: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub fee_vault: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+    pub pyth_price: Pubkey,
+}
+
+impl Market {
+    pub fn new() -> Self {
+        let lending_market = Pubkey::new_unique();
+        let liquidity_mint = Pubkey::new_unique();
+        let program = mock_kamino::ID;
+        Self {
+            lending_market,
+            lending_market_authority: kamino_pda::lending_market_authority(&program, &lending_market),
+            reserve: Pubkey::new_unique(),
+            liquidity_mint,
+            liquidity_supply: kamino_pda::reserve_liquidity_supply(&program, &lending_market, &liquidity_mint),
+            fee_vault: kamino_pda::reserve_fee_vault(&program, &lending_market, &liquidity_mint),
+            collateral_mint: kamino_pda::reserve_collateral_mint(&program, &lending_market, &liquidity_mint),
+            collateral_supply: kamino_pda::reserve_collateral_supply(&program, &lending_market, &liquidity_mint),
+            pyth_price: Pubkey::new_unique(),
+        }
+    }
+
+    /// The reserve, refreshed this slot: 75% LTV, 85% liquidation threshold, no fees
+    pub fn reserve_state(&self) -> Reserve {
+        let mut reserve = Reserve::default();
+        reserve.last_update.slot = SLOT;
+        reserve.lending_market = self.lending_market;
+        reserve.liquidity.mint_pubkey = self.liquidity_mint;
+        reserve.liquidity.supply_vault = self.liquidity_supply;
+        reserve.liquidity.fee_vault = self.fee_vault;
+        reserve.liquidity.available_amount = 1_000_000 * TOKEN;
+        reserve.liquidity.market_price_sf = ONE;
+        reserve.liquidity.market_price_last_updated_ts = NOW as u64;
+        reserve.liquidity.mint_decimals = DECIMALS.into();
+        reserve.liquidity.cumulative_borrow_rate_bsf = BigFractionBytes::one();
+        reserve.liquidity.token_program = spl_token::ID;
+        reserve.collateral.mint_pubkey = self.collateral_mint;
+        reserve.collateral.mint_total_supply = 1_000_000 * TOKEN;
+        reserve.collateral.supply_vault = self.collateral_supply;
+        reserve.config.loan_to_value_pct = 75;
+        reserve.config.liquidation_threshold_pct = 85;
+        reserve.config.borrow_factor_pct = 100;
+        reserve
+    }
+
+    /// Account data of `reserve`, configured to read its price from `pyth_price`
+    pub fn reserve_data(&self, reserve: &Reserve) -> Vec<u8> {
+        let mut data = reserve.account_data().unwrap();
+        let mut token_info = TokenInfo {
+            max_age_price_seconds: 60,
+            max_age_twap_seconds: 240,
+            ..TokenInfo::default()
+        };
+        token_info.pyth_configuration.price = self.pyth_price;
+        token_info.scope_configuration.price_chain = [scope_layout::CHAIN_END; 4];
+        token_info.scope_configuration.twap_chain = [scope_layout::CHAIN_END; 4];
+        kamino::write(&mut data, reserve_layout::CONFIG_TOKEN_INFO, &token_info).unwrap();
+        data
+    }
+
+    /// An obligation of `owner` in this market, refreshed this slot, with `collateral` of the
+    /// reserve's collateral deposited and valued
+    pub fn obligation_state(&self, owner: Pubkey, collateral: u64) -> Obligation {
+        let mut obligation = Obligation {
+            lending_market: self.lending_market,
+            owner,
+            ..Obligation::default()
+        };
+        obligation.last_update.slot = SLOT;
+        obligation.deposits[0].deposit_reserve = self.reserve;
+        obligation.deposits[0].deposited_amount = collateral;
+        obligation.revalue(&[self.reserve_state()], &[]).unwrap();
+        obligation
+    }
+
+    pub fn registry_entry(&self) -> ReserveEntry {
+        ReserveEntry {
+            reserve: self.reserve,
+            lending_market: self.lending_market,
+            symbol: *b"USDC\0\0\0\0\0\0\0\0\0\0\0\0",
+            decimals: DECIMALS,
+            max_exposure: u64::MAX,
+            borrow_allowed: true,
+            epoch_deposit_limit: u64::MAX,
+            epoch_borrow_limit: u64::MAX,
+        }
+    }
+
+    /// Kamino's side of the market, with `reserve` as the reserve's state and no collateral
+    /// pledged to obligations yet
+    pub fn kamino_accounts(&self, reserve: &Reserve) -> Vec<(Pubkey, Account)> {
+        let liquidity = &reserve.liquidity;
+        vec![
+            (self.lending_market, kamino_account(kamino::lending_market_data())),
+            (self.lending_market_authority, system_account()),
+            (self.reserve, kamino_account(self.reserve_data(reserve))),
+            (self.liquidity_mint, mint_account(Pubkey::new_unique(), u64::MAX / 2)),
+            (
+                self.liquidity_supply,
+                token_account(self.liquidity_mint, self.lending_market_authority, liquidity.available_amount),
+            ),
+            (self.fee_vault, token_account(self.liquidity_mint, self.lending_market_authority, 0)),
+            (
+                self.collateral_mint,
+                mint_account(self.lending_market_authority, reserve.collateral.mint_total_supply),
+            ),
+            (self.collateral_supply, token_account(self.collateral_mint, self.lending_market_authority, 0)),
+            (self.pyth_price, kamino_account(pyth_price_update(100_000_000, NOW))),
+            (mock_kamino::ID, mollusk_svm::program::create_program_account_loader_v3(&mock_kamino::ID)),
+            (mock_farms::ID, mollusk_svm::program::create_program_account_loader_v3(&mock_farms::ID)),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (sysvar::instructions::ID, Account::default()),
+        ]
+    }
+}
+
+/// Our program's accounts for `owner` using `market`: configuration, registry, the
+/// reserve's usage and the owner's position, tied to `obligation`
+pub struct Protocol {
+    pub config: Pubkey,
+    pub registry: Pubkey,
+    pub reserve_usage: Pubkey,
+    pub user_position: Pubkey,
+    pub user_borrows: Pubkey,
+    pub event_authority: Pubkey,
+}
+
+impl Protocol {
+    pub fn new(market: &Market, owner: &Pubkey) -> Self {
+        let program = liquidity_lending::ID;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program).0;
+        Self {
+            config: pda(&[CONFIG_SEED]),
+            registry: pda(&[REGISTRY_SEED]),
+            reserve_usage: pda(&[RESERVE_USAGE_SEED, market.reserve.as_ref()]),
+            user_position: pda(&[USER_POSITION_SEED, owner.as_ref(), market.lending_market.as_ref()]),
+            user_borrows: pda(&[USER_BORROWS_SEED, owner.as_ref()]),
+            event_authority: pda(&[b"__event_authority"]),
+        }
+    }
+
+    pub fn accounts(&self, market: &Market, owner: &Pubkey, obligation: &Pubkey) -> Vec<(Pubkey, Account)> {
+        let program = liquidity_lending::ID;
+        let bump = |key: &Pubkey, seeds: &[&[u8]]| {
+            let (address, bump) = Pubkey::find_program_address(seeds, &program);
+            assert_eq!(address, *key);
+            bump
+        };
+
+        let config = Config {
+            admin: Pubkey::new_unique(),
+            pending_admin: Pubkey::default(),
+            fee_bps: 0,
+            fee_recipient: Pubkey::default(),
+            timelock_delay: 0,
+            config_change_count: 0,
+            max_user_borrow_value: u64::MAX,
+            price_guard: DEFAULT_PRICE_GUARD,
+            paused: 0,
+            pause_updated_at: 0,
+            pause_updated_by: Pubkey::default(),
+            pause_update_count: 0,
+            bump: bump(&self.config, &[CONFIG_SEED]),
+        };
+        let mut registry = Registry {
+            markets: Default::default(),
+            reserves: Default::default(),
+            bump: bump(&self.registry, &[REGISTRY_SEED]),
+        };
+        registry.set_market(market.lending_market, true).unwrap();
+        registry.set_reserve(market.registry_entry()).unwrap();
+        let reserve_usage = ReserveUsage {
+            reserve: market.reserve,
+            exposure: 0,
+            epoch: 0,
+            epoch_deposited: 0,
+            epoch_borrowed: 0,
+            bump: bump(&self.reserve_usage, &[RESERVE_USAGE_SEED, market.reserve.as_ref()]),
+        };
+        let user_position = UserPosition {
+            owner: *owner,
+            lending_market: market.lending_market,
+            obligation: *obligation,
+            created_at: NOW,
+            updated_at: NOW,
+            bump: bump(
+                &self.user_position,
+                &[USER_POSITION_SEED, owner.as_ref(), market.lending_market.as_ref()],
+            ),
+            reserves: [ReservePosition::default(); MAX_POSITION_RESERVES],
+        };
+        let user_borrows = UserBorrows {
+            owner: *owner,
+            bump: bump(&self.user_borrows, &[USER_BORROWS_SEED, owner.as_ref()]),
+            obligations: Default::default(),
+        };
+
+        vec![
+            (*owner, system_account()),
+            (self.config, program_account(&config)),
+            (self.registry, program_account(&registry)),
+            (self.reserve_usage, program_account(&reserve_usage)),
+            (self.user_position, program_account(&user_position)),
+            (self.user_borrows, program_account(&user_borrows)),
+            (self.event_authority, Account::default()),
+            (program, mollusk_svm::program::create_program_account_loader_v3(&program)),
+        ]
+    }
+}
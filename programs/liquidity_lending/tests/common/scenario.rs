@@ -0,0 +1,921 @@
+//! Scripted multi-step flows against the mock Kamino program. A `Scenario` keeps every account
+//! between instructions, so each step runs on the state the previous one left behind and
+//! tests can assert balances, obligation state and our ledgers after every step.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::{instruction::Instruction, program_error::ProgramError, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use mollusk_svm::result::{Check, InstructionResult};
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_log_collector::LogCollector;
+
+use liquidity_lending::batch::BatchAction;
+use liquidity_lending::kamino::ReserveFarmKind;
+use liquidity_lending::kamino_pda;
+use liquidity_lending::state::{
+    Config, ConfigChange, PauseTarget, PendingConfigChange, Registry, ReserveEntry, ReserveUsage,
+    Role, UserBorrows, UserPosition, CONFIG_CHANGE_SEED, RESERVE_USAGE_SEED, ROLE_SEED,
+};
+use mock_kamino::state::{Obligation, Reserve};
+
+use super::{
+    decode, fast_forward, kamino_account, mollusk, pyth_price_update, system_account, token_account,
+    token_balance, Market, Protocol, SLOT, SLOT_DURATION_MS, TOKEN,
+};
+
+/// Liquidity the owner starts with
+pub const STARTING_BALANCE: u64 = 10_000 * TOKEN;
+
+/// Slots `seconds` of wall time take at `SLOT_DURATION_MS`
+pub fn slots(seconds: i64) -> u64 {
+    seconds as u64 * 1_000 / SLOT_DURATION_MS
+}
+
+/// The program data account of an upgradeable program, with `authority` able to upgrade it
+fn program_data_account(authority: Pubkey) -> Account {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// One user of one market, from a freshly deployed program to a closed position. The owner
+/// starts with `STARTING_BALANCE` of liquidity and an empty obligation, and nothing of ours
+/// exists until `initialize`.
+pub struct Scenario {
+    pub mollusk: Mollusk,
+    pub market: Market,
+    pub protocol: Protocol,
+    pub admin: Pubkey,
+    pub owner: Pubkey,
+    pub obligation: Pubkey,
+    pub user_liquidity: Pubkey,
+    pub user_collateral: Pubkey,
+    accounts: BTreeMap<Pubkey, Account>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        let market = Market::new();
+        let admin = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let obligation = kamino_pda::obligation(&mock_kamino::ID, &owner, &market.lending_market);
+        let user_liquidity = Pubkey::new_unique();
+        let user_collateral = Pubkey::new_unique();
+
+        let mut state = Obligation {
+            lending_market: market.lending_market,
+            owner,
+            ..Obligation::default()
+        };
+        state.last_update.slot = SLOT;
+
+        let program = liquidity_lending::ID;
+        let program_data = Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID).0;
+        let protocol = Protocol::new(&market, &owner);
+        let mut accounts: BTreeMap<Pubkey, Account> = market.kamino_accounts(&market.reserve_state()).into_iter().collect();
+        accounts.extend([
+            (admin, system_account()),
+            (owner, system_account()),
+            (obligation, kamino_account(state.account_data().unwrap())),
+            (user_liquidity, token_account(market.liquidity_mint, owner, STARTING_BALANCE)),
+            (user_collateral, token_account(market.collateral_mint, owner, 0)),
+            (program, mollusk_svm::program::create_program_account_loader_v3(&program)),
+            (program_data, program_data_account(admin)),
+            (protocol.event_authority, Account::default()),
+            mollusk_svm::program::keyed_account_for_system_program(),
+        ]);
+        let mollusk = mollusk();
+        let (rent, rent_account) = mollusk.sysvars.keyed_account_for_rent_sysvar();
+        accounts.insert(rent, rent_account);
+
+        Self {
+            mollusk,
+            market,
+            protocol,
+            admin,
+            owner,
+            obligation,
+            user_liquidity,
+            user_collateral,
+            accounts,
+        }
+    }
+
+    /// Run `instruction`, which must succeed, and keep the state it leaves
+    pub fn process(&mut self, instruction: &Instruction) -> InstructionResult {
+        let accounts: Vec<_> = self.accounts.iter().map(|(key, account)| (*key, account.clone())).collect();
+        let result = self
+            .mollusk
+            .process_and_validate_instruction(instruction, &accounts, &[Check::success()]);
+        self.accounts
+            .extend(result.resulting_accounts.iter().map(|(key, account)| (*key, account.clone())));
+        result
+    }
+
+    /// Run `instruction`, which must fail with `error`, leaving the state as it was
+    pub fn process_err(&self, instruction: &Instruction, error: ProgramError) {
+        let accounts: Vec<_> = self.accounts.iter().map(|(key, account)| (*key, account.clone())).collect();
+        self.mollusk
+            .process_and_validate_instruction(instruction, &accounts, &[Check::err(error)]);
+    }
+
+    /// Run `instruction` on the current state without keeping what it leaves, whatever the
+    /// result, and return the result with every log line of the run
+    pub fn run(&mut self, instruction: &Instruction) -> (InstructionResult, Vec<String>) {
+        let accounts: Vec<_> = self.accounts.iter().map(|(key, account)| (*key, account.clone())).collect();
+        let logger = LogCollector::new_ref_with_limit(None);
+        self.mollusk.logger = Some(logger.clone());
+        let result = self.mollusk.process_instruction(instruction, &accounts);
+        self.mollusk.logger = None;
+        (result, logger.take().into_messages())
+    }
+
+    pub fn account(&self, key: &Pubkey) -> &Account {
+        self.accounts
+            .get(key)
+            .unwrap_or_else(|| panic!("no account {key}"))
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn balance(&self, key: &Pubkey) -> u64 {
+        token_balance(self.account(key))
+    }
+
+    pub fn reserve(&self) -> Reserve {
+        Reserve::decode(&self.account(&self.market.reserve).data).unwrap()
+    }
+
+    pub fn obligation_state(&self) -> Obligation {
+        Obligation::decode(&self.account(&self.obligation).data).unwrap()
+    }
+
+    /// Overwrite the state of `key`, a reserve, keeping the rest of its data
+    pub fn store_reserve(&mut self, key: Pubkey, reserve: &Reserve) {
+        let mut data = self.account(&key).data.clone();
+        reserve.encode(&mut data).unwrap();
+        self.set_account(key, kamino_account(data));
+    }
+
+    pub fn store_obligation(&mut self, obligation: &Obligation) {
+        self.set_account(self.obligation, kamino_account(obligation.account_data().unwrap()));
+    }
+
+    /// Another reserve in the market, configured and priced like the first, for obligations
+    /// spread over several reserves
+    pub fn add_reserve(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let data = self.market.reserve_data(&self.market.reserve_state());
+        self.set_account(key, kamino_account(data));
+        key
+    }
+
+    pub fn config(&self) -> Config {
+        decode(self.account(&self.protocol.config))
+    }
+
+    pub fn registry(&self) -> Registry {
+        decode(self.account(&self.protocol.registry))
+    }
+
+    pub fn reserve_usage(&self) -> ReserveUsage {
+        decode(self.account(&self.protocol.reserve_usage))
+    }
+
+    pub fn user_position(&self) -> UserPosition {
+        decode(self.account(&self.protocol.user_position))
+    }
+
+    pub fn user_borrows(&self) -> UserBorrows {
+        decode(self.account(&self.protocol.user_borrows))
+    }
+
+    /// Let `slots` pass
+    pub fn wait(&mut self, slots: u64) {
+        fast_forward(&mut self.mollusk, slots);
+    }
+
+    pub fn initialize_instruction(&self) -> Instruction {
+        let program = liquidity_lending::ID;
+        Instruction {
+            program_id: program,
+            accounts: liquidity_lending::accounts::Initialize {
+                admin: self.admin,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                program,
+                program_data: Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID).0,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::Initialize {}.data(),
+        }
+    }
+
+    /// The change the admin would queue next
+    pub fn queue_config_change_instruction(&self, change: ConfigChange) -> Instruction {
+        self.propose_config_change_instruction(self.admin, change)
+    }
+
+    /// Queue `change` as `proposer`, passing its fee manager role if it holds one
+    pub fn propose_config_change_instruction(&self, proposer: Pubkey, change: ConfigChange) -> Instruction {
+        let fee_manager_role = self.role(Role::FeeManager, &proposer);
+        let has_role = self.accounts.get(&fee_manager_role).is_some_and(|account| account.lamports > 0);
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::QueueConfigChange {
+                proposer,
+                fee_manager_role: has_role.then_some(fee_manager_role),
+                config: self.protocol.config,
+                pending_change: self.pending_change(self.config().config_change_count),
+                system_program: anchor_lang::system_program::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::QueueConfigChange { change }.data(),
+        }
+    }
+
+    /// Execute queued change `id` as the admin, refunding its proposer
+    pub fn execute_config_change_instruction(&self, id: u64) -> Instruction {
+        let pending: PendingConfigChange = decode(self.account(&self.pending_change(id)));
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::ExecuteConfigChange {
+                executor: self.admin,
+                proposer: pending.proposer,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                pending_change: self.pending_change(id),
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::ExecuteConfigChange {}.data(),
+        }
+    }
+
+    /// Address of `holder`'s assignment of `role`
+    pub fn role(&self, role: Role, holder: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[ROLE_SEED, &role.seed(), holder.as_ref()], &liquidity_lending::ID).0
+    }
+
+    pub fn grant_role_instruction(&self, admin: Pubkey, role: Role, holder: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::GrantRole {
+                admin,
+                config: self.protocol.config,
+                role_assignment: self.role(role, &holder),
+                system_program: anchor_lang::system_program::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::GrantRole { role, holder }.data(),
+        }
+    }
+
+    pub fn revoke_role_instruction(&self, admin: Pubkey, role: Role, holder: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::RevokeRole {
+                admin,
+                config: self.protocol.config,
+                role_assignment: self.role(role, &holder),
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::RevokeRole {}.data(),
+        }
+    }
+
+    pub fn propose_admin_instruction(&self, admin: Pubkey, new_admin: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::ProposeAdmin {
+                admin,
+                config: self.protocol.config,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::ProposeAdmin { new_admin }.data(),
+        }
+    }
+
+    pub fn accept_admin_instruction(&self, pending_admin: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::AcceptAdmin {
+                pending_admin,
+                config: self.protocol.config,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::AcceptAdmin {}.data(),
+        }
+    }
+
+    /// Set the pause of `target` as `authority`, passing its guardian role if it holds one
+    pub fn set_pause_instruction(&self, authority: Pubkey, target: PauseTarget, paused: bool) -> Instruction {
+        let guardian_role = self.role(Role::Guardian, &authority);
+        let has_role = self.accounts.get(&guardian_role).is_some_and(|account| account.lamports > 0);
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::SetPause {
+                authority,
+                config: self.protocol.config,
+                guardian_role: has_role.then_some(guardian_role),
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::SetPause { target, paused }.data(),
+        }
+    }
+
+    /// Refresh the obligation's collateral farm as `crank`, with its keeper role. The
+    /// scenario's reserve has no farm, so past the role check Kamino is never reached.
+    pub fn refresh_obligation_farms_instruction(&self, crank: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::KaminoRefreshObligationFarmsForReserve {
+                crank,
+                keeper_role: self.role(Role::Keeper, &crank),
+                registry: self.protocol.registry,
+                obligation: self.obligation,
+                lending_market_authority: self.market.lending_market_authority,
+                reserve: self.market.reserve,
+                reserve_farm_state: self.market.lending_market_authority,
+                obligation_farm_user_state: self.market.lending_market_authority,
+                lending_market: self.market.lending_market,
+                farms_program: mock_farms::ID,
+                rent: sysvar::rent::ID,
+                system_program: anchor_lang::system_program::ID,
+                kamino_lending_program: mock_kamino::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoRefreshObligationFarmsForReserve {
+                mode: ReserveFarmKind::Collateral,
+            }
+            .data(),
+        }
+    }
+
+    pub fn pending_change(&self, id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[CONFIG_CHANGE_SEED, &id.to_le_bytes()], &liquidity_lending::ID).0
+    }
+
+    pub fn init_reserve_usage_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::InitReserveUsage {
+                payer: self.admin,
+                registry: self.protocol.registry,
+                reserve: self.market.reserve,
+                reserve_usage: self.protocol.reserve_usage,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::InitReserveUsage {}.data(),
+        }
+    }
+
+    pub fn init_user_position_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::InitUserPosition {
+                payer: self.owner,
+                owner: self.owner,
+                lending_market: self.market.lending_market,
+                obligation: self.obligation,
+                user_position: self.protocol.user_position,
+                system_program: anchor_lang::system_program::ID,
+                kamino_lending_program: mock_kamino::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::InitUserPosition {}.data(),
+        }
+    }
+
+    pub fn init_user_borrows_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::InitUserBorrows {
+                payer: self.owner,
+                owner: self.owner,
+                user_borrows: self.protocol.user_borrows,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::InitUserBorrows {}.data(),
+        }
+    }
+
+    /// Sync the owner's borrows with `obligation`, which must be fresh
+    pub fn sync_user_borrows_instruction(&self, obligation: Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::SyncUserBorrows {
+                user_borrows: self.protocol.user_borrows,
+                obligation,
+                kamino_lending_program: mock_kamino::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::SyncUserBorrows {}.data(),
+        }
+    }
+
+    /// Create the configuration and registry, with the admin as the upgrade authority
+    pub fn initialize(&mut self) {
+        self.process(&self.initialize_instruction());
+    }
+
+    /// Queue `change` as the admin, wait out the timelock and execute it
+    pub fn change_config(&mut self, change: ConfigChange) {
+        let id = self.config().config_change_count;
+        self.process(&self.queue_config_change_instruction(change));
+        self.wait(slots(self.config().timelock_delay));
+        self.process(&self.execute_config_change_instruction(id));
+    }
+
+    /// Approve the market and its reserve through the timelock
+    pub fn approve_market(&mut self) {
+        self.change_config(ConfigChange::SetMarket {
+            lending_market: self.market.lending_market,
+            allowed: true,
+        });
+        self.change_config(ConfigChange::SetReserve {
+            entry: self.market.registry_entry(),
+        });
+    }
+
+    /// The usage account of `reserve`
+    pub fn reserve_usage_address(&self, reserve: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[RESERVE_USAGE_SEED, reserve.as_ref()], &liquidity_lending::ID).0
+    }
+
+    /// Approve `reserve`, one from `add_reserve`, with the market reserve's limits and create
+    /// its usage account
+    pub fn approve_reserve(&mut self, reserve: Pubkey) {
+        self.change_config(ConfigChange::SetReserve {
+            entry: ReserveEntry { reserve, ..self.market.registry_entry() },
+        });
+        let init_usage = Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::InitReserveUsage {
+                payer: self.admin,
+                registry: self.protocol.registry,
+                reserve,
+                reserve_usage: self.reserve_usage_address(&reserve),
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::InitReserveUsage {}.data(),
+        };
+        self.process(&init_usage);
+    }
+
+    /// Create the reserve's usage account, the owner's position and their borrows account
+    pub fn open_position(&mut self) {
+        self.process(&self.init_reserve_usage_instruction());
+        self.process(&self.init_user_position_instruction());
+        self.process(&self.init_user_borrows_instruction());
+    }
+
+    /// Everything a user needs before their first deposit
+    pub fn setup(&mut self) {
+        self.initialize();
+        self.approve_market();
+        self.open_position();
+    }
+
+    pub fn refresh_reserve_instruction(&self, reserve: Pubkey) -> Instruction {
+        Instruction {
+            program_id: mock_kamino::ID,
+            accounts: mock_kamino::accounts::RefreshReserve {
+                reserve,
+                lending_market: self.market.lending_market,
+                pyth_oracle: Some(self.market.pyth_price),
+                switchboard_price_oracle: None,
+                switchboard_twap_oracle: None,
+                scope_prices: None,
+            }
+            .to_account_metas(None),
+            data: mock_kamino::instruction::RefreshReserve {}.data(),
+        }
+    }
+
+    /// Publish the price now, then refresh the market's reserve, every reserve the
+    /// obligation uses and the obligation, as clients do at the start of every transaction
+    pub fn refresh(&mut self) {
+        let now = self.mollusk.sysvars.clock.unix_timestamp;
+        let price = (self.reserve().liquidity.market_price_sf * 100_000_000) >> 60;
+        self.set_account(
+            self.market.pyth_price,
+            kamino_account(pyth_price_update(price as i64, now)),
+        );
+
+        let obligation = self.obligation_state();
+        let obligation_reserves: Vec<Pubkey> = obligation
+            .active_deposits()
+            .map(|deposit| deposit.deposit_reserve)
+            .chain(obligation.active_borrows().map(|borrow| borrow.borrow_reserve))
+            .collect();
+        let mut reserves = vec![self.market.reserve];
+        for reserve in &obligation_reserves {
+            if !reserves.contains(reserve) {
+                reserves.push(*reserve);
+            }
+        }
+        for reserve in reserves {
+            self.process(&self.refresh_reserve_instruction(reserve));
+        }
+
+        let mut accounts = mock_kamino::accounts::RefreshObligation {
+            lending_market: self.market.lending_market,
+            obligation: self.obligation,
+        }
+        .to_account_metas(None);
+        accounts.extend(obligation_reserves.into_iter().map(|reserve| AccountMeta::new_readonly(reserve, false)));
+        let refresh_obligation = Instruction {
+            program_id: mock_kamino::ID,
+            accounts,
+            data: mock_kamino::instruction::RefreshObligation {}.data(),
+        };
+        self.process(&refresh_obligation);
+    }
+
+    pub fn deposit_instruction(&self, liquidity_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::KaminoDepositReserveLiquidity {
+                owner: self.owner,
+                user_position: self.protocol.user_position,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                reserve_usage: self.protocol.reserve_usage,
+                reserve: self.market.reserve,
+                lending_market: self.market.lending_market,
+                lending_market_authority: self.market.lending_market_authority,
+                reserve_liquidity_mint: self.market.liquidity_mint,
+                reserve_liquidity_supply: self.market.liquidity_supply,
+                reserve_collateral_mint: self.market.collateral_mint,
+                user_source_liquidity: self.user_liquidity,
+                user_destination_collateral: self.user_collateral,
+                collateral_token_program: anchor_spl::token::ID,
+                liquidity_token_program: anchor_spl::token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: mock_kamino::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoDepositReserveLiquidity { liquidity_amount }.data(),
+        }
+    }
+
+    /// Pledge collateral to the obligation. We do not wrap this, so the owner calls Kamino.
+    pub fn collateralize_instruction(&self, collateral_amount: u64) -> Instruction {
+        Instruction {
+            program_id: mock_kamino::ID,
+            accounts: mock_kamino::accounts::DepositObligationCollateral {
+                owner: self.owner,
+                obligation: self.obligation,
+                lending_market: self.market.lending_market,
+                deposit_reserve: self.market.reserve,
+                reserve_destination_collateral: self.market.collateral_supply,
+                user_source_collateral: self.user_collateral,
+                token_program: anchor_spl::token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+            }
+            .to_account_metas(None),
+            data: mock_kamino::instruction::DepositObligationCollateral { collateral_amount }.data(),
+        }
+    }
+
+    pub fn borrow_instruction(&self, liquidity_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::KaminoBorrowObligationLiquidity {
+                owner: self.owner,
+                user_position: self.protocol.user_position,
+                user_borrows: self.protocol.user_borrows,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                reserve_usage: self.protocol.reserve_usage,
+                obligation: self.obligation,
+                lending_market: self.market.lending_market,
+                lending_market_authority: self.market.lending_market_authority,
+                borrow_reserve: self.market.reserve,
+                borrow_reserve_liquidity_mint: self.market.liquidity_mint,
+                reserve_source_liquidity: self.market.liquidity_supply,
+                borrow_reserve_liquidity_fee_receiver: self.market.fee_vault,
+                user_destination_liquidity: self.user_liquidity,
+                referrer_token_state: None,
+                pyth_price: Some(self.market.pyth_price),
+                switchboard_price: None,
+                switchboard_twap: None,
+                scope_prices: None,
+                token_program: anchor_spl::token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: mock_kamino::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoBorrowObligationLiquidity { liquidity_amount }.data(),
+        }
+    }
+
+    pub fn repay_instruction(&self, liquidity_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::KaminoRepayObligationLiquidity {
+                owner: self.owner,
+                user_position: self.protocol.user_position,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                obligation: self.obligation,
+                lending_market: self.market.lending_market,
+                repay_reserve: self.market.reserve,
+                reserve_liquidity_mint: self.market.liquidity_mint,
+                reserve_destination_liquidity: self.market.liquidity_supply,
+                user_source_liquidity: self.user_liquidity,
+                token_program: anchor_spl::token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: mock_kamino::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoRepayObligationLiquidity { liquidity_amount }.data(),
+        }
+    }
+
+    pub fn withdraw_instruction(&self, collateral_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts: liquidity_lending::accounts::KaminoWithdrawObligationCollateralAndRedeemReserveCollateral {
+                owner: self.owner,
+                user_position: self.protocol.user_position,
+                config: self.protocol.config,
+                registry: self.protocol.registry,
+                reserve_usage: self.protocol.reserve_usage,
+                obligation: self.obligation,
+                lending_market: self.market.lending_market,
+                lending_market_authority: self.market.lending_market_authority,
+                withdraw_reserve: self.market.reserve,
+                reserve_liquidity_mint: self.market.liquidity_mint,
+                reserve_source_collateral: self.market.collateral_supply,
+                reserve_collateral_mint: self.market.collateral_mint,
+                reserve_liquidity_supply: self.market.liquidity_supply,
+                user_destination_liquidity: self.user_liquidity,
+                collateral_token_program: anchor_spl::token::ID,
+                liquidity_token_program: anchor_spl::token::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                kamino_lending_program: mock_kamino::ID,
+                event_authority: self.protocol.event_authority,
+                program: liquidity_lending::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_lending::instruction::KaminoWithdrawObligationCollateralAndRedeemReserveCollateral {
+                collateral_amount,
+            }
+            .data(),
+        }
+    }
+
+    /// Repay then withdraw on the market's reserve in one instruction. `obligation_reserves`
+    /// are the obligation's reserves once the repayment is done.
+    pub fn repay_and_withdraw_instruction(
+        &self,
+        repay_amount: u64,
+        withdraw_collateral_amount: u64,
+        obligation_reserves: &[Pubkey],
+    ) -> Instruction {
+        let mut accounts = liquidity_lending::accounts::KaminoRepayAndWithdrawAndRedeem {
+            owner: self.owner,
+            user_position: self.protocol.user_position,
+            user_borrows: self.protocol.user_borrows,
+            config: self.protocol.config,
+            registry: self.protocol.registry,
+            withdraw_reserve_usage: self.protocol.reserve_usage,
+            obligation: self.obligation,
+            lending_market: self.market.lending_market,
+            lending_market_authority: self.market.lending_market_authority,
+            repay_reserve: self.market.reserve,
+            repay_reserve_liquidity_mint: self.market.liquidity_mint,
+            repay_reserve_liquidity_supply: self.market.liquidity_supply,
+            user_source_liquidity: self.user_liquidity,
+            withdraw_reserve: self.market.reserve,
+            withdraw_reserve_liquidity_mint: self.market.liquidity_mint,
+            withdraw_reserve_collateral_supply: self.market.collateral_supply,
+            withdraw_reserve_collateral_mint: self.market.collateral_mint,
+            withdraw_reserve_liquidity_supply: self.market.liquidity_supply,
+            user_destination_liquidity: self.user_liquidity,
+            collateral_obligation_farm_user_state: None,
+            collateral_reserve_farm_state: None,
+            debt_obligation_farm_user_state: None,
+            debt_reserve_farm_state: None,
+            farms_program: mock_farms::ID,
+            collateral_token_program: anchor_spl::token::ID,
+            repay_liquidity_token_program: anchor_spl::token::ID,
+            withdraw_liquidity_token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: self.protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(obligation_reserves.iter().map(|reserve| AccountMeta::new_readonly(*reserve, false)));
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts,
+            data: liquidity_lending::instruction::KaminoRepayAndWithdrawAndRedeem {
+                repay_amount,
+                withdraw_collateral_amount,
+            }
+            .data(),
+        }
+    }
+
+    /// Deposit into `deposit_reserve` then withdraw from `withdraw_reserve` in one instruction.
+    /// Reserves from `add_reserve` share the market reserve's mints and vaults.
+    /// `obligation_reserves` are the obligation's reserves once the deposit is done.
+    pub fn deposit_and_withdraw_instruction(
+        &self,
+        deposit_reserve: Pubkey,
+        withdraw_reserve: Pubkey,
+        liquidity_amount: u64,
+        withdraw_collateral_amount: u64,
+        obligation_reserves: &[Pubkey],
+    ) -> Instruction {
+        let mut accounts = liquidity_lending::accounts::KaminoDepositAndWithdraw {
+            owner: self.owner,
+            user_position: self.protocol.user_position,
+            user_borrows: self.protocol.user_borrows,
+            config: self.protocol.config,
+            registry: self.protocol.registry,
+            deposit_reserve_usage: self.reserve_usage_address(&deposit_reserve),
+            withdraw_reserve_usage: self.reserve_usage_address(&withdraw_reserve),
+            obligation: self.obligation,
+            lending_market: self.market.lending_market,
+            lending_market_authority: self.market.lending_market_authority,
+            deposit_reserve,
+            deposit_reserve_liquidity_mint: self.market.liquidity_mint,
+            deposit_reserve_liquidity_supply: self.market.liquidity_supply,
+            deposit_reserve_collateral_mint: self.market.collateral_mint,
+            deposit_reserve_collateral_supply: self.market.collateral_supply,
+            user_source_liquidity: self.user_liquidity,
+            withdraw_reserve,
+            withdraw_reserve_liquidity_mint: self.market.liquidity_mint,
+            withdraw_reserve_collateral_supply: self.market.collateral_supply,
+            withdraw_reserve_collateral_mint: self.market.collateral_mint,
+            withdraw_reserve_liquidity_supply: self.market.liquidity_supply,
+            user_destination_liquidity: self.user_liquidity,
+            deposit_obligation_farm_user_state: None,
+            deposit_reserve_farm_state: None,
+            withdraw_obligation_farm_user_state: None,
+            withdraw_reserve_farm_state: None,
+            farms_program: mock_farms::ID,
+            collateral_token_program: anchor_spl::token::ID,
+            deposit_liquidity_token_program: anchor_spl::token::ID,
+            withdraw_liquidity_token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: self.protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(obligation_reserves.iter().map(|reserve| AccountMeta::new_readonly(*reserve, false)));
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts,
+            data: liquidity_lending::instruction::KaminoDepositAndWithdraw {
+                liquidity_amount,
+                withdraw_collateral_amount,
+            }
+            .data(),
+        }
+    }
+
+    /// Run `actions` on the market's reserve in one instruction, each with the reserve's
+    /// accounts and its Pyth oracle
+    pub fn batch_instruction(&self, actions: &[BatchAction]) -> Instruction {
+        let mut accounts = liquidity_lending::accounts::KaminoBatch {
+            owner: self.owner,
+            user_position: self.protocol.user_position,
+            user_borrows: self.protocol.user_borrows,
+            config: self.protocol.config,
+            registry: self.protocol.registry,
+            obligation: self.obligation,
+            lending_market: self.market.lending_market,
+            lending_market_authority: self.market.lending_market_authority,
+            collateral_token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: self.protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None);
+        for _ in actions {
+            accounts.extend([
+                AccountMeta::new(self.market.reserve, false),
+                AccountMeta::new(self.protocol.reserve_usage, false),
+                AccountMeta::new_readonly(self.market.liquidity_mint, false),
+                AccountMeta::new(self.market.liquidity_supply, false),
+                AccountMeta::new(self.market.collateral_mint, false),
+                AccountMeta::new(self.market.collateral_supply, false),
+                AccountMeta::new(self.market.fee_vault, false),
+                AccountMeta::new(self.user_liquidity, false),
+                AccountMeta::new_readonly(anchor_spl::token::ID, false),
+                AccountMeta::new_readonly(self.market.pyth_price, false),
+                AccountMeta::new_readonly(mock_kamino::ID, false),
+                AccountMeta::new_readonly(mock_kamino::ID, false),
+                AccountMeta::new_readonly(mock_kamino::ID, false),
+            ]);
+        }
+        Instruction {
+            program_id: liquidity_lending::ID,
+            accounts,
+            data: liquidity_lending::instruction::KaminoBatch { actions: actions.to_vec() }.data(),
+        }
+    }
+
+    /// Deposit liquidity for reserve collateral
+    pub fn deposit(&mut self, liquidity_amount: u64) {
+        self.refresh();
+        self.process(&self.deposit_instruction(liquidity_amount));
+    }
+
+    pub fn collateralize(&mut self, collateral_amount: u64) {
+        self.refresh();
+        self.process(&self.collateralize_instruction(collateral_amount));
+    }
+
+    pub fn borrow(&mut self, liquidity_amount: u64) {
+        self.refresh();
+        self.process(&self.borrow_instruction(liquidity_amount));
+    }
+
+    pub fn repay(&mut self, liquidity_amount: u64) {
+        self.refresh();
+        self.process(&self.repay_instruction(liquidity_amount));
+    }
+
+    /// Withdraw collateral and redeem it for liquidity
+    pub fn withdraw(&mut self, collateral_amount: u64) {
+        self.refresh();
+        self.process(&self.withdraw_instruction(collateral_amount));
+    }
+
+    /// Let `slots` pass while the reserve's debt, and the obligation's share of it, grows by
+    /// `interest_bps`. The mock does not accrue interest, so this applies it the way
+    /// `refreshReserve` would.
+    pub fn accrue(&mut self, slots: u64, interest_bps: u128) {
+        self.wait(slots);
+        let grow = |value: u128| value * (10_000 + interest_bps) / 10_000;
+
+        let mut reserve = self.reserve();
+        let rate = &mut reserve.liquidity.cumulative_borrow_rate_bsf.value;
+        let grown = grow(u128::from(rate[0]) | u128::from(rate[1]) << 64);
+        (rate[0], rate[1]) = (grown as u64, (grown >> 64) as u64);
+        reserve.liquidity.borrowed_amount_sf = grow(reserve.liquidity.borrowed_amount_sf);
+        self.store_reserve(self.market.reserve, &reserve);
+
+        let mut obligation = self.obligation_state();
+        let reserve_key = self.market.reserve;
+        for borrow in obligation.borrows.iter_mut().filter(|borrow| borrow.borrow_reserve == reserve_key) {
+            borrow.borrowed_amount_sf = grow(borrow.borrowed_amount_sf);
+            borrow.cumulative_borrow_rate_bsf = reserve.liquidity.cumulative_borrow_rate_bsf;
+        }
+        self.store_obligation(&obligation);
+    }
+
+    /// Debt of the obligation to the reserve, in whole base units rounded up
+    pub fn debt(&self) -> u64 {
+        let obligation = self.obligation_state();
+        obligation
+            .find_borrow(&self.market.reserve)
+            .map_or(0, |index| obligation.borrows[index].borrowed_amount_sf.div_ceil(1 << 60) as u64)
+    }
+}
@@ -0,0 +1,208 @@
+//! Property tests feeding the Kamino wrappers random account orderings, amounts of 0, 1 and
+//! `u64::MAX`, a missing or foreign referrer token state, and a mint other than the reserve's.
+//! Whatever the input, the program must not panic, and bad input must be refused by our own
+//! checks, as a `LendingError` or one of Anchor's account errors, before anything reaches
+//! Kamino. `PROPTEST_CASES` raises the number of cases from proptest's default of 256.
+
+mod common;
+
+use std::cell::RefCell;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program_error::ProgramError};
+use mollusk_svm::result::ProgramResult;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use common::scenario::{Scenario, STARTING_BALANCE};
+use common::{mint_account, system_account, token_account, TOKEN};
+use liquidity_lending::LendingError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Handler {
+    Deposit,
+    Borrow,
+    Repay,
+    Withdraw,
+}
+
+#[derive(Clone, Debug)]
+struct Case {
+    handler: Handler,
+    amount: u64,
+    /// Account positions to swap, in order
+    swaps: Vec<(Index, Index)>,
+    /// Use another mint, and the owner's account of it, in place of the reserve's liquidity
+    mismatched_mint: bool,
+    /// Pass a referrer token state Kamino does not own instead of none, when borrowing
+    foreign_referrer: bool,
+}
+
+fn case() -> impl Strategy<Value = Case> {
+    (
+        prop_oneof![
+            Just(Handler::Deposit),
+            Just(Handler::Borrow),
+            Just(Handler::Repay),
+            Just(Handler::Withdraw),
+        ],
+        prop_oneof![Just(0), Just(1), Just(u64::MAX), 1..=STARTING_BALANCE],
+        prop::collection::vec((any::<Index>(), any::<Index>()), 0..4),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(handler, amount, swaps, mismatched_mint, foreign_referrer)| Case {
+            handler,
+            amount,
+            swaps,
+            mismatched_mint,
+            foreign_referrer,
+        })
+}
+
+/// An owner with collateral pledged and debt outstanding, refreshed in the current slot, so
+/// every handler can succeed on well-formed input
+struct Fixture {
+    scenario: Scenario,
+    other_mint: Pubkey,
+    other_liquidity: Pubkey,
+    referrer_token_state: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let mut scenario = Scenario::new();
+        scenario.setup();
+        scenario.deposit(1_000 * TOKEN);
+        scenario.collateralize(1_000 * TOKEN);
+        scenario.borrow(500 * TOKEN);
+        scenario.refresh();
+
+        let other_mint = Pubkey::new_unique();
+        let other_liquidity = Pubkey::new_unique();
+        let referrer_token_state = Pubkey::new_unique();
+        scenario.set_account(other_mint, mint_account(Pubkey::new_unique(), STARTING_BALANCE));
+        scenario.set_account(other_liquidity, token_account(other_mint, scenario.owner, STARTING_BALANCE));
+        scenario.set_account(referrer_token_state, system_account());
+
+        Self {
+            scenario,
+            other_mint,
+            other_liquidity,
+            referrer_token_state,
+        }
+    }
+
+    /// The handler's instruction with the case's accounts, in their declared order
+    fn instruction(&self, case: &Case) -> Instruction {
+        let scenario = &self.scenario;
+        let mut instruction = match case.handler {
+            Handler::Deposit => scenario.deposit_instruction(case.amount),
+            Handler::Borrow => scenario.borrow_instruction(case.amount),
+            Handler::Repay => scenario.repay_instruction(case.amount),
+            Handler::Withdraw => scenario.withdraw_instruction(case.amount),
+        };
+
+        if case.handler == Handler::Borrow && case.foreign_referrer {
+            // The referrer token state follows the user's liquidity account
+            let slot = instruction
+                .accounts
+                .iter()
+                .position(|meta| meta.pubkey == scenario.user_liquidity)
+                .unwrap()
+                + 1;
+            instruction.accounts[slot] = AccountMeta::new(self.referrer_token_state, false);
+        }
+        if case.mismatched_mint {
+            for meta in &mut instruction.accounts {
+                if meta.pubkey == scenario.market.liquidity_mint {
+                    meta.pubkey = self.other_mint;
+                } else if meta.pubkey == scenario.user_liquidity {
+                    meta.pubkey = self.other_liquidity;
+                }
+            }
+        }
+        instruction
+    }
+
+    /// Apply the case's swaps. The price guard does not read the oracle when it is off, so
+    /// moving it is not always bad input, and it stays in place.
+    fn reorder(&self, case: &Case, accounts: &mut [AccountMeta]) {
+        let movable: Vec<usize> = (0..accounts.len())
+            .filter(|&index| accounts[index].pubkey != self.scenario.market.pyth_price)
+            .collect();
+        for (a, b) in &case.swaps {
+            accounts.swap(movable[a.index(movable.len())], movable[b.index(movable.len())]);
+        }
+    }
+}
+
+thread_local! {
+    static FIXTURE: RefCell<Fixture> = RefCell::new(Fixture::new());
+}
+
+fn check(case: &Case) -> std::result::Result<(), TestCaseError> {
+    FIXTURE.with(|fixture| {
+        let mut fixture = fixture.borrow_mut();
+        let mut instruction = fixture.instruction(case);
+        let declared = instruction.accounts.clone();
+        fixture.reorder(case, &mut instruction.accounts);
+        let reordered = instruction.accounts != declared;
+        let bad_accounts = reordered
+            || case.mismatched_mint
+            || (case.handler == Handler::Borrow && case.foreign_referrer);
+        let bad = bad_accounts || case.amount == 0;
+
+        let (result, logs) = fixture.scenario.run(&instruction);
+        let kamino_invoke = format!("Program {} invoke", mock_kamino::ID);
+        let reached_kamino = logs.iter().any(|line| line.starts_with(&kamino_invoke));
+
+        let code = match result.program_result {
+            ProgramResult::Success => {
+                prop_assert!(!bad, "bad input succeeded: {logs:#?}");
+                return Ok(());
+            }
+            ProgramResult::Failure(ProgramError::Custom(code)) => code,
+            other => return Err(TestCaseError::fail(format!("{other:?} is not a program error: {logs:#?}"))),
+        };
+        if reached_kamino {
+            // Kamino can still refuse well-formed input, such as a withdrawal the debt needs
+            prop_assert!(!bad, "bad input reached Kamino: {logs:#?}");
+            return Ok(());
+        }
+
+        // Only our program ran, and the error went through Anchor rather than a panic
+        let error_number = format!("Error Number: {code}.");
+        prop_assert!(
+            logs.iter().any(|line| line.contains("AnchorError") && line.contains(&error_number)),
+            "{code} was not raised by the program: {logs:#?}"
+        );
+        if case.amount == 0 && !bad_accounts {
+            prop_assert_eq!(code, u32::from(LendingError::InvalidAmount));
+        }
+        Ok(())
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_handlers_refuse_bad_input_before_kamino(case in case()) {
+        check(&case)?;
+    }
+}
+
+#[test]
+fn test_edge_amounts_without_referrer() {
+    for handler in [Handler::Deposit, Handler::Borrow, Handler::Repay, Handler::Withdraw] {
+        for amount in [0, 1, u64::MAX] {
+            let case = Case {
+                handler,
+                amount,
+                swaps: Vec::new(),
+                mismatched_mint: false,
+                foreign_referrer: false,
+            };
+            check(&case).unwrap_or_else(|error| panic!("{handler:?} of {amount}: {error}"));
+        }
+    }
+}
@@ -1,126 +1,78 @@
-use std::path::PathBuf;
-use mollusk_svm::{Mollusk, result::Check};
-use anchor_lang::{prelude::*, solana_program::{
-    instruction::Instruction,
-    sysvar,
-}, InstructionData};
+mod common;
 
-use solana_account::Account;
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction, solana_program::sysvar, InstructionData, ToAccountMetas};
+use mollusk_svm::result::Check;
 
-use liquidity_lending::{
-    ID as PROGRAM_ID,
-    instruction::KaminoBorrowObligationLiquidity
-};
+use common::{decode, kamino_account, mollusk, token_account, token_balance, Market, Protocol, ONE, TOKEN};
+use liquidity_lending::state::{ReserveUsage, UserPosition};
+use mock_kamino::state::{Obligation, Reserve};
 
 #[test]
 fn test_kamino_borrow_obligation_liquidity() {
-    let program_id = PROGRAM_ID;
-
-    // Create unique pubkeys for all accounts
-    let owner_pubkey = Pubkey::new_unique();
-    let obligation_pubkey = Pubkey::new_unique();
-    let lending_market_pubkey = Pubkey::new_unique();
-    let lending_market_authority_pubkey = Pubkey::new_unique();
-    let borrow_reserve_pubkey = Pubkey::new_unique();
-    let borrow_reserve_liquidity_mint_pubkey = Pubkey::new_unique();
-    let reserve_source_liquidity_pubkey = Pubkey::new_unique();
-    let borrow_reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
-    let user_destination_liquidity_pubkey = Pubkey::new_unique();
-    let token_program_pubkey = Pubkey::new_unique();
-    let instruction_sysvar_pubkey = sysvar::instructions::id();
-    let kamino_lending_program_pubkey = Pubkey::new_unique();
-
-    // Create a referrer token state pubkey for this test
-    let referrer_token_state_pubkey = Pubkey::new_unique();
-    let use_referrer = true; // Set to true to use referrer, false to use token program as fallback
-
-    // Build accounts required by the instruction
-    let mut accounts = vec![
-        AccountMeta::new_readonly(owner_pubkey, true),
-        AccountMeta::new(obligation_pubkey, false),
-        AccountMeta::new_readonly(lending_market_pubkey, false),
-        AccountMeta::new_readonly(lending_market_authority_pubkey, false),
-        AccountMeta::new(borrow_reserve_pubkey, false),
-        AccountMeta::new_readonly(borrow_reserve_liquidity_mint_pubkey, false),
-        AccountMeta::new(reserve_source_liquidity_pubkey, false),
-        AccountMeta::new(borrow_reserve_liquidity_fee_receiver_pubkey, false),
-        AccountMeta::new(user_destination_liquidity_pubkey, false),
-    ];
-
-    // Handle referrer token state - make it mutable when used
-    if use_referrer {
-        accounts.push(AccountMeta::new(referrer_token_state_pubkey, false));
-    } else {
-        accounts.push(AccountMeta::new_readonly(token_program_pubkey, false));
-    }
-
-    // Add remaining required accounts
-    accounts.push(AccountMeta::new_readonly(token_program_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(instruction_sysvar_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(kamino_lending_program_pubkey, false));
-
-    // Set borrow amount
-    let liquidity_amount: u64 = 1_000_000_000;
-
-    // Create the instruction
+    let mollusk = mollusk();
+    let market = Market::new();
+    let owner = Pubkey::new_unique();
+    let protocol = Protocol::new(&market, &owner);
+    let obligation = Pubkey::new_unique();
+    let user_destination_liquidity = Pubkey::new_unique();
+
+    let liquidity_amount = 100 * TOKEN;
     let instruction = Instruction {
-        program_id,
-        accounts,
-        data: KaminoBorrowObligationLiquidity {
-            liquidity_amount,
-        }.data(),
+        program_id: liquidity_lending::ID,
+        accounts: liquidity_lending::accounts::KaminoBorrowObligationLiquidity {
+            owner,
+            user_position: protocol.user_position,
+            user_borrows: protocol.user_borrows,
+            config: protocol.config,
+            registry: protocol.registry,
+            reserve_usage: protocol.reserve_usage,
+            obligation,
+            lending_market: market.lending_market,
+            lending_market_authority: market.lending_market_authority,
+            borrow_reserve: market.reserve,
+            borrow_reserve_liquidity_mint: market.liquidity_mint,
+            reserve_source_liquidity: market.liquidity_supply,
+            borrow_reserve_liquidity_fee_receiver: market.fee_vault,
+            user_destination_liquidity,
+            referrer_token_state: None,
+            pyth_price: Some(market.pyth_price),
+            switchboard_price: None,
+            switchboard_twap: None,
+            scope_prices: None,
+            token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::KaminoBorrowObligationLiquidity { liquidity_amount }.data(),
     };
 
-    // Define account states for testing
-    let mut mollusk_accounts = vec![
-        (owner_pubkey, Account::default()),
-        (obligation_pubkey, Account::default()),
-        (lending_market_pubkey, Account::default()),
-        (lending_market_authority_pubkey, Account::default()),
-        (borrow_reserve_pubkey, Account::default()),
-        (borrow_reserve_liquidity_mint_pubkey, Account::default()),
-        (reserve_source_liquidity_pubkey, Account::default()),
-        (borrow_reserve_liquidity_fee_receiver_pubkey, Account::default()),
-        (user_destination_liquidity_pubkey, Account::default()),
-        (token_program_pubkey, Account::default()),
-        (instruction_sysvar_pubkey, Account::default()),
-        (kamino_lending_program_pubkey, Account::default()),
-    ];
-
-    // Add referrer token state account if used
-    if use_referrer {
-        mollusk_accounts.push((referrer_token_state_pubkey, Account::default()));
-    }
-
-    // Initialize Mollusk instance with your compiled program ELF
-    let mut mollusk = Mollusk::default();
+    // $2,000 of collateral allows $1,500 of debt
+    let mut accounts = market.kamino_accounts(&market.reserve_state());
+    accounts.extend(protocol.accounts(&market, &owner, &obligation));
+    let collateral = market.obligation_state(owner, 2_000 * TOKEN);
+    accounts.push((obligation, kamino_account(collateral.account_data().unwrap())));
+    accounts.push((user_destination_liquidity, token_account(market.liquidity_mint, owner, 0)));
 
-    // Add the program to Mollusk's cache
-    let program_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("../../target/deploy/liquidity_lending.so");
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
 
-    // Read the program ELF
-    let program_elf = std::fs::read(program_path).expect("Failed to read program file");
+    let account = |key| result.get_account(key).unwrap();
+    assert_eq!(token_balance(account(&user_destination_liquidity)), liquidity_amount);
+    assert_eq!(token_balance(account(&market.liquidity_supply)), 999_900 * TOKEN);
 
-    // Add the program to Mollusk
-    mollusk.add_program_with_elf_and_loader(&program_id, &program_elf, &mollusk_svm::program::loader_keys::LOADER_V3);
+    let reserve = Reserve::decode(&account(&market.reserve).data).unwrap();
+    assert_eq!(reserve.liquidity.available_amount, 999_900 * TOKEN);
+    assert_eq!(reserve.liquidity.borrowed_amount_sf, u128::from(liquidity_amount) * ONE);
 
-    // Define checks
-    let checks = vec![
-        Check::success(),
-    ];
-
-    // Execute instruction and validate results
-    mollusk.process_and_validate_instruction(
-        &instruction,
-        &mollusk_accounts,
-        &checks,
-    );
-}
+    let obligation = Obligation::decode(&account(&obligation).data).unwrap();
+    let borrow = &obligation.borrows[obligation.find_borrow(&market.reserve).unwrap()];
+    assert_eq!(borrow.borrowed_amount_sf, u128::from(liquidity_amount) * ONE);
+    assert_eq!(obligation.borrowed_assets_market_value_sf, 100 * ONE);
 
-// Helper function to serialize instruction data for Kamino
-fn serialize_kamino_instruction(instruction_index: u8, amount: &u64) -> Vec<u8> {
-    let mut data = vec![instruction_index];
-    data.extend_from_slice(&amount.to_le_bytes());
-    data
+    let reserve_usage: ReserveUsage = decode(account(&protocol.reserve_usage));
+    assert_eq!(reserve_usage.epoch_borrowed, liquidity_amount);
+    let user_position: UserPosition = decode(account(&protocol.user_position));
+    assert_eq!(user_position.reserve(&market.reserve).unwrap().borrowed, liquidity_amount);
 }
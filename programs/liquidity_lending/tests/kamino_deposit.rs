@@ -1,115 +1,68 @@
-use std::path::PathBuf;
-use mollusk_svm::{Mollusk, result::Check};
-use anchor_lang::{prelude::*, solana_program::{
-    sysvar,
-    instruction::Instruction,
-}, InstructionData};
+mod common;
 
-use solana_account::Account;
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction, solana_program::sysvar, InstructionData, ToAccountMetas};
+use mollusk_svm::result::Check;
 
-use liquidity_lending::{
-    ID as PROGRAM_ID,
-    instruction::KaminoDepositReserveLiquidity
-};
+use common::{decode, mollusk, token_account, token_balance, Market, Protocol, TOKEN};
+use liquidity_lending::state::{ReserveUsage, UserPosition};
+use mock_kamino::state::Reserve;
 
 #[test]
 fn test_kamino_deposit_reserve_liquidity() {
-    let program_id = PROGRAM_ID;
+    let mollusk = mollusk();
+    let market = Market::new();
+    let owner = Pubkey::new_unique();
+    let protocol = Protocol::new(&market, &owner);
+    let user_source_liquidity = Pubkey::new_unique();
+    let user_destination_collateral = Pubkey::new_unique();
 
-    let owner_pubkey = Pubkey::new_unique();
-    let reserve_pubkey = Pubkey::new_unique();
-    let lending_market_pubkey = Pubkey::new_unique();
-    let lending_market_authority_pubkey = Pubkey::new_unique();
-    let reserve_liquidity_mint_pubkey = Pubkey::new_unique();
-    let reserve_liquidity_supply_pubkey = Pubkey::new_unique();
-    let reserve_collateral_mint_pubkey = Pubkey::new_unique();
-    let user_source_liquidity_pubkey = Pubkey::new_unique();
-    let user_destination_collateral_pubkey = Pubkey::new_unique();
-    let collateral_token_program_pubkey = Pubkey::new_unique();
-    let liquidity_token_program_pubkey = Pubkey::new_unique();
-    let instruction_sysvar_pubkey = sysvar::instructions::id();
-    let kamino_lending_program_pubkey = Pubkey::new_unique();
-    // let system_program_pubkey = system_program::ID;
-
-    // Build CPI accounts required by the instruction
-    let accounts = vec![
-        AccountMeta::new_readonly(owner_pubkey, true),
-        AccountMeta::new(reserve_pubkey, false),
-        AccountMeta::new_readonly(lending_market_pubkey, false),
-        AccountMeta::new_readonly(lending_market_authority_pubkey, false),
-        AccountMeta::new_readonly(reserve_liquidity_mint_pubkey, false),
-        AccountMeta::new(reserve_liquidity_supply_pubkey, false),
-        AccountMeta::new(reserve_collateral_mint_pubkey, false),
-        AccountMeta::new(user_source_liquidity_pubkey, false),
-        AccountMeta::new(user_destination_collateral_pubkey, false),
-        AccountMeta::new_readonly(collateral_token_program_pubkey, false),
-        AccountMeta::new_readonly(liquidity_token_program_pubkey, false),
-        AccountMeta::new_readonly(instruction_sysvar_pubkey, false),
-        AccountMeta::new_readonly(kamino_lending_program_pubkey, false),
-        // AccountMeta::new_readonly(system_program_pubkey, false),
-    ];
-
-    // Set initial liquidity amount
-    let liquidity_amount: u64 = 1_000_000_000;
-
-    // Create the instruction
+    let liquidity_amount = 1_000 * TOKEN;
     let instruction = Instruction {
-        program_id,
-        accounts,
-        data: KaminoDepositReserveLiquidity {
-            liquidity_amount,
-        }.data()
+        program_id: liquidity_lending::ID,
+        accounts: liquidity_lending::accounts::KaminoDepositReserveLiquidity {
+            owner,
+            user_position: protocol.user_position,
+            config: protocol.config,
+            registry: protocol.registry,
+            reserve_usage: protocol.reserve_usage,
+            reserve: market.reserve,
+            lending_market: market.lending_market,
+            lending_market_authority: market.lending_market_authority,
+            reserve_liquidity_mint: market.liquidity_mint,
+            reserve_liquidity_supply: market.liquidity_supply,
+            reserve_collateral_mint: market.collateral_mint,
+            user_source_liquidity,
+            user_destination_collateral,
+            collateral_token_program: anchor_spl::token::ID,
+            liquidity_token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::KaminoDepositReserveLiquidity { liquidity_amount }.data(),
     };
 
-    // Define account states for testing
-    let mollusk_accounts = vec![
-        (owner_pubkey, Account::default()),
-        (reserve_pubkey, Account::default()),
-        (lending_market_pubkey, Account::default()),
-        (lending_market_authority_pubkey, Account::default()),
-        (reserve_liquidity_mint_pubkey, Account::default()),
-        (reserve_liquidity_supply_pubkey, Account::default()),
-        (reserve_collateral_mint_pubkey, Account::default()),
-        (user_source_liquidity_pubkey, Account::default()),
-        (user_destination_collateral_pubkey, Account::default()),
-        (collateral_token_program_pubkey, Account::default()),
-        (liquidity_token_program_pubkey, Account::default()),
-        (instruction_sysvar_pubkey, Account::default()),
-        (kamino_lending_program_pubkey, Account::default()),
-        // (system_program_pubkey, Account::default()),
-    ];
-
-    // Initialize Mollusk instance with your compiled program ELF
-    // let mut mollusk = Mollusk::new(&program_id, "liquidity_lending");
-    // Create a mutable Mollusk instance
-    let mut mollusk = Mollusk::default();
+    let mut accounts = market.kamino_accounts(&market.reserve_state());
+    accounts.extend(protocol.accounts(&market, &owner, &Pubkey::new_unique()));
+    accounts.push((user_source_liquidity, token_account(market.liquidity_mint, owner, 5_000 * TOKEN)));
+    accounts.push((user_destination_collateral, token_account(market.collateral_mint, owner, 0)));
 
-    // Add the program to Mollusk's cache
-    let program_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("../../target/deploy/liquidity_lending.so");
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
 
-    // Read the program ELF
-    let program_elf = std::fs::read(program_path).expect("Failed to read program file");
+    // The reserve has not earned interest, so collateral is minted one to one
+    let account = |key| result.get_account(key).unwrap();
+    assert_eq!(token_balance(account(&user_source_liquidity)), 4_000 * TOKEN);
+    assert_eq!(token_balance(account(&user_destination_collateral)), liquidity_amount);
+    assert_eq!(token_balance(account(&market.liquidity_supply)), 1_001_000 * TOKEN);
 
-    // Add the program to Mollusk
-    mollusk.add_program_with_elf_and_loader(&program_id, &program_elf, &mollusk_svm::program::loader_keys::LOADER_V3);
-
-    // Define checks (adjust according to your expected outcomes)
-    let checks = vec![
-        Check::success(),
-        // You can add more checks here, e.g., account balances or data changes
-    ];
-
-    // Execute instruction and validate results
-    mollusk.process_and_validate_instruction(
-        &instruction,
-        &mollusk_accounts,
-        &checks,
-    );
-}
+    let reserve = Reserve::decode(&account(&market.reserve).data).unwrap();
+    assert_eq!(reserve.liquidity.available_amount, 1_001_000 * TOKEN);
+    assert_eq!(reserve.collateral.mint_total_supply, 1_001_000 * TOKEN);
 
-fn serialize_kamino_instruction(instruction_index: u8, amount: &u64) -> Vec<u8> {
-    let mut data = vec![instruction_index];
-    data.extend_from_slice(&amount.to_le_bytes());
-    data
+    let reserve_usage: ReserveUsage = decode(account(&protocol.reserve_usage));
+    assert_eq!(reserve_usage.exposure, liquidity_amount);
+    let user_position: UserPosition = decode(account(&protocol.user_position));
+    assert_eq!(user_position.reserve(&market.reserve).unwrap().deposited, liquidity_amount);
 }
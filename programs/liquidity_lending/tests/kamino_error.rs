@@ -0,0 +1,39 @@
+use liquidity_lending::kamino_error::{describe, lookup, KAMINO_ERRORS, KAMINO_ERROR_OFFSET};
+use liquidity_lending::LendingError;
+
+#[test]
+fn test_error_table_is_indexed_by_code() {
+    for (index, error) in KAMINO_ERRORS.iter().enumerate() {
+        assert_eq!(error.code, KAMINO_ERROR_OFFSET + index as u32, "{}", error.name);
+    }
+}
+
+#[test]
+fn test_lookup() {
+    assert_eq!(lookup(6009).unwrap().name, "ReserveStale");
+    assert_eq!(lookup(6013).unwrap().name, "BorrowTooLarge");
+    assert_eq!(lookup(6017).unwrap().name, "ObligationStale");
+    assert_eq!(lookup(6064).unwrap().name, "WithdrawalCapReached");
+    assert_eq!(lookup(6122).unwrap().code, 6122);
+    assert!(lookup(5999).is_none());
+    assert!(lookup(6123).is_none());
+    assert!(lookup(0).is_none());
+}
+
+#[test]
+fn test_lending_error_mapping() {
+    assert!(matches!(lookup(6009).unwrap().lending_error(), Some(LendingError::ReserveStale)));
+    assert!(matches!(lookup(6013).unwrap().lending_error(), Some(LendingError::BorrowTooLarge)));
+    assert!(matches!(lookup(6017).unwrap().lending_error(), Some(LendingError::ObligationStale)));
+    assert!(matches!(
+        lookup(6064).unwrap().lending_error(),
+        Some(LendingError::WithdrawalCapReached)
+    ));
+    assert!(lookup(6000).unwrap().lending_error().is_none());
+}
+
+#[test]
+fn test_describe() {
+    assert_eq!(describe(6009), "Kamino error 6009 ReserveStale: Reserve state needs to be refreshed");
+    assert_eq!(describe(42), "Unknown Kamino error 42");
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program_error::ProgramError};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use mollusk_svm::result::Check;
+use solana_account::Account;
+
+use common::{
+    decode, mint_account, mollusk, program_account, system_account, token_account, token_balance, Market,
+    Protocol, TOKEN,
+};
+use liquidity_lending::{
+    farms::{UserState, FARMS_MAX_REWARDS, USER_STATE_DISCRIMINATOR},
+    kamino::{discriminator, LastUpdate, ReserveFarmKind, ReserveHeader},
+    state::Config,
+    LendingError,
+};
+use mock_farms::FARM_VAULTS_AUTHORITY_SEED;
+
+fn account_data<T: AnchorSerialize>(discriminator: &[u8; 8], value: &T) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    value.serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_user_state_pending_rewards() {
+    let farms_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let mut rewards_issued_unclaimed = [0u64; FARMS_MAX_REWARDS];
+    rewards_issued_unclaimed[0] = 1_500;
+    rewards_issued_unclaimed[3] = 42;
+
+    let user_state = UserState {
+        user_id: 7,
+        farm_state: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        is_farm_delegated: 1,
+        padding0: [0; 7],
+        rewards_tally_scaled: [0; FARMS_MAX_REWARDS],
+        rewards_issued_unclaimed,
+        last_claim_ts: [0; FARMS_MAX_REWARDS],
+        active_stake_scaled: 1 << 60,
+        pending_deposit_stake_scaled: 0,
+        pending_deposit_stake_ts: 0,
+        pending_withdrawal_unstake_scaled: 0,
+        pending_withdrawal_unstake_ts: 0,
+        bump: 255,
+        delegatee: Pubkey::new_unique(),
+        last_stake_ts: 1_700_000_000,
+        padding1: [0; 50],
+    };
+
+    let mut lamports = 0;
+    let mut data = account_data(&USER_STATE_DISCRIMINATOR, &user_state);
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &farms_program, false, 0);
+
+    let decoded = UserState::load(&info, &farms_program).unwrap();
+    assert_eq!(decoded, user_state);
+    assert_eq!(decoded.pending_rewards(0), Some(1_500));
+    assert_eq!(decoded.pending_rewards(3), Some(42));
+    assert_eq!(decoded.pending_rewards(FARMS_MAX_REWARDS), None);
+
+    // A user state owned by another program is rejected
+    assert!(UserState::load(&info, &Pubkey::new_unique()).is_err());
+}
+
+#[test]
+fn test_reserve_header_farms() {
+    let kamino_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let header = ReserveHeader {
+        version: 1,
+        last_update: LastUpdate::default(),
+        lending_market: Pubkey::new_unique(),
+        farm_collateral: Pubkey::new_unique(),
+        farm_debt: Pubkey::default(),
+    };
+
+    let mut lamports = 0;
+    let mut data = account_data(&discriminator::RESERVE, &header);
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &kamino_program, false, 0);
+
+    let decoded = ReserveHeader::load(&info, &kamino_program).unwrap();
+    assert_eq!(decoded.farm(ReserveFarmKind::Collateral), Some(header.farm_collateral));
+    assert_eq!(decoded.farm(ReserveFarmKind::Debt), None);
+}
+
+#[test]
+fn test_claim_farm_rewards_harvests_through_farms() {
+    let market = Market::new();
+    let owner = Pubkey::new_unique();
+    let protocol = Protocol::new(&market, &owner);
+    let farm_state = Pubkey::new_unique();
+    let global_config = Pubkey::new_unique();
+    let farm_vaults_authority =
+        Pubkey::find_program_address(&[FARM_VAULTS_AUTHORITY_SEED, farm_state.as_ref()], &mock_farms::ID).0;
+    let user_state = Pubkey::new_unique();
+    let reward_mint = Pubkey::new_unique();
+    let user_reward_ata = Pubkey::new_unique();
+    let rewards_vault = Pubkey::new_unique();
+    let rewards_treasury_vault = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+
+    let mut rewards_issued_unclaimed = [0u64; FARMS_MAX_REWARDS];
+    rewards_issued_unclaimed[2] = 300 * TOKEN;
+    let state = UserState {
+        user_id: 0,
+        farm_state,
+        owner,
+        is_farm_delegated: 0,
+        padding0: [0; 7],
+        rewards_tally_scaled: [0; FARMS_MAX_REWARDS],
+        rewards_issued_unclaimed,
+        last_claim_ts: [0; FARMS_MAX_REWARDS],
+        active_stake_scaled: 1 << 60,
+        pending_deposit_stake_scaled: 0,
+        pending_deposit_stake_ts: 0,
+        pending_withdrawal_unstake_scaled: 0,
+        pending_withdrawal_unstake_ts: 0,
+        bump: 255,
+        delegatee: owner,
+        last_stake_ts: 0,
+        padding1: [0; 50],
+    };
+    let farms_account = |data: Vec<u8>| Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: mock_farms::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let obligation = Pubkey::new_unique();
+    let mut accounts = protocol.accounts(&market, &owner, &obligation);
+    accounts.extend([
+        (user_state, farms_account(account_data(&USER_STATE_DISCRIMINATOR, &state))),
+        (farm_state, farms_account(vec![0; 8])),
+        (global_config, system_account()),
+        (farm_vaults_authority, Account::default()),
+        (reward_mint, mint_account(Pubkey::new_unique(), 1_000 * TOKEN)),
+        (user_reward_ata, token_account(reward_mint, owner, 0)),
+        (rewards_vault, token_account(reward_mint, farm_vaults_authority, 1_000 * TOKEN)),
+        (rewards_treasury_vault, token_account(reward_mint, farm_vaults_authority, 0)),
+        (vault, token_account(reward_mint, Pubkey::new_unique(), 0)),
+        (mock_farms::ID, mollusk_svm::program::create_program_account_loader_v3(&mock_farms::ID)),
+        (mock_kamino::ID, mollusk_svm::program::create_program_account_loader_v3(&mock_kamino::ID)),
+        mollusk_svm_programs_token::token::keyed_account(),
+    ]);
+
+    let claim = |farms_program: Pubkey, rewards_destination: Option<Pubkey>, fee_account: Option<Pubkey>| Instruction {
+        program_id: liquidity_lending::ID,
+        accounts: liquidity_lending::accounts::KaminoClaimFarmRewards {
+            owner,
+            config: protocol.config,
+            registry: protocol.registry,
+            reserve: market.reserve,
+            lending_market: market.lending_market,
+            obligation_farm_user_state: user_state,
+            farm_state,
+            global_config,
+            reward_mint,
+            user_reward_ata,
+            rewards_vault,
+            rewards_treasury_vault,
+            farm_vaults_authority,
+            scope_prices: None,
+            rewards_destination,
+            fee_account,
+            token_program: anchor_spl::token::ID,
+            farms_program,
+            kamino_lending_program: mock_kamino::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::KaminoClaimFarmRewards { reward_index: 2 }.data(),
+    };
+    let mollusk = mollusk();
+
+    // Only Kamino's farms program is called
+    mollusk.process_and_validate_instruction(
+        &claim(mock_kamino::ID, None, None),
+        &accounts,
+        &[Check::err(ProgramError::Custom(ErrorCode::ConstraintAddress.into()))],
+    );
+
+    // Only a farm attached to the approved reserve pays out
+    mollusk.process_and_validate_instruction(
+        &claim(mock_farms::ID, None, None),
+        &accounts,
+        &[Check::err(ProgramError::Custom(u32::from(LendingError::InvalidFarmAccount)))],
+    );
+    let (_, reserve_account) = accounts.iter_mut().find(|(key, _)| *key == market.reserve).unwrap();
+    let mut header = ReserveHeader::deserialize(&mut &reserve_account.data[8..]).unwrap();
+    header.farm_debt = farm_state;
+    header.serialize(&mut &mut reserve_account.data[8..]).unwrap();
+
+    // The pending rewards land in the owner's account, and the farm clears them
+    let result =
+        mollusk.process_and_validate_instruction(&claim(mock_farms::ID, None, None), &accounts, &[Check::success()]);
+    let account = |key: &Pubkey| &result.resulting_accounts.iter().find(|(address, _)| address == key).unwrap().1;
+    assert_eq!(token_balance(account(&user_reward_ata)), 300 * TOKEN);
+    assert_eq!(token_balance(account(&rewards_vault)), 700 * TOKEN);
+    let harvested = UserState::deserialize(&mut &account(&user_state).data[8..]).unwrap();
+    assert_eq!(harvested.pending_rewards(2), Some(0));
+
+    // Or are forwarded on to another account
+    let result = mollusk.process_and_validate_instruction(
+        &claim(mock_farms::ID, Some(vault), None),
+        &accounts,
+        &[Check::success()],
+    );
+    let account = |key: &Pubkey| &result.resulting_accounts.iter().find(|(address, _)| address == key).unwrap().1;
+    assert_eq!(token_balance(account(&user_reward_ata)), 0);
+    assert_eq!(token_balance(account(&vault)), 300 * TOKEN);
+
+    // With a 10% fee, the fee recipient's account is required and paid before forwarding
+    let fee_recipient = Pubkey::new_unique();
+    let fee_account = Pubkey::new_unique();
+    let (_, config_account) = accounts.iter_mut().find(|(key, _)| *key == protocol.config).unwrap();
+    let mut config: Config = decode(config_account);
+    config.fee_bps = 1_000;
+    config.fee_recipient = fee_recipient;
+    *config_account = program_account(&config);
+    accounts.push((fee_account, token_account(reward_mint, fee_recipient, 0)));
+
+    mollusk.process_and_validate_instruction(
+        &claim(mock_farms::ID, Some(vault), None),
+        &accounts,
+        &[Check::err(ProgramError::Custom(u32::from(LendingError::MissingFeeAccount)))],
+    );
+    mollusk.process_and_validate_instruction(
+        &claim(mock_farms::ID, Some(vault), Some(vault)),
+        &accounts,
+        &[Check::err(ProgramError::Custom(ErrorCode::ConstraintTokenOwner.into()))],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &claim(mock_farms::ID, Some(vault), Some(fee_account)),
+        &accounts,
+        &[Check::success()],
+    );
+    let account = |key: &Pubkey| &result.resulting_accounts.iter().find(|(address, _)| address == key).unwrap().1;
+    assert_eq!(token_balance(account(&fee_account)), 30 * TOKEN);
+    assert_eq!(token_balance(account(&vault)), 270 * TOKEN);
+    assert_eq!(token_balance(account(&user_reward_ata)), 0);
+}
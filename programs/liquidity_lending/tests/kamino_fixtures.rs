@@ -0,0 +1,184 @@
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::sysvar::clock;
+use mollusk_svm::result::Check;
+
+use common::fixtures::{self, account_json, parse_account, Fixtures};
+use common::{fast_forward, kamino_account, pyth_price_update, Market, NOW, ONE, SLOT_DURATION_MS};
+use liquidity_lending::kamino::{
+    deserialize_data, discriminator as kamino_discriminator, reserve_layout, TokenInfo,
+};
+use liquidity_lending::oracle::{
+    discriminator, read_price, read_twap, scope_layout, switchboard_layout, OracleAccounts,
+};
+use mock_kamino::state::{self as kamino, Reserve};
+
+const SLOTS_PER_DAY: u64 = 24 * 60 * 60 * 1_000 / SLOT_DURATION_MS;
+
+/// The clock sysvar at slot 300,000,000, as printed by `solana account --output json`
+const CLOCK_JSON: &str = r#"{"pubkey":"SysvarC1ock11111111111111111111111111111111","account":{"lamports":1169280,"data":["AKPhEQAAAACAkClmAAAAALYCAAAAAAAAtwIAAAAAAAAgFytmAAAAAA==","base64"],"owner":"Sysvar1111111111111111111111111111111111111","executable":false,"rentEpoch":18446744073709551615,"space":40}}"#;
+
+/// Lower 128 bits of a cumulative borrow rate, which is where realistic rates live
+fn cumulative_borrow_rate(reserve: &Reserve) -> u128 {
+    let value = reserve.liquidity.cumulative_borrow_rate_bsf.value;
+    u128::from(value[0]) | u128::from(value[1]) << 64
+}
+
+#[test]
+fn test_parse_cli_account_json() {
+    let (key, account) = parse_account(CLOCK_JSON);
+    assert_eq!(key, clock::ID);
+    assert_eq!(account.lamports, 1_169_280);
+    assert_eq!(account.owner, anchor_lang::solana_program::sysvar::ID);
+    assert_eq!(account.rent_epoch, u64::MAX);
+    assert_eq!(account.data.len(), 40);
+
+    let mut fixtures = Fixtures::default();
+    fixtures.insert("clock", key, account.clone());
+    let clock = fixtures.clock();
+    assert_eq!((clock.slot, clock.epoch, clock.leader_schedule_epoch), (300_000_000, 694, 695));
+    assert_eq!(clock.unix_timestamp, 1_714_100_000);
+    assert_eq!(fixtures.key("clock"), clock::ID);
+
+    // Snapshots read back as they were written
+    assert_eq!(parse_account(&account_json(&key, &account)), (key, account));
+}
+
+#[test]
+fn test_set_reserve_price_on_every_oracle() {
+    let market = Market::new();
+    let switchboard = Pubkey::new_unique();
+    let scope = Pubkey::new_unique();
+
+    // Pyth, Switchboard, and a two-entry Scope chain with an 8 decimal first entry
+    let mut data = market.reserve_data(&market.reserve_state());
+    let mut token_info: TokenInfo =
+        deserialize_data(&data, &kamino_discriminator::RESERVE, reserve_layout::CONFIG_TOKEN_INFO).unwrap();
+    token_info.switchboard_configuration.price_aggregator = switchboard;
+    token_info.scope_configuration.price_feed = scope;
+    token_info.scope_configuration.price_chain = [3, 7, scope_layout::CHAIN_END, scope_layout::CHAIN_END];
+    kamino::write(&mut data, reserve_layout::CONFIG_TOKEN_INFO, &token_info).unwrap();
+
+    let mut switchboard_data = vec![0; 8 + switchboard_layout::RESULT + 128];
+    switchboard_data[..8].copy_from_slice(&discriminator::SWITCHBOARD_PULL_FEED);
+    let mut scope_data =
+        vec![0; 8 + scope_layout::PRICES + scope_layout::MAX_ENTRIES * scope_layout::DATED_PRICE_SIZE];
+    scope_data[..8].copy_from_slice(&discriminator::SCOPE_ORACLE_PRICES);
+    let exp = 8 + scope_layout::PRICES + 3 * scope_layout::DATED_PRICE_SIZE + 8;
+    scope_data[exp..exp + 8].copy_from_slice(&8u64.to_le_bytes());
+
+    let mut fixtures = Fixtures::default();
+    fixtures.insert("reserve", market.reserve, kamino_account(data));
+    fixtures.insert("pyth", market.pyth_price, kamino_account(pyth_price_update(100_000_000, NOW - 600)));
+    fixtures.insert("switchboard", switchboard, kamino_account(switchboard_data));
+    fixtures.insert("scope", scope, kamino_account(scope_data));
+
+    let clock = Clock { slot: 5_000, unix_timestamp: NOW, ..Clock::default() };
+    fixtures.set_reserve_price(&market.reserve, 2.5, &clock);
+
+    for key in [market.pyth_price, switchboard, scope] {
+        let mut account = fixtures.get(&key).unwrap().clone();
+        let info = AccountInfo::new(&key, false, false, &mut account.lamports, &mut account.data, &account.owner, false, 0);
+        // Read each oracle alone, as if the reserve configured only that one
+        let mut config = token_info;
+        let accounts = if key == market.pyth_price {
+            config.switchboard_configuration = Default::default();
+            config.scope_configuration.price_feed = Pubkey::default();
+            OracleAccounts { pyth_price: Some(&info), ..OracleAccounts::default() }
+        } else if key == switchboard {
+            config.pyth_configuration = Default::default();
+            config.scope_configuration.price_feed = Pubkey::default();
+            OracleAccounts { switchboard_price: Some(&info), ..OracleAccounts::default() }
+        } else {
+            config.pyth_configuration = Default::default();
+            config.switchboard_configuration = Default::default();
+            OracleAccounts { scope_prices: Some(&info), ..OracleAccounts::default() }
+        };
+        let price = read_price(&config, &accounts, NOW).unwrap();
+        assert_eq!((price.price_sf, price.timestamp), (5 * ONE / 2, NOW));
+        let twap = read_twap(&config, &accounts, NOW).unwrap();
+        assert!(twap.is_none_or(|twap| twap.price_sf == price.price_sf));
+    }
+}
+
+#[test]
+fn test_refresh_reserve_instruction() {
+    let market = Market::new();
+    let mut fixtures = Fixtures::default();
+    fixtures.insert("reserve", market.reserve, kamino_account(market.reserve_data(&market.reserve_state())));
+
+    // Kamino's `refreshReserve` discriminator, and its own id for the oracles not configured
+    let ix = fixtures.refresh_reserve(&market.reserve);
+    assert_eq!(ix.program_id, mock_kamino::ID);
+    assert_eq!(ix.data, mock_kamino::instruction::RefreshReserve::DISCRIMINATOR);
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| (meta.pubkey, meta.is_writable)).collect();
+    assert_eq!(
+        keys,
+        [
+            (market.reserve, true),
+            (market.lending_market, false),
+            (market.pyth_price, false),
+            (mock_kamino::ID, false),
+            (mock_kamino::ID, false),
+            (mock_kamino::ID, false),
+        ]
+    );
+}
+
+/// Write the reserve before and after Kamino refreshed it, with its lending market, to
+/// `fixtures/refresh/{reserve}` for the client's accrual tests
+fn capture_refresh(fixtures: &Fixtures, reserve: &Pubkey, before: &solana_account::Account) {
+    let dir = fixtures::fixtures_dir().join("refresh").join(reserve.to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    let lending_market = Reserve::decode(&before.data).unwrap().lending_market;
+    let snapshots = [
+        ("before", reserve, before),
+        ("after", reserve, fixtures.get(reserve).unwrap()),
+        ("lending_market", &lending_market, fixtures.get(&lending_market).unwrap()),
+    ];
+    for (name, key, account) in snapshots {
+        std::fs::write(dir.join(format!("{name}.json")), account_json(key, account)).unwrap();
+    }
+}
+
+#[test]
+fn test_refresh_dumped_reserve_a_day_later() {
+    let Some(mut fixtures) = Fixtures::dumped() else {
+        eprintln!("no mainnet dump in fixtures/, skipping; run fixtures/dump.sh to replay one");
+        return;
+    };
+    let mut mollusk = fixtures::mollusk(&fixtures);
+    let reserve = fixtures.key("reserve");
+    let dumped = fixtures.account("reserve").clone();
+    let before = Reserve::decode(&dumped.data).unwrap();
+
+    // A day on, at a price 1% above the cached one
+    fast_forward(&mut mollusk, SLOTS_PER_DAY);
+    let clock = mollusk.sysvars.clock.clone();
+    let price = before.liquidity.market_price_sf as f64 / ONE as f64 * 1.01;
+    fixtures.set_reserve_price(&reserve, price, &clock);
+
+    let result = mollusk.process_and_validate_instruction(
+        &fixtures.refresh_reserve(&reserve),
+        &fixtures.accounts(),
+        &[Check::success()],
+    );
+    fixtures.update(&result.resulting_accounts);
+    if std::env::var_os("CAPTURE_REFRESH").is_some() {
+        capture_refresh(&fixtures, &reserve, &dumped);
+    }
+
+    let after = Reserve::decode(&fixtures.account("reserve").data).unwrap();
+    assert_eq!(after.last_update.slot, clock.slot);
+    assert_eq!(after.liquidity.market_price_last_updated_ts, clock.unix_timestamp as u64);
+    let refreshed_price = after.liquidity.market_price_sf as f64 / ONE as f64;
+    assert!((refreshed_price / price - 1.0).abs() < 1e-4, "{refreshed_price} != {price}");
+
+    // A day of interest on whatever was borrowed
+    if before.liquidity.borrowed_amount_sf > 0 {
+        assert!(cumulative_borrow_rate(&after) > cumulative_borrow_rate(&before));
+        assert!(after.liquidity.borrowed_amount_sf > before.liquidity.borrowed_amount_sf);
+    }
+}
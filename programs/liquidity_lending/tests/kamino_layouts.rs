@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::Discriminator;
+
+use liquidity_lending::kamino::{
+    discriminator, instruction_discriminator, obligation_borrowed_amount_sf, obligation_deposited_amount, obligation_layout,
+    obligation_reserves, reserve_layout, ObligationMetrics, ReserveStatus,
+};
+use mock_kamino::state::{Obligation, ObligationCollateral, ObligationLiquidity};
+
+/// Size of a Kamino `Obligation` account, discriminator included
+const OBLIGATION_SIZE: usize = 3344;
+
+/// Size of a Kamino `Reserve` account, discriminator included
+const RESERVE_SIZE: usize = 8624;
+
+fn write_u128(data: &mut [u8], offset: usize, value: u128) {
+    data[8 + offset..8 + offset + 16].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Anchor's discriminator for the instruction `name`
+fn sighash(name: &str) -> [u8; 8] {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].try_into().unwrap()
+}
+
+#[test]
+fn test_instruction_discriminators() {
+    use mock_kamino::instruction as mock;
+
+    let cases: [(&str, [u8; 8], &[u8]); 14] = [
+        ("refresh_reserve", instruction_discriminator::REFRESH_RESERVE, mock::RefreshReserve::DISCRIMINATOR),
+        (
+            "deposit_reserve_liquidity",
+            instruction_discriminator::DEPOSIT_RESERVE_LIQUIDITY,
+            mock::DepositReserveLiquidity::DISCRIMINATOR,
+        ),
+        (
+            "init_obligation_farms_for_reserve",
+            instruction_discriminator::INIT_OBLIGATION_FARMS_FOR_RESERVE,
+            mock::InitObligationFarmsForReserve::DISCRIMINATOR,
+        ),
+        (
+            "refresh_obligation_farms_for_reserve",
+            instruction_discriminator::REFRESH_OBLIGATION_FARMS_FOR_RESERVE,
+            mock::RefreshObligationFarmsForReserve::DISCRIMINATOR,
+        ),
+        ("refresh_obligation", instruction_discriminator::REFRESH_OBLIGATION, mock::RefreshObligation::DISCRIMINATOR),
+        (
+            "deposit_obligation_collateral",
+            instruction_discriminator::DEPOSIT_OBLIGATION_COLLATERAL,
+            mock::DepositObligationCollateral::DISCRIMINATOR,
+        ),
+        (
+            "borrow_obligation_liquidity",
+            instruction_discriminator::BORROW_OBLIGATION_LIQUIDITY,
+            mock::BorrowObligationLiquidity::DISCRIMINATOR,
+        ),
+        (
+            "repay_obligation_liquidity",
+            instruction_discriminator::REPAY_OBLIGATION_LIQUIDITY,
+            mock::RepayObligationLiquidity::DISCRIMINATOR,
+        ),
+        (
+            "repay_and_withdraw_and_redeem",
+            instruction_discriminator::REPAY_AND_WITHDRAW_AND_REDEEM,
+            mock::RepayAndWithdrawAndRedeem::DISCRIMINATOR,
+        ),
+        (
+            "deposit_and_withdraw",
+            instruction_discriminator::DEPOSIT_AND_WITHDRAW,
+            mock::DepositAndWithdraw::DISCRIMINATOR,
+        ),
+        (
+            "deposit_reserve_liquidity_and_obligation_collateral",
+            instruction_discriminator::DEPOSIT_RESERVE_LIQUIDITY_AND_OBLIGATION_COLLATERAL,
+            mock::DepositReserveLiquidityAndObligationCollateral::DISCRIMINATOR,
+        ),
+        (
+            "withdraw_obligation_collateral_and_redeem_reserve_collateral",
+            instruction_discriminator::WITHDRAW_OBLIGATION_COLLATERAL_AND_REDEEM_RESERVE_COLLATERAL,
+            mock::WithdrawObligationCollateralAndRedeemReserveCollateral::DISCRIMINATOR,
+        ),
+        (
+            "flash_borrow_reserve_liquidity",
+            instruction_discriminator::FLASH_BORROW_RESERVE_LIQUIDITY,
+            mock::FlashBorrowReserveLiquidity::DISCRIMINATOR,
+        ),
+        (
+            "flash_repay_reserve_liquidity",
+            instruction_discriminator::FLASH_REPAY_RESERVE_LIQUIDITY,
+            mock::FlashRepayReserveLiquidity::DISCRIMINATOR,
+        ),
+    ];
+    for (name, ours, mock) in cases {
+        assert_eq!(ours, sighash(name), "{name}");
+        assert_eq!(&ours[..], mock, "{name}");
+    }
+    // The mock does not liquidate
+    assert_eq!(
+        instruction_discriminator::LIQUIDATE_OBLIGATION_AND_REDEEM_RESERVE_COLLATERAL,
+        sighash("liquidate_obligation_and_redeem_reserve_collateral")
+    );
+}
+
+#[test]
+fn test_obligation_metrics_offsets() {
+    let kamino_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let mut data = vec![0u8; OBLIGATION_SIZE];
+    data[..8].copy_from_slice(&discriminator::OBLIGATION);
+
+    let debt = obligation_layout::BORROW_FACTOR_ADJUSTED_DEBT_VALUE_SF;
+    write_u128(&mut data, obligation_layout::DEPOSITED_VALUE_SF, 1000 << 60);
+    write_u128(&mut data, debt, 400 << 60);
+    write_u128(&mut data, debt + 16, 350 << 60);
+    write_u128(&mut data, debt + 32, 750 << 60);
+    write_u128(&mut data, debt + 48, 850 << 60);
+    data[8 + obligation_layout::ELEVATION_GROUP] = 2;
+
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &kamino_program, false, 0);
+
+    let metrics = ObligationMetrics::load(&info, &kamino_program).unwrap();
+    assert_eq!(
+        metrics,
+        ObligationMetrics {
+            deposited_value_sf: 1000 << 60,
+            borrow_factor_adjusted_debt_value_sf: 400 << 60,
+            borrowed_assets_market_value_sf: 350 << 60,
+            allowed_borrow_value_sf: 750 << 60,
+            unhealthy_borrow_value_sf: 850 << 60,
+            elevation_group: 2,
+        }
+    );
+}
+
+#[test]
+fn test_obligation_metrics_rejects_other_accounts() {
+    let kamino_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    // Right size, wrong discriminator
+    let mut data = vec![0u8; OBLIGATION_SIZE];
+    data[..8].copy_from_slice(&discriminator::RESERVE);
+
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &kamino_program, false, 0);
+
+    assert!(ObligationMetrics::load(&info, &kamino_program).is_err());
+    assert!(ObligationMetrics::load(&info, &Pubkey::new_unique()).is_err());
+}
+
+#[test]
+fn test_obligation_slot_readers() {
+    let key = Pubkey::new_unique();
+    let (collateral, debt, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut obligation = Obligation::default();
+    obligation.deposits[2] = ObligationCollateral {
+        deposit_reserve: collateral,
+        deposited_amount: 1_000,
+        ..ObligationCollateral::default()
+    };
+    obligation.borrows[1] = ObligationLiquidity {
+        borrow_reserve: debt,
+        borrowed_amount_sf: (400 << 60) + 1,
+        ..ObligationLiquidity::default()
+    };
+    let mut data = obligation.account_data().unwrap();
+
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &mock_kamino::ID, false, 0);
+
+    assert_eq!(obligation_deposited_amount(&info, &mock_kamino::ID, &collateral).unwrap(), 1_000);
+    assert_eq!(obligation_borrowed_amount_sf(&info, &mock_kamino::ID, &debt).unwrap(), (400 << 60) + 1);
+    // A reserve the obligation does not hold reads as empty, on either side
+    assert_eq!(obligation_deposited_amount(&info, &mock_kamino::ID, &debt).unwrap(), 0);
+    assert_eq!(obligation_borrowed_amount_sf(&info, &mock_kamino::ID, &other).unwrap(), 0);
+    assert_eq!(obligation_reserves(&info, &mock_kamino::ID).unwrap(), vec![collateral, debt]);
+}
+
+#[test]
+fn test_reserve_config_offset() {
+    assert_eq!(reserve_layout::COLLATERAL, 2552);
+    assert_eq!(reserve_layout::LIQUIDITY_MARKET_PRICE_SF, 240);
+    assert_eq!(reserve_layout::CONFIG, 4848);
+    assert_eq!(reserve_layout::CONFIG_TOKEN_INFO, 5024);
+}
+
+#[test]
+fn test_reserve_status() {
+    let kamino_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let mut data = vec![0u8; RESERVE_SIZE];
+    data[..8].copy_from_slice(&discriminator::RESERVE);
+    data[8 + reserve_layout::CONFIG_STATUS] = 1;
+
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &kamino_program, false, 0);
+
+    assert_eq!(ReserveStatus::load(&info, &kamino_program).unwrap(), ReserveStatus::Obsolete);
+    assert!(ReserveStatus::require_active(&info, &kamino_program).is_err());
+    assert!(ReserveStatus::load(&info, &Pubkey::new_unique()).is_err());
+}
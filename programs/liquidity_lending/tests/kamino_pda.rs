@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+use liquidity_lending::kamino_pda::{
+    lending_market_authority, obligation, obligation_with_seeds, referrer_token_state,
+    reserve_collateral_mint, reserve_collateral_supply, reserve_fee_vault, reserve_liquidity_supply,
+    user_metadata, ReserveAccounts,
+};
+
+const KAMINO_PROGRAM: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// Kamino's main market and its USDC reserve
+const MAIN_MARKET: Pubkey = pubkey!("7u3HeHxYDLhnCoErrtycNokbQYbWGzLs6JSDqGAv5PfF");
+const USDC_MINT: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+
+const OWNER: Pubkey = pubkey!("11111111111111111111111111111112");
+
+#[test]
+fn test_main_market_addresses() {
+    assert_eq!(
+        lending_market_authority(&KAMINO_PROGRAM, &MAIN_MARKET),
+        pubkey!("9DrvZvyWh1HuAoZxvYWMvkf2XCzryCpGgHqrMjyDWpmo")
+    );
+    assert_eq!(
+        reserve_liquidity_supply(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT),
+        pubkey!("Bgq7trRgVMeq33yt235zM2onQ4bRDBsY5EWiTetF4qw6")
+    );
+    assert_eq!(
+        reserve_fee_vault(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT),
+        pubkey!("BbDUrk1bVtSixgQsPLBJFZEF7mwGstnD5joA1WzYvYFX")
+    );
+    assert_eq!(
+        reserve_collateral_mint(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT),
+        pubkey!("B8V6WVjPxW1UGwVDfxH2d2r8SyT4cqn7dQRK6XneVa7D")
+    );
+    assert_eq!(
+        reserve_collateral_supply(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT),
+        pubkey!("3DzjXRfxRm6iejfyyMynR4tScddaanrePJ1NJU2XnPPL")
+    );
+}
+
+#[test]
+fn test_user_addresses() {
+    assert_eq!(
+        obligation(&KAMINO_PROGRAM, &OWNER, &MAIN_MARKET),
+        pubkey!("5R8c9NP5pPV4nVSu1N4LEf8s3yjk3S9CxJ8JMQ7iqNkj")
+    );
+    assert_eq!(
+        user_metadata(&KAMINO_PROGRAM, &OWNER),
+        pubkey!("8RYFMQY79YBGEJH6G6TRXYKVHJUNTir8YaCL9B7SckxJ")
+    );
+    // Other tags and ids give other obligations
+    assert_ne!(
+        obligation_with_seeds(&KAMINO_PROGRAM, 0, 1, &OWNER, &MAIN_MARKET, &Pubkey::default(), &Pubkey::default()),
+        obligation(&KAMINO_PROGRAM, &OWNER, &MAIN_MARKET)
+    );
+    assert_ne!(
+        referrer_token_state(&KAMINO_PROGRAM, &OWNER, &USDC_MINT),
+        referrer_token_state(&KAMINO_PROGRAM, &Pubkey::default(), &USDC_MINT)
+    );
+}
+
+#[test]
+fn test_require_derived() {
+    let authority = lending_market_authority(&KAMINO_PROGRAM, &MAIN_MARKET);
+    let supply = reserve_liquidity_supply(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT);
+    let fee_vault = reserve_fee_vault(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT);
+
+    let accounts = ReserveAccounts {
+        lending_market_authority: Some(&authority),
+        liquidity_supply: Some(&supply),
+        fee_vault: Some(&fee_vault),
+        ..ReserveAccounts::default()
+    };
+    assert!(accounts.require_derived(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT).is_ok());
+    assert!(accounts.require_derived(&KAMINO_PROGRAM, &MAIN_MARKET, &Pubkey::new_unique()).is_err());
+    assert!(accounts.require_derived(&Pubkey::new_unique(), &MAIN_MARKET, &USDC_MINT).is_err());
+
+    // The fee vault passed as the liquidity supply
+    let swapped = ReserveAccounts { liquidity_supply: Some(&fee_vault), ..accounts };
+    assert!(swapped.require_derived(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT).is_err());
+
+    assert!(ReserveAccounts::default().require_derived(&KAMINO_PROGRAM, &MAIN_MARKET, &USDC_MINT).is_ok());
+}
@@ -1,101 +1,72 @@
-use std::path::PathBuf;
-use mollusk_svm::{Mollusk, result::Check};
-use anchor_lang::{prelude::*, solana_program::{
-    instruction::Instruction,
-    sysvar,
-}, InstructionData};
+mod common;
 
-use solana_account::Account;
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction, solana_program::sysvar, InstructionData, ToAccountMetas};
+use mollusk_svm::result::Check;
 
-use liquidity_lending::{
-    ID as PROGRAM_ID,
-    instruction::KaminoRepayObligationLiquidity
-};
+use common::{decode, kamino_account, mollusk, token_account, token_balance, Market, Protocol, ONE, TOKEN};
+use liquidity_lending::state::UserPosition;
+use mock_kamino::state::{Obligation, Reserve};
 
 #[test]
 fn test_kamino_repay_obligation_liquidity() {
-    let program_id = PROGRAM_ID;
+    let mollusk = mollusk();
+    let market = Market::new();
+    let owner = Pubkey::new_unique();
+    let protocol = Protocol::new(&market, &owner);
+    let obligation = Pubkey::new_unique();
+    let user_source_liquidity = Pubkey::new_unique();
 
-    // Create unique pubkeys for all accounts
-    let owner_pubkey = Pubkey::new_unique();
-    let obligation_pubkey = Pubkey::new_unique();
-    let lending_market_pubkey = Pubkey::new_unique();
-    let repay_reserve_pubkey = Pubkey::new_unique();
-    let reserve_liquidity_mint_pubkey = Pubkey::new_unique();
-    let reserve_destination_liquidity_pubkey = Pubkey::new_unique();
-    let user_source_liquidity_pubkey = Pubkey::new_unique();
-    let token_program_pubkey = Pubkey::new_unique();
-    let instruction_sysvar_pubkey = sysvar::instructions::id();
-    let kamino_lending_program_pubkey = Pubkey::new_unique();
-
-    // Build accounts required by the instruction
-    let accounts = vec![
-        AccountMeta::new_readonly(owner_pubkey, true),
-        AccountMeta::new(obligation_pubkey, false),
-        AccountMeta::new_readonly(lending_market_pubkey, false),
-        AccountMeta::new(repay_reserve_pubkey, false),
-        AccountMeta::new_readonly(reserve_liquidity_mint_pubkey, false),
-        AccountMeta::new(reserve_destination_liquidity_pubkey, false),
-        AccountMeta::new(user_source_liquidity_pubkey, false),
-        AccountMeta::new_readonly(token_program_pubkey, false),
-        AccountMeta::new_readonly(instruction_sysvar_pubkey, false),
-        AccountMeta::new_readonly(kamino_lending_program_pubkey, false),
-    ];
-
-    // Set repay amount
-    let liquidity_amount: u64 = 1_000_000_000;
-
-    // Create the instruction
+    let liquidity_amount = 40 * TOKEN;
     let instruction = Instruction {
-        program_id,
-        accounts,
-        data: KaminoRepayObligationLiquidity {
-            liquidity_amount,
-        }.data(),
+        program_id: liquidity_lending::ID,
+        accounts: liquidity_lending::accounts::KaminoRepayObligationLiquidity {
+            owner,
+            user_position: protocol.user_position,
+            config: protocol.config,
+            registry: protocol.registry,
+            obligation,
+            lending_market: market.lending_market,
+            repay_reserve: market.reserve,
+            reserve_liquidity_mint: market.liquidity_mint,
+            reserve_destination_liquidity: market.liquidity_supply,
+            user_source_liquidity,
+            token_program: anchor_spl::token::ID,
+            instruction_sysvar_account: sysvar::instructions::ID,
+            kamino_lending_program: mock_kamino::ID,
+            event_authority: protocol.event_authority,
+            program: liquidity_lending::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_lending::instruction::KaminoRepayObligationLiquidity { liquidity_amount }.data(),
     };
 
-    // Define account states for testing
-    let mollusk_accounts = vec![
-        (owner_pubkey, Account::default()),
-        (obligation_pubkey, Account::default()),
-        (lending_market_pubkey, Account::default()),
-        (repay_reserve_pubkey, Account::default()),
-        (reserve_liquidity_mint_pubkey, Account::default()),
-        (reserve_destination_liquidity_pubkey, Account::default()),
-        (user_source_liquidity_pubkey, Account::default()),
-        (token_program_pubkey, Account::default()),
-        (instruction_sysvar_pubkey, Account::default()),
-        (kamino_lending_program_pubkey, Account::default()),
-    ];
+    // The obligation already owes 100 tokens
+    let mut reserve = market.reserve_state();
+    let mut state = market.obligation_state(owner, 2_000 * TOKEN);
+    state.borrow(&reserve, market.reserve, 100 * u128::from(TOKEN) * ONE).unwrap();
+    reserve.liquidity.available_amount -= 100 * TOKEN;
+    reserve.liquidity.borrowed_amount_sf = 100 * u128::from(TOKEN) * ONE;
 
-    // Initialize Mollusk instance with your compiled program ELF
-    let mut mollusk = Mollusk::default();
+    let mut accounts = market.kamino_accounts(&reserve);
+    accounts.extend(protocol.accounts(&market, &owner, &obligation));
+    accounts.push((obligation, kamino_account(state.account_data().unwrap())));
+    accounts.push((user_source_liquidity, token_account(market.liquidity_mint, owner, 100 * TOKEN)));
 
-    // Add the program to Mollusk's cache
-    let program_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("../../target/deploy/liquidity_lending.so");
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[Check::success()]);
 
-    // Read the program ELF
-    let program_elf = std::fs::read(program_path).expect("Failed to read program file");
+    let account = |key| result.get_account(key).unwrap();
+    assert_eq!(token_balance(account(&user_source_liquidity)), 60 * TOKEN);
+    assert_eq!(token_balance(account(&market.liquidity_supply)), 999_940 * TOKEN);
 
-    // Add the program to Mollusk
-    mollusk.add_program_with_elf_and_loader(&program_id, &program_elf, &mollusk_svm::program::loader_keys::LOADER_V3);
+    let reserve = Reserve::decode(&account(&market.reserve).data).unwrap();
+    assert_eq!(reserve.liquidity.available_amount, 999_940 * TOKEN);
+    assert_eq!(reserve.liquidity.borrowed_amount_sf, 60 * u128::from(TOKEN) * ONE);
 
-    // Define checks
-    let checks = vec![
-        Check::success(),
-    ];
-
-    // Execute instruction and validate results
-    mollusk.process_and_validate_instruction(
-        &instruction,
-        &mollusk_accounts,
-        &checks,
-    );
-}
+    let state = Obligation::decode(&account(&obligation).data).unwrap();
+    let borrow = &state.borrows[state.find_borrow(&market.reserve).unwrap()];
+    assert_eq!(borrow.borrowed_amount_sf, 60 * u128::from(TOKEN) * ONE);
+    assert_eq!(state.borrowed_assets_market_value_sf, 60 * ONE);
 
-fn serialize_kamino_instruction(instruction_index: u8, amount: &u64) -> Vec<u8> {
-    let mut data = vec![instruction_index];
-    data.extend_from_slice(&amount.to_le_bytes());
-    data
+    let user_position: UserPosition = decode(account(&protocol.user_position));
+    assert_eq!(user_position.reserve(&market.reserve).unwrap().repaid, liquidity_amount);
 }
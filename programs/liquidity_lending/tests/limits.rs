@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::kamino::ObligationMetrics;
+use liquidity_lending::limits::{record_user_borrow_value, require_user_borrow_value};
+use liquidity_lending::state::{
+    Config, ReserveEntry, ReserveUsage, UserBorrows, DEFAULT_PRICE_GUARD, MAX_USER_OBLIGATIONS,
+};
+
+fn entry() -> ReserveEntry {
+    ReserveEntry {
+        reserve: Pubkey::new_unique(),
+        lending_market: Pubkey::new_unique(),
+        symbol: [0; 16],
+        decimals: 6,
+        max_exposure: 1_000,
+        borrow_allowed: true,
+        epoch_deposit_limit: 600,
+        epoch_borrow_limit: 300,
+    }
+}
+
+fn usage(reserve: Pubkey) -> ReserveUsage {
+    ReserveUsage {
+        reserve,
+        exposure: 0,
+        epoch: 10,
+        epoch_deposited: 0,
+        epoch_borrowed: 0,
+        bump: 255,
+    }
+}
+
+fn user_borrows() -> UserBorrows {
+    UserBorrows {
+        owner: Pubkey::new_unique(),
+        bump: 255,
+        obligations: Default::default(),
+    }
+}
+
+#[test]
+fn test_deposit_cap_and_epoch_volume() {
+    let entry = entry();
+    let mut usage = usage(entry.reserve);
+
+    usage.record_deposit(&entry, 500, 10).unwrap();
+    // Over the epoch volume, and a failed deposit leaves the usage untouched
+    assert!(usage.record_deposit(&entry, 200, 10).is_err());
+    assert_eq!((usage.exposure, usage.epoch_deposited), (500, 500));
+
+    // A new epoch resets the volume but not the exposure
+    usage.record_deposit(&entry, 500, 11).unwrap();
+    assert_eq!((usage.exposure, usage.epoch_deposited, usage.epoch), (1_000, 500, 11));
+    assert!(usage.record_deposit(&entry, 1, 12).is_err());
+
+    usage.record_withdrawal(400);
+    usage.record_deposit(&entry, 400, 12).unwrap();
+    usage.record_withdrawal(u64::MAX);
+    assert_eq!(usage.exposure, 0);
+}
+
+#[test]
+fn test_borrow_epoch_volume() {
+    let entry = entry();
+    let mut usage = usage(entry.reserve);
+
+    usage.record_borrow(&entry, 300, 10).unwrap();
+    assert!(usage.record_borrow(&entry, 1, 10).is_err());
+    usage.record_borrow(&entry, 300, 11).unwrap();
+    assert_eq!(usage.epoch_borrowed, 300);
+}
+
+#[test]
+fn test_user_borrow_value_limit() {
+    let mut config = Config {
+        admin: Pubkey::new_unique(),
+        pending_admin: Pubkey::default(),
+        fee_bps: 0,
+        fee_recipient: Pubkey::default(),
+        timelock_delay: 0,
+        config_change_count: 0,
+        max_user_borrow_value: 10_000,
+        price_guard: DEFAULT_PRICE_GUARD,
+        paused: 0,
+        pause_updated_at: 0,
+        pause_updated_by: Pubkey::default(),
+        pause_update_count: 0,
+        bump: 255,
+    };
+    let metrics = |value: u128| ObligationMetrics {
+        borrowed_assets_market_value_sf: value,
+        ..ObligationMetrics::default()
+    };
+    let mut borrows = user_borrows();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    // Obligations in two markets, each under the limit but not together
+    record_user_borrow_value(&config, &mut borrows, first, &metrics(6_000 << 60), 1).unwrap();
+    assert!(record_user_borrow_value(&config, &mut borrows, second, &metrics(4_001 << 60), 2).is_err());
+    record_user_borrow_value(&config, &mut borrows, second, &metrics(4_000 << 60), 2).unwrap();
+    assert_eq!(borrows.total_sf().unwrap(), 10_000 << 60);
+
+    // Repaying one obligation frees room in the other
+    record_user_borrow_value(&config, &mut borrows, first, &metrics(0), 3).unwrap();
+    assert!(borrows.obligations.iter().all(|entry| entry.obligation != first));
+    record_user_borrow_value(&config, &mut borrows, second, &metrics(10_000 << 60), 4).unwrap();
+    assert!(require_user_borrow_value(&config, &borrows).is_ok());
+
+    config.max_user_borrow_value = 9_999;
+    assert!(require_user_borrow_value(&config, &borrows).is_err());
+    config.max_user_borrow_value = u64::MAX;
+    record_user_borrow_value(&config, &mut borrows, first, &metrics(u128::from(u64::MAX) << 59), 5).unwrap();
+}
+
+#[test]
+fn test_user_borrows_track_a_bounded_number_of_obligations() {
+    let mut borrows = user_borrows();
+    for _ in 0..MAX_USER_OBLIGATIONS {
+        borrows.record(Pubkey::new_unique(), 1 << 60, 1).unwrap();
+    }
+    assert!(borrows.record(Pubkey::new_unique(), 1 << 60, 2).is_err());
+    // Nothing borrowed takes no entry
+    borrows.record(Pubkey::new_unique(), 0, 2).unwrap();
+
+    let tracked = borrows.obligations[3].obligation;
+    borrows.record(tracked, 5 << 60, 3).unwrap();
+    assert_eq!(borrows.obligations[3].borrowed_value_sf, 5 << 60);
+    assert_eq!(borrows.obligations[3].updated_at, 3);
+    assert_eq!(borrows.total_sf().unwrap(), (MAX_USER_OBLIGATIONS as u128 + 4) << 60);
+}
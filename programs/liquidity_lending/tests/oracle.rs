@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::kamino::{PriceHeuristic, TokenInfo};
+use liquidity_lending::oracle::{
+    discriminator, mul_sf, read_price, read_twap, scope_layout, switchboard_layout, to_sf,
+    OracleAccounts, OracleSource,
+};
+use liquidity_lending::LendingError;
+
+const NOW: i64 = 1_700_000_000;
+const ONE: u128 = 1 << 60;
+
+/// A Pyth `PriceUpdateV2` with full verification and a -8 exponent
+fn pyth_data(price: i64, conf: u64, ema_price: i64, publish_time: i64) -> Vec<u8> {
+    let mut data = discriminator::PYTH_PRICE_UPDATE.to_vec();
+    data.extend_from_slice(&[0; 32]);
+    data.push(1);
+    data.extend_from_slice(&[0; 32]);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&ema_price.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data
+}
+
+fn switchboard_data(value: i128, std_dev: i128, timestamp: i64) -> Vec<u8> {
+    let mut data = vec![0u8; 8 + switchboard_layout::RESULT + 128];
+    data[..8].copy_from_slice(&discriminator::SWITCHBOARD_PULL_FEED);
+    let result = 8 + switchboard_layout::RESULT;
+    data[result..result + 16].copy_from_slice(&value.to_le_bytes());
+    data[result + 16..result + 32].copy_from_slice(&std_dev.to_le_bytes());
+    let ts = 8 + switchboard_layout::LAST_UPDATE_TIMESTAMP;
+    data[ts..ts + 8].copy_from_slice(&timestamp.to_le_bytes());
+    data
+}
+
+fn scope_data(entries: &[(usize, u64, u64, i64)]) -> Vec<u8> {
+    let mut data = vec![
+        0u8;
+        8 + scope_layout::PRICES + scope_layout::MAX_ENTRIES * scope_layout::DATED_PRICE_SIZE
+    ];
+    data[..8].copy_from_slice(&discriminator::SCOPE_ORACLE_PRICES);
+    for &(index, value, exp, timestamp) in entries {
+        let offset = 8 + scope_layout::PRICES + index * scope_layout::DATED_PRICE_SIZE;
+        data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        data[offset + 8..offset + 16].copy_from_slice(&exp.to_le_bytes());
+        data[offset + 24..offset + 32].copy_from_slice(&(timestamp as u64).to_le_bytes());
+    }
+    data
+}
+
+fn token_info() -> TokenInfo {
+    let mut token_info = TokenInfo {
+        max_age_price_seconds: 60,
+        max_age_twap_seconds: 240,
+        ..TokenInfo::default()
+    };
+    token_info.scope_configuration.price_chain = [scope_layout::CHAIN_END; 4];
+    token_info.scope_configuration.twap_chain = [scope_layout::CHAIN_END; 4];
+    token_info
+}
+
+#[test]
+fn test_scaled_fraction_math() {
+    assert_eq!(to_sf(15, 1).unwrap(), ONE + ONE / 2);
+    assert_eq!(to_sf(100_000 * 10u128.pow(18), 18).unwrap(), 100_000 * ONE);
+    assert_eq!(mul_sf(3 * ONE, ONE / 2).unwrap(), 3 * ONE / 2);
+    assert_eq!(mul_sf(60_000 * ONE, 2 * ONE).unwrap(), 120_000 * ONE);
+    assert!(mul_sf(u128::MAX, 2 * ONE).is_err());
+}
+
+#[test]
+fn test_pyth_price_and_ema() {
+    let owner = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+    let mut info = token_info();
+    info.pyth_configuration.price = key;
+
+    // 150.00 +/- 0.10, EMA 149.00
+    let mut data = pyth_data(15_000_000_000, 10_000_000, 14_900_000_000, NOW - 5);
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    let accounts = OracleAccounts {
+        pyth_price: Some(&account),
+        ..OracleAccounts::default()
+    };
+
+    let price = read_price(&info, &accounts, NOW).unwrap();
+    assert_eq!(price.source, OracleSource::Pyth);
+    assert_eq!(price.price_sf, to_sf(15_000_000_000, 8).unwrap());
+    assert_eq!(price.confidence_sf, to_sf(10_000_000, 8).unwrap());
+    assert_eq!(price.timestamp, NOW - 5);
+
+    let twap = read_twap(&info, &accounts, NOW).unwrap().unwrap();
+    assert_eq!(twap.price_sf, to_sf(14_900_000_000, 8).unwrap());
+
+    // Too old for the spot price
+    assert!(read_price(&info, &accounts, NOW + 60).is_err());
+
+    // Heuristic of [100, 140]
+    info.heuristic = PriceHeuristic { lower: 100, upper: 140, exp: 0 };
+    assert!(read_price(&info, &accounts, NOW).is_err());
+    info.heuristic.upper = 200;
+    assert!(read_price(&info, &accounts, NOW).is_ok());
+
+    info.block_price_usage = 1;
+    assert!(read_price(&info, &accounts, NOW).is_err());
+}
+
+#[test]
+fn test_pyth_price_must_be_fully_verified() {
+    let owner = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+    let mut info = token_info();
+    info.pyth_configuration.price = key;
+
+    // `Partial { num_signatures: 5 }` in place of `Full`
+    let mut data = pyth_data(15_000_000_000, 0, 15_000_000_000, NOW);
+    data[40] = 0;
+    data.insert(41, 5);
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    let accounts = OracleAccounts {
+        pyth_price: Some(&account),
+        ..OracleAccounts::default()
+    };
+    let unverified: Error = LendingError::UnverifiedOraclePrice.into();
+    assert_eq!(read_price(&info, &accounts, NOW).unwrap_err(), unverified);
+    assert_eq!(read_twap(&info, &accounts, NOW).unwrap_err(), unverified);
+}
+
+#[test]
+fn test_oracle_account_must_match_config() {
+    let owner = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+    let mut info = token_info();
+    info.pyth_configuration.price = Pubkey::new_unique();
+
+    let mut data = pyth_data(15_000_000_000, 0, 15_000_000_000, NOW);
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    let accounts = OracleAccounts {
+        pyth_price: Some(&account),
+        ..OracleAccounts::default()
+    };
+    assert!(read_price(&info, &accounts, NOW).is_err());
+
+    // The configured oracle left out
+    let missing: Error = LendingError::OracleAccountMissing.into();
+    assert_eq!(read_price(&info, &OracleAccounts::default(), NOW).unwrap_err(), missing);
+    assert_eq!(read_twap(&info, &OracleAccounts::default(), NOW).unwrap_err(), missing);
+
+    // A reserve with no oracle at all
+    let info = token_info();
+    let not_configured: Error = LendingError::OracleNotConfigured.into();
+    assert_eq!(read_price(&info, &OracleAccounts::default(), NOW).unwrap_err(), not_configured);
+    assert_eq!(read_twap(&info, &OracleAccounts::default(), NOW).unwrap(), None);
+}
+
+#[test]
+fn test_most_recent_of_switchboard_and_scope() {
+    let owner = Pubkey::new_unique();
+    let switchboard_key = Pubkey::new_unique();
+    let scope_key = Pubkey::new_unique();
+    let mut info = token_info();
+    info.switchboard_configuration.price_aggregator = switchboard_key;
+    info.scope_configuration.price_feed = scope_key;
+    // mSOL = 1.2 SOL and SOL = 150 USD
+    info.scope_configuration.price_chain = [7, 3, scope_layout::CHAIN_END, scope_layout::CHAIN_END];
+
+    let mut switchboard = switchboard_data(181 * 10i128.pow(18), 10i128.pow(17), NOW - 20);
+    let mut scope = scope_data(&[(7, 12, 1, NOW - 3), (3, 150_000, 3, NOW - 9)]);
+    let (mut lamports_a, mut lamports_b) = (0, 0);
+    let switchboard_account = AccountInfo::new(
+        &switchboard_key, false, false, &mut lamports_a, &mut switchboard, &owner, false, 0,
+    );
+    let scope_account =
+        AccountInfo::new(&scope_key, false, false, &mut lamports_b, &mut scope, &owner, false, 0);
+    let accounts = OracleAccounts {
+        switchboard_price: Some(&switchboard_account),
+        scope_prices: Some(&scope_account),
+        ..OracleAccounts::default()
+    };
+
+    // Scope's chain is as old as its oldest entry, and still newer than Switchboard
+    let price = read_price(&info, &accounts, NOW).unwrap();
+    assert_eq!(price.source, OracleSource::Scope);
+    assert_eq!(price.timestamp, NOW - 9);
+    let expected = 180 * ONE;
+    assert!(price.price_sf.abs_diff(expected) < 1 << 10);
+
+    // Leaving out Scope to pick Switchboard's price is refused
+    let accounts = OracleAccounts {
+        switchboard_price: Some(&switchboard_account),
+        ..OracleAccounts::default()
+    };
+    let missing: Error = LendingError::OracleAccountMissing.into();
+    assert_eq!(read_price(&info, &accounts, NOW).unwrap_err(), missing);
+
+    // Switchboard wins once it is the most recent
+    info.scope_configuration.price_chain = [scope_layout::CHAIN_END; 4];
+    let price = read_price(&info, &accounts, NOW).unwrap();
+    assert_eq!(price.source, OracleSource::Switchboard);
+    assert_eq!(price.price_sf, 181 * ONE);
+    assert_eq!(price.confidence_sf, to_sf(1, 1).unwrap());
+}
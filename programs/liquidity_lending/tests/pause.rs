@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::state::{Config, PauseTarget, DEFAULT_PRICE_GUARD};
+
+fn config() -> Config {
+    Config {
+        admin: Pubkey::new_unique(),
+        pending_admin: Pubkey::default(),
+        fee_bps: 0,
+        fee_recipient: Pubkey::default(),
+        timelock_delay: 0,
+        config_change_count: 0,
+        max_user_borrow_value: u64::MAX,
+        price_guard: DEFAULT_PRICE_GUARD,
+        paused: 0,
+        pause_updated_at: 0,
+        pause_updated_by: Pubkey::default(),
+        pause_update_count: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn test_borrow_pause_leaves_repay_and_withdraw_open() {
+    let mut config = config();
+    let guardian = Pubkey::new_unique();
+    config.set_paused(PauseTarget::Borrows, true, guardian, 100);
+
+    assert!(config.require_not_paused(PauseTarget::Borrows).is_err());
+    assert!(config.require_not_paused(PauseTarget::Global).is_ok());
+    assert!(config.require_not_paused(PauseTarget::Withdrawals).is_ok());
+    assert!(config.require_not_paused(PauseTarget::Deposits).is_ok());
+
+    assert_eq!(config.pause_updated_by, guardian);
+    assert_eq!(config.pause_updated_at, 100);
+    assert_eq!(config.pause_update_count, 1);
+}
+
+#[test]
+fn test_global_pause_stops_everything() {
+    let mut config = config();
+    let admin = config.admin;
+    config.set_paused(PauseTarget::Global, true, admin, 100);
+
+    for target in [
+        PauseTarget::Global,
+        PauseTarget::Deposits,
+        PauseTarget::Borrows,
+        PauseTarget::Withdrawals,
+        PauseTarget::Liquidations,
+    ] {
+        assert!(config.require_not_paused(target).is_err());
+    }
+
+    config.set_paused(PauseTarget::Global, false, admin, 200);
+    assert_eq!(config.paused, 0);
+    assert!(config.require_not_paused(PauseTarget::Liquidations).is_ok());
+    assert_eq!(config.pause_update_count, 2);
+}
+
+#[test]
+fn test_pause_flags_are_independent() {
+    let mut config = config();
+    let admin = config.admin;
+    config.set_paused(PauseTarget::Deposits, true, admin, 1);
+    config.set_paused(PauseTarget::Liquidations, true, admin, 2);
+    config.set_paused(PauseTarget::Deposits, false, admin, 3);
+
+    assert!(!config.is_paused(PauseTarget::Deposits));
+    assert!(config.is_paused(PauseTarget::Liquidations));
+    assert_eq!(config.paused, PauseTarget::Liquidations.flag());
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::kamino::{
+    discriminator, reserve_layout, LastUpdate, ObligationMetrics, WithdrawalCaps,
+};
+use liquidity_lending::oracle::to_sf;
+use liquidity_lending::preflight::{
+    borrow_value_sf, require_borrow_within_limit, require_reserve_fresh,
+};
+
+/// Size of a Kamino `Reserve` account, discriminator included
+const RESERVE_SIZE: usize = 8624;
+
+fn metrics(allowed: u128, debt: u128) -> ObligationMetrics {
+    ObligationMetrics {
+        allowed_borrow_value_sf: allowed << 60,
+        borrow_factor_adjusted_debt_value_sf: debt << 60,
+        ..ObligationMetrics::default()
+    }
+}
+
+#[test]
+fn test_last_update_is_stale() {
+    let fresh = LastUpdate { slot: 10, ..LastUpdate::default() };
+    assert!(!fresh.is_stale(10));
+    assert!(fresh.is_stale(11));
+    assert!(LastUpdate { stale: 1, ..fresh }.is_stale(10));
+}
+
+#[test]
+fn test_require_reserve_fresh() {
+    let kamino_program = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let mut data = vec![0u8; RESERVE_SIZE];
+    data[..8].copy_from_slice(&discriminator::RESERVE);
+    // Reserve header starts with the version, then the last update
+    data[16..24].copy_from_slice(&7u64.to_le_bytes());
+
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &kamino_program, false, 0);
+
+    assert!(require_reserve_fresh(&info, &kamino_program, 7).is_ok());
+    assert!(require_reserve_fresh(&info, &kamino_program, 8).is_err());
+}
+
+#[test]
+fn test_borrow_value() {
+    // 2.5 tokens with 6 decimals at $4 is $10, $12 at a 120% borrow factor
+    let price_sf = to_sf(4, 0).unwrap();
+    assert_eq!(borrow_value_sf(2_500_000, 6, price_sf, 100), Some(10 << 60));
+    assert_eq!(borrow_value_sf(2_500_000, 6, price_sf, 120), Some(12 << 60));
+    // A zero borrow factor counts as 100%
+    assert_eq!(borrow_value_sf(2_500_000, 6, price_sf, 0), Some(10 << 60));
+    assert_eq!(borrow_value_sf(u64::MAX, 0, u128::MAX, 100), None);
+}
+
+#[test]
+fn test_require_borrow_within_limit() {
+    let price_sf = to_sf(1, 0).unwrap();
+    // $100 allowed, $60 already borrowed
+    let metrics = metrics(100, 60);
+    assert!(require_borrow_within_limit(&metrics, 40, 0, price_sf, 100).is_ok());
+    assert!(require_borrow_within_limit(&metrics, 41, 0, price_sf, 100).is_err());
+    assert!(require_borrow_within_limit(&metrics, 35, 0, price_sf, 120).is_err());
+    assert!(require_borrow_within_limit(&metrics, u64::MAX, 0, u128::MAX, 100).is_err());
+}
+
+#[test]
+fn test_elevation_group_borrows_at_face_value() {
+    let price_sf = to_sf(1, 0).unwrap();
+    // $100 allowed, $60 already borrowed, from a reserve with a 150% borrow factor
+    let outside = metrics(100, 60);
+    assert!(require_borrow_within_limit(&outside, 27, 0, price_sf, 150).is_err());
+    assert!(require_borrow_within_limit(&outside, 26, 0, price_sf, 150).is_ok());
+
+    // In an elevation group Kamino ignores the borrow factor, so all $40 are left
+    let elevated = ObligationMetrics { elevation_group: 1, ..outside };
+    assert!(require_borrow_within_limit(&elevated, 40, 0, price_sf, 150).is_ok());
+    assert!(require_borrow_within_limit(&elevated, 41, 0, price_sf, 150).is_err());
+}
+
+#[test]
+fn test_withdrawal_caps() {
+    let caps = WithdrawalCaps {
+        config_capacity: 100,
+        current_total: 80,
+        last_interval_start_timestamp: 1_000,
+        config_interval_length_seconds: 60,
+    };
+    assert!(caps.allows(20, 1_030));
+    assert!(!caps.allows(21, 1_030));
+    // A new interval starts from zero
+    assert!(caps.allows(100, 1_060));
+    assert!(!caps.allows(101, 1_060));
+    // No capacity or interval disables the cap
+    assert!(WithdrawalCaps { config_capacity: 0, ..caps }.allows(u64::MAX, 1_030));
+    assert!(WithdrawalCaps { config_interval_length_seconds: 0, ..caps }.allows(u64::MAX, 1_030));
+}
+
+#[test]
+fn test_withdrawal_caps_offset() {
+    assert_eq!(reserve_layout::CONFIG_BORROW_FACTOR_PCT, 5000);
+    assert_eq!(reserve_layout::CONFIG_WITHDRAWAL_CAPS, 5408);
+}
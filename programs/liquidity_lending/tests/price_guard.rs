@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::kamino::{ReserveMarketPrice, TokenInfo};
+use liquidity_lending::oracle::{discriminator, scope_layout, to_sf, OracleAccounts};
+use liquidity_lending::price_guard::{check_prices, deviation_bps};
+use liquidity_lending::state::{PriceGuard, DEFAULT_PRICE_GUARD};
+
+const NOW: i64 = 1_700_000_000;
+
+/// Scope prices with spot price at entry 0 and TWAP at entry 1, both with 2 decimals
+fn scope_data(spot: u64, twap: u64, timestamp: i64) -> Vec<u8> {
+    let mut data = vec![
+        0u8;
+        8 + scope_layout::PRICES + scope_layout::MAX_ENTRIES * scope_layout::DATED_PRICE_SIZE
+    ];
+    data[..8].copy_from_slice(&discriminator::SCOPE_ORACLE_PRICES);
+    for (index, value) in [spot, twap].into_iter().enumerate() {
+        let offset = 8 + scope_layout::PRICES + index * scope_layout::DATED_PRICE_SIZE;
+        data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        data[offset + 8..offset + 16].copy_from_slice(&2u64.to_le_bytes());
+        data[offset + 24..offset + 32].copy_from_slice(&(timestamp as u64).to_le_bytes());
+    }
+    data
+}
+
+fn token_info(scope_feed: Pubkey) -> TokenInfo {
+    let mut token_info = TokenInfo {
+        max_age_price_seconds: 600,
+        max_age_twap_seconds: 600,
+        ..TokenInfo::default()
+    };
+    token_info.scope_configuration.price_feed = scope_feed;
+    token_info.scope_configuration.price_chain = [0, u16::MAX, u16::MAX, u16::MAX];
+    token_info.scope_configuration.twap_chain = [1, u16::MAX, u16::MAX, u16::MAX];
+    token_info
+}
+
+fn cached(price_cents: u64, updated_at: i64) -> ReserveMarketPrice {
+    ReserveMarketPrice {
+        market_price_sf: to_sf(price_cents.into(), 2).unwrap(),
+        market_price_last_updated_ts: updated_at as u64,
+    }
+}
+
+#[test]
+fn test_deviation_bps() {
+    assert_eq!(deviation_bps(101, 100).unwrap(), 100);
+    assert_eq!(deviation_bps(99, 100).unwrap(), 100);
+    assert_eq!(deviation_bps(100, 100).unwrap(), 0);
+    assert!(deviation_bps(1, 0).is_err());
+}
+
+#[test]
+fn test_guard_checks_age_and_deviation() {
+    let owner = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+    let info = token_info(key);
+    let guard = DEFAULT_PRICE_GUARD;
+
+    // Oracle at 100.00 with a TWAP of 97.00
+    let mut data = scope_data(10_000, 9_700, NOW - 10);
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    let oracles = OracleAccounts {
+        scope_prices: Some(&account),
+        ..OracleAccounts::default()
+    };
+
+    assert!(check_prices(&guard, &cached(10_100, NOW - 5), &info, &oracles, NOW).is_ok());
+    // Cached price 3% off the oracle
+    assert!(check_prices(&guard, &cached(10_300, NOW - 5), &info, &oracles, NOW).is_err());
+    // Cached price too old for our guard, though Kamino would still accept the oracle
+    assert!(check_prices(&guard, &cached(10_000, NOW - 121), &info, &oracles, NOW).is_err());
+
+    // TWAP 3% off, past a tighter limit
+    let tight = PriceGuard {
+        max_twap_deviation_bps: 200,
+        ..guard
+    };
+    assert!(check_prices(&tight, &cached(10_000, NOW), &info, &oracles, NOW).is_err());
+
+    // A configured TWAP must be supplied
+    assert!(check_prices(&guard, &cached(10_000, NOW), &info, &OracleAccounts::default(), NOW).is_err());
+}
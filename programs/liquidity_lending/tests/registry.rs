@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::state::{
+    Config, ConfigChange, Registry, ReserveEntry, DEFAULT_PRICE_GUARD, MAX_REGISTRY_MARKETS,
+    MAX_REGISTRY_RESERVES,
+};
+
+fn registry() -> Registry {
+    Registry {
+        markets: [Pubkey::default(); MAX_REGISTRY_MARKETS],
+        reserves: [ReserveEntry::default(); MAX_REGISTRY_RESERVES],
+        bump: 255,
+    }
+}
+
+fn usdc_entry(lending_market: Pubkey) -> ReserveEntry {
+    let mut symbol = [0u8; 16];
+    symbol[..4].copy_from_slice(b"USDC");
+    ReserveEntry {
+        reserve: Pubkey::new_unique(),
+        lending_market,
+        symbol,
+        decimals: 6,
+        max_exposure: 1_000_000_000_000,
+        borrow_allowed: true,
+        epoch_deposit_limit: u64::MAX,
+        epoch_borrow_limit: u64::MAX,
+    }
+}
+
+#[test]
+fn test_require_reserve_checks_market_and_reserve() {
+    let mut registry = registry();
+    let market = Pubkey::new_unique();
+    let entry = usdc_entry(market);
+
+    // Approved reserve in a market that is not approved yet
+    registry.set_reserve(entry).unwrap();
+    assert!(registry.require_reserve(&market, &entry.reserve).is_err());
+
+    registry.set_market(market, true).unwrap();
+    assert_eq!(registry.require_reserve(&market, &entry.reserve).unwrap(), &entry);
+
+    // Unknown reserve, or a known reserve passed with another approved market
+    let other_market = Pubkey::new_unique();
+    registry.set_market(other_market, true).unwrap();
+    assert!(registry.require_reserve(&market, &Pubkey::new_unique()).is_err());
+    assert!(registry.require_reserve(&other_market, &entry.reserve).is_err());
+    assert!(registry.require_reserve(&market, &Pubkey::default()).is_err());
+
+    registry.remove_reserve(&entry.reserve);
+    assert!(registry.require_reserve(&market, &entry.reserve).is_err());
+
+    registry.set_reserve(entry).unwrap();
+    registry.set_market(market, false).unwrap();
+    assert!(registry.require_reserve(&market, &entry.reserve).is_err());
+}
+
+#[test]
+fn test_set_reserve_replaces_metadata() {
+    let mut registry = registry();
+    let entry = usdc_entry(Pubkey::new_unique());
+    registry.set_reserve(entry).unwrap();
+    registry
+        .set_reserve(ReserveEntry { borrow_allowed: false, ..entry })
+        .unwrap();
+
+    assert!(!registry.reserve(&entry.reserve).unwrap().borrow_allowed);
+    assert_eq!(
+        registry.reserves.iter().filter(|e| e.reserve == entry.reserve).count(),
+        1
+    );
+}
+
+#[test]
+fn test_registry_full() {
+    let mut registry = registry();
+    for _ in 0..MAX_REGISTRY_MARKETS {
+        registry.set_market(Pubkey::new_unique(), true).unwrap();
+    }
+    assert!(registry.set_market(Pubkey::new_unique(), true).is_err());
+    // Re-approving a listed market needs no new slot
+    let listed = registry.markets[0];
+    registry.set_market(listed, true).unwrap();
+
+    for _ in 0..MAX_REGISTRY_RESERVES {
+        registry.set_reserve(usdc_entry(listed)).unwrap();
+    }
+    assert!(registry.set_reserve(usdc_entry(listed)).is_err());
+}
+
+#[test]
+fn test_registry_changes_go_through_config_change() {
+    let mut registry = registry();
+    let mut config = Config {
+        admin: Pubkey::new_unique(),
+        pending_admin: Pubkey::default(),
+        fee_bps: 0,
+        fee_recipient: Pubkey::default(),
+        timelock_delay: 0,
+        config_change_count: 0,
+        max_user_borrow_value: u64::MAX,
+        price_guard: DEFAULT_PRICE_GUARD,
+        paused: 0,
+        pause_updated_at: 0,
+        pause_updated_by: Pubkey::default(),
+        pause_update_count: 0,
+        bump: 255,
+    };
+    let market = Pubkey::new_unique();
+    let entry = usdc_entry(market);
+
+    ConfigChange::SetMarket { lending_market: market, allowed: true }
+        .apply(&mut config, &mut registry)
+        .unwrap();
+    ConfigChange::SetReserve { entry }
+        .apply(&mut config, &mut registry)
+        .unwrap();
+    assert!(registry.require_reserve(&market, &entry.reserve).is_ok());
+
+    ConfigChange::RemoveReserve { reserve: entry.reserve }
+        .apply(&mut config, &mut registry)
+        .unwrap();
+    assert!(registry.reserve(&entry.reserve).is_none());
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+use common::scenario::{slots, Scenario};
+use common::{decode, system_account};
+use liquidity_lending::state::{ConfigChange, PauseTarget, Role, RoleAssignment, ROLE_SEED};
+use liquidity_lending::LendingError;
+
+fn lending_error(error: LendingError) -> ProgramError {
+    ProgramError::Custom(u32::from(error))
+}
+
+/// A scenario with the program initialized and `count` funded keys with no role
+fn scenario(count: usize) -> (Scenario, Vec<Pubkey>) {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    let keys: Vec<_> = (0..count).map(|_| Pubkey::new_unique()).collect();
+    for key in &keys {
+        scenario.set_account(*key, system_account());
+    }
+    (scenario, keys)
+}
+
+#[test]
+fn test_role_assignments_are_per_role_and_holder() {
+    let holder = Pubkey::new_unique();
+    let address = |role: Role, holder: &Pubkey| {
+        Pubkey::find_program_address(&[ROLE_SEED, &role.seed(), holder.as_ref()], &liquidity_lending::ID).0
+    };
+
+    let guardian = address(Role::Guardian, &holder);
+    let keeper = address(Role::Keeper, &holder);
+    let fee_manager = address(Role::FeeManager, &holder);
+
+    assert_ne!(guardian, keeper);
+    assert_ne!(keeper, fee_manager);
+    assert_ne!(guardian, fee_manager);
+    assert_ne!(guardian, address(Role::Guardian, &Pubkey::new_unique()));
+}
+
+#[test]
+fn test_role_seeds_are_stable() {
+    assert_eq!(Role::Guardian.seed(), [0]);
+    assert_eq!(Role::Keeper.seed(), [1]);
+    assert_eq!(Role::FeeManager.seed(), [2]);
+}
+
+#[test]
+fn test_admin_grants_and_revokes_roles() {
+    let (mut scenario, keys) = scenario(2);
+    let (holder, stranger) = (keys[0], keys[1]);
+    let admin = scenario.admin;
+
+    let grant = scenario.grant_role_instruction(stranger, Role::Guardian, holder);
+    scenario.process_err(&grant, lending_error(LendingError::NotAdmin));
+
+    scenario.process(&scenario.grant_role_instruction(admin, Role::Guardian, holder));
+    let assignment: RoleAssignment = decode(scenario.account(&scenario.role(Role::Guardian, &holder)));
+    assert_eq!((assignment.role, assignment.holder, assignment.granted_by), (Role::Guardian, holder, admin));
+
+    let revoke = scenario.revoke_role_instruction(stranger, Role::Guardian, holder);
+    scenario.process_err(&revoke, lending_error(LendingError::NotAdmin));
+
+    scenario.process(&scenario.revoke_role_instruction(admin, Role::Guardian, holder));
+    assert_eq!(scenario.account(&scenario.role(Role::Guardian, &holder)).lamports, 0);
+}
+
+#[test]
+fn test_admin_transfer_takes_two_steps() {
+    let (mut scenario, keys) = scenario(3);
+    let (new_admin, stranger, holder) = (keys[0], keys[1], keys[2]);
+    let admin = scenario.admin;
+
+    let propose = scenario.propose_admin_instruction(stranger, stranger);
+    scenario.process_err(&propose, lending_error(LendingError::NotAdmin));
+    scenario.process(&scenario.propose_admin_instruction(admin, new_admin));
+    assert_eq!(scenario.config().pending_admin, new_admin);
+
+    // Until the proposed admin accepts, the old one stays in charge
+    let accept = scenario.accept_admin_instruction(stranger);
+    scenario.process_err(&accept, lending_error(LendingError::NotPendingAdmin));
+    assert_eq!(scenario.config().admin, admin);
+
+    scenario.process(&scenario.accept_admin_instruction(new_admin));
+    let config = scenario.config();
+    assert_eq!((config.admin, config.pending_admin), (new_admin, Pubkey::default()));
+
+    let grant = scenario.grant_role_instruction(admin, Role::Keeper, holder);
+    scenario.process_err(&grant, lending_error(LendingError::NotAdmin));
+    scenario.process(&scenario.grant_role_instruction(new_admin, Role::Keeper, holder));
+}
+
+#[test]
+fn test_guardian_pauses_but_cannot_unpause() {
+    let (mut scenario, keys) = scenario(2);
+    let (guardian, stranger) = (keys[0], keys[1]);
+    let admin = scenario.admin;
+
+    let pause = scenario.set_pause_instruction(stranger, PauseTarget::Borrows, true);
+    scenario.process_err(&pause, lending_error(LendingError::NotAdminOrGuardian));
+
+    scenario.process(&scenario.grant_role_instruction(admin, Role::Guardian, guardian));
+    scenario.process(&scenario.set_pause_instruction(guardian, PauseTarget::Borrows, true));
+    let config = scenario.config();
+    assert!(config.is_paused(PauseTarget::Borrows));
+    assert_eq!(config.pause_updated_by, guardian);
+
+    let unpause = scenario.set_pause_instruction(guardian, PauseTarget::Borrows, false);
+    scenario.process_err(&unpause, lending_error(LendingError::NotAdmin));
+    scenario.process(&scenario.set_pause_instruction(admin, PauseTarget::Borrows, false));
+    assert!(!scenario.config().is_paused(PauseTarget::Borrows));
+
+    // Once revoked, the guardian is a stranger
+    scenario.process(&scenario.revoke_role_instruction(admin, Role::Guardian, guardian));
+    let pause = scenario.set_pause_instruction(guardian, PauseTarget::Global, true);
+    scenario.process_err(&pause, lending_error(LendingError::NotAdminOrGuardian));
+}
+
+#[test]
+fn test_revoked_keeper_is_refused() {
+    let (mut scenario, keys) = scenario(1);
+    let keeper = keys[0];
+    let admin = scenario.admin;
+    let refresh = scenario.refresh_obligation_farms_instruction(keeper);
+
+    // A keeper gets past the role check, to the reserve's missing farm
+    scenario.process(&scenario.grant_role_instruction(admin, Role::Keeper, keeper));
+    scenario.process_err(&refresh, lending_error(LendingError::FarmNotConfigured));
+
+    scenario.process(&scenario.revoke_role_instruction(admin, Role::Keeper, keeper));
+    scenario.process_err(&refresh, ProgramError::Custom(ErrorCode::AccountNotInitialized.into()));
+}
+
+#[test]
+fn test_fee_manager_queues_only_fee_changes() {
+    let (mut scenario, keys) = scenario(2);
+    let (fee_manager, fee_recipient) = (keys[0], keys[1]);
+    let admin = scenario.admin;
+    let fee_change = ConfigChange::FeeConfig { fee_bps: 50, fee_recipient };
+
+    let queue = scenario.propose_config_change_instruction(fee_manager, fee_change.clone());
+    scenario.process_err(&queue, lending_error(LendingError::NotFeeManager));
+
+    scenario.process(&scenario.grant_role_instruction(admin, Role::FeeManager, fee_manager));
+    let queue = scenario.propose_config_change_instruction(fee_manager, ConfigChange::MaxUserBorrowValue { value: 1 });
+    scenario.process_err(&queue, lending_error(LendingError::NotAdmin));
+
+    // The fee change waits out the timelock like any other
+    let id = scenario.config().config_change_count;
+    scenario.process(&scenario.propose_config_change_instruction(fee_manager, fee_change.clone()));
+    scenario.wait(slots(scenario.config().timelock_delay));
+    scenario.process(&scenario.execute_config_change_instruction(id));
+    let config = scenario.config();
+    assert_eq!((config.fee_bps, config.fee_recipient), (50, fee_recipient));
+
+    scenario.process(&scenario.revoke_role_instruction(admin, Role::FeeManager, fee_manager));
+    let queue = scenario.propose_config_change_instruction(fee_manager, fee_change);
+    scenario.process_err(&queue, lending_error(LendingError::NotFeeManager));
+}
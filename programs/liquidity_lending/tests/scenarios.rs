@@ -0,0 +1,270 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+use common::scenario::{slots, Scenario, STARTING_BALANCE};
+use common::{program_account, system_account, ONE, TOKEN};
+use liquidity_lending::sentinel::ALL;
+use liquidity_lending::state::{ConfigChange, ObligationBorrow, DEFAULT_TIMELOCK_DELAY};
+use liquidity_lending::LendingError;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[test]
+fn test_deposit_collateralize_borrow_accrue_repay_withdraw() {
+    let mut scenario = Scenario::new();
+    let market_liquidity = scenario.reserve().liquidity.available_amount;
+
+    // Init: the upgrade authority becomes admin and approves the market through the timelock
+    scenario.setup();
+    let config = scenario.config();
+    assert_eq!(config.admin, scenario.admin);
+    assert_eq!(config.timelock_delay, DEFAULT_TIMELOCK_DELAY);
+    assert_eq!(config.config_change_count, 2);
+    let registry = scenario.registry();
+    assert!(registry.require_reserve(&scenario.market.lending_market, &scenario.market.reserve).is_ok());
+    assert_eq!(scenario.user_position().obligation, scenario.obligation);
+    assert_eq!(scenario.reserve_usage().exposure, 0);
+
+    // Deposit: collateral is minted one to one while the reserve has earned nothing
+    scenario.deposit(1_000 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 1_000 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_collateral), 1_000 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.market.liquidity_supply), market_liquidity + 1_000 * TOKEN);
+    assert_eq!(scenario.reserve_usage().exposure, 1_000 * TOKEN);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().deposited, 1_000 * TOKEN);
+
+    // Collateralize: $1,000 pledged allows $750 of debt
+    scenario.collateralize(1_000 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_collateral), 0);
+    assert_eq!(scenario.balance(&scenario.market.collateral_supply), 1_000 * TOKEN);
+    scenario.refresh();
+    let obligation = scenario.obligation_state();
+    assert_eq!(obligation.deposits[0].deposit_reserve, scenario.market.reserve);
+    assert_eq!(obligation.deposits[0].deposited_amount, 1_000 * TOKEN);
+    assert_eq!(obligation.deposited_value_sf, 1_000 * ONE);
+    assert_eq!(obligation.allowed_borrow_value_sf, 750 * ONE);
+
+    // Borrow
+    scenario.borrow(500 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 500 * TOKEN);
+    assert_eq!(scenario.debt(), 500 * TOKEN);
+    assert_eq!(scenario.obligation_state().borrowed_assets_market_value_sf, 500 * ONE);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().borrowed, 500 * TOKEN);
+
+    // The collateral backs the debt and cannot all be withdrawn
+    scenario.refresh();
+    let withdraw_all = scenario.withdraw_instruction(1_000 * TOKEN);
+    scenario.process_err(&withdraw_all, ProgramError::Custom(6011));
+
+    // Accrue: a day at 1% grows the debt to 505, which all depositors earn on
+    scenario.accrue(slots(SECONDS_PER_DAY), 100);
+    assert_eq!(scenario.debt(), 505 * TOKEN);
+    scenario.refresh();
+    assert_eq!(scenario.obligation_state().borrowed_assets_market_value_sf, 505 * ONE);
+
+    // Repay the debt with interest, which frees the borrow slot
+    scenario.repay(505 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 1_005 * TOKEN);
+    assert_eq!(scenario.debt(), 0);
+    assert_eq!(scenario.obligation_state().active_borrows().count(), 0);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().repaid, 505 * TOKEN);
+
+    // Withdraw everything: the collateral redeems at 1,001,005 / 1,001,000, its share of the
+    // interest
+    scenario.withdraw(1_000 * TOKEN);
+    let redeemed = 1_000_004_995;
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 1_005 * TOKEN + redeemed);
+    assert_eq!(scenario.balance(&scenario.market.collateral_supply), 0);
+    assert_eq!(scenario.obligation_state().active_deposits().count(), 0);
+    assert_eq!(scenario.obligation_state().deposited_value_sf, 0);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().withdrawn, redeemed);
+    assert_eq!(scenario.reserve_usage().exposure, 0);
+    assert_eq!(
+        scenario.balance(&scenario.market.liquidity_supply),
+        market_liquidity + 1_000 * TOKEN + 5 * TOKEN - redeemed
+    );
+}
+
+#[test]
+fn test_repay_all_and_withdraw_all_leave_no_dust() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.borrow(500 * TOKEN);
+
+    // Interest leaves a fraction of a base unit on the borrow
+    let mut obligation = scenario.obligation_state();
+    obligation.borrows[0].borrowed_amount_sf += 1;
+    scenario.store_obligation(&obligation);
+    assert_eq!(scenario.debt(), 500 * TOKEN + 1);
+
+    // Repaying everything rounds the fraction up and frees the borrow slot
+    scenario.repay(ALL);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 1_000 * TOKEN - 1);
+    assert_eq!(scenario.debt(), 0);
+    assert_eq!(scenario.obligation_state().active_borrows().count(), 0);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().repaid, 500 * TOKEN + 1);
+
+    scenario.refresh();
+    let repay_nothing = scenario.repay_instruction(ALL);
+    scenario.process_err(&repay_nothing, ProgramError::Custom(u32::from(LendingError::NoDebt)));
+
+    // Withdrawing everything empties the deposit slot
+    let balance_before = scenario.balance(&scenario.user_liquidity);
+    scenario.withdraw(ALL);
+    assert_eq!(scenario.obligation_state().active_deposits().count(), 0);
+    assert_eq!(scenario.balance(&scenario.market.collateral_supply), 0);
+    let redeemed = scenario.balance(&scenario.user_liquidity) - balance_before;
+    assert!(redeemed >= 1_000 * TOKEN);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&scenario.market.reserve).unwrap().withdrawn, redeemed);
+
+    scenario.refresh();
+    let withdraw_nothing = scenario.withdraw_instruction(ALL);
+    scenario.process_err(&withdraw_nothing, ProgramError::Custom(u32::from(LendingError::NoCollateral)));
+}
+
+#[test]
+fn test_repay_and_withdraw_unwinds_part_of_a_position() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.borrow(500 * TOKEN);
+
+    // Half the debt goes back, which frees enough collateral for 400 to come out
+    scenario.refresh();
+    let reserve = scenario.market.reserve;
+    let unwind = scenario.repay_and_withdraw_instruction(250 * TOKEN, 400 * TOKEN, &[reserve, reserve]);
+    scenario.process(&unwind);
+    assert_eq!(scenario.debt(), 250 * TOKEN);
+    assert_eq!(scenario.obligation_state().deposits[0].deposited_amount, 600 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 350 * TOKEN);
+    let position = scenario.user_position();
+    let reserve_position = position.reserve(&reserve).unwrap();
+    assert_eq!(reserve_position.repaid, 250 * TOKEN);
+    assert_eq!(reserve_position.withdrawn, 400 * TOKEN);
+    assert_eq!(scenario.reserve_usage().exposure, 600 * TOKEN);
+
+    // Kamino allows what is left, but not over our per-user limit
+    scenario.change_config(ConfigChange::MaxUserBorrowValue { value: 100 });
+    scenario.refresh();
+    let over_limit = scenario.repay_and_withdraw_instruction(50 * TOKEN, 10 * TOKEN, &[reserve, reserve]);
+    scenario.process_err(
+        &over_limit,
+        ProgramError::Custom(u32::from(LendingError::UserBorrowLimitExceeded)),
+    );
+}
+
+#[test]
+fn test_user_borrow_limit_spans_obligations() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.borrow(300 * TOKEN);
+    let tracked = scenario.user_borrows().obligations[0];
+    assert_eq!(tracked.obligation, scenario.obligation);
+    assert_eq!(tracked.borrowed_value_sf, 300 * ONE);
+
+    // What the user owes in another market counts against the same limit
+    let mut borrows = scenario.user_borrows();
+    borrows.obligations[1] = ObligationBorrow {
+        obligation: Pubkey::new_unique(),
+        borrowed_value_sf: 200 * ONE,
+        updated_at: 0,
+    };
+    scenario.set_account(scenario.protocol.user_borrows, program_account(&borrows));
+    scenario.change_config(ConfigChange::MaxUserBorrowValue { value: 550 });
+    scenario.refresh();
+    let over_limit = scenario.borrow_instruction(100 * TOKEN);
+    scenario.process_err(
+        &over_limit,
+        ProgramError::Custom(u32::from(LendingError::UserBorrowLimitExceeded)),
+    );
+    scenario.borrow(20 * TOKEN);
+
+    // Repaying leaves the tracked value until a refreshed obligation is synced
+    scenario.repay(ALL);
+    assert!(scenario.user_borrows().obligations[0].borrowed_value_sf >= 320 * ONE);
+    scenario.wait(1);
+    let sync = scenario.sync_user_borrows_instruction(scenario.obligation);
+    scenario.process_err(&sync, ProgramError::Custom(u32::from(LendingError::ObligationStale)));
+    scenario.refresh();
+    scenario.process(&sync);
+    let borrows = scenario.user_borrows();
+    assert!(borrows.obligations.iter().all(|entry| entry.obligation != scenario.obligation));
+    assert_eq!(borrows.total_sf().unwrap(), 200 * ONE);
+}
+
+#[test]
+fn test_deposit_and_withdraw_swaps_collateral() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.borrow(500 * TOKEN);
+    let other = scenario.add_reserve();
+    scenario.approve_reserve(other);
+
+    let reserve = scenario.market.reserve;
+    scenario.refresh();
+    let same_reserve = scenario.deposit_and_withdraw_instruction(reserve, reserve, 1_000 * TOKEN, 1_000 * TOKEN, &[]);
+    scenario.process_err(&same_reserve, ProgramError::Custom(u32::from(LendingError::SameReserve)));
+
+    // The new collateral backs the debt before the old collateral leaves
+    scenario.process(&scenario.refresh_reserve_instruction(other));
+    let swap = scenario.deposit_and_withdraw_instruction(
+        other,
+        reserve,
+        1_000 * TOKEN,
+        1_000 * TOKEN,
+        &[reserve, other, reserve],
+    );
+    scenario.process(&swap);
+    let obligation = scenario.obligation_state();
+    let deposits: Vec<_> = obligation
+        .active_deposits()
+        .map(|deposit| (deposit.deposit_reserve, deposit.deposited_amount))
+        .collect();
+    assert_eq!(deposits, [(other, 1_000 * TOKEN)]);
+    assert_eq!(scenario.debt(), 500 * TOKEN);
+    assert_eq!(scenario.balance(&scenario.user_liquidity), STARTING_BALANCE - 500 * TOKEN);
+    let position = scenario.user_position();
+    assert_eq!(position.reserve(&other).unwrap().deposited, 1_000 * TOKEN);
+    assert_eq!(position.reserve(&reserve).unwrap().withdrawn, 1_000 * TOKEN);
+    assert_eq!(scenario.reserve_usage().exposure, 0);
+}
+
+#[test]
+fn test_wrappers_refuse_another_kamino_program() {
+    let mut scenario = Scenario::new();
+    scenario.setup();
+    scenario.deposit(1_000 * TOKEN);
+    scenario.collateralize(1_000 * TOKEN);
+    scenario.borrow(500 * TOKEN);
+    scenario.refresh();
+    let impostor = Pubkey::new_unique();
+    scenario.set_account(impostor, system_account());
+
+    for mut instruction in [
+        scenario.deposit_instruction(TOKEN),
+        scenario.borrow_instruction(TOKEN),
+        scenario.repay_instruction(TOKEN),
+        scenario.withdraw_instruction(TOKEN),
+    ] {
+        for meta in instruction.accounts.iter_mut().filter(|meta| meta.pubkey == mock_kamino::ID) {
+            meta.pubkey = impostor;
+        }
+        scenario.process_err(&instruction, ProgramError::Custom(ErrorCode::ConstraintAddress.into()));
+    }
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::state::{
+    Config, ConfigChange, PendingConfigChange, Registry, ReserveEntry, DEFAULT_PRICE_GUARD,
+    DEFAULT_TIMELOCK_DELAY, MAX_FEE_BPS, MAX_REGISTRY_MARKETS, MAX_REGISTRY_RESERVES,
+    MAX_TIMELOCK_DELAY,
+};
+
+fn config() -> Config {
+    Config {
+        admin: Pubkey::new_unique(),
+        pending_admin: Pubkey::default(),
+        fee_bps: 0,
+        fee_recipient: Pubkey::default(),
+        timelock_delay: DEFAULT_TIMELOCK_DELAY,
+        config_change_count: 0,
+        max_user_borrow_value: u64::MAX,
+        price_guard: DEFAULT_PRICE_GUARD,
+        paused: 0,
+        pause_updated_at: 0,
+        pause_updated_by: Pubkey::default(),
+        pause_update_count: 0,
+        bump: 255,
+    }
+}
+
+fn registry() -> Registry {
+    Registry {
+        markets: [Pubkey::default(); MAX_REGISTRY_MARKETS],
+        reserves: [ReserveEntry::default(); MAX_REGISTRY_RESERVES],
+        bump: 255,
+    }
+}
+
+#[test]
+fn test_change_applies_only_once_ready() {
+    let mut config = config();
+    let mut registry = registry();
+    let fee_recipient = Pubkey::new_unique();
+    let pending = PendingConfigChange {
+        id: 0,
+        change: ConfigChange::FeeConfig { fee_bps: 25, fee_recipient },
+        proposer: config.admin,
+        queued_at: 1_000,
+        eta: 1_000 + config.timelock_delay,
+        bump: 255,
+    };
+
+    assert!(!pending.is_ready(1_000));
+    assert!(!pending.is_ready(pending.eta - 1));
+    assert!(pending.is_ready(pending.eta));
+
+    pending.change.apply(&mut config, &mut registry).unwrap();
+    assert_eq!(config.fee_bps, 25);
+    assert_eq!(config.fee_recipient, fee_recipient);
+    // The fee rounds down
+    assert_eq!(config.fee(10_000), 25);
+    assert_eq!(config.fee(399), 0);
+    assert_eq!(config.fee(u64::MAX), u64::MAX / 400);
+
+    ConfigChange::TimelockDelay { delay: 60 }
+        .apply(&mut config, &mut registry)
+        .unwrap();
+    assert_eq!(config.timelock_delay, 60);
+}
+
+#[test]
+fn test_change_validation() {
+    let fee_recipient = Pubkey::new_unique();
+    assert!(ConfigChange::FeeConfig { fee_bps: MAX_FEE_BPS, fee_recipient }.validate().is_ok());
+    assert!(ConfigChange::FeeConfig { fee_bps: MAX_FEE_BPS + 1, fee_recipient }.validate().is_err());
+
+    assert!(ConfigChange::TimelockDelay { delay: 0 }.validate().is_ok());
+    assert!(ConfigChange::TimelockDelay { delay: MAX_TIMELOCK_DELAY }.validate().is_ok());
+    assert!(ConfigChange::TimelockDelay { delay: -1 }.validate().is_err());
+    assert!(ConfigChange::TimelockDelay { delay: MAX_TIMELOCK_DELAY + 1 }.validate().is_err());
+
+    let entry = ReserveEntry {
+        reserve: Pubkey::new_unique(),
+        ..ReserveEntry::default()
+    };
+    assert!(ConfigChange::SetReserve { entry }.validate().is_err());
+    let lending_market = Pubkey::default();
+    assert!(ConfigChange::SetMarket { lending_market, allowed: true }.validate().is_err());
+}
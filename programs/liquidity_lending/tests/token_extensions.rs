@@ -0,0 +1,97 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program_option::COption, program_pack::Pack},
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        transfer_hook::TransferHook,
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::Mint,
+};
+use anchor_spl::token::spl_token;
+
+use liquidity_lending::token;
+
+/// Build Token-2022 mint data with the given extensions, letting `init` configure them
+fn mint_data(
+    extensions: &[ExtensionType],
+    init: impl FnOnce(&mut StateWithExtensionsMut<Mint>),
+) -> Vec<u8> {
+    let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    init(&mut state);
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+fn transfer_fee_mint_data(basis_points: u16, maximum_fee: u64) -> Vec<u8> {
+    mint_data(&[ExtensionType::TransferFeeConfig], |state| {
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+    })
+}
+
+#[test]
+fn test_transfer_fee_mint_is_supported() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = transfer_fee_mint_data(100, 5_000);
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token_2022::ID, false, 0);
+
+    token::validate_mint(&mint).unwrap();
+
+    // 1% fee, capped at the maximum
+    assert_eq!(token::transfer_fee(&mint, 10_000, 0).unwrap(), 100);
+    assert_eq!(token::transfer_fee(&mint, 1_000_000_000, 0).unwrap(), 5_000);
+    assert_eq!(token::transfer_fee(&mint, 0, 0).unwrap(), 0);
+}
+
+#[test]
+fn test_transfer_hook_mint_is_refused() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = mint_data(&[ExtensionType::TransferHook], |state| {
+        state.init_extension::<TransferHook>(true).unwrap();
+    });
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token_2022::ID, false, 0);
+
+    assert!(token::validate_mint(&mint).is_err());
+}
+
+#[test]
+fn test_spl_token_mint_has_no_fee() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    let base = spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    spl_token::state::Mint::pack(base, &mut data).unwrap();
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token::ID, false, 0);
+
+    token::validate_mint(&mint).unwrap();
+    assert_eq!(token::transfer_fee(&mint, 10_000, 0).unwrap(), 0);
+    assert_eq!(token::transfer_fee(&mint, u64::MAX, 0).unwrap(), 0);
+}
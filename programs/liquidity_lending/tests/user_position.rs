@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::state::{
+    PositionAction, ReservePosition, UserPosition, MAX_POSITION_RESERVES,
+};
+
+fn empty_position() -> UserPosition {
+    UserPosition {
+        owner: Pubkey::new_unique(),
+        lending_market: Pubkey::new_unique(),
+        obligation: Pubkey::new_unique(),
+        created_at: 1_000,
+        updated_at: 1_000,
+        bump: 255,
+        reserves: [ReservePosition::default(); MAX_POSITION_RESERVES],
+    }
+}
+
+#[test]
+fn test_record_accumulates_per_reserve() {
+    let mut position = empty_position();
+    let usdc = Pubkey::new_unique();
+    let sol = Pubkey::new_unique();
+
+    position.record(usdc, PositionAction::Deposit, 1_000, 1_100).unwrap();
+    position.record(sol, PositionAction::Borrow, 50, 1_200).unwrap();
+    position.record(usdc, PositionAction::Deposit, 500, 1_300).unwrap();
+    position.record(sol, PositionAction::Repay, 52, 1_400).unwrap();
+    position.record(usdc, PositionAction::Withdraw, 1_520, 1_500).unwrap();
+
+    let usdc_entry = position.reserve(&usdc).unwrap();
+    assert_eq!(usdc_entry.deposited, 1_500);
+    assert_eq!(usdc_entry.withdrawn, 1_520);
+    assert_eq!(usdc_entry.last_action_ts, 1_500);
+
+    let sol_entry = position.reserve(&sol).unwrap();
+    assert_eq!(sol_entry.borrowed, 50);
+    assert_eq!(sol_entry.repaid, 52);
+    assert_eq!(sol_entry.last_action_ts, 1_400);
+
+    assert_eq!(position.updated_at, 1_500);
+    assert_eq!(position.created_at, 1_000);
+}
+
+#[test]
+fn test_record_rejects_extra_reserves_and_overflow() {
+    let mut position = empty_position();
+    for _ in 0..MAX_POSITION_RESERVES {
+        position
+            .record(Pubkey::new_unique(), PositionAction::Deposit, 1, 0)
+            .unwrap();
+    }
+    assert!(position
+        .record(Pubkey::new_unique(), PositionAction::Deposit, 1, 0)
+        .is_err());
+
+    let reserve = position.reserves[0].reserve;
+    assert!(position
+        .record(reserve, PositionAction::Deposit, u64::MAX, 0)
+        .is_err());
+}
@@ -0,0 +1,108 @@
+//! A stand-in for the Kamino farms program, deployed at its address in local tests so the
+//! reward harvest wrapper runs end to end. It implements `harvestReward` with the accounts
+//! and arguments of `external_idls/kamino_farms.json`: the user state's unclaimed rewards for
+//! the slot are paid from the reward vault, signed by the farm vaults authority, and cleared.
+//! Farm states are not decoded and no treasury fee is taken.
+
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use liquidity_lending::farms::{UserState, USER_STATE_DISCRIMINATOR};
+
+declare_id!("FarmsPZpWu9i7Kky8tPN37rs2TpmMrAZrC7S7vJa91Hr");
+
+/// Seed of the farm vaults authority, with the farm state's address
+pub const FARM_VAULTS_AUTHORITY_SEED: &[u8] = b"authority";
+
+#[error_code]
+pub enum MockFarmsError {
+    #[msg("Invalid account input")]
+    InvalidAccountInput,
+    #[msg("Reward index out of range")]
+    RewardIndexOutOfRange,
+}
+
+#[program]
+pub mod mock_farms {
+    use super::*;
+
+    pub fn harvest_reward(ctx: Context<HarvestReward>, reward_index: u64) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let mut user_state = UserState::load(&accounts.user_state, &crate::ID)
+            .map_err(|_| error!(MockFarmsError::InvalidAccountInput))?;
+        require_keys_eq!(user_state.owner, accounts.owner.key(), MockFarmsError::InvalidAccountInput);
+        require_keys_eq!(user_state.farm_state, accounts.farm_state.key(), MockFarmsError::InvalidAccountInput);
+
+        let index = usize::try_from(reward_index)
+            .ok()
+            .filter(|&index| index < user_state.rewards_issued_unclaimed.len())
+            .ok_or(MockFarmsError::RewardIndexOutOfRange)?;
+        let amount = user_state.rewards_issued_unclaimed[index];
+        user_state.rewards_issued_unclaimed[index] = 0;
+
+        let farm_state = accounts.farm_state.key();
+        let (authority, bump) =
+            Pubkey::find_program_address(&[FARM_VAULTS_AUTHORITY_SEED, farm_state.as_ref()], &crate::ID);
+        require_keys_eq!(authority, accounts.farm_vaults_authority.key(), MockFarmsError::InvalidAccountInput);
+
+        if amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: accounts.rewards_vault.to_account_info(),
+                        mint: accounts.reward_mint.to_account_info(),
+                        to: accounts.user_reward_ata.to_account_info(),
+                        authority: accounts.farm_vaults_authority.clone(),
+                    },
+                    &[&[FARM_VAULTS_AUTHORITY_SEED, farm_state.as_ref(), &[bump]]],
+                ),
+                amount,
+                accounts.reward_mint.decimals,
+            )?;
+        }
+
+        let mut data = accounts.user_state.try_borrow_mut_data()?;
+        let mut writer = &mut data[USER_STATE_DISCRIMINATOR.len()..];
+        user_state.serialize(&mut writer)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct HarvestReward<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as a `UserState` this program owns
+    #[account(mut)]
+    pub user_state: AccountInfo<'info>,
+
+    /// CHECK: Matched against the user state
+    #[account(mut)]
+    pub farm_state: AccountInfo<'info>,
+
+    /// CHECK: Not read by the mock
+    pub global_config: AccountInfo<'info>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = reward_mint)]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = reward_mint, token::authority = farm_vaults_authority)]
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Not read by the mock
+    #[account(mut)]
+    pub rewards_treasury_vault: AccountInfo<'info>,
+
+    /// CHECK: Derived from the farm state
+    pub farm_vaults_authority: AccountInfo<'info>,
+
+    /// CHECK: Not read by the mock
+    pub scope_prices: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
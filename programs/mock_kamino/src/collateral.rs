@@ -0,0 +1,339 @@
+//! Moving reserve collateral in and out of obligations: `depositObligationCollateral`,
+//! `depositReserveLiquidityAndObligationCollateral`, which mints the collateral from liquidity
+//! in the same step, and `withdrawObligationCollateralAndRedeemReserveCollateral`, which
+//! redeems what is withdrawn for liquidity in the same step.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Transfer};
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface};
+use liquidity_lending::kamino_pda::LENDING_MARKET_AUTHORITY_SEED;
+
+use crate::liquidity::{transfer_from_reserve, transfer_from_user};
+use crate::state::{require_lending_market, Obligation, Reserve};
+use crate::MockKaminoError;
+
+pub fn deposit_collateral(ctx: Context<DepositObligationCollateral>, collateral_amount: u64) -> Result<()> {
+    require!(collateral_amount > 0, MockKaminoError::InvalidAmount);
+    let accounts = &ctx.accounts;
+    require_lending_market(&accounts.lending_market)?;
+    let reserve = Reserve::load(&accounts.deposit_reserve)?;
+    let mut obligation = Obligation::load(&accounts.obligation)?;
+    require_keys_eq!(reserve.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.owner, accounts.owner.key(), MockKaminoError::InvalidObligationOwner);
+    reserve.require_fresh(Clock::get()?.slot)?;
+    require_keys_eq!(
+        reserve.collateral.supply_vault,
+        accounts.reserve_destination_collateral.key(),
+        MockKaminoError::InvalidAccountInput
+    );
+    require_keys_eq!(
+        reserve.collateral.mint_pubkey,
+        accounts.user_source_collateral.mint,
+        MockKaminoError::InvalidAccountInput
+    );
+
+    // Kamino passes no mint here, so this is a plain transfer
+    token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.user_source_collateral.to_account_info(),
+                to: accounts.reserve_destination_collateral.to_account_info(),
+                authority: accounts.owner.to_account_info(),
+            },
+        ),
+        collateral_amount,
+    )?;
+
+    obligation.deposit(accounts.deposit_reserve.key(), collateral_amount)?;
+    obligation.last_update.stale = 1;
+    obligation.store(&accounts.obligation)
+}
+
+pub fn deposit_liquidity_and_collateral(
+    ctx: Context<DepositReserveLiquidityAndObligationCollateral>,
+    liquidity_amount: u64,
+) -> Result<()> {
+    deposit_liquidity_and_collateral_from(ctx.accounts, ctx.bumps.lending_market_authority, liquidity_amount)
+}
+
+/// Deposit from `accounts`, which `depositAndWithdraw` also takes as its first step. `bump`
+/// is the lending market authority's.
+pub(crate) fn deposit_liquidity_and_collateral_from(
+    accounts: &DepositReserveLiquidityAndObligationCollateral,
+    bump: u8,
+    liquidity_amount: u64,
+) -> Result<()> {
+    require!(liquidity_amount > 0, MockKaminoError::InvalidAmount);
+    let slot = Clock::get()?.slot;
+    require_lending_market(&accounts.lending_market)?;
+    let mut reserve = Reserve::load(&accounts.reserve)?;
+    let mut obligation = Obligation::load(&accounts.obligation)?;
+    require_keys_eq!(reserve.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.owner, accounts.owner.key(), MockKaminoError::InvalidObligationOwner);
+    reserve.require_fresh(slot)?;
+    obligation.require_fresh(slot)?;
+    reserve.require_active()?;
+    for (key, expected) in [
+        (accounts.reserve_liquidity_mint.key(), reserve.liquidity.mint_pubkey),
+        (accounts.reserve_liquidity_supply.key(), reserve.liquidity.supply_vault),
+        (accounts.reserve_collateral_mint.key(), reserve.collateral.mint_pubkey),
+        (accounts.reserve_destination_deposit_collateral.key(), reserve.collateral.supply_vault),
+    ] {
+        require_keys_eq!(key, expected, MockKaminoError::InvalidAccountInput);
+    }
+
+    let collateral_amount = reserve.liquidity_to_collateral(liquidity_amount)?;
+    require!(collateral_amount > 0, MockKaminoError::InvalidAmount);
+
+    transfer_from_user(
+        &accounts.liquidity_token_program,
+        &accounts.user_source_liquidity,
+        &accounts.reserve_liquidity_mint,
+        &accounts.reserve_liquidity_supply,
+        &accounts.owner,
+        liquidity_amount,
+    )?;
+    // The collateral goes straight to the reserve's supply, on the obligation's books
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            accounts.collateral_token_program.to_account_info(),
+            MintTo {
+                mint: accounts.reserve_collateral_mint.to_account_info(),
+                to: accounts.reserve_destination_deposit_collateral.to_account_info(),
+                authority: accounts.lending_market_authority.to_account_info(),
+            },
+            &[&[
+                LENDING_MARKET_AUTHORITY_SEED,
+                accounts.lending_market.key.as_ref(),
+                &[bump],
+            ]],
+        ),
+        collateral_amount,
+    )?;
+
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_add(liquidity_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    reserve.collateral.mint_total_supply = reserve
+        .collateral
+        .mint_total_supply
+        .checked_add(collateral_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    obligation.deposit(accounts.reserve.key(), collateral_amount)?;
+    reserve.last_update.stale = 1;
+    obligation.last_update.stale = 1;
+    reserve.store(&accounts.reserve)?;
+    obligation.store(&accounts.obligation)
+}
+
+pub fn withdraw_collateral(
+    ctx: Context<WithdrawObligationCollateralAndRedeemReserveCollateral>,
+    collateral_amount: u64,
+) -> Result<()> {
+    withdraw_collateral_from(ctx.accounts, ctx.bumps.lending_market_authority, collateral_amount)
+}
+
+/// Withdraw from `accounts`, which both combined instructions take as their second step.
+/// `bump` is the lending market authority's.
+pub(crate) fn withdraw_collateral_from(
+    accounts: &WithdrawObligationCollateralAndRedeemReserveCollateral,
+    bump: u8,
+    collateral_amount: u64,
+) -> Result<()> {
+    require!(collateral_amount > 0, MockKaminoError::InvalidAmount);
+    let slot = Clock::get()?.slot;
+    require_lending_market(&accounts.lending_market)?;
+    let mut reserve = Reserve::load(&accounts.withdraw_reserve)?;
+    let mut obligation = Obligation::load(&accounts.obligation)?;
+    require_keys_eq!(reserve.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.lending_market, accounts.lending_market.key(), MockKaminoError::InvalidAccountInput);
+    require_keys_eq!(obligation.owner, accounts.owner.key(), MockKaminoError::InvalidObligationOwner);
+    reserve.require_fresh(slot)?;
+    obligation.require_fresh(slot)?;
+    for (key, expected) in [
+        (accounts.reserve_liquidity_mint.key(), reserve.liquidity.mint_pubkey),
+        (accounts.reserve_liquidity_supply.key(), reserve.liquidity.supply_vault),
+        (accounts.reserve_collateral_mint.key(), reserve.collateral.mint_pubkey),
+        (accounts.reserve_source_collateral.key(), reserve.collateral.supply_vault),
+    ] {
+        require_keys_eq!(key, expected, MockKaminoError::InvalidAccountInput);
+    }
+
+    let index = obligation
+        .find_deposit(&accounts.withdraw_reserve.key())
+        .ok_or(MockKaminoError::InvalidObligationCollateral)?;
+    let deposited = obligation.deposits[index].deposited_amount;
+    require!(deposited > 0, MockKaminoError::ObligationCollateralEmpty);
+    let collateral_amount = collateral_amount.min(deposited);
+
+    // What is left must still cover the debt
+    obligation.withdraw(index, &reserve, collateral_amount)?;
+    if obligation.active_borrows().next().is_some() {
+        require!(
+            obligation.borrow_factor_adjusted_debt_value_sf <= obligation.allowed_borrow_value_sf,
+            MockKaminoError::WithdrawTooLarge
+        );
+    }
+
+    let liquidity_amount = u64::try_from(reserve.collateral_to_liquidity_sf(collateral_amount)? >> 60)
+        .map_err(|_| error!(MockKaminoError::MathOverflow))?;
+    require!(liquidity_amount <= reserve.liquidity.available_amount, MockKaminoError::InsufficientLiquidity);
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            accounts.collateral_token_program.to_account_info(),
+            Burn {
+                mint: accounts.reserve_collateral_mint.to_account_info(),
+                from: accounts.reserve_source_collateral.to_account_info(),
+                authority: accounts.lending_market_authority.to_account_info(),
+            },
+            &[&[LENDING_MARKET_AUTHORITY_SEED, accounts.lending_market.key.as_ref(), &[bump]]],
+        ),
+        collateral_amount,
+    )?;
+    transfer_from_reserve(
+        &accounts.liquidity_token_program,
+        &accounts.reserve_liquidity_supply,
+        &accounts.reserve_liquidity_mint,
+        &accounts.user_destination_liquidity,
+        &accounts.lending_market_authority,
+        accounts.lending_market.key,
+        bump,
+        liquidity_amount,
+    )?;
+
+    reserve.liquidity.available_amount -= liquidity_amount;
+    reserve.collateral.mint_total_supply = reserve.collateral.mint_total_supply.saturating_sub(collateral_amount);
+    reserve.last_update.stale = 1;
+    obligation.last_update.stale = 1;
+    reserve.store(&accounts.withdraw_reserve)?;
+    obligation.store(&accounts.obligation)
+}
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateral<'info> {
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub deposit_reserve: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub reserve_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_source_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositReserveLiquidityAndObligationCollateral<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: PDA signing for the reserve's collateral mint
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = liquidity_token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_collateral_mint)]
+    pub reserve_destination_deposit_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Accepted and ignored, as Kamino does
+    pub placeholder_user_destination_collateral: Option<AccountInfo<'info>>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawObligationCollateralAndRedeemReserveCollateral<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: PDA signing for the reserve's vaults and collateral mint
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub withdraw_reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = liquidity_token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_collateral_mint)]
+    pub reserve_source_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Accepted and ignored, as Kamino does
+    pub placeholder_user_destination_collateral: Option<AccountInfo<'info>>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
@@ -0,0 +1,111 @@
+//! `repayAndWithdrawAndRedeem` and `depositAndWithdraw`: a repayment or a liquidity deposit,
+//! then a withdrawal from the same obligation. In between, the first step's reserve and the
+//! obligation are refreshed, with the obligation's reserves as they stand after the first
+//! step as remaining accounts, so the withdrawal is checked against what the first step left.
+//! Farms are not simulated and their accounts are ignored.
+
+use anchor_lang::prelude::*;
+
+// Whole modules, as deriving `Accounts` over nested account structs needs what their own
+// derives generated next to them
+use crate::collateral::*;
+use crate::liquidity::*;
+use crate::refresh::{refresh_obligation, refresh_reserve};
+use crate::MockKaminoError;
+
+/// Both steps must act on the same owner, obligation and market
+fn require_same_obligation(
+    first: [&AccountInfo; 3],
+    withdraw: &WithdrawObligationCollateralAndRedeemReserveCollateral,
+) -> Result<()> {
+    let [owner, obligation, lending_market] = first;
+    for (key, expected) in [
+        (owner.key(), withdraw.owner.key()),
+        (obligation.key(), withdraw.obligation.key()),
+        (lending_market.key(), withdraw.lending_market.key()),
+    ] {
+        require_keys_eq!(key, expected, MockKaminoError::InvalidAccountInput);
+    }
+    Ok(())
+}
+
+pub fn repay_then_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RepayAndWithdrawAndRedeem<'info>>,
+    repay_amount: u64,
+    withdraw_collateral_amount: u64,
+) -> Result<()> {
+    let repay = &ctx.accounts.repay_accounts;
+    let withdraw = &ctx.accounts.withdraw_accounts;
+    require_same_obligation([&repay.owner, &repay.obligation, &repay.lending_market], withdraw)?;
+
+    repay_from(repay, repay_amount)?;
+    refresh_reserve(&repay.lending_market, &repay.repay_reserve)?;
+    refresh_obligation(&repay.lending_market, &repay.obligation, ctx.remaining_accounts)?;
+    withdraw_collateral_from(
+        withdraw,
+        ctx.bumps.withdraw_accounts.lending_market_authority,
+        withdraw_collateral_amount,
+    )
+}
+
+pub fn deposit_then_withdraw<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositAndWithdraw<'info>>,
+    liquidity_amount: u64,
+    withdraw_collateral_amount: u64,
+) -> Result<()> {
+    let deposit = &ctx.accounts.deposit_accounts;
+    let withdraw = &ctx.accounts.withdraw_accounts;
+    require_same_obligation([&deposit.owner, &deposit.obligation, &deposit.lending_market], withdraw)?;
+
+    deposit_liquidity_and_collateral_from(
+        deposit,
+        ctx.bumps.deposit_accounts.lending_market_authority,
+        liquidity_amount,
+    )?;
+    refresh_reserve(&deposit.lending_market, &deposit.reserve)?;
+    refresh_obligation(&deposit.lending_market, &deposit.obligation, ctx.remaining_accounts)?;
+    withdraw_collateral_from(
+        withdraw,
+        ctx.bumps.withdraw_accounts.lending_market_authority,
+        withdraw_collateral_amount,
+    )
+}
+
+#[derive(Accounts)]
+pub struct OptionalObligationFarmsAccounts<'info> {
+    /// CHECK: Accepted and ignored; farms are not simulated
+    #[account(mut)]
+    pub obligation_farm_user_state: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    #[account(mut)]
+    pub reserve_farm_state: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RepayAndWithdrawAndRedeem<'info> {
+    pub repay_accounts: RepayObligationLiquidity<'info>,
+
+    pub withdraw_accounts: WithdrawObligationCollateralAndRedeemReserveCollateral<'info>,
+
+    pub collateral_farms_accounts: OptionalObligationFarmsAccounts<'info>,
+
+    pub debt_farms_accounts: OptionalObligationFarmsAccounts<'info>,
+
+    /// CHECK: Accepted and ignored
+    pub farms_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositAndWithdraw<'info> {
+    pub deposit_accounts: DepositReserveLiquidityAndObligationCollateral<'info>,
+
+    pub withdraw_accounts: WithdrawObligationCollateralAndRedeemReserveCollateral<'info>,
+
+    pub deposit_farms_accounts: OptionalObligationFarmsAccounts<'info>,
+
+    pub withdraw_farms_accounts: OptionalObligationFarmsAccounts<'info>,
+
+    /// CHECK: Accepted and ignored
+    pub farms_program: AccountInfo<'info>,
+}
@@ -0,0 +1,130 @@
+//! `initObligationFarmsForReserve` and `refreshObligationFarmsForReserve`. Farms are not
+//! simulated: the reserve's farm is checked against the one given and nothing is staked, so
+//! the wrappers around them run end to end.
+
+use anchor_lang::prelude::*;
+use liquidity_lending::kamino::{ReserveFarmKind, ReserveHeader};
+use liquidity_lending::kamino_pda::LENDING_MARKET_AUTHORITY_SEED;
+
+use crate::state::{require_lending_market, Obligation};
+use crate::MockKaminoError;
+
+fn require_reserve_farm(
+    lending_market: &AccountInfo,
+    obligation: &AccountInfo,
+    reserve: &AccountInfo,
+    reserve_farm_state: &AccountInfo,
+    mode: u8,
+) -> Result<Obligation> {
+    require_lending_market(lending_market)?;
+    let obligation = Obligation::load(obligation)?;
+    require_keys_eq!(obligation.lending_market, lending_market.key(), MockKaminoError::InvalidAccountInput);
+    let header = ReserveHeader::load(reserve, &crate::ID).map_err(|_| error!(MockKaminoError::InvalidAccountOwner))?;
+    require_keys_eq!(header.lending_market, lending_market.key(), MockKaminoError::InvalidAccountInput);
+
+    let kind = match mode {
+        0 => ReserveFarmKind::Collateral,
+        1 => ReserveFarmKind::Debt,
+        _ => return err!(MockKaminoError::InvalidAccountInput),
+    };
+    let farm = header.farm(kind).ok_or(MockKaminoError::InvalidAccountInput)?;
+    require_keys_eq!(farm, reserve_farm_state.key(), MockKaminoError::InvalidAccountInput);
+    Ok(obligation)
+}
+
+pub fn init_obligation_farm(ctx: Context<InitObligationFarmsForReserve>, mode: u8) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let obligation = require_reserve_farm(
+        &accounts.lending_market,
+        &accounts.obligation,
+        &accounts.reserve,
+        &accounts.reserve_farm_state,
+        mode,
+    )?;
+    require_keys_eq!(obligation.owner, accounts.owner.key(), MockKaminoError::InvalidObligationOwner);
+    Ok(())
+}
+
+pub fn refresh_obligation_farm(ctx: Context<RefreshObligationFarmsForReserve>, mode: u8) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require_reserve_farm(
+        &accounts.lending_market,
+        &accounts.obligation,
+        &accounts.reserve,
+        &accounts.reserve_farm_state,
+        mode,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitObligationFarmsForReserve<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Matched against the obligation's owner
+    pub owner: AccountInfo<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: PDA of the lending market
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Matched against the reserve's farm
+    #[account(mut)]
+    pub reserve_farm_state: AccountInfo<'info>,
+
+    /// CHECK: Accepted and left untouched
+    #[account(mut)]
+    pub obligation_farm: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Accepted and ignored
+    pub farms_program: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshObligationFarmsForReserve<'info> {
+    pub crank: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: PDA of the lending market
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Matched against the reserve's farm
+    #[account(mut)]
+    pub reserve_farm_state: AccountInfo<'info>,
+
+    /// CHECK: Accepted and left untouched
+    #[account(mut)]
+    pub obligation_farm_user_state: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Accepted and ignored
+    pub farms_program: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+}
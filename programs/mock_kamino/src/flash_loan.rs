@@ -0,0 +1,245 @@
+//! Flash borrow and repay under Kamino's rules: both must be top-level instructions, a
+//! borrow needs a matching repay later in the transaction, and the repay pays the flash
+//! loan fee to the reserve's fee vault.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::{
+    self,
+    instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use liquidity_lending::kamino::instruction_discriminator;
+use liquidity_lending::kamino_pda::LENDING_MARKET_AUTHORITY_SEED;
+
+use crate::liquidity::{transfer_from_reserve, transfer_from_user};
+use crate::state::{require_lending_market, Reserve};
+use crate::MockKaminoError;
+
+/// Position of the reserve in flash borrow and flash repay accounts
+const RESERVE_ACCOUNT: usize = 3;
+
+/// The amount and reserve of `ix` if it is one of this program's flash loan instructions
+/// of the given kind
+fn flash_loan(ix: &Instruction, discriminator: [u8; 8]) -> Option<(u64, Pubkey, Option<u8>)> {
+    if ix.program_id != crate::ID || ix.data.get(..8) != Some(&discriminator[..]) {
+        return None;
+    }
+    let amount = u64::from_le_bytes(ix.data.get(8..16)?.try_into().ok()?);
+    let reserve = ix.accounts.get(RESERVE_ACCOUNT)?.pubkey;
+    Some((amount, reserve, ix.data.get(16).copied()))
+}
+
+/// Load the flash loaned reserve, checking it against the market, mint and vaults passed
+fn load_reserve(
+    reserve: &AccountInfo,
+    lending_market: &AccountInfo,
+    mint: Pubkey,
+    supply_vault: Pubkey,
+    fee_vault: Pubkey,
+) -> Result<Reserve> {
+    require_lending_market(lending_market)?;
+    let loaded = Reserve::load(reserve)?;
+    let checks = [
+        (loaded.lending_market, lending_market.key()),
+        (loaded.liquidity.mint_pubkey, mint),
+        (loaded.liquidity.supply_vault, supply_vault),
+        (loaded.liquidity.fee_vault, fee_vault),
+    ];
+    for (key, expected) in checks {
+        require_keys_eq!(key, expected, MockKaminoError::InvalidAccountInput);
+    }
+    Ok(loaded)
+}
+
+pub fn flash_borrow(ctx: Context<FlashBorrowReserveLiquidity>, liquidity_amount: u64) -> Result<()> {
+    require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, MockKaminoError::FlashBorrowCpi);
+    require!(liquidity_amount > 0, MockKaminoError::InvalidAmount);
+    let accounts = &ctx.accounts;
+    let mut reserve = load_reserve(
+        &accounts.reserve,
+        &accounts.lending_market,
+        accounts.reserve_liquidity_mint.key(),
+        accounts.reserve_source_liquidity.key(),
+        accounts.reserve_liquidity_fee_receiver.key(),
+    )?;
+    reserve.require_active()?;
+
+    // The first flash loan instruction after this one must be the repay that closes it
+    let instructions = accounts.sysvar_info.to_account_info();
+    let current = load_current_index_checked(&instructions)?;
+    let mut index = usize::from(current) + 1;
+    loop {
+        let ix = load_instruction_at_checked(index, &instructions)
+            .map_err(|_| error!(MockKaminoError::NoFlashRepayFound))?;
+        require!(
+            flash_loan(&ix, instruction_discriminator::FLASH_BORROW_RESERVE_LIQUIDITY).is_none(),
+            MockKaminoError::MultipleFlashBorrows
+        );
+        if let Some((amount, reserve_key, borrow_index)) =
+            flash_loan(&ix, instruction_discriminator::FLASH_REPAY_RESERVE_LIQUIDITY)
+        {
+            require!(
+                amount == liquidity_amount
+                    && reserve_key == accounts.reserve.key()
+                    && borrow_index.map(usize::from) == Some(usize::from(current)),
+                MockKaminoError::InvalidFlashRepay
+            );
+            break;
+        }
+        index += 1;
+    }
+
+    require!(
+        liquidity_amount <= reserve.liquidity.available_amount,
+        MockKaminoError::InsufficientLiquidity
+    );
+    reserve.liquidity.available_amount -= liquidity_amount;
+    reserve.store(&accounts.reserve)?;
+
+    transfer_from_reserve(
+        &accounts.token_program,
+        &accounts.reserve_source_liquidity,
+        &accounts.reserve_liquidity_mint,
+        &accounts.user_destination_liquidity,
+        &accounts.lending_market_authority,
+        accounts.lending_market.key,
+        ctx.bumps.lending_market_authority,
+        liquidity_amount,
+    )
+}
+
+pub fn flash_repay(
+    ctx: Context<FlashRepayReserveLiquidity>,
+    liquidity_amount: u64,
+    borrow_instruction_index: u8,
+) -> Result<()> {
+    require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, MockKaminoError::FlashRepayCpi);
+    let accounts = &ctx.accounts;
+    let mut reserve = load_reserve(
+        &accounts.reserve,
+        &accounts.lending_market,
+        accounts.reserve_liquidity_mint.key(),
+        accounts.reserve_destination_liquidity.key(),
+        accounts.reserve_liquidity_fee_receiver.key(),
+    )?;
+
+    // The instruction pointed at must be the flash borrow this repays
+    let instructions = accounts.sysvar_info.to_account_info();
+    let borrow = load_instruction_at_checked(borrow_instruction_index.into(), &instructions)
+        .map_err(|_| error!(MockKaminoError::InvalidFlashRepay))?;
+    let matches = flash_loan(&borrow, instruction_discriminator::FLASH_BORROW_RESERVE_LIQUIDITY)
+        .is_some_and(|(amount, reserve_key, _)| amount == liquidity_amount && reserve_key == accounts.reserve.key());
+    require!(matches, MockKaminoError::InvalidFlashRepay);
+
+    let fee = Reserve::fee(liquidity_amount, reserve.config.fees.flash_loan_fee_sf)?;
+    transfer_from_user(
+        &accounts.token_program,
+        &accounts.user_source_liquidity,
+        &accounts.reserve_liquidity_mint,
+        &accounts.reserve_destination_liquidity,
+        &accounts.user_transfer_authority,
+        liquidity_amount,
+    )?;
+    if fee > 0 {
+        transfer_from_user(
+            &accounts.token_program,
+            &accounts.user_source_liquidity,
+            &accounts.reserve_liquidity_mint,
+            &accounts.reserve_liquidity_fee_receiver,
+            &accounts.user_transfer_authority,
+            fee,
+        )?;
+    }
+
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_add(liquidity_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    reserve.store(&accounts.reserve)
+}
+
+#[derive(Accounts)]
+pub struct FlashBorrowReserveLiquidity<'info> {
+    pub user_transfer_authority: Signer<'info>,
+
+    /// CHECK: PDA signing for the reserve's vaults
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_liquidity_fee_receiver: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Accepted and ignored; the mock pays no referrers
+    #[account(mut)]
+    pub referrer_token_state: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    #[account(mut)]
+    pub referrer_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_info: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepayReserveLiquidity<'info> {
+    pub user_transfer_authority: Signer<'info>,
+
+    /// CHECK: PDA signing for the reserve's vaults
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_liquidity_fee_receiver: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Accepted and ignored; the mock pays no referrers
+    #[account(mut)]
+    pub referrer_token_state: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    #[account(mut)]
+    pub referrer_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_info: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,153 @@
+//! A stand-in for the Kamino lending program, deployed at Kamino's address in local tests so
+//! our CPIs run end to end without a network. It takes Kamino's instruction names, and so its
+//! Anchor discriminators, account orders and account layouts, and implements refresh, deposit, collateral deposits (alone or
+//! with the liquidity deposit) and withdrawals, borrow, repay, the combined repay or deposit
+//! and withdraw, and flash loans with Kamino's checks and error codes. Oracles are not read and interest does not accrue: tests set
+//! prices on the reserve directly. Obligation farm instructions only check the reserve's farm.
+
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+
+pub mod collateral;
+pub mod combined;
+pub mod farms;
+pub mod flash_loan;
+pub mod liquidity;
+pub mod refresh;
+pub mod state;
+
+pub use collateral::*;
+pub use combined::*;
+pub use farms::*;
+pub use flash_loan::*;
+pub use liquidity::*;
+pub use refresh::*;
+
+declare_id!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// The subset of Kamino's errors the mock raises, at their Kamino codes
+#[error_code]
+pub enum MockKaminoError {
+    #[msg("Input account owner is not the program address")]
+    InvalidAccountOwner = 2,
+    #[msg("Input amount is invalid")]
+    InvalidAmount = 3,
+    #[msg("Invalid account input")]
+    InvalidAccountInput = 6,
+    #[msg("Math operation overflow")]
+    MathOverflow = 7,
+    #[msg("Insufficient liquidity available")]
+    InsufficientLiquidity = 8,
+    #[msg("Reserve state needs to be refreshed")]
+    ReserveStale = 9,
+    #[msg("Withdraw amount too large")]
+    WithdrawTooLarge = 11,
+    #[msg("Borrow amount too large for deposited collateral")]
+    BorrowTooLarge = 13,
+    #[msg("Obligation state needs to be refreshed")]
+    ObligationStale = 17,
+    #[msg("Obligation reserve limit exceeded")]
+    ObligationReserveLimit = 18,
+    #[msg("Obligation owner is invalid")]
+    InvalidObligationOwner = 19,
+    #[msg("Invalid obligation collateral")]
+    InvalidObligationCollateral = 24,
+    #[msg("Invalid obligation liquidity")]
+    InvalidObligationLiquidity = 25,
+    #[msg("Obligation collateral is empty")]
+    ObligationCollateralEmpty = 26,
+    #[msg("Obligation liquidity is empty")]
+    ObligationLiquidityEmpty = 27,
+    #[msg("No cpi flash borrows allowed")]
+    FlashBorrowCpi = 31,
+    #[msg("No corresponding repay found for flash borrow")]
+    NoFlashRepayFound = 32,
+    #[msg("Invalid repay found")]
+    InvalidFlashRepay = 33,
+    #[msg("No cpi flash repays allowed")]
+    FlashRepayCpi = 34,
+    #[msg("Multiple flash borrows not allowed in the same transaction")]
+    MultipleFlashBorrows = 35,
+    #[msg("Reserve is marked as obsolete")]
+    ReserveObsolete = 82,
+}
+
+#[program]
+pub mod mock_kamino {
+    use super::*;
+
+    pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
+        refresh::reserve(ctx)
+    }
+
+    pub fn deposit_reserve_liquidity(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) -> Result<()> {
+        liquidity::deposit(ctx, liquidity_amount)
+    }
+
+    pub fn init_obligation_farms_for_reserve(ctx: Context<InitObligationFarmsForReserve>, mode: u8) -> Result<()> {
+        farms::init_obligation_farm(ctx, mode)
+    }
+
+    pub fn refresh_obligation_farms_for_reserve(ctx: Context<RefreshObligationFarmsForReserve>, mode: u8) -> Result<()> {
+        farms::refresh_obligation_farm(ctx, mode)
+    }
+
+    pub fn refresh_obligation<'info>(ctx: Context<'_, '_, 'info, 'info, RefreshObligation<'info>>) -> Result<()> {
+        refresh::obligation(ctx)
+    }
+
+    pub fn deposit_obligation_collateral(ctx: Context<DepositObligationCollateral>, collateral_amount: u64) -> Result<()> {
+        collateral::deposit_collateral(ctx, collateral_amount)
+    }
+
+    pub fn repay_and_withdraw_and_redeem<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RepayAndWithdrawAndRedeem<'info>>,
+        repay_amount: u64,
+        withdraw_collateral_amount: u64,
+    ) -> Result<()> {
+        combined::repay_then_withdraw(ctx, repay_amount, withdraw_collateral_amount)
+    }
+
+    pub fn deposit_and_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositAndWithdraw<'info>>,
+        liquidity_amount: u64,
+        withdraw_collateral_amount: u64,
+    ) -> Result<()> {
+        combined::deposit_then_withdraw(ctx, liquidity_amount, withdraw_collateral_amount)
+    }
+
+    pub fn deposit_reserve_liquidity_and_obligation_collateral(
+        ctx: Context<DepositReserveLiquidityAndObligationCollateral>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        collateral::deposit_liquidity_and_collateral(ctx, liquidity_amount)
+    }
+
+    pub fn borrow_obligation_liquidity(ctx: Context<BorrowObligationLiquidity>, liquidity_amount: u64) -> Result<()> {
+        liquidity::borrow(ctx, liquidity_amount)
+    }
+
+    pub fn repay_obligation_liquidity(ctx: Context<RepayObligationLiquidity>, liquidity_amount: u64) -> Result<()> {
+        liquidity::repay(ctx, liquidity_amount)
+    }
+
+    pub fn withdraw_obligation_collateral_and_redeem_reserve_collateral(
+        ctx: Context<WithdrawObligationCollateralAndRedeemReserveCollateral>,
+        collateral_amount: u64,
+    ) -> Result<()> {
+        collateral::withdraw_collateral(ctx, collateral_amount)
+    }
+
+    pub fn flash_repay_reserve_liquidity(
+        ctx: Context<FlashRepayReserveLiquidity>,
+        liquidity_amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Result<()> {
+        flash_loan::flash_repay(ctx, liquidity_amount, borrow_instruction_index)
+    }
+
+    pub fn flash_borrow_reserve_liquidity(ctx: Context<FlashBorrowReserveLiquidity>, liquidity_amount: u64) -> Result<()> {
+        flash_loan::flash_borrow(ctx, liquidity_amount)
+    }
+}
@@ -0,0 +1,354 @@
+//! Deposits, borrows and repayments, moving tokens as Kamino does and keeping the reserve's
+//! and obligation's books. Interest is not accrued and no referrer is paid.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{
+    self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+use liquidity_lending::kamino_pda::LENDING_MARKET_AUTHORITY_SEED;
+
+use crate::state::{require_lending_market, Obligation, Reserve};
+use crate::MockKaminoError;
+
+fn require_key(key: Pubkey, expected: Pubkey) -> Result<()> {
+    require_keys_eq!(key, expected, MockKaminoError::InvalidAccountInput);
+    Ok(())
+}
+
+/// Move `amount` out of a reserve vault, signed by the lending market authority
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn transfer_from_reserve<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &InterfaceAccount<'info, TokenAccount>,
+    lending_market_authority: &AccountInfo<'info>,
+    lending_market: &Pubkey,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: destination.to_account_info(),
+                authority: lending_market_authority.clone(),
+            },
+            &[&[LENDING_MARKET_AUTHORITY_SEED, lending_market.as_ref(), &[bump]]],
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+/// Move `amount` from a user's account, signed by `authority`
+pub(crate) fn transfer_from_user<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    source: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &InterfaceAccount<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: source.to_account_info(),
+                mint: mint.to_account_info(),
+                to: destination.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+pub fn deposit(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) -> Result<()> {
+    require!(liquidity_amount > 0, MockKaminoError::InvalidAmount);
+    let accounts = &ctx.accounts;
+    require_lending_market(&accounts.lending_market)?;
+    let mut reserve = Reserve::load(&accounts.reserve)?;
+    require_key(reserve.lending_market, accounts.lending_market.key())?;
+    reserve.require_fresh(Clock::get()?.slot)?;
+    reserve.require_active()?;
+    require_key(reserve.liquidity.mint_pubkey, accounts.reserve_liquidity_mint.key())?;
+    require_key(reserve.liquidity.supply_vault, accounts.reserve_liquidity_supply.key())?;
+    require_key(reserve.collateral.mint_pubkey, accounts.reserve_collateral_mint.key())?;
+
+    let collateral_amount = reserve.liquidity_to_collateral(liquidity_amount)?;
+    require!(collateral_amount > 0, MockKaminoError::InvalidAmount);
+
+    transfer_from_user(
+        &accounts.liquidity_token_program,
+        &accounts.user_source_liquidity,
+        &accounts.reserve_liquidity_mint,
+        &accounts.reserve_liquidity_supply,
+        &accounts.owner,
+        liquidity_amount,
+    )?;
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            accounts.collateral_token_program.to_account_info(),
+            MintTo {
+                mint: accounts.reserve_collateral_mint.to_account_info(),
+                to: accounts.user_destination_collateral.to_account_info(),
+                authority: accounts.lending_market_authority.to_account_info(),
+            },
+            &[&[
+                LENDING_MARKET_AUTHORITY_SEED,
+                accounts.lending_market.key.as_ref(),
+                &[ctx.bumps.lending_market_authority],
+            ]],
+        ),
+        collateral_amount,
+    )?;
+
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_add(liquidity_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    reserve.collateral.mint_total_supply = reserve
+        .collateral
+        .mint_total_supply
+        .checked_add(collateral_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    reserve.last_update.stale = 1;
+    reserve.store(&accounts.reserve)
+}
+
+pub fn borrow(ctx: Context<BorrowObligationLiquidity>, liquidity_amount: u64) -> Result<()> {
+    require!(liquidity_amount > 0, MockKaminoError::InvalidAmount);
+    let accounts = &ctx.accounts;
+    let slot = Clock::get()?.slot;
+    require_lending_market(&accounts.lending_market)?;
+    let mut reserve = Reserve::load(&accounts.borrow_reserve)?;
+    let mut obligation = Obligation::load(&accounts.obligation)?;
+    require_key(reserve.lending_market, accounts.lending_market.key())?;
+    require_key(obligation.lending_market, accounts.lending_market.key())?;
+    require_keys_eq!(obligation.owner, accounts.owner.key(), MockKaminoError::InvalidObligationOwner);
+    reserve.require_fresh(slot)?;
+    obligation.require_fresh(slot)?;
+    reserve.require_active()?;
+    require_key(reserve.liquidity.mint_pubkey, accounts.borrow_reserve_liquidity_mint.key())?;
+    require_key(reserve.liquidity.supply_vault, accounts.reserve_source_liquidity.key())?;
+    require_key(reserve.liquidity.fee_vault, accounts.borrow_reserve_liquidity_fee_receiver.key())?;
+
+    // The fee is owed on top of the amount and paid out of it to the fee vault
+    let fee = Reserve::fee(liquidity_amount, reserve.config.fees.borrow_fee_sf)?;
+    let debt = liquidity_amount.checked_add(fee).ok_or(MockKaminoError::MathOverflow)?;
+    require!(debt <= reserve.liquidity.available_amount, MockKaminoError::InsufficientLiquidity);
+
+    let debt_sf = u128::from(debt) << 60;
+    let value_sf = reserve.borrow_factor_adjusted(reserve.market_value_sf(debt_sf)?)?;
+    let remaining_sf = obligation
+        .allowed_borrow_value_sf
+        .saturating_sub(obligation.borrow_factor_adjusted_debt_value_sf);
+    require!(value_sf <= remaining_sf, MockKaminoError::BorrowTooLarge);
+
+    obligation.borrow(&reserve, accounts.borrow_reserve.key(), debt_sf)?;
+    reserve.liquidity.available_amount -= debt;
+    reserve.liquidity.borrowed_amount_sf = reserve
+        .liquidity
+        .borrowed_amount_sf
+        .checked_add(debt_sf)
+        .ok_or(MockKaminoError::MathOverflow)?;
+
+    let bump = ctx.bumps.lending_market_authority;
+    transfer_from_reserve(
+        &accounts.token_program,
+        &accounts.reserve_source_liquidity,
+        &accounts.borrow_reserve_liquidity_mint,
+        &accounts.user_destination_liquidity,
+        &accounts.lending_market_authority,
+        accounts.lending_market.key,
+        bump,
+        liquidity_amount,
+    )?;
+    if fee > 0 {
+        transfer_from_reserve(
+            &accounts.token_program,
+            &accounts.reserve_source_liquidity,
+            &accounts.borrow_reserve_liquidity_mint,
+            &accounts.borrow_reserve_liquidity_fee_receiver,
+            &accounts.lending_market_authority,
+            accounts.lending_market.key,
+            bump,
+            fee,
+        )?;
+    }
+
+    reserve.last_update.stale = 1;
+    obligation.last_update.stale = 1;
+    reserve.store(&accounts.borrow_reserve)?;
+    obligation.store(&accounts.obligation)
+}
+
+pub fn repay(ctx: Context<RepayObligationLiquidity>, liquidity_amount: u64) -> Result<()> {
+    repay_from(ctx.accounts, liquidity_amount)
+}
+
+/// Repay from `accounts`, which `repayAndWithdrawAndRedeem` also takes as its first step
+pub(crate) fn repay_from(accounts: &RepayObligationLiquidity, liquidity_amount: u64) -> Result<()> {
+    require!(liquidity_amount > 0, MockKaminoError::InvalidAmount);
+    let slot = Clock::get()?.slot;
+    require_lending_market(&accounts.lending_market)?;
+    let mut reserve = Reserve::load(&accounts.repay_reserve)?;
+    let mut obligation = Obligation::load(&accounts.obligation)?;
+    require_key(reserve.lending_market, accounts.lending_market.key())?;
+    require_key(obligation.lending_market, accounts.lending_market.key())?;
+    reserve.require_fresh(slot)?;
+    obligation.require_fresh(slot)?;
+    require_key(reserve.liquidity.mint_pubkey, accounts.reserve_liquidity_mint.key())?;
+    require_key(reserve.liquidity.supply_vault, accounts.reserve_destination_liquidity.key())?;
+
+    let index = obligation
+        .find_borrow(&accounts.repay_reserve.key())
+        .ok_or(MockKaminoError::InvalidObligationLiquidity)?;
+    let borrowed_sf = obligation.borrows[index].borrowed_amount_sf;
+    require!(borrowed_sf > 0, MockKaminoError::ObligationLiquidityEmpty);
+
+    // Repaying more than is owed settles the debt, rounded up to whole base units
+    let owed = u64::try_from(borrowed_sf.div_ceil(1 << 60)).map_err(|_| error!(MockKaminoError::MathOverflow))?;
+    let repay_amount = liquidity_amount.min(owed);
+    let repay_sf = (u128::from(repay_amount) << 60).min(borrowed_sf);
+
+    transfer_from_user(
+        &accounts.token_program,
+        &accounts.user_source_liquidity,
+        &accounts.reserve_liquidity_mint,
+        &accounts.reserve_destination_liquidity,
+        &accounts.owner,
+        repay_amount,
+    )?;
+
+    obligation.repay(index, repay_sf);
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_add(repay_amount)
+        .ok_or(MockKaminoError::MathOverflow)?;
+    reserve.liquidity.borrowed_amount_sf = reserve.liquidity.borrowed_amount_sf.saturating_sub(repay_sf);
+
+    reserve.last_update.stale = 1;
+    obligation.last_update.stale = 1;
+    reserve.store(&accounts.repay_reserve)?;
+    obligation.store(&accounts.obligation)
+}
+
+#[derive(Accounts)]
+pub struct DepositReserveLiquidity<'info> {
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: PDA signing for the reserve's vaults and collateral mint
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    #[account(mint::token_program = liquidity_token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_collateral_mint)]
+    pub user_destination_collateral: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowObligationLiquidity<'info> {
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: PDA signing for the reserve's vaults
+    #[account(seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()], bump)]
+    pub lending_market_authority: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub borrow_reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub borrow_reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = borrow_reserve_liquidity_mint)]
+    pub reserve_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = borrow_reserve_liquidity_mint)]
+    pub borrow_reserve_liquidity_fee_receiver: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = borrow_reserve_liquidity_mint)]
+    pub user_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Accepted and ignored; the mock pays no referrers
+    #[account(mut)]
+    pub referrer_token_state: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RepayObligationLiquidity<'info> {
+    pub owner: Signer<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub repay_reserve: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub reserve_liquidity_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub reserve_destination_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = reserve_liquidity_mint)]
+    pub user_source_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Address checked
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+}
@@ -0,0 +1,96 @@
+//! `refreshReserve` and `refreshObligation`. Reserves keep the price tests give them rather
+//! than reading an oracle, and obligations are revalued at their reserves' prices.
+
+use anchor_lang::prelude::*;
+
+use crate::state::{require_lending_market, Obligation, Reserve};
+use crate::MockKaminoError;
+
+pub fn reserve(ctx: Context<RefreshReserve>) -> Result<()> {
+    refresh_reserve(&ctx.accounts.lending_market, &ctx.accounts.reserve)
+}
+
+/// Refresh an obligation, given its deposit reserves then its borrow reserves, in order, as
+/// remaining accounts. Every reserve must have been refreshed in this slot.
+pub fn obligation<'info>(ctx: Context<'_, '_, 'info, 'info, RefreshObligation<'info>>) -> Result<()> {
+    refresh_obligation(&ctx.accounts.lending_market, &ctx.accounts.obligation, ctx.remaining_accounts)
+}
+
+/// `refreshReserve` on its accounts, which the combined instructions also run between steps
+pub(crate) fn refresh_reserve(lending_market: &AccountInfo, reserve_account: &AccountInfo) -> Result<()> {
+    let clock = Clock::get()?;
+    require_lending_market(lending_market)?;
+    let mut reserve = Reserve::load(reserve_account)?;
+    require_keys_eq!(reserve.lending_market, lending_market.key(), MockKaminoError::InvalidAccountInput);
+
+    reserve.liquidity.market_price_last_updated_ts =
+        u64::try_from(clock.unix_timestamp).map_err(|_| error!(MockKaminoError::MathOverflow))?;
+    reserve.last_update.slot = clock.slot;
+    reserve.last_update.stale = 0;
+    reserve.store(reserve_account)
+}
+
+/// `refreshObligation` with `reserve_accounts` as its reserves, which the combined
+/// instructions also run between steps
+pub(crate) fn refresh_obligation(
+    lending_market: &AccountInfo,
+    obligation_account: &AccountInfo,
+    reserve_accounts: &[AccountInfo],
+) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    require_lending_market(lending_market)?;
+    let mut obligation = Obligation::load(obligation_account)?;
+    require_keys_eq!(obligation.lending_market, lending_market.key(), MockKaminoError::InvalidAccountInput);
+
+    let expected: Vec<Pubkey> = obligation
+        .active_deposits()
+        .map(|deposit| deposit.deposit_reserve)
+        .chain(obligation.active_borrows().map(|borrow| borrow.borrow_reserve))
+        .collect();
+    require!(reserve_accounts.len() == expected.len(), MockKaminoError::InvalidAccountInput);
+    let mut reserves = Vec::with_capacity(expected.len());
+    for (account, key) in reserve_accounts.iter().zip(&expected) {
+        require_keys_eq!(account.key(), *key, MockKaminoError::InvalidAccountInput);
+        let reserve = Reserve::load(account)?;
+        reserve.require_fresh(slot)?;
+        reserves.push(reserve);
+    }
+
+    let (deposit_reserves, borrow_reserves) = reserves.split_at(obligation.active_deposits().count());
+    obligation.revalue(deposit_reserves, borrow_reserves)?;
+    obligation.last_update.slot = slot;
+    obligation.last_update.stale = 0;
+    obligation.store(obligation_account)
+}
+
+#[derive(Accounts)]
+pub struct RefreshReserve<'info> {
+    /// CHECK: Decoded as a reserve owned by this program
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Accepted and ignored; prices are set by tests
+    pub pyth_oracle: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    pub switchboard_price_oracle: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    pub switchboard_twap_oracle: Option<AccountInfo<'info>>,
+
+    /// CHECK: Accepted and ignored
+    pub scope_prices: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshObligation<'info> {
+    /// CHECK: Checked to be a lending market owned by this program
+    pub lending_market: AccountInfo<'info>,
+
+    /// CHECK: Decoded as an obligation owned by this program
+    #[account(mut)]
+    pub obligation: AccountInfo<'info>,
+}
@@ -0,0 +1,506 @@
+//! Kamino `Reserve`, `Obligation` and `LendingMarket` accounts as the mock reads and writes
+//! them, at the offsets of the mainnet accounts. Only the leading fields the mock needs are
+//! mirrored; the rest of an account is left as it was.
+
+use anchor_lang::prelude::*;
+use liquidity_lending::kamino::{discriminator, reserve_layout, LastUpdate};
+
+use crate::MockKaminoError;
+
+pub const RESERVE_SIZE: usize = 8624;
+pub const OBLIGATION_SIZE: usize = 3344;
+pub const LENDING_MARKET_SIZE: usize = 4664;
+
+/// Offset of `last_update` in reserves and obligations, past the discriminator
+const LAST_UPDATE: usize = 8;
+const LENDING_MARKET: usize = 24;
+
+mod big {
+    #![allow(clippy::all)]
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+use big::U256;
+
+/// Deserialize `T` from account data, `offset` bytes past the discriminator
+fn read<T: AnchorDeserialize>(data: &[u8], discriminator: &[u8; 8], offset: usize) -> Result<T> {
+    require!(
+        data.len() >= 8 && data[..8] == discriminator[..],
+        MockKaminoError::InvalidAccountInput
+    );
+    let mut body = data
+        .get(8 + offset..)
+        .ok_or(MockKaminoError::InvalidAccountInput)?;
+    T::deserialize(&mut body).map_err(|_| error!(MockKaminoError::InvalidAccountInput))
+}
+
+/// Serialize `value` into account data, `offset` bytes past the discriminator
+pub fn write<T: AnchorSerialize>(data: &mut [u8], offset: usize, value: &T) -> Result<()> {
+    let bytes = value.try_to_vec()?;
+    data.get_mut(8 + offset..8 + offset + bytes.len())
+        .ok_or(MockKaminoError::InvalidAccountInput)?
+        .copy_from_slice(&bytes);
+    Ok(())
+}
+
+fn require_owned(account: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*account.owner, crate::ID, MockKaminoError::InvalidAccountOwner);
+    Ok(())
+}
+
+/// Value of `amount_sf` base units at `price_sf` per whole token, as a scaled fraction
+pub fn market_value_sf(amount_sf: u128, price_sf: u128, decimals: u64) -> Result<u128> {
+    let decimals = u32::try_from(decimals).map_err(|_| error!(MockKaminoError::MathOverflow))?;
+    let factor = 10u128.checked_pow(decimals).ok_or(MockKaminoError::MathOverflow)?;
+    let value = (U256::from(amount_sf) * U256::from(price_sf) / U256::from(factor)) >> 60;
+    if value.bits() > 128 {
+        return err!(MockKaminoError::MathOverflow);
+    }
+    Ok(value.low_u128())
+}
+
+/// `value * pct / 100`
+fn pct_of(value: u128, pct: u64) -> Result<u128> {
+    value
+        .checked_mul(pct.into())
+        .map(|product| product / 100)
+        .ok_or_else(|| error!(MockKaminoError::MathOverflow))
+}
+
+/// Kamino `BigFractionBytes`: a 256-bit fraction with 60 fractional bits, limbs little endian
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BigFractionBytes {
+    pub value: [u64; 4],
+    pub padding: [u64; 2],
+}
+
+impl BigFractionBytes {
+    pub fn one() -> Self {
+        Self { value: [1 << 60, 0, 0, 0], padding: [0; 2] }
+    }
+}
+
+/// Kamino `ReserveLiquidity`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveLiquidity {
+    pub mint_pubkey: Pubkey,
+    pub supply_vault: Pubkey,
+    pub fee_vault: Pubkey,
+    pub available_amount: u64,
+    pub borrowed_amount_sf: u128,
+    pub market_price_sf: u128,
+    pub market_price_last_updated_ts: u64,
+    pub mint_decimals: u64,
+    pub deposit_limit_crossed_timestamp: u64,
+    pub borrow_limit_crossed_timestamp: u64,
+    pub cumulative_borrow_rate_bsf: BigFractionBytes,
+    pub accumulated_protocol_fees_sf: u128,
+    pub accumulated_referrer_fees_sf: u128,
+    pub pending_referrer_fees_sf: u128,
+    pub absolute_referral_rate_sf: u128,
+    pub token_program: Pubkey,
+}
+
+/// Kamino `ReserveCollateral`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveCollateral {
+    pub mint_pubkey: Pubkey,
+    pub mint_total_supply: u64,
+    pub supply_vault: Pubkey,
+}
+
+/// Kamino `ReserveFees`, scaled fractions of the amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveFees {
+    pub borrow_fee_sf: u64,
+    pub flash_loan_fee_sf: u64,
+    pub padding: [u8; 8],
+}
+
+/// Kamino `CurvePoint`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub utilization_rate_bps: u32,
+    pub borrow_rate_bps: u32,
+}
+
+/// Leading fields of a Kamino `ReserveConfig`, up to its borrow limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveConfig {
+    pub status: u8,
+    pub asset_tier: u8,
+    pub host_fixed_interest_rate_bps: u16,
+    pub reserved2: [u8; 2],
+    pub reserved3: [u8; 8],
+    pub protocol_take_rate_pct: u8,
+    pub protocol_liquidation_fee_pct: u8,
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub min_liquidation_bonus_bps: u16,
+    pub max_liquidation_bonus_bps: u16,
+    pub bad_debt_liquidation_bonus_bps: u16,
+    pub deleveraging_margin_call_period_secs: u64,
+    pub deleveraging_threshold_decrease_bps_per_day: u64,
+    pub fees: ReserveFees,
+    pub borrow_rate_curve: [CurvePoint; 11],
+    pub borrow_factor_pct: u64,
+    pub deposit_limit: u64,
+    pub borrow_limit: u64,
+}
+
+/// A Kamino `Reserve`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reserve {
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub liquidity: ReserveLiquidity,
+    pub collateral: ReserveCollateral,
+    pub config: ReserveConfig,
+}
+
+impl Reserve {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Ok(Self {
+            last_update: read(data, &discriminator::RESERVE, LAST_UPDATE)?,
+            lending_market: read(data, &discriminator::RESERVE, LENDING_MARKET)?,
+            liquidity: read(data, &discriminator::RESERVE, reserve_layout::LIQUIDITY)?,
+            collateral: read(data, &discriminator::RESERVE, reserve_layout::COLLATERAL)?,
+            config: read(data, &discriminator::RESERVE, reserve_layout::CONFIG)?,
+        })
+    }
+
+    pub fn encode(&self, data: &mut [u8]) -> Result<()> {
+        write(data, LAST_UPDATE, &self.last_update)?;
+        write(data, LENDING_MARKET, &self.lending_market)?;
+        write(data, reserve_layout::LIQUIDITY, &self.liquidity)?;
+        write(data, reserve_layout::COLLATERAL, &self.collateral)?;
+        write(data, reserve_layout::CONFIG, &self.config)
+    }
+
+    /// Full account data of a reserve with these fields and everything else zeroed
+    pub fn account_data(&self) -> Result<Vec<u8>> {
+        let mut data = vec![0; RESERVE_SIZE];
+        data[..8].copy_from_slice(&discriminator::RESERVE);
+        self.encode(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_owned(account)?;
+        Self::decode(&account.try_borrow_data()?)
+    }
+
+    pub fn store(&self, account: &AccountInfo) -> Result<()> {
+        self.encode(&mut account.try_borrow_mut_data()?)
+    }
+
+    pub fn require_fresh(&self, slot: u64) -> Result<()> {
+        require!(!self.last_update.is_stale(slot), MockKaminoError::ReserveStale);
+        Ok(())
+    }
+
+    /// Fail unless the reserve is active, as Kamino does for deposits and borrows
+    pub fn require_active(&self) -> Result<()> {
+        require!(self.config.status == 0, MockKaminoError::ReserveObsolete);
+        Ok(())
+    }
+
+    /// Liquidity owned by depositors, borrowed or not, as a scaled fraction
+    pub fn total_supply_sf(&self) -> u128 {
+        let liquidity = &self.liquidity;
+        (u128::from(liquidity.available_amount) << 60)
+            .saturating_add(liquidity.borrowed_amount_sf)
+            .saturating_sub(liquidity.accumulated_protocol_fees_sf)
+            .saturating_sub(liquidity.accumulated_referrer_fees_sf)
+            .saturating_sub(liquidity.pending_referrer_fees_sf)
+    }
+
+    /// Collateral minted for depositing `amount` of liquidity; one to one in an empty reserve
+    pub fn liquidity_to_collateral(&self, amount: u64) -> Result<u64> {
+        let total_supply_sf = self.total_supply_sf();
+        if self.collateral.mint_total_supply == 0 || total_supply_sf == 0 {
+            return Ok(amount);
+        }
+        let collateral = (U256::from(amount) << 60) * U256::from(self.collateral.mint_total_supply)
+            / U256::from(total_supply_sf);
+        u64::try_from(collateral).map_err(|_| error!(MockKaminoError::MathOverflow))
+    }
+
+    /// Liquidity `amount` of collateral redeems for, as a scaled fraction
+    pub fn collateral_to_liquidity_sf(&self, amount: u64) -> Result<u128> {
+        if self.collateral.mint_total_supply == 0 {
+            return Ok(u128::from(amount) << 60);
+        }
+        let liquidity = U256::from(amount) * U256::from(self.total_supply_sf())
+            / U256::from(self.collateral.mint_total_supply);
+        u128::try_from(liquidity).map_err(|_| error!(MockKaminoError::MathOverflow))
+    }
+
+    /// Market value of `amount_sf` of liquidity, as a scaled fraction
+    pub fn market_value_sf(&self, amount_sf: u128) -> Result<u128> {
+        market_value_sf(amount_sf, self.liquidity.market_price_sf, self.liquidity.mint_decimals)
+    }
+
+    /// `value_sf` weighted by the reserve's borrow factor; Kamino treats zero as 100%
+    pub fn borrow_factor_adjusted(&self, value_sf: u128) -> Result<u128> {
+        pct_of(value_sf, self.config.borrow_factor_pct.max(100))
+    }
+
+    /// Fee on `amount` at `fee_sf`, rounded up
+    pub fn fee(amount: u64, fee_sf: u64) -> Result<u64> {
+        let fee_sf = u128::from(amount) * u128::from(fee_sf);
+        u64::try_from(fee_sf.div_ceil(1 << 60)).map_err(|_| error!(MockKaminoError::MathOverflow))
+    }
+}
+
+/// Kamino `ObligationCollateral`; unused slots have a default reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObligationCollateral {
+    pub deposit_reserve: Pubkey,
+    pub deposited_amount: u64,
+    pub market_value_sf: u128,
+    pub borrowed_amount_against_this_collateral_in_elevation_group: u64,
+    pub padding: [u64; 9],
+}
+
+/// Kamino `ObligationLiquidity`; unused slots have a default reserve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObligationLiquidity {
+    pub borrow_reserve: Pubkey,
+    pub cumulative_borrow_rate_bsf: BigFractionBytes,
+    pub padding: u64,
+    pub borrowed_amount_sf: u128,
+    pub market_value_sf: u128,
+    pub borrow_factor_adjusted_market_value_sf: u128,
+    pub borrowed_amount_outside_elevation_groups: u64,
+    pub padding2: [u64; 7],
+}
+
+/// Leading fields of a Kamino `Obligation`, up to its health figures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Obligation {
+    pub tag: u64,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub deposits: [ObligationCollateral; 8],
+    pub lowest_reserve_deposit_liquidation_ltv: u64,
+    pub deposited_value_sf: u128,
+    pub borrows: [ObligationLiquidity; 5],
+    pub borrow_factor_adjusted_debt_value_sf: u128,
+    pub borrowed_assets_market_value_sf: u128,
+    pub allowed_borrow_value_sf: u128,
+    pub unhealthy_borrow_value_sf: u128,
+}
+
+impl Obligation {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        read(data, &discriminator::OBLIGATION, 0)
+    }
+
+    pub fn encode(&self, data: &mut [u8]) -> Result<()> {
+        write(data, 0, self)
+    }
+
+    /// Full account data of an obligation with these fields and everything else zeroed
+    pub fn account_data(&self) -> Result<Vec<u8>> {
+        let mut data = vec![0; OBLIGATION_SIZE];
+        data[..8].copy_from_slice(&discriminator::OBLIGATION);
+        self.encode(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_owned(account)?;
+        Self::decode(&account.try_borrow_data()?)
+    }
+
+    pub fn store(&self, account: &AccountInfo) -> Result<()> {
+        self.encode(&mut account.try_borrow_mut_data()?)
+    }
+
+    pub fn require_fresh(&self, slot: u64) -> Result<()> {
+        require!(!self.last_update.is_stale(slot), MockKaminoError::ObligationStale);
+        Ok(())
+    }
+
+    pub fn active_deposits(&self) -> impl Iterator<Item = &ObligationCollateral> {
+        self.deposits.iter().filter(|deposit| deposit.deposit_reserve != Pubkey::default())
+    }
+
+    pub fn active_borrows(&self) -> impl Iterator<Item = &ObligationLiquidity> {
+        self.borrows.iter().filter(|borrow| borrow.borrow_reserve != Pubkey::default())
+    }
+
+    /// Index of the deposit in `reserve`, if any
+    pub fn find_deposit(&self, reserve: &Pubkey) -> Option<usize> {
+        self.deposits.iter().position(|deposit| deposit.deposit_reserve == *reserve)
+    }
+
+    /// Add `amount` of collateral to the deposit in `reserve_key`, taking a free slot if there
+    /// is none yet. It is valued at the next refresh.
+    pub fn deposit(&mut self, reserve_key: Pubkey, amount: u64) -> Result<()> {
+        let index = match self.find_deposit(&reserve_key) {
+            Some(index) => index,
+            None => self
+                .find_deposit(&Pubkey::default())
+                .ok_or(MockKaminoError::ObligationReserveLimit)?,
+        };
+        let deposit = &mut self.deposits[index];
+        deposit.deposit_reserve = reserve_key;
+        deposit.deposited_amount = deposit
+            .deposited_amount
+            .checked_add(amount)
+            .ok_or(MockKaminoError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Take `amount` of collateral off the deposit at `index`, scaling its value and what it
+    /// allows down with it and freeing the slot once it is empty
+    pub fn withdraw(&mut self, index: usize, reserve: &Reserve, amount: u64) -> Result<()> {
+        let deposit = &mut self.deposits[index];
+        let removed_value_sf = (U256::from(deposit.market_value_sf) * U256::from(amount)
+            / U256::from(deposit.deposited_amount.max(1)))
+        .low_u128();
+        self.deposited_value_sf = self.deposited_value_sf.saturating_sub(removed_value_sf);
+        self.allowed_borrow_value_sf = self
+            .allowed_borrow_value_sf
+            .saturating_sub(pct_of(removed_value_sf, reserve.config.loan_to_value_pct.into())?);
+        self.unhealthy_borrow_value_sf = self
+            .unhealthy_borrow_value_sf
+            .saturating_sub(pct_of(removed_value_sf, reserve.config.liquidation_threshold_pct.into())?);
+
+        deposit.deposited_amount = deposit.deposited_amount.saturating_sub(amount);
+        deposit.market_value_sf = deposit.market_value_sf.saturating_sub(removed_value_sf);
+        if deposit.deposited_amount == 0 {
+            *deposit = ObligationCollateral::default();
+        }
+        Ok(())
+    }
+
+    /// Index of the borrow from `reserve`, if any
+    pub fn find_borrow(&self, reserve: &Pubkey) -> Option<usize> {
+        self.borrows.iter().position(|borrow| borrow.borrow_reserve == *reserve)
+    }
+
+    /// Add `amount_sf` of debt to the borrow from `reserve_key`, valued at the reserve's
+    /// price, taking a free slot if there is none yet
+    pub fn borrow(&mut self, reserve: &Reserve, reserve_key: Pubkey, amount_sf: u128) -> Result<()> {
+        let index = match self.find_borrow(&reserve_key) {
+            Some(index) => index,
+            None => self
+                .find_borrow(&Pubkey::default())
+                .ok_or(MockKaminoError::ObligationReserveLimit)?,
+        };
+        let market_value_sf = reserve.market_value_sf(amount_sf)?;
+        let adjusted_value_sf = reserve.borrow_factor_adjusted(market_value_sf)?;
+
+        let borrow = &mut self.borrows[index];
+        borrow.borrow_reserve = reserve_key;
+        borrow.cumulative_borrow_rate_bsf = reserve.liquidity.cumulative_borrow_rate_bsf;
+        borrow.borrowed_amount_sf = borrow
+            .borrowed_amount_sf
+            .checked_add(amount_sf)
+            .ok_or(MockKaminoError::MathOverflow)?;
+        borrow.market_value_sf = borrow.market_value_sf.saturating_add(market_value_sf);
+        borrow.borrow_factor_adjusted_market_value_sf =
+            borrow.borrow_factor_adjusted_market_value_sf.saturating_add(adjusted_value_sf);
+        self.borrowed_assets_market_value_sf =
+            self.borrowed_assets_market_value_sf.saturating_add(market_value_sf);
+        self.borrow_factor_adjusted_debt_value_sf =
+            self.borrow_factor_adjusted_debt_value_sf.saturating_add(adjusted_value_sf);
+        Ok(())
+    }
+
+    /// Take `amount_sf` off the borrow at `index`, scaling its values down with it and
+    /// freeing the slot once nothing is owed
+    pub fn repay(&mut self, index: usize, amount_sf: u128) {
+        let borrow = &mut self.borrows[index];
+        let remaining_sf = borrow.borrowed_amount_sf.saturating_sub(amount_sf);
+        let scale = |value: u128| -> u128 {
+            let scaled = U256::from(value) * U256::from(remaining_sf)
+                / U256::from(borrow.borrowed_amount_sf.max(1));
+            scaled.low_u128()
+        };
+        let market_value_sf = scale(borrow.market_value_sf);
+        let adjusted_value_sf = scale(borrow.borrow_factor_adjusted_market_value_sf);
+
+        self.borrowed_assets_market_value_sf = self
+            .borrowed_assets_market_value_sf
+            .saturating_sub(borrow.market_value_sf - market_value_sf);
+        self.borrow_factor_adjusted_debt_value_sf = self
+            .borrow_factor_adjusted_debt_value_sf
+            .saturating_sub(borrow.borrow_factor_adjusted_market_value_sf - adjusted_value_sf);
+        if remaining_sf == 0 {
+            *borrow = ObligationLiquidity::default();
+        } else {
+            borrow.borrowed_amount_sf = remaining_sf;
+            borrow.market_value_sf = market_value_sf;
+            borrow.borrow_factor_adjusted_market_value_sf = adjusted_value_sf;
+        }
+    }
+
+    /// Revalue every deposit and borrow at their reserves' prices, `deposit_reserves` and
+    /// `borrow_reserves` given in the obligation's order
+    pub fn revalue(&mut self, deposit_reserves: &[Reserve], borrow_reserves: &[Reserve]) -> Result<()> {
+        let mut deposited_value_sf = 0u128;
+        let mut allowed_borrow_value_sf = 0u128;
+        let mut unhealthy_borrow_value_sf = 0u128;
+        let deposits = self
+            .deposits
+            .iter_mut()
+            .filter(|deposit| deposit.deposit_reserve != Pubkey::default());
+        for (deposit, reserve) in deposits.zip(deposit_reserves) {
+            let liquidity_sf = reserve.collateral_to_liquidity_sf(deposit.deposited_amount)?;
+            deposit.market_value_sf = reserve.market_value_sf(liquidity_sf)?;
+            deposited_value_sf = deposited_value_sf.saturating_add(deposit.market_value_sf);
+            allowed_borrow_value_sf = allowed_borrow_value_sf
+                .saturating_add(pct_of(deposit.market_value_sf, reserve.config.loan_to_value_pct.into())?);
+            unhealthy_borrow_value_sf = unhealthy_borrow_value_sf.saturating_add(pct_of(
+                deposit.market_value_sf,
+                reserve.config.liquidation_threshold_pct.into(),
+            )?);
+        }
+
+        let mut borrowed_assets_market_value_sf = 0u128;
+        let mut borrow_factor_adjusted_debt_value_sf = 0u128;
+        let borrows = self
+            .borrows
+            .iter_mut()
+            .filter(|borrow| borrow.borrow_reserve != Pubkey::default());
+        for (borrow, reserve) in borrows.zip(borrow_reserves) {
+            borrow.market_value_sf = reserve.market_value_sf(borrow.borrowed_amount_sf)?;
+            borrow.borrow_factor_adjusted_market_value_sf = reserve.borrow_factor_adjusted(borrow.market_value_sf)?;
+            borrowed_assets_market_value_sf = borrowed_assets_market_value_sf.saturating_add(borrow.market_value_sf);
+            borrow_factor_adjusted_debt_value_sf =
+                borrow_factor_adjusted_debt_value_sf.saturating_add(borrow.borrow_factor_adjusted_market_value_sf);
+        }
+
+        self.deposited_value_sf = deposited_value_sf;
+        self.allowed_borrow_value_sf = allowed_borrow_value_sf;
+        self.unhealthy_borrow_value_sf = unhealthy_borrow_value_sf;
+        self.borrowed_assets_market_value_sf = borrowed_assets_market_value_sf;
+        self.borrow_factor_adjusted_debt_value_sf = borrow_factor_adjusted_debt_value_sf;
+        Ok(())
+    }
+}
+
+/// Full account data of a lending market; the mock reads nothing from it but its type
+pub fn lending_market_data() -> Vec<u8> {
+    let mut data = vec![0; LENDING_MARKET_SIZE];
+    data[..8].copy_from_slice(&discriminator::LENDING_MARKET);
+    data
+}
+
+/// Fail unless `account` is a lending market owned by the mock
+pub fn require_lending_market(account: &AccountInfo) -> Result<()> {
+    require_owned(account)?;
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == discriminator::LENDING_MARKET[..],
+        MockKaminoError::InvalidAccountInput
+    );
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+
+use liquidity_lending::kamino::{
+    deserialize_data, discriminator, reserve_layout, ObligationHeader, ReserveHeader,
+    ReserveMarketPrice, ReserveStatus,
+};
+use liquidity_lending::kamino_error;
+use mock_kamino::state::{
+    BigFractionBytes, Obligation, ObligationCollateral, Reserve, OBLIGATION_SIZE, RESERVE_SIZE,
+};
+use mock_kamino::MockKaminoError;
+
+const ONE: u128 = 1 << 60;
+
+/// A 6-decimal reserve priced at $2 with 1,000 tokens available, 60% LTV, 80% liquidation
+/// threshold and a 150% borrow factor
+fn reserve() -> Reserve {
+    let mut reserve = Reserve::default();
+    reserve.last_update.slot = 10;
+    reserve.lending_market = Pubkey::new_unique();
+    reserve.liquidity.mint_pubkey = Pubkey::new_unique();
+    reserve.liquidity.available_amount = 1_000_000_000;
+    reserve.liquidity.market_price_sf = 2 * ONE;
+    reserve.liquidity.market_price_last_updated_ts = 1_700_000_000;
+    reserve.liquidity.mint_decimals = 6;
+    reserve.liquidity.cumulative_borrow_rate_bsf = BigFractionBytes::one();
+    reserve.collateral.mint_total_supply = 1_000_000_000;
+    reserve.config.loan_to_value_pct = 60;
+    reserve.config.liquidation_threshold_pct = 80;
+    reserve.config.borrow_factor_pct = 150;
+    reserve
+}
+
+#[test]
+fn test_layouts_match_liquidity_lending() {
+    let reserve = reserve();
+    let data = reserve.account_data().unwrap();
+    assert_eq!(data.len(), RESERVE_SIZE);
+    assert_eq!(Reserve::decode(&data).unwrap(), reserve);
+
+    // What the mock writes is what the wrappers read
+    let header: ReserveHeader = deserialize_data(&data, &discriminator::RESERVE, 0).unwrap();
+    assert_eq!(header.last_update, reserve.last_update);
+    assert_eq!(header.lending_market, reserve.lending_market);
+    let price: ReserveMarketPrice =
+        deserialize_data(&data, &discriminator::RESERVE, reserve_layout::LIQUIDITY_MARKET_PRICE_SF).unwrap();
+    assert_eq!(price.market_price_sf, 2 * ONE);
+    assert_eq!(price.market_price_last_updated_ts, 1_700_000_000);
+    let status: ReserveStatus = deserialize_data(&data, &discriminator::RESERVE, reserve_layout::CONFIG_STATUS).unwrap();
+    assert_eq!(status, ReserveStatus::Active);
+    let borrow_factor: u64 =
+        deserialize_data(&data, &discriminator::RESERVE, reserve_layout::CONFIG_BORROW_FACTOR_PCT).unwrap();
+    assert_eq!(borrow_factor, 150);
+
+    let obligation = Obligation {
+        lending_market: reserve.lending_market,
+        owner: Pubkey::new_unique(),
+        ..Obligation::default()
+    };
+    let data = obligation.account_data().unwrap();
+    assert_eq!(data.len(), OBLIGATION_SIZE);
+    let header: ObligationHeader = deserialize_data(&data, &discriminator::OBLIGATION, 0).unwrap();
+    assert_eq!((header.lending_market, header.owner), (obligation.lending_market, obligation.owner));
+}
+
+#[test]
+fn test_error_codes_match_kamino() {
+    let errors = [
+        (MockKaminoError::InvalidAccountOwner, "InvalidAccountOwner"),
+        (MockKaminoError::InvalidAmount, "InvalidAmount"),
+        (MockKaminoError::InvalidAccountInput, "InvalidAccountInput"),
+        (MockKaminoError::MathOverflow, "MathOverflow"),
+        (MockKaminoError::InsufficientLiquidity, "InsufficientLiquidity"),
+        (MockKaminoError::ReserveStale, "ReserveStale"),
+        (MockKaminoError::WithdrawTooLarge, "WithdrawTooLarge"),
+        (MockKaminoError::BorrowTooLarge, "BorrowTooLarge"),
+        (MockKaminoError::ObligationStale, "ObligationStale"),
+        (MockKaminoError::ObligationReserveLimit, "ObligationReserveLimit"),
+        (MockKaminoError::InvalidObligationOwner, "InvalidObligationOwner"),
+        (MockKaminoError::InvalidObligationCollateral, "InvalidObligationCollateral"),
+        (MockKaminoError::InvalidObligationLiquidity, "InvalidObligationLiquidity"),
+        (MockKaminoError::ObligationCollateralEmpty, "ObligationCollateralEmpty"),
+        (MockKaminoError::ObligationLiquidityEmpty, "ObligationLiquidityEmpty"),
+        (MockKaminoError::FlashBorrowCpi, "FlashBorrowCpi"),
+        (MockKaminoError::NoFlashRepayFound, "NoFlashRepayFound"),
+        (MockKaminoError::InvalidFlashRepay, "InvalidFlashRepay"),
+        (MockKaminoError::FlashRepayCpi, "FlashRepayCpi"),
+        (MockKaminoError::MultipleFlashBorrows, "MultipleFlashBorrows"),
+        (MockKaminoError::ReserveObsolete, "ReserveObsolete"),
+    ];
+    for (error, name) in errors {
+        let code: u32 = error.into();
+        assert_eq!(kamino_error::lookup(code).unwrap().name, name);
+    }
+}
+
+#[test]
+fn test_collateral_exchange_rate() {
+    let mut reserve = reserve();
+    // One to one until interest is earned
+    assert_eq!(reserve.liquidity_to_collateral(500).unwrap(), 500);
+
+    // 1,000 tokens of collateral now redeem for 1,250 tokens
+    reserve.liquidity.borrowed_amount_sf = 250_000_000 * ONE;
+    assert_eq!(reserve.liquidity_to_collateral(1_250).unwrap(), 1_000);
+    assert_eq!(reserve.collateral_to_liquidity_sf(1_000).unwrap(), 1_250 * ONE);
+
+    // An empty reserve mints one to one
+    let empty = Reserve::default();
+    assert_eq!(empty.liquidity_to_collateral(42).unwrap(), 42);
+}
+
+#[test]
+fn test_borrow_repay_and_revalue() {
+    let reserve = reserve();
+    let reserve_key = Pubkey::new_unique();
+    let mut obligation = Obligation::default();
+    obligation.deposits[0] = ObligationCollateral {
+        deposit_reserve: reserve_key,
+        deposited_amount: 100_000_000,
+        ..ObligationCollateral::default()
+    };
+
+    // $200 of collateral allows $120 of borrow-factor-adjusted debt
+    obligation.revalue(&[reserve], &[]).unwrap();
+    assert_eq!(obligation.deposited_value_sf, 200 * ONE);
+    assert_eq!(obligation.allowed_borrow_value_sf, 120 * ONE);
+    assert_eq!(obligation.unhealthy_borrow_value_sf, 160 * ONE);
+
+    // 10 tokens are $20 of debt, $30 once weighted by the borrow factor
+    obligation.borrow(&reserve, reserve_key, 10_000_000 * ONE).unwrap();
+    assert_eq!(obligation.borrowed_assets_market_value_sf, 20 * ONE);
+    assert_eq!(obligation.borrow_factor_adjusted_debt_value_sf, 30 * ONE);
+    let index = obligation.find_borrow(&reserve_key).unwrap();
+    assert_eq!(obligation.borrows[index].borrowed_amount_sf, 10_000_000 * ONE);
+
+    // Repaying half halves the values, and repaying the rest frees the slot
+    obligation.repay(index, 5_000_000 * ONE);
+    assert_eq!(obligation.borrowed_assets_market_value_sf, 10 * ONE);
+    assert_eq!(obligation.borrow_factor_adjusted_debt_value_sf, 15 * ONE);
+    obligation.repay(index, 5_000_000 * ONE);
+    assert_eq!(obligation.active_borrows().count(), 0);
+    assert_eq!(obligation.borrow_factor_adjusted_debt_value_sf, 0);
+
+    // Five reserves fill every borrow slot
+    for _ in 0..5 {
+        obligation.borrow(&reserve, Pubkey::new_unique(), ONE).unwrap();
+    }
+    assert!(obligation.borrow(&reserve, Pubkey::new_unique(), ONE).is_err());
+}
+
+#[test]
+fn test_collateral_deposit_and_withdraw() {
+    let reserve = reserve();
+    let reserve_key = Pubkey::new_unique();
+    let mut obligation = Obligation::default();
+
+    // Deposits land in a free slot and are valued at the next refresh
+    obligation.deposit(reserve_key, 60_000_000).unwrap();
+    obligation.deposit(reserve_key, 40_000_000).unwrap();
+    let index = obligation.find_deposit(&reserve_key).unwrap();
+    assert_eq!(obligation.deposits[index].deposited_amount, 100_000_000);
+    assert_eq!(obligation.deposited_value_sf, 0);
+    obligation.revalue(&[reserve], &[]).unwrap();
+    assert_eq!(obligation.deposited_value_sf, 200 * ONE);
+
+    // Withdrawing a quarter takes a quarter of the value and of what it allows
+    obligation.withdraw(index, &reserve, 25_000_000).unwrap();
+    assert_eq!(obligation.deposits[index].deposited_amount, 75_000_000);
+    assert_eq!(obligation.deposited_value_sf, 150 * ONE);
+    assert_eq!(obligation.allowed_borrow_value_sf, 90 * ONE);
+    assert_eq!(obligation.unhealthy_borrow_value_sf, 120 * ONE);
+
+    // Withdrawing the rest frees the slot
+    obligation.withdraw(index, &reserve, 75_000_000).unwrap();
+    assert_eq!(obligation.active_deposits().count(), 0);
+    assert_eq!(obligation.deposited_value_sf, 0);
+
+    // Eight reserves fill every deposit slot
+    for _ in 0..8 {
+        obligation.deposit(Pubkey::new_unique(), 1).unwrap();
+    }
+    assert!(obligation.deposit(Pubkey::new_unique(), 1).is_err());
+}
+
+#[test]
+fn test_fees_round_up() {
+    // 0.1% of 1,001 base units is 1.001, charged as 2
+    let fee_sf = (ONE / 1_000) as u64;
+    assert_eq!(Reserve::fee(1_001, fee_sf).unwrap(), 2);
+    assert_eq!(Reserve::fee(1_000, 0).unwrap(), 0);
+}
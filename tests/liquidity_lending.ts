@@ -10,7 +10,14 @@ describe("liquidity_lending", () => {
 
   it("Is initialized!", async () => {
     // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const tx = await program.methods
+      .initialize()
+      .accounts({ programData })
+      .rpc();
     console.log("Your transaction signature", tx);
   });
 });